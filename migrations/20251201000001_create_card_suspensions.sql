@@ -0,0 +1,47 @@
+-- Issuer-initiated card suspensions
+-- A suspension flips the card to status = 'suspended' until it is lifted manually
+-- or its ends_at passes and the scheduler reinstates the card.
+
+CREATE TABLE card_suspensions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    card_id UUID NOT NULL REFERENCES membership_cards(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    suspended_by UUID REFERENCES members(id),
+    suspended_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ends_at TIMESTAMPTZ,           -- NULL means suspended until lifted manually
+    lifted_at TIMESTAMPTZ,
+    lifted_by UUID REFERENCES members(id)  -- NULL when reinstated automatically
+);
+
+-- Only one open suspension per card
+CREATE UNIQUE INDEX uniq_open_card_suspension
+ON card_suspensions(card_id)
+WHERE lifted_at IS NULL;
+
+-- Scheduler lookup for suspensions that are due to be lifted
+CREATE INDEX idx_card_suspensions_due
+ON card_suspensions(ends_at)
+WHERE lifted_at IS NULL AND ends_at IS NOT NULL;
+
+CREATE INDEX idx_card_suspensions_card ON card_suspensions(card_id, suspended_at DESC);
+
+-- Allow scans of non-active cards to be recorded with a distinct result
+ALTER TABLE verification_events
+DROP CONSTRAINT IF EXISTS verification_events_verification_result_check;
+
+ALTER TABLE verification_events
+ADD CONSTRAINT verification_events_verification_result_check CHECK (
+    verification_result IN (
+        'success',
+        'invalid_signature',
+        'card_not_found',
+        'invalid_payload',
+        'card_expired',
+        'card_revoked',
+        'card_suspended',
+        'card_deleted'
+    )
+);
+
+COMMENT ON TABLE card_suspensions IS 'Issuer-initiated card suspensions with optional automatic reinstatement';
+COMMENT ON COLUMN card_suspensions.ends_at IS 'When the scheduler reinstates the card; NULL for indefinite suspensions';
@@ -0,0 +1,21 @@
+-- Per-issuer card lifecycle policy
+-- Replaces the hard-coded 30-day validity/extension, 24-hour recheck interval
+-- and 3-failure threshold. Defaults preserve the previous behavior.
+
+ALTER TABLE card_issuers
+  ADD COLUMN initial_validity_days INT NOT NULL DEFAULT 30
+    CHECK (initial_validity_days BETWEEN 1 AND 366),
+  ADD COLUMN extension_days INT NOT NULL DEFAULT 30
+    CHECK (extension_days BETWEEN 1 AND 366),
+  ADD COLUMN recheck_interval_hours INT NOT NULL DEFAULT 24
+    CHECK (recheck_interval_hours BETWEEN 1 AND 720),
+  ADD COLUMN failure_threshold INT NOT NULL DEFAULT 3
+    CHECK (failure_threshold BETWEEN 1 AND 30),
+  ADD COLUMN grace_period_days INT NOT NULL DEFAULT 0
+    CHECK (grace_period_days BETWEEN 0 AND 90);
+
+COMMENT ON COLUMN card_issuers.initial_validity_days IS 'Days a newly issued card is valid before its first renewal';
+COMMENT ON COLUMN card_issuers.extension_days IS 'Days added to expires_at after each successful membership check';
+COMMENT ON COLUMN card_issuers.recheck_interval_hours IS 'Minimum hours between background membership checks for a card';
+COMMENT ON COLUMN card_issuers.failure_threshold IS 'Consecutive failed checks before a card stops being renewed';
+COMMENT ON COLUMN card_issuers.grace_period_days IS 'Days a card stays valid after reaching the failure threshold (0 = expire immediately)';
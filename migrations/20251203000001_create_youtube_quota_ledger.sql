@@ -0,0 +1,13 @@
+-- Daily YouTube Data API quota ledger
+-- One row per quota day. YouTube resets quotas at midnight Pacific time, so
+-- usage_date is the current date in America/Los_Angeles.
+
+CREATE TABLE youtube_quota_ledger (
+  usage_date DATE PRIMARY KEY,
+  units_spent INT NOT NULL DEFAULT 0 CHECK (units_spent >= 0),
+  exhausted_at TIMESTAMPTZ,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE youtube_quota_ledger IS 'YouTube Data API units spent per quota day (Pacific time)';
+COMMENT ON COLUMN youtube_quota_ledger.exhausted_at IS 'Set when the API reported quotaExceeded; no more calls are made that day';
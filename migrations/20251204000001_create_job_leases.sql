@@ -0,0 +1,14 @@
+-- Leases for background jobs
+-- Every replica runs the scheduler; a job only runs on the instance holding its
+-- lease. Leases expire on their own, so a replica that dies mid-run does not
+-- block the job forever.
+
+CREATE TABLE job_leases (
+  job_name VARCHAR(100) PRIMARY KEY,
+  holder_id UUID NOT NULL,
+  acquired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  leased_until TIMESTAMPTZ NOT NULL
+);
+
+COMMENT ON TABLE job_leases IS 'Which instance currently runs each background job, and until when';
+COMMENT ON COLUMN job_leases.holder_id IS 'Random ID of the holding process, generated at startup';
@@ -0,0 +1,37 @@
+-- Durable background task queue (transactional outbox)
+-- Tasks are inserted in the same transaction as the state change that needs
+-- them (e.g. card creation -> wallet QR generation), then executed by the task
+-- worker with retries and exponential backoff. Tasks that keep failing are
+-- dead-lettered for an operator to inspect and retry.
+
+CREATE TABLE background_tasks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'succeeded', 'dead')),
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL DEFAULT 8 CHECK (max_attempts > 0),
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_by UUID,
+    locked_until TIMESTAMPTZ,
+    last_error TEXT,
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Due pending tasks, and running tasks whose worker lease may have expired
+CREATE INDEX idx_background_tasks_due ON background_tasks(run_at)
+    WHERE status = 'pending';
+CREATE INDEX idx_background_tasks_running ON background_tasks(locked_until)
+    WHERE status = 'running';
+CREATE INDEX idx_background_tasks_dead ON background_tasks(updated_at DESC)
+    WHERE status = 'dead';
+
+CREATE TRIGGER update_background_tasks_updated_at
+    BEFORE UPDATE ON background_tasks
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+COMMENT ON COLUMN background_tasks.locked_until IS 'Worker lease; a running task past this time is picked up again';
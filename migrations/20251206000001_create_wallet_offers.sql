@@ -0,0 +1,23 @@
+-- Wallet offer history
+-- Each row is one attempt to put a card into the member's wallet: the initial
+-- offer at issuance, a re-offer after the QR expired unscanned, or a re-issue
+-- after a lost device (which revokes the previous credential).
+
+CREATE TABLE wallet_offers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    card_id UUID NOT NULL REFERENCES membership_cards(id) ON DELETE CASCADE,
+    reason VARCHAR(20) NOT NULL
+        CHECK (reason IN ('initial', 'expired', 'lost_device')),
+    replaced_cid TEXT, -- credential revoked by this offer (lost_device only)
+    transaction_id TEXT, -- NULL until the wallet API returned the offer
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    offered_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_wallet_offers_card ON wallet_offers(card_id, requested_at DESC);
+
+-- Existing cards got exactly one offer when they were issued
+INSERT INTO wallet_offers (card_id, reason, transaction_id, requested_at, offered_at)
+SELECT id, 'initial', wallet_transaction_id, issued_at, issued_at
+FROM membership_cards
+WHERE wallet_transaction_id IS NOT NULL;
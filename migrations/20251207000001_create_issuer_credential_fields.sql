@@ -0,0 +1,16 @@
+-- Per-issuer credential schema
+-- Maps the fields of an issuer's wallet credential template (vc_uid) to card
+-- data. Issuers without rows keep sending the single `name` field (display name).
+
+CREATE TABLE issuer_credential_fields (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    issuer_id UUID NOT NULL REFERENCES card_issuers(id) ON DELETE CASCADE,
+    ename VARCHAR(50) NOT NULL, -- field name in the wallet credential template
+    source VARCHAR(30) NOT NULL
+        CHECK (source IN ('display_name', 'tier_label', 'channel_name', 'member_since', 'card_id', 'expires_at')),
+    position INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (issuer_id, ename)
+);
+
+CREATE INDEX idx_issuer_credential_fields_issuer ON issuer_credential_fields(issuer_id, position);
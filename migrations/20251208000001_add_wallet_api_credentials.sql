@@ -0,0 +1,21 @@
+-- Per-issuer and per-event wallet API accounts
+-- Lets a community use its own Taiwan Digital Wallet issuer/verifier account instead
+-- of the global one from the environment. Tokens are encrypted by the application
+-- (AES-256-GCM, CREDENTIAL_ENCRYPTION_KEY); a NULL token means "use the global account".
+
+ALTER TABLE card_issuers
+  ADD COLUMN wallet_issuer_api_url TEXT,
+  ADD COLUMN wallet_issuer_token_encrypted TEXT,
+  ADD COLUMN wallet_verifier_api_url TEXT,
+  ADD COLUMN wallet_verifier_token_encrypted TEXT;
+
+ALTER TABLE events
+  ADD COLUMN verifier_api_url TEXT,
+  ADD COLUMN verifier_token_encrypted TEXT;
+
+COMMENT ON COLUMN card_issuers.wallet_issuer_api_url IS 'Issuer API base URL for this issuer''s own wallet account (NULL = global URL)';
+COMMENT ON COLUMN card_issuers.wallet_issuer_token_encrypted IS 'Encrypted issuer API access token (NULL = global account)';
+COMMENT ON COLUMN card_issuers.wallet_verifier_api_url IS 'Verifier API base URL for this issuer''s events (NULL = global URL)';
+COMMENT ON COLUMN card_issuers.wallet_verifier_token_encrypted IS 'Encrypted verifier API access token (NULL = global account)';
+COMMENT ON COLUMN events.verifier_api_url IS 'Verifier API base URL for this event only (NULL = issuer/global URL)';
+COMMENT ON COLUMN events.verifier_token_encrypted IS 'Encrypted verifier API access token for this event only (NULL = issuer/global account)';
@@ -0,0 +1,52 @@
+-- Self-hosted OpenID4VCI / OpenID4VP wallet provider
+-- Issuers can issue SD-JWT VCs from VPass itself (any standards-compliant wallet)
+-- instead of through the Taiwan Digital Wallet.
+
+ALTER TABLE card_issuers
+    ADD COLUMN wallet_provider VARCHAR(20) NOT NULL DEFAULT 'taiwan_wallet'
+        CHECK (wallet_provider IN ('taiwan_wallet', 'openid'));
+
+COMMENT ON COLUMN card_issuers.wallet_provider IS 'Wallet new credentials are offered to: taiwan_wallet or openid (self-hosted)';
+
+-- Keys VPass signs credentials with (ES256); private keys are encrypted with CREDENTIAL_ENCRYPTION_KEY
+CREATE TABLE signing_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kid VARCHAR(64) NOT NULL UNIQUE, -- JWK thumbprint
+    algorithm VARCHAR(10) NOT NULL DEFAULT 'ES256',
+    private_key_encrypted TEXT NOT NULL,
+    public_jwk JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    retired_at TIMESTAMPTZ -- no longer signs; still verifies credentials it signed
+);
+
+-- One row per credential offer (pre-authorized code flow); the offer ID is the
+-- card's wallet transaction ID
+CREATE TABLE openid_credential_offers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    card_id UUID NOT NULL REFERENCES membership_cards(id) ON DELETE CASCADE,
+    issuer_id UUID NOT NULL REFERENCES card_issuers(id) ON DELETE CASCADE,
+    claims JSONB NOT NULL, -- credential fields, fixed when the offer is made
+    pre_authorized_code_hash VARCHAR(64) NOT NULL UNIQUE,
+    access_token_hash VARCHAR(64) UNIQUE,
+    access_token_expires_at TIMESTAMPTZ,
+    c_nonce VARCHAR(64),
+    credential_id UUID UNIQUE, -- CID of the issued credential
+    expires_at TIMESTAMPTZ NOT NULL,
+    issued_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_openid_credential_offers_card ON openid_credential_offers(card_id);
+
+-- One row per presentation request shown at an event's scanner
+CREATE TABLE openid_presentations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(), -- transaction ID
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    issuer_id UUID NOT NULL REFERENCES card_issuers(id) ON DELETE CASCADE,
+    nonce VARCHAR(64) NOT NULL,
+    result JSONB, -- NULL until the wallet responded
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
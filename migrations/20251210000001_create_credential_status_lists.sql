@@ -0,0 +1,54 @@
+-- Token status lists for credentials VPass signs itself
+-- Verifiers check revocation by downloading one signed list per issuer instead of
+-- asking about each card. Each card gets an index the first time VPass signs a
+-- credential for it; the card's status is mirrored into the list by a trigger, so
+-- every status transition (revoke, suspend, expire, delete) flips its bits.
+
+CREATE TABLE credential_status_lists (
+    issuer_id UUID PRIMARY KEY REFERENCES card_issuers(id) ON DELETE CASCADE,
+    statuses BYTEA NOT NULL DEFAULT ''::BYTEA, -- 2 bits per card, least significant bits first
+    next_index INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE membership_cards
+    ADD COLUMN status_list_index INTEGER;
+
+CREATE UNIQUE INDEX idx_membership_cards_status_list_index
+    ON membership_cards(issuer_id, status_list_index)
+    WHERE status_list_index IS NOT NULL;
+
+COMMENT ON COLUMN membership_cards.status_list_index IS 'Index of the card in its issuer''s credential status list';
+
+-- Status values of the token status list: 0 valid, 1 invalid, 2 suspended
+CREATE OR REPLACE FUNCTION sync_credential_status()
+RETURNS TRIGGER AS $$
+DECLARE
+    status_value INTEGER;
+BEGIN
+    status_value := CASE NEW.status
+        WHEN 'active' THEN 0
+        WHEN 'suspended' THEN 2
+        ELSE 1
+    END;
+
+    UPDATE credential_status_lists
+    SET statuses = set_bit(
+            set_bit(statuses, NEW.status_list_index * 2, status_value & 1),
+            NEW.status_list_index * 2 + 1, (status_value >> 1) & 1
+        ),
+        updated_at = NOW()
+    WHERE issuer_id = NEW.issuer_id;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER sync_membership_card_credential_status
+    AFTER UPDATE OF status, status_list_index ON membership_cards
+    FOR EACH ROW
+    WHEN (NEW.status_list_index IS NOT NULL
+          AND (OLD.status IS DISTINCT FROM NEW.status
+               OR OLD.status_list_index IS DISTINCT FROM NEW.status_list_index))
+    EXECUTE FUNCTION sync_credential_status();
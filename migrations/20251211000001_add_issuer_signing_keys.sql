@@ -0,0 +1,14 @@
+-- Per-issuer signing keys, published as did:web documents and JWKS
+-- Keys without an issuer are VPass's own (credentials signed before per-issuer keys).
+
+ALTER TABLE signing_keys
+    ADD COLUMN issuer_id UUID REFERENCES card_issuers(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_signing_keys_issuer ON signing_keys(issuer_id, created_at DESC);
+
+-- At most one active key per issuer; rotation retires it before creating the next
+CREATE UNIQUE INDEX idx_signing_keys_active_issuer
+    ON signing_keys(issuer_id)
+    WHERE retired_at IS NULL AND issuer_id IS NOT NULL;
+
+COMMENT ON COLUMN signing_keys.issuer_id IS 'Issuer the key signs for; NULL for VPass''s own key';
@@ -0,0 +1,11 @@
+-- Nonces of scanned card presentation tokens (the rotating QR on /cards/:id/present)
+-- A token is accepted once; rows are only needed until the token expires.
+
+CREATE TABLE presentation_token_nonces (
+    nonce VARCHAR(64) PRIMARY KEY,
+    card_id UUID NOT NULL REFERENCES membership_cards(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_presentation_token_nonces_expires ON presentation_token_nonces(expires_at);
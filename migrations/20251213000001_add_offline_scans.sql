@@ -0,0 +1,28 @@
+-- Offline scanning: scans verified on a device against a signed event bundle and
+-- uploaded later in batches. The device's scan ID makes re-uploads idempotent.
+
+ALTER TABLE verification_events ADD COLUMN client_scan_id UUID;
+
+CREATE UNIQUE INDEX idx_verification_events_client_scan ON verification_events(client_scan_id);
+
+-- A presentation token scanned again (on another device, or online and offline)
+ALTER TABLE verification_events
+DROP CONSTRAINT IF EXISTS verification_events_verification_result_check;
+
+ALTER TABLE verification_events
+ADD CONSTRAINT verification_events_verification_result_check CHECK (
+    verification_result IN (
+        'success',
+        'invalid_signature',
+        'card_not_found',
+        'invalid_payload',
+        'card_expired',
+        'card_revoked',
+        'card_suspended',
+        'card_deleted',
+        'token_reused'
+    )
+);
+
+COMMENT ON COLUMN verification_events.client_scan_id IS 'Scan ID assigned by an offline scanner; NULL for online verifications';
+COMMENT ON TABLE presentation_token_nonces IS 'Used presentation token nonces, kept for a while after expiry so uploaded offline scans are checked for reuse';
@@ -0,0 +1,19 @@
+-- Scanner devices (gates) registered for an event
+-- A device pairs by opening a link carrying its token; only the token's hash is stored.
+
+CREATE TABLE scanner_devices (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    operator_name TEXT,
+    token_hash TEXT NOT NULL UNIQUE,
+    registered_by UUID REFERENCES members(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_scanner_devices_event ON scanner_devices(event_id, created_at);
+
+COMMENT ON TABLE scanner_devices IS 'Named scanner devices/gates of an event, authenticated by a revocable device token';
+COMMENT ON COLUMN scanner_devices.token_hash IS 'SHA-256 (hex) of the device token';
@@ -0,0 +1,39 @@
+-- Per-event re-entry policy, evaluated at scan time against the card's earlier
+-- admissions at the event. The default preserves the previous behavior.
+
+ALTER TABLE events
+  ADD COLUMN reentry_policy TEXT NOT NULL DEFAULT 'unlimited'
+    CHECK (reentry_policy IN ('single_entry', 'unlimited', 'after_checkout', 'max_entries')),
+  ADD COLUMN max_entries INT
+    CHECK (max_entries BETWEEN 1 AND 100),
+  ADD CONSTRAINT events_max_entries_required
+    CHECK (reentry_policy <> 'max_entries' OR max_entries IS NOT NULL);
+
+-- Denied re-entries and check-out scans
+ALTER TABLE verification_events
+DROP CONSTRAINT IF EXISTS verification_events_verification_result_check;
+
+ALTER TABLE verification_events
+ADD CONSTRAINT verification_events_verification_result_check CHECK (
+    verification_result IN (
+        'success',
+        'invalid_signature',
+        'card_not_found',
+        'invalid_payload',
+        'card_expired',
+        'card_revoked',
+        'card_suspended',
+        'card_deleted',
+        'token_reused',
+        'already_checked_in',
+        'checked_out'
+    )
+);
+
+-- Admission history of a card at an event
+CREATE INDEX idx_verification_events_event_card
+    ON verification_events(event_id, card_id, verified_at)
+    WHERE verification_result IN ('success', 'checked_out');
+
+COMMENT ON COLUMN events.reentry_policy IS 'single_entry, unlimited, after_checkout (re-entry only after a check-out scan) or max_entries';
+COMMENT ON COLUMN events.max_entries IS 'Admissions allowed per card when reentry_policy is max_entries';
@@ -0,0 +1,47 @@
+-- Event access rules: which issuers' cards an event accepts, and the lowest
+-- membership tier accepted per issuer. Tiers are ranked per issuer; a card's
+-- tier is its membership_level_label. Events without rules accept any valid
+-- card of their own issuer, as before.
+
+CREATE TABLE issuer_membership_tiers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    issuer_id UUID NOT NULL REFERENCES card_issuers(id) ON DELETE CASCADE,
+    label TEXT NOT NULL,
+    rank INT NOT NULL, -- 0 = lowest tier
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (issuer_id, label),
+    UNIQUE (issuer_id, rank)
+);
+
+CREATE TABLE event_access_rules (
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    issuer_id UUID NOT NULL REFERENCES card_issuers(id) ON DELETE CASCADE,
+    min_tier_label TEXT, -- NULL = any tier
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_id, issuer_id)
+);
+
+ALTER TABLE verification_events
+DROP CONSTRAINT IF EXISTS verification_events_verification_result_check;
+
+ALTER TABLE verification_events
+ADD CONSTRAINT verification_events_verification_result_check CHECK (
+    verification_result IN (
+        'success',
+        'invalid_signature',
+        'card_not_found',
+        'invalid_payload',
+        'card_expired',
+        'card_revoked',
+        'card_suspended',
+        'card_deleted',
+        'token_reused',
+        'already_checked_in',
+        'checked_out',
+        'issuer_not_accepted',
+        'tier_too_low'
+    )
+);
+
+COMMENT ON TABLE issuer_membership_tiers IS 'Membership tiers of an issuer, ranked lowest (0) to highest';
+COMMENT ON TABLE event_access_rules IS 'Issuers whose cards an event accepts, with the lowest accepted tier; no rows = the event''s own issuer, any tier';
@@ -0,0 +1,59 @@
+-- Event capacity and RSVPs. A member holding a valid card the event accepts
+-- reserves a spot; once the event is full further RSVPs join a waitlist, promoted
+-- in order as spots free up. At the gate, reserved cards are always admitted and
+-- walk-ins only while reserved spots plus admitted walk-ins stay below capacity.
+
+ALTER TABLE events
+  ADD COLUMN capacity INT
+    CHECK (capacity > 0); -- NULL = no limit
+
+CREATE TABLE event_reservations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    member_id UUID NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+    card_id UUID NOT NULL REFERENCES membership_cards(id) ON DELETE CASCADE,
+    status TEXT NOT NULL CHECK (status IN ('reserved', 'waitlisted', 'cancelled')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), -- waitlist order
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cancelled_at TIMESTAMPTZ
+);
+
+-- One open RSVP per member and event
+CREATE UNIQUE INDEX idx_event_reservations_open_member
+    ON event_reservations(event_id, member_id)
+    WHERE status <> 'cancelled';
+
+CREATE INDEX idx_event_reservations_event_card
+    ON event_reservations(event_id, card_id)
+    WHERE status = 'reserved';
+
+CREATE INDEX idx_event_reservations_waitlist
+    ON event_reservations(event_id, created_at)
+    WHERE status = 'waitlisted';
+
+-- Walk-ins turned away at a full event
+ALTER TABLE verification_events
+DROP CONSTRAINT IF EXISTS verification_events_verification_result_check;
+
+ALTER TABLE verification_events
+ADD CONSTRAINT verification_events_verification_result_check CHECK (
+    verification_result IN (
+        'success',
+        'invalid_signature',
+        'card_not_found',
+        'invalid_payload',
+        'card_expired',
+        'card_revoked',
+        'card_suspended',
+        'card_deleted',
+        'token_reused',
+        'already_checked_in',
+        'checked_out',
+        'issuer_not_accepted',
+        'tier_too_low',
+        'capacity_reached'
+    )
+);
+
+COMMENT ON COLUMN events.capacity IS 'Most attendees (reserved spots plus admitted walk-ins); NULL = no limit';
+COMMENT ON TABLE event_reservations IS 'RSVPs of card holders: reserved, waitlisted (promoted oldest first) or cancelled';
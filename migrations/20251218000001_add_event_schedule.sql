@@ -0,0 +1,56 @@
+-- Event start/end times in the event's IANA time zone, an optional check-in
+-- window, and automatic closing. Existing events become all-day events in
+-- Asia/Taipei; event_date stays as the local date the event starts.
+
+ALTER TABLE events
+  ADD COLUMN timezone TEXT NOT NULL DEFAULT 'Asia/Taipei',
+  ADD COLUMN starts_at TIMESTAMPTZ,
+  ADD COLUMN ends_at TIMESTAMPTZ,
+  ADD COLUMN checkin_opens_at TIMESTAMPTZ,  -- NULL = two hours before starts_at
+  ADD COLUMN checkin_closes_at TIMESTAMPTZ, -- NULL = ends_at
+  ADD COLUMN closed_at TIMESTAMPTZ;         -- set when the scheduler closed the event
+
+UPDATE events
+SET starts_at = event_date::timestamp AT TIME ZONE 'Asia/Taipei',
+    ends_at = (event_date + 1)::timestamp AT TIME ZONE 'Asia/Taipei';
+
+ALTER TABLE events
+  ALTER COLUMN starts_at SET NOT NULL,
+  ALTER COLUMN ends_at SET NOT NULL,
+  ADD CONSTRAINT events_ends_after_start CHECK (ends_at > starts_at),
+  ADD CONSTRAINT events_checkin_window_order
+    CHECK (checkin_opens_at IS NULL OR checkin_closes_at IS NULL OR checkin_closes_at > checkin_opens_at);
+
+-- Active events due to be closed
+CREATE INDEX idx_events_active_ends_at ON events(ends_at) WHERE is_active = TRUE;
+
+-- Valid cards scanned outside the check-in window
+ALTER TABLE verification_events
+DROP CONSTRAINT IF EXISTS verification_events_verification_result_check;
+
+ALTER TABLE verification_events
+ADD CONSTRAINT verification_events_verification_result_check CHECK (
+    verification_result IN (
+        'success',
+        'invalid_signature',
+        'card_not_found',
+        'invalid_payload',
+        'card_expired',
+        'card_revoked',
+        'card_suspended',
+        'card_deleted',
+        'token_reused',
+        'already_checked_in',
+        'checked_out',
+        'issuer_not_accepted',
+        'tier_too_low',
+        'capacity_reached',
+        'checkin_not_open',
+        'checkin_closed'
+    )
+);
+
+COMMENT ON COLUMN events.timezone IS 'IANA time zone the event''s times are entered and shown in';
+COMMENT ON COLUMN events.checkin_opens_at IS 'Start of the check-in window; NULL = two hours before starts_at';
+COMMENT ON COLUMN events.checkin_closes_at IS 'End of the check-in window; NULL = ends_at';
+COMMENT ON COLUMN events.closed_at IS 'When the event was closed automatically after its check-in window';
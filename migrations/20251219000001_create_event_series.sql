@@ -0,0 +1,68 @@
+-- Recurring event series. A series holds a recurrence rule and the details its
+-- occurrences share; occurrences are ordinary events materialized ahead of time
+-- by the scheduler and linked back through events.series_id. An occurrence edited
+-- on its own keeps its edits when the series changes; a cancelled occurrence is
+-- never materialized again.
+
+CREATE TABLE event_series (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    issuer_id UUID NOT NULL REFERENCES card_issuers(id) ON DELETE CASCADE,
+    series_name TEXT NOT NULL,
+    event_description TEXT,
+    event_location TEXT,
+    verifier_ref TEXT NOT NULL,
+    reentry_policy TEXT NOT NULL DEFAULT 'unlimited'
+        CHECK (reentry_policy IN ('single_entry', 'unlimited', 'after_checkout', 'max_entries')),
+    max_entries INT CHECK (max_entries BETWEEN 1 AND 100),
+    capacity INT CHECK (capacity > 0), -- NULL = no limit
+    timezone TEXT NOT NULL,
+    start_time TIME NOT NULL,          -- local time each occurrence starts
+    duration_minutes INT NOT NULL CHECK (duration_minutes > 0),
+    checkin_opens_before_minutes INT,  -- NULL = two hours before the start
+    checkin_closes_after_minutes INT,  -- after the start; NULL = at the end
+    recurrence TEXT NOT NULL
+        CHECK (recurrence IN ('weekly', 'monthly_by_day', 'monthly_by_weekday')),
+    recurrence_interval INT NOT NULL DEFAULT 1 CHECK (recurrence_interval BETWEEN 1 AND 12),
+    starts_on DATE NOT NULL,           -- first occurrence; sets the weekday / day of month
+    repeat_until DATE,                 -- NULL = no end
+    materialized_until DATE,           -- occurrences exist up to this local date
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    ended_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (reentry_policy <> 'max_entries' OR max_entries IS NOT NULL),
+    CHECK (repeat_until IS NULL OR repeat_until >= starts_on)
+);
+
+CREATE INDEX idx_event_series_issuer ON event_series(issuer_id);
+
+CREATE TRIGGER update_event_series_updated_at
+    BEFORE UPDATE ON event_series
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Access rules copied to every upcoming occurrence
+CREATE TABLE event_series_access_rules (
+    series_id UUID NOT NULL REFERENCES event_series(id) ON DELETE CASCADE,
+    issuer_id UUID NOT NULL REFERENCES card_issuers(id) ON DELETE CASCADE,
+    min_tier_label TEXT, -- NULL = any tier
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (series_id, issuer_id)
+);
+
+ALTER TABLE events
+  ADD COLUMN series_id UUID REFERENCES event_series(id) ON DELETE SET NULL,
+  ADD COLUMN occurrence_date DATE,  -- the series date this occurrence was materialized for
+  ADD COLUMN series_overridden BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN cancelled_at TIMESTAMPTZ;
+
+-- One occurrence per series date, even after it was moved or cancelled
+CREATE UNIQUE INDEX idx_events_series_occurrence ON events(series_id, occurrence_date)
+    WHERE series_id IS NOT NULL;
+
+COMMENT ON TABLE event_series IS 'Recurring events; occurrences are materialized into events ahead of time';
+COMMENT ON COLUMN event_series.recurrence IS 'weekly, monthly_by_day (same day of month) or monthly_by_weekday (e.g. second Saturday)';
+COMMENT ON COLUMN event_series.materialized_until IS 'Last local date occurrences were materialized for';
+COMMENT ON TABLE event_series_access_rules IS 'Access rules shared by a series'' occurrences; no rows = the series'' own issuer, any tier';
+COMMENT ON COLUMN events.series_overridden IS 'Edited on its own; series changes no longer apply';
+COMMENT ON COLUMN events.cancelled_at IS 'When an organizer cancelled (deactivated) the event';
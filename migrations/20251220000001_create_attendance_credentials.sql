@@ -0,0 +1,35 @@
+-- Proof-of-attendance credentials
+-- Every card admitted to an event earns an attendance record; the member can
+-- claim it from their cards page as a wallet credential (event name, date and
+-- issuer) issued with the event issuer's attendance credential template.
+
+ALTER TABLE card_issuers
+    ADD COLUMN attendance_vc_uid TEXT;
+
+COMMENT ON COLUMN card_issuers.attendance_vc_uid IS 'Taiwan Digital Wallet VC UID of the attendance credential; NULL = not offered';
+
+CREATE TABLE attendance_credentials (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    card_id UUID NOT NULL REFERENCES membership_cards(id) ON DELETE CASCADE,
+    admitted_at TIMESTAMPTZ NOT NULL, -- first admission at the event
+    transaction_id TEXT,              -- latest wallet offer; NULL until claimed
+    wallet_qr_code TEXT,
+    wallet_deep_link TEXT,
+    offered_at TIMESTAMPTZ,
+    cid TEXT,                         -- set once the member accepted the credential
+    claimed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (event_id, card_id)
+);
+
+CREATE INDEX idx_attendance_credentials_card ON attendance_credentials(card_id);
+
+-- Cards already admitted before attendance credentials existed
+INSERT INTO attendance_credentials (event_id, card_id, admitted_at)
+SELECT event_id, card_id, MIN(verified_at)
+FROM verification_events
+WHERE verification_result = 'success' AND card_id IS NOT NULL
+GROUP BY event_id, card_id;
+
+COMMENT ON TABLE attendance_credentials IS 'One row per card admitted to an event; claimed ones were accepted into the wallet';
@@ -0,0 +1,23 @@
+-- Loyalty credential fields
+-- Credentials can carry the member's continuous tenure with the channel (in
+-- months and since when) and how many events they attended, computed from the
+-- member's card history and verification_events whenever the card is offered.
+
+ALTER TABLE issuer_credential_fields
+DROP CONSTRAINT IF EXISTS issuer_credential_fields_source_check;
+
+ALTER TABLE issuer_credential_fields
+ADD CONSTRAINT issuer_credential_fields_source_check CHECK (
+    source IN (
+        'display_name',
+        'tier_label',
+        'channel_name',
+        'member_since',
+        'card_id',
+        'expires_at',
+        'tenure_months',
+        'tenure_since',
+        'events_attended'
+    )
+);
+
@@ -0,0 +1,92 @@
+-- Status list index per issued credential
+-- A lost-device reissue revokes the old credential while the card stays active,
+-- so a card-wide index would keep showing the stolen device's credential as
+-- valid. Each credential now gets its own index: its bits follow the card's
+-- status until the credential itself is revoked, then stay invalid. Cards keep
+-- their index for credentials signed before this change.
+--
+-- The card trigger fires AFTER UPDATE, so every status transition (revoke,
+-- suspend, expire, and the soft delete that sets status 'deleted') flips the
+-- bits. Rows removed with DELETE are not covered and keep their last bits.
+
+ALTER TABLE openid_credential_offers
+    ADD COLUMN status_list_index INTEGER;
+
+CREATE UNIQUE INDEX idx_openid_credential_offers_status_list_index
+    ON openid_credential_offers(issuer_id, status_list_index)
+    WHERE status_list_index IS NOT NULL;
+
+COMMENT ON COLUMN openid_credential_offers.status_list_index IS 'Index of the issued credential in its issuer''s credential status list';
+
+-- Status values of the token status list: 0 valid, 1 invalid, 2 suspended
+CREATE OR REPLACE FUNCTION credential_status_value(status card_status, revoked_at TIMESTAMPTZ)
+RETURNS INTEGER AS $$
+    SELECT CASE
+        WHEN revoked_at IS NOT NULL THEN 1
+        WHEN status = 'active' THEN 0
+        WHEN status = 'suspended' THEN 2
+        ELSE 1
+    END;
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION set_credential_status(list_issuer_id UUID, list_index INTEGER, status_value INTEGER)
+RETURNS VOID AS $$
+    UPDATE credential_status_lists
+    SET statuses = set_bit(
+            set_bit(statuses, list_index * 2, status_value & 1),
+            list_index * 2 + 1, (status_value >> 1) & 1
+        ),
+        updated_at = NOW()
+    WHERE issuer_id = list_issuer_id;
+$$ LANGUAGE sql;
+
+-- A card's status change flips its own bits and those of its unrevoked credentials
+CREATE OR REPLACE FUNCTION sync_credential_status()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status_list_index IS NOT NULL THEN
+        PERFORM set_credential_status(
+            NEW.issuer_id, NEW.status_list_index, credential_status_value(NEW.status, NULL)
+        );
+    END IF;
+
+    PERFORM set_credential_status(
+        o.issuer_id, o.status_list_index, credential_status_value(NEW.status, o.revoked_at)
+    )
+    FROM openid_credential_offers o
+    WHERE o.card_id = NEW.id AND o.status_list_index IS NOT NULL;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS sync_membership_card_credential_status ON membership_cards;
+
+CREATE TRIGGER sync_membership_card_credential_status
+    AFTER UPDATE OF status, status_list_index ON membership_cards
+    FOR EACH ROW
+    WHEN (OLD.status IS DISTINCT FROM NEW.status
+          OR OLD.status_list_index IS DISTINCT FROM NEW.status_list_index)
+    EXECUTE FUNCTION sync_credential_status();
+
+-- A credential's index assignment or revocation writes its own bits
+CREATE OR REPLACE FUNCTION sync_offer_credential_status()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM set_credential_status(
+        NEW.issuer_id, NEW.status_list_index, credential_status_value(c.status, NEW.revoked_at)
+    )
+    FROM membership_cards c
+    WHERE c.id = NEW.card_id;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER sync_openid_credential_offer_status
+    AFTER UPDATE OF revoked_at, status_list_index ON openid_credential_offers
+    FOR EACH ROW
+    WHEN (NEW.status_list_index IS NOT NULL
+          AND (OLD.revoked_at IS DISTINCT FROM NEW.revoked_at
+               OR OLD.status_list_index IS DISTINCT FROM NEW.status_list_index))
+    EXECUTE FUNCTION sync_offer_credential_status();
@@ -0,0 +1,9 @@
+-- Expected issuer of credentials from an issuer's own wallet account
+-- Credentials returned by a channel's own Taiwan Digital Wallet issuer account are
+-- signed by that organization, not by the one in WALLET_CREDENTIAL_ISSUER, so
+-- they are checked against the issuer identifier configured with the account.
+
+ALTER TABLE card_issuers
+  ADD COLUMN wallet_credential_issuer TEXT;
+
+COMMENT ON COLUMN card_issuers.wallet_credential_issuer IS 'Issuer identifier (iss) of credentials from this issuer''s own wallet account (NULL = not verified)';
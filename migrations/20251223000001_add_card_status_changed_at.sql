@@ -0,0 +1,31 @@
+-- Card status change timestamp
+-- Records when a card's status last changed, so the end of a card's active
+-- period is known even when it was expired, revoked or suspended without its
+-- expires_at being touched (e.g. by the subscription checker).
+
+ALTER TABLE membership_cards
+ADD COLUMN status_changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Best estimate for existing cards: active since issued, otherwise since
+-- deleted or last (unsuccessfully) verified
+UPDATE membership_cards
+SET status_changed_at = CASE
+    WHEN status = 'active' THEN issued_at
+    ELSE COALESCE(deleted_at, last_verified_at, expires_at, issued_at)
+END;
+
+CREATE OR REPLACE FUNCTION touch_card_status_changed_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.status_changed_at := NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER touch_membership_card_status_changed_at
+    BEFORE UPDATE OF status ON membership_cards
+    FOR EACH ROW
+    WHEN (OLD.status IS DISTINCT FROM NEW.status)
+    EXECUTE FUNCTION touch_card_status_changed_at();
+
+COMMENT ON COLUMN membership_cards.status_changed_at IS 'When the card last changed status (e.g. left active)';
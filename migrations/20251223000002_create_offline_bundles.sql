@@ -0,0 +1,14 @@
+-- Offline scanning bundles issued to scanners
+-- Uploaded scans name the bundle they were verified with; its validity window
+-- bounds when the scans can have happened, whatever time the device reports.
+
+CREATE TABLE offline_bundles (
+    id VARCHAR(64) PRIMARY KEY,
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    issued_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_offline_bundles_event ON offline_bundles(event_id);
+
+COMMENT ON TABLE offline_bundles IS 'Signed event bundles issued to offline scanners (the `jti`, `iat` and `exp` they were signed with)';
@@ -0,0 +1,13 @@
+-- Track how long what a signing key signed stays valid
+-- A retired key stays published until the last credential or token it signed
+-- expires; credentials without an expiry keep it published for good.
+
+ALTER TABLE signing_keys
+    ADD COLUMN signed_until TIMESTAMPTZ,
+    ADD COLUMN signed_unexpiring BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- What existing keys signed is not known, so they stay published
+UPDATE signing_keys SET signed_unexpiring = TRUE;
+
+COMMENT ON COLUMN signing_keys.signed_until IS 'Latest expiry of what the key signed';
+COMMENT ON COLUMN signing_keys.signed_unexpiring IS 'Whether the key signed a credential that never expires';
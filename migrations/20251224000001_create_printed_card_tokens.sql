@@ -0,0 +1,24 @@
+-- Tokens printed on membership cards
+-- A printed card's QR code carries a signed token whose nonce is a per-card
+-- serial. Printing the card again revokes the previous serial, and a serial is
+-- accepted once per event, so a photo of the card cannot be replayed.
+
+CREATE TABLE printed_card_tokens (
+    serial VARCHAR(64) PRIMARY KEY,
+    card_id UUID NOT NULL REFERENCES membership_cards(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_printed_card_tokens_card ON printed_card_tokens(card_id)
+    WHERE revoked_at IS NULL;
+
+CREATE TABLE printed_card_token_uses (
+    serial VARCHAR(64) NOT NULL REFERENCES printed_card_tokens(serial) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    used_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (serial, event_id)
+);
+
+COMMENT ON TABLE printed_card_tokens IS 'Serials of the tokens printed on cards; only the latest print of a card is valid';
+COMMENT ON TABLE printed_card_token_uses IS 'Events a printed card token was scanned at (once per event)';
@@ -4,11 +4,11 @@ use axum::{
     http::{header, StatusCode},
     middleware,
     response::{IntoResponse, Redirect, Response},
-    routing::{delete, get},
-    Router,
+    routing::{delete, get, post},
+    Form, Json, Router,
 };
+use base64::{engine::general_purpose::STANDARD, Engine};
 use chrono::{DateTime, Utc};
-use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
 use uuid::Uuid;
@@ -18,9 +18,24 @@ use crate::api::middleware::{
     session::{AppState, SESSION_KEY_SESSION_STARTED_AT},
 };
 use crate::models::{
-    card::MembershipCard, issuer::CardIssuer, oauth_session::OAuthSession,
+    attendance_credential::{AttendanceCredential, AttendanceRecord},
+    background_task::{BackgroundTask, TaskPayload},
+    card::{CardStatus, MembershipCard},
+    event::Event,
+    issuer::CardIssuer,
+    member::Member,
+    oauth_session::OAuthSession,
+    suspension::CardSuspension,
+    wallet_offer::WalletOffer,
+};
+use crate::services::{
+    attendance_credentials::{self, AttendanceCredentialError},
+    card_issuer,
+    loyalty::{self, Loyalty},
+    presentation_token,
+    qr_render::{self, CardFace, QrRenderOptions},
+    wallet_accounts, wallet_provider, wallet_qr,
 };
-use crate::services::{card_issuer, wallet_qr};
 
 #[derive(Debug)]
 pub enum CardsError {
@@ -30,6 +45,13 @@ pub enum CardsError {
     SessionError(String),
     NotFound,
     WalletQrError(wallet_qr::WalletQrError),
+    InvalidQuery(String),
+    WalletNotReady,
+    CardInactive,
+    RenderError(qr_render::QrRenderError),
+    WalletAccountError(wallet_accounts::WalletAccountError),
+    PresentationTokenError(presentation_token::PresentationTokenError),
+    AttendanceError(AttendanceCredentialError),
 }
 
 impl IntoResponse for CardsError {
@@ -55,6 +77,44 @@ impl IntoResponse for CardsError {
                 }
                 (StatusCode::BAD_REQUEST, format!("Wallet QR error: {}", e))
             }
+            CardsError::InvalidQuery(msg) => (StatusCode::BAD_REQUEST, msg),
+            CardsError::WalletNotReady => (
+                StatusCode::CONFLICT,
+                "Wallet QR code not generated yet".to_string(),
+            ),
+            CardsError::CardInactive => (StatusCode::CONFLICT, "Card is not active".to_string()),
+            CardsError::RenderError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Render error: {}", e),
+            ),
+            CardsError::WalletAccountError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Wallet account error: {}", e),
+            ),
+            CardsError::PresentationTokenError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Presentation token error: {}", e),
+            ),
+            CardsError::AttendanceError(e) => match e {
+                AttendanceCredentialError::WalletQr(e) => {
+                    return CardsError::WalletQrError(e).into_response()
+                }
+                AttendanceCredentialError::NotFound => {
+                    (StatusCode::NOT_FOUND, "Attendance not found".to_string())
+                }
+                AttendanceCredentialError::NotOffered | AttendanceCredentialError::NoOffer => {
+                    (StatusCode::CONFLICT, e.to_string())
+                }
+                AttendanceCredentialError::DatabaseError(_)
+                | AttendanceCredentialError::WalletAccount(_) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Attendance credential error: {}", e),
+                ),
+                AttendanceCredentialError::InvalidCredentialField(_) => (
+                    StatusCode::BAD_REQUEST,
+                    format!("Attendance credential error: {}", e),
+                ),
+            },
         };
 
         (status, message).into_response()
@@ -66,6 +126,18 @@ impl IntoResponse for CardsError {
 #[template(path = "cards/show.html")]
 struct ShowCardTemplate {
     card: MembershipCard,
+    loyalty: Loyalty,
+    suspension: Option<CardSuspension>,
+    offers: Vec<WalletOffer>,
+    is_authenticated: bool,
+}
+
+#[derive(Template)]
+#[template(path = "cards/present.html")]
+struct PresentCardTemplate {
+    card: MembershipCard,
+    issuer: CardIssuer,
+    token_ttl_secs: i64,
     is_authenticated: bool,
 }
 
@@ -81,10 +153,34 @@ struct ClaimCardTemplate {
 #[template(path = "cards/list.html")]
 struct MyCardsTemplate {
     cards: Vec<MembershipCard>,
+    attendances: Vec<AttendanceRecord>,
     success_message: Option<String>,
     is_authenticated: bool,
 }
 
+#[derive(Template)]
+#[template(path = "cards/attendance.html")]
+struct AttendanceTemplate {
+    attendance: AttendanceCredential,
+    event: Event,
+    issuer: CardIssuer,
+    card: MembershipCard,
+    claimable: bool,
+    is_authenticated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct QrImageQuery {
+    format: Option<String>,
+    size: Option<u32>,
+    margin: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrintCardQuery {
+    format: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct MyCardsQuery {
     deleted: Option<bool>,
@@ -121,7 +217,7 @@ async fn claim_card_for_channel(
         .await
         .map_err(CardsError::AuthError)?;
 
-    let member_record = crate::models::member::Member::find_by_id(&state.pool, member.member_id)
+    let member_record = Member::find_by_id(&state.pool, member.member_id)
         .await
         .map_err(CardsError::DatabaseError)?
         .ok_or(CardsError::AuthError(
@@ -189,18 +285,9 @@ async fn claim_card_for_channel(
         .map_err(|e| CardsError::SessionError(e.to_string()))?
         .with_timezone(&Utc);
 
-    // Prepare wallet API configuration if available
-    let issuer_api_config = state.config.issuer_api_url.as_ref().and_then(|url| {
-        state
-            .config
-            .issuer_access_token
-            .as_ref()
-            .map(|token| (url.as_str(), token.expose_secret().as_str()))
-    });
-
     let result = card_issuer::issue_card(
         &state.pool,
-        issuer_api_config,
+        &state.config,
         card_issuer::IssueCardRequest {
             issuer_id,
             member_youtube_user_id: member_record.youtube_user_id,
@@ -213,7 +300,11 @@ async fn claim_card_for_channel(
     .await
     .map_err(CardsError::IssuanceError)?;
 
-    tracing::info!(card_id = %result.card.id, "Card issued successfully");
+    tracing::info!(
+        card_id = %result.card.id,
+        reused = result.reused,
+        "Card issued successfully"
+    );
 
     Ok(axum::response::Redirect::to(&format!("/cards/{}", result.card.id)).into_response())
 }
@@ -236,19 +327,190 @@ async fn show_card(
         return Err(CardsError::NotFound);
     }
 
+    let suspension = CardSuspension::find_open_for_card(&state.pool, card.id)
+        .await
+        .map_err(CardsError::DatabaseError)?;
+
     // Card already contains wallet QR data (no separate table lookup needed)
+    let offers = WalletOffer::list_by_card(&state.pool, card.id)
+        .await
+        .map_err(CardsError::DatabaseError)?;
+
+    let loyalty = loyalty::for_card(&state.pool, &card)
+        .await
+        .map_err(CardsError::DatabaseError)?;
+
     Ok(ShowCardTemplate {
         card,
+        loyalty,
+        suspension,
+        offers,
         is_authenticated: true,
     })
 }
 
+/// Renders the card's wallet offer (the deep link the wallet app opens) as a QR code
+/// `format` is `svg` (default), `png`, or `text` for the raw deep link.
 async fn card_qr(
     State(state): State<AppState>,
     Path(card_id): Path<Uuid>,
     session: Session,
+    Query(params): Query<QrImageQuery>,
 ) -> Result<Response, CardsError> {
-    let member = get_authenticated_member(&session)
+    let card = find_usable_card(&state, &session, card_id).await?;
+
+    let deep_link = card
+        .wallet_deep_link
+        .or(card.wallet_qr_code)
+        .ok_or(CardsError::WalletNotReady)?;
+    let options = QrRenderOptions::clamped(params.size, params.margin);
+
+    match params.format.as_deref().unwrap_or("svg") {
+        "svg" => {
+            let svg =
+                qr_render::render_qr_svg(&deep_link, options).map_err(CardsError::RenderError)?;
+            Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+        }
+        "png" => {
+            let png =
+                qr_render::render_qr_png(&deep_link, options).map_err(CardsError::RenderError)?;
+            Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+        }
+        "text" => Ok(([(header::CONTENT_TYPE, "text/plain")], deep_link).into_response()),
+        other => Err(CardsError::InvalidQuery(format!(
+            "Unsupported format: {}",
+            other
+        ))),
+    }
+}
+
+/// "Show my card" page: a rotating QR code event staff scan, for members without the wallet app
+async fn present_card(
+    State(state): State<AppState>,
+    Path(card_id): Path<Uuid>,
+    session: Session,
+) -> Result<PresentCardTemplate, CardsError> {
+    let card = find_usable_card(&state, &session, card_id).await?;
+
+    let issuer = CardIssuer::find_by_id(&state.pool, card.issuer_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+
+    Ok(PresentCardTemplate {
+        card,
+        issuer,
+        token_ttl_secs: presentation_token::PRESENTATION_TOKEN_TTL_SECS,
+        is_authenticated: true,
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct PresentationTokenResponse {
+    qrcode_image: String, // base64 PNG
+    expires_in_seconds: i64,
+}
+
+/// Signs a fresh presentation token for the card and renders it as a QR code
+/// The page fetches a new one before the previous expires, so a screenshot is
+/// useless after a minute (and after the first scan).
+async fn presentation_token_qr(
+    State(state): State<AppState>,
+    Path(card_id): Path<Uuid>,
+    session: Session,
+) -> Result<Response, CardsError> {
+    let card = find_usable_card(&state, &session, card_id).await?;
+
+    let issued = presentation_token::issue(&state.pool, &state.config, &card)
+        .await
+        .map_err(CardsError::PresentationTokenError)?;
+    let png = qr_render::render_qr_png(&issued.token, QrRenderOptions::default())
+        .map_err(CardsError::RenderError)?;
+
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(PresentationTokenResponse {
+            qrcode_image: STANDARD.encode(png),
+            expires_in_seconds: (issued.expires_at - Utc::now()).num_seconds(),
+        }),
+    )
+        .into_response())
+}
+
+/// Renders a printable card face (channel, tier, member name, expiry and a QR code)
+/// A printed card cannot carry the rotating presentation token, so its QR code is
+/// a long-lived token scanned once per event; printing again revokes the last print.
+async fn print_card(
+    State(state): State<AppState>,
+    Path(card_id): Path<Uuid>,
+    session: Session,
+    Query(params): Query<PrintCardQuery>,
+) -> Result<Response, CardsError> {
+    let card = find_usable_card(&state, &session, card_id).await?;
+
+    let issuer = CardIssuer::find_by_id(&state.pool, card.issuer_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+    let member = Member::find_by_id(&state.pool, card.member_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+
+    let printed = presentation_token::issue_printed(&state.pool, &state.config, &card)
+        .await
+        .map_err(CardsError::PresentationTokenError)?;
+    let qr_data = printed.token;
+    let card_id = card.id.to_string();
+    let face = CardFace {
+        channel_name: &issuer.channel_name,
+        tier_label: &card.membership_level_label,
+        member_name: &member.default_display_name,
+        expires_at: card.expires_at,
+        card_id: &card_id,
+        qr_data: &qr_data,
+    };
+
+    let format = params.format.as_deref().unwrap_or("png");
+    let (content_type, body) = match format {
+        "png" => (
+            "image/png",
+            qr_render::render_card_png(&face)
+                .await
+                .map_err(CardsError::RenderError)?,
+        ),
+        "svg" => (
+            "image/svg+xml",
+            qr_render::render_card_svg(&face)
+                .map_err(CardsError::RenderError)?
+                .into_bytes(),
+        ),
+        other => {
+            return Err(CardsError::InvalidQuery(format!(
+                "Unsupported format: {}",
+                other
+            )))
+        }
+    };
+    let disposition = format!("inline; filename=\"vpass-card-{}.{}\"", card.id, format);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Loads one of the member's own cards that can currently be presented
+async fn find_usable_card(
+    state: &AppState,
+    session: &Session,
+    card_id: Uuid,
+) -> Result<MembershipCard, CardsError> {
+    let member = get_authenticated_member(session)
         .await
         .map_err(CardsError::AuthError)?;
 
@@ -261,17 +523,11 @@ async fn card_qr(
         return Err(CardsError::NotFound);
     }
 
-    // Get wallet QR code from card (now stored directly on card)
-    let wallet_qr_data = card
-        .wallet_qr_code
-        .unwrap_or_else(|| "Not available".to_string());
+    if card.status != CardStatus::Active || card.is_expired() {
+        return Err(CardsError::CardInactive);
+    }
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "text/plain")],
-        wallet_qr_data,
-    )
-        .into_response())
+    Ok(card)
 }
 
 async fn my_cards(
@@ -287,6 +543,11 @@ async fn my_cards(
         .await
         .map_err(CardsError::DatabaseError)?;
 
+    let attendances =
+        attendance_credentials::list_for_member(&state.pool, &state.config, member.member_id)
+            .await
+            .map_err(CardsError::DatabaseError)?;
+
     let success_message = if params.deleted == Some(true) {
         Some("Card deleted successfully".to_string())
     } else {
@@ -295,6 +556,7 @@ async fn my_cards(
 
     Ok(MyCardsTemplate {
         cards,
+        attendances,
         success_message,
         is_authenticated: true,
     })
@@ -307,7 +569,7 @@ struct PollCredentialResponse {
     message: String,
 }
 
-/// Polls the Taiwan Digital Wallet API to check credential status and store CID
+/// Polls the wallet provider to check credential status and store CID
 async fn poll_credential(
     State(state): State<AppState>,
     Path(card_id): Path<Uuid>,
@@ -344,30 +606,27 @@ async fn poll_credential(
         }));
     }
 
-    // Poll the wallet API
-    let issuer_api_url = state.config.issuer_api_url.as_deref().ok_or_else(|| {
-        CardsError::WalletQrError(wallet_qr::WalletQrError::ApiError(
-            "Issuer API URL not configured. Set ISSUER_API_URL.".to_string(),
-        ))
-    })?;
-
-    let issuer_access_token = state
-        .config
-        .issuer_access_token
-        .as_ref()
-        .map(|token| token.expose_secret().as_str());
-
-    let credential_response = wallet_qr::poll_credential_status(
-        issuer_api_url,
-        issuer_access_token,
-        transaction_id,
-    )
-    .await
-    .map_err(CardsError::WalletQrError)?;
-
-    // Extract CID from JWT
-    let cid = wallet_qr::extract_cid_from_jwt(&credential_response.credential)
-        .map_err(CardsError::WalletQrError)?;
+    // Poll the provider (and wallet account) the offer was made with
+    let issuer = CardIssuer::find_by_id(&state.pool, card.issuer_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+    let kind = wallet_provider::kind_of_transaction(&state.pool, transaction_id)
+        .await
+        .map_err(CardsError::DatabaseError)?;
+    let wallet = wallet_provider::provider_of_kind(&state.pool, &state.config, &issuer, kind)
+        .map_err(CardsError::WalletAccountError)?
+        .ok_or_else(|| {
+            CardsError::WalletQrError(wallet_qr::WalletQrError::ApiError(
+                "Issuer API URL not configured. Set ISSUER_API_URL.".to_string(),
+            ))
+        })?;
+
+    let cid = wallet
+        .poll_issuance(transaction_id)
+        .await
+        .map_err(CardsError::WalletQrError)?
+        .cid;
 
     // Store the CID in the database
     MembershipCard::mark_wallet_scanned(&state.pool, card_id, cid.clone())
@@ -388,6 +647,165 @@ async fn poll_credential(
     }))
 }
 
+#[derive(Debug, Serialize)]
+struct WalletStatusResponse {
+    ready: bool,
+}
+
+/// Reports whether a wallet-pending card has received its QR code
+async fn wallet_status(
+    State(state): State<AppState>,
+    Path(card_id): Path<Uuid>,
+    session: Session,
+) -> Result<axum::Json<WalletStatusResponse>, CardsError> {
+    let member = get_authenticated_member(&session)
+        .await
+        .map_err(CardsError::AuthError)?;
+
+    let card = MembershipCard::find_by_id(&state.pool, card_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+
+    if card.member_id != member.member_id {
+        return Err(CardsError::NotFound);
+    }
+
+    Ok(axum::Json(WalletStatusResponse {
+        ready: card.wallet_qr_code.is_some(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReofferForm {
+    lost_device: Option<String>,
+}
+
+/// Generates a fresh wallet offer for the card (expired QR or lost device)
+async fn reoffer_wallet(
+    State(state): State<AppState>,
+    Path(card_id): Path<Uuid>,
+    session: Session,
+    Form(form): Form<ReofferForm>,
+) -> Result<Redirect, CardsError> {
+    let member = get_authenticated_member(&session)
+        .await
+        .map_err(CardsError::AuthError)?;
+
+    let card = MembershipCard::find_by_id(&state.pool, card_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+
+    if card.member_id != member.member_id {
+        return Err(CardsError::NotFound);
+    }
+
+    card_issuer::reoffer_wallet(
+        &state.pool,
+        &state.config,
+        card.id,
+        form.lost_device.is_some(),
+    )
+    .await
+    .map_err(CardsError::IssuanceError)?;
+
+    Ok(Redirect::to(&format!("/cards/{}", card.id)))
+}
+
+/// Loads one of the member's attendances with the card it was recorded for
+async fn find_member_attendance(
+    state: &AppState,
+    session: &Session,
+    attendance_id: Uuid,
+) -> Result<(AttendanceCredential, MembershipCard), CardsError> {
+    let member = get_authenticated_member(session)
+        .await
+        .map_err(CardsError::AuthError)?;
+
+    let attendance = AttendanceCredential::find_by_id(&state.pool, attendance_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+
+    let card = MembershipCard::find_by_id(&state.pool, attendance.card_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+
+    if card.member_id != member.member_id {
+        return Err(CardsError::NotFound);
+    }
+
+    Ok((attendance, card))
+}
+
+/// Attendance credential page: the wallet offer to scan, or that it was claimed
+async fn show_attendance(
+    State(state): State<AppState>,
+    Path(attendance_id): Path<Uuid>,
+    session: Session,
+) -> Result<AttendanceTemplate, CardsError> {
+    let (attendance, card) = find_member_attendance(&state, &session, attendance_id).await?;
+
+    let event = Event::find_by_id(&state.pool, attendance.event_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+    let issuer = CardIssuer::find_by_id(&state.pool, event.issuer_id)
+        .await
+        .map_err(CardsError::DatabaseError)?
+        .ok_or(CardsError::NotFound)?;
+
+    let claimable = attendance_credentials::is_offered(&state.config, &issuer);
+
+    Ok(AttendanceTemplate {
+        attendance,
+        event,
+        issuer,
+        card,
+        claimable,
+        is_authenticated: true,
+    })
+}
+
+/// Offers the attendance credential to the member's wallet
+async fn claim_attendance(
+    State(state): State<AppState>,
+    Path(attendance_id): Path<Uuid>,
+    session: Session,
+) -> Result<Redirect, CardsError> {
+    let (attendance, _) = find_member_attendance(&state, &session, attendance_id).await?;
+
+    attendance_credentials::offer(&state.pool, &state.config, attendance.id)
+        .await
+        .map_err(CardsError::AttendanceError)?;
+
+    Ok(Redirect::to(&format!(
+        "/cards/attendance/{}",
+        attendance.id
+    )))
+}
+
+/// Polls the wallet provider until the member accepted the attendance credential
+async fn poll_attendance_credential(
+    State(state): State<AppState>,
+    Path(attendance_id): Path<Uuid>,
+    session: Session,
+) -> Result<axum::Json<PollCredentialResponse>, CardsError> {
+    let (attendance, _) = find_member_attendance(&state, &session, attendance_id).await?;
+
+    let cid = attendance_credentials::poll_claim(&state.pool, &state.config, &attendance)
+        .await
+        .map_err(CardsError::AttendanceError)?;
+
+    Ok(axum::Json(PollCredentialResponse {
+        status: "ready".to_string(),
+        cid: Some(cid),
+        message: "Attendance credential claimed".to_string(),
+    }))
+}
+
 async fn delete_card(
     State(state): State<AppState>,
     session: Session,
@@ -407,10 +825,27 @@ async fn delete_card(
         return Err(CardsError::NotFound);
     }
 
-    // Soft delete the card
-    MembershipCard::soft_delete(&state.pool, id)
+    // Soft delete the card; revoke its wallet credential in the background
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(CardsError::DatabaseError)?;
+
+    MembershipCard::soft_delete(&mut *tx, id)
+        .await
+        .map_err(CardsError::DatabaseError)?;
+
+    if let Some(cid) = card.wallet_cid {
+        BackgroundTask::enqueue(
+            &mut *tx,
+            &TaskPayload::RevokeWalletCredential { card_id: id, cid },
+        )
         .await
         .map_err(CardsError::DatabaseError)?;
+    }
+
+    tx.commit().await.map_err(CardsError::DatabaseError)?;
 
     tracing::info!(
         member_id = %member.member_id,
@@ -433,7 +868,18 @@ pub fn router() -> Router<AppState> {
         .route("/cards/:id", get(show_card))
         .route("/cards/:id", delete(delete_card))
         .route("/cards/:id/qr", get(card_qr))
+        .route("/cards/:id/print", get(print_card))
+        .route("/cards/:id/present", get(present_card))
+        .route("/cards/:id/presentation-token", get(presentation_token_qr))
         .route("/cards/:id/poll-credential", get(poll_credential))
+        .route("/cards/:id/wallet-status", get(wallet_status))
+        .route("/cards/:id/reoffer", post(reoffer_wallet))
+        .route("/cards/attendance/:id", get(show_attendance))
+        .route("/cards/attendance/:id/claim", post(claim_attendance))
+        .route(
+            "/cards/attendance/:id/poll-credential",
+            get(poll_attendance_credential),
+        )
         .route(
             "/channels/:issuer_id/claim",
             axum::routing::post(claim_card_for_channel),
@@ -2,17 +2,46 @@ use askama::Template;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
+    middleware,
     response::IntoResponse,
     routing::{get, post},
     Form, Json, Router,
 };
-use chrono::NaiveDate;
-use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use tower_sessions::Session;
 use uuid::Uuid;
 
-use crate::api::middleware::session::{AppState, SESSION_KEY_MEMBER_ID};
-use crate::models::event::{CreateEventData, Event, UpdateEventData};
+use crate::api::middleware::{
+    auth::{get_authenticated_member, require_auth, AuthError, AuthenticatedMember},
+    session::{AppState, SESSION_KEY_MEMBER_ID},
+};
+use crate::models::attendance_credential::AttendanceCredential;
+use crate::models::event::{
+    CreateEventData, Event, EventSchedule, ReentryPolicy, UpdateEventData, DEFAULT_TIMEZONE,
+};
+use crate::models::event_access_rule::{AccessRuleData, EventAccessRule, SeriesAccessRule};
+use crate::models::event_reservation::{EventReservation, ReservationCounts};
+use crate::models::event_series::{
+    CreateEventSeriesData, EventSeries, Recurrence, RecurrenceRule, UpdateEventSeriesData,
+};
+use crate::models::issuer::CardIssuer;
+use crate::models::membership_tier::MembershipTier;
+use crate::models::scanner_device::ScannerDevice;
+use crate::models::verification_event::{DeviceScanCounts, SeriesAttendance, VerificationEvent};
+use crate::services::{
+    event_access::{self, AccessRuleError},
+    event_series,
+    qr_render::{self, QrRenderError, QrRenderOptions},
+    reservations::{self, ReservationError},
+    scanner_devices, wallet_accounts,
+};
+
+/// Window the per-gate scan rate on the event page is measured over
+const GATE_RATE_WINDOW_MINUTES: i64 = 15;
 
 #[derive(Debug)]
 pub enum EventError {
@@ -20,6 +49,8 @@ pub enum EventError {
     NotFound,
     ValidationError(String),
     SessionError(String),
+    AuthError(AuthError),
+    RenderError(QrRenderError),
 }
 
 impl IntoResponse for EventError {
@@ -35,6 +66,11 @@ impl IntoResponse for EventError {
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Session error: {}", msg),
             ),
+            EventError::AuthError(e) => return e.into_response(),
+            EventError::RenderError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("QR render error: {}", e),
+            ),
         };
 
         (status, message).into_response()
@@ -62,6 +98,91 @@ struct ShowEventTemplate {
     event: Event,
     issuer: crate::models::issuer::CardIssuer,
     stats: EventStats,
+    gates: Vec<GateThroughput>,
+    access_rows: Vec<AccessRuleRow>,
+    spots: EventSpots,
+    rsvp: Option<RsvpPanel>,
+    is_authenticated: bool,
+}
+
+/// Capacity and RSVPs of an event
+struct EventSpots {
+    capacity: Option<i32>,
+    occupancy: i64,
+    counts: ReservationCounts,
+}
+
+impl EventSpots {
+    fn has_room(&self) -> bool {
+        reservations::has_room(self.capacity, self.occupancy)
+    }
+
+    fn capacity_label(&self) -> String {
+        match self.capacity {
+            Some(capacity) => format!("{} / {} 人", self.occupancy, capacity),
+            None => "不限人數".to_string(),
+        }
+    }
+}
+
+/// The signed-in member's RSVP for the event
+struct RsvpPanel {
+    reservation: Option<EventReservation>,
+    waitlist_position: Option<i64>,
+    has_eligible_card: bool,
+}
+
+/// One issuer in the event's access rule form
+struct AccessRuleRow {
+    issuer_id: Uuid,
+    channel_name: String,
+    accepted: bool,
+    min_tier: String,   // empty = any tier
+    tiers: Vec<String>, // the issuer's ranked tiers, lowest first
+}
+
+#[derive(Template)]
+#[template(path = "events/series.html")]
+struct SeriesTemplate {
+    series: EventSeries,
+    issuer: CardIssuer,
+    occurrences: Vec<OccurrenceRow>,
+    attendance: SeriesAttendance,
+    access_rows: Vec<AccessRuleRow>,
+    is_authenticated: bool,
+}
+
+/// An occurrence of a series with the cards admitted to it
+struct OccurrenceRow {
+    event: Event,
+    attendees: i64,
+}
+
+impl OccurrenceRow {
+    fn is_upcoming(&self) -> bool {
+        self.event.is_active && self.event.starts_at > Utc::now()
+    }
+
+    fn status_label(&self) -> &'static str {
+        if self.event.cancelled_at.is_some() {
+            "已取消"
+        } else if self.is_upcoming() {
+            "即將舉行"
+        } else if self.event.is_active {
+            "進行中"
+        } else {
+            "已結束"
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "events/scanner_registered.html")]
+struct ScannerRegisteredTemplate {
+    event: Event,
+    device: ScannerDevice,
+    pairing_url: String,
+    pairing_qr_svg: String, // base64
     is_authenticated: bool,
 }
 
@@ -77,18 +198,169 @@ pub struct CreateEventRequest {
     pub issuer_id: Uuid,
     pub event_name: String,
     pub event_description: Option<String>,
-    pub event_date: NaiveDate,
+    /// Local start and end in `timezone`; an `event_date` alone makes an all-day event
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub starts_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub ends_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub event_date: Option<NaiveDate>,
+    /// IANA time zone (default Asia/Taipei)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Check-in window (default: two hours before the start until the end)
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub checkin_opens_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub checkin_closes_at: Option<NaiveDateTime>,
     pub event_location: Option<String>,
     pub verifier_ref: String,
+    /// Own verifier account for this event (optional; blank uses the issuer/global one)
+    #[serde(default)]
+    pub verifier_api_url: Option<String>,
+    #[serde(default)]
+    pub verifier_access_token: Option<String>,
+    /// "single_entry", "unlimited" (default), "after_checkout" or "max_entries"
+    #[serde(default)]
+    pub reentry_policy: Option<String>,
+    #[serde(default, deserialize_with = "optional_count")]
+    pub max_entries: Option<i32>,
+    /// Most attendees (blank = no limit)
+    #[serde(default, deserialize_with = "optional_count")]
+    pub capacity: Option<i32>,
+    /// "weekly", "monthly_by_day" or "monthly_by_weekday" starts a recurring
+    /// series with this event as its first occurrence; blank or "none" = one event
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    /// Every n weeks or months (default 1)
+    #[serde(default, deserialize_with = "optional_count")]
+    pub recurrence_interval: Option<i32>,
+    /// Last date of the series (blank = no end)
+    #[serde(default, deserialize_with = "optional_date")]
+    pub repeat_until: Option<NaiveDate>,
+}
+
+/// Details shared by a series' occurrences; applied to upcoming occurrences
+/// that were not edited on their own
+#[derive(Debug, Deserialize)]
+pub struct UpdateEventSeriesRequest {
+    pub series_name: Option<String>,
+    pub event_description: Option<String>,
+    pub event_location: Option<String>,
+    pub verifier_ref: Option<String>,
+    #[serde(default)]
+    pub reentry_policy: Option<String>,
+    #[serde(default, deserialize_with = "optional_count")]
+    pub max_entries: Option<i32>,
+    /// New capacity; 0 removes the limit
+    #[serde(default, deserialize_with = "optional_count")]
+    pub capacity: Option<i32>,
+}
+
+/// A series with its occurrences and attendance across them
+#[derive(Debug, Serialize)]
+pub struct EventSeriesResponse {
+    pub series: EventSeries,
+    pub occurrences: Vec<OccurrenceSummary>,
+    pub attendance: SeriesAttendance,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OccurrenceSummary {
+    #[serde(flatten)]
+    pub event: Event,
+    pub attendees: i64,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateEventRequest {
     pub event_name: Option<String>,
     pub event_description: Option<String>,
+    /// A new schedule replaces the old one, check-in window included
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub starts_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub ends_at: Option<NaiveDateTime>,
+    #[serde(default)]
     pub event_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub checkin_opens_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "optional_local_time")]
+    pub checkin_closes_at: Option<NaiveDateTime>,
     pub event_location: Option<String>,
     pub verifier_ref: Option<String>,
+    /// Replaces the event's own verifier account when a token is given
+    #[serde(default)]
+    pub verifier_api_url: Option<String>,
+    #[serde(default)]
+    pub verifier_access_token: Option<String>,
+    #[serde(default)]
+    pub reentry_policy: Option<String>,
+    #[serde(default, deserialize_with = "optional_count")]
+    pub max_entries: Option<i32>,
+    /// New capacity; 0 removes the limit
+    #[serde(default, deserialize_with = "optional_count")]
+    pub capacity: Option<i32>,
+}
+
+/// Reads a count sent as a JSON number or a form field; a blank field is None
+fn optional_count<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Count {
+        Number(i32),
+        Text(String),
+    }
+
+    match Option::<Count>::deserialize(deserializer)? {
+        Some(Count::Number(n)) => Ok(Some(n)),
+        Some(Count::Text(text)) if text.trim().is_empty() => Ok(None),
+        Some(Count::Text(text)) => text
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom("expected a whole number")),
+        None => Ok(None),
+    }
+}
+
+/// Reads a wall-clock time such as "2025-12-20T19:00" (as sent by
+/// `datetime-local` inputs); a blank field is None
+fn optional_local_time<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(text) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom("expected a local time like 2025-12-20T19:00"))
+}
+
+/// Reads a date such as "2026-06-30"; a blank field is None
+fn optional_date<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(text) if !text.trim().is_empty() => NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom("expected a date like 2026-06-30")),
+        _ => Ok(None),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -97,6 +369,43 @@ pub struct EventStats {
     pub successful_scans: i64,
     pub failed_scans: i64,
     pub unique_cards: i64,
+    pub claimed_credentials: i64, // attendance credentials accepted into a wallet
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterScannerForm {
+    pub name: String,
+    #[serde(default)]
+    pub operator_name: Option<String>,
+}
+
+/// A scanner device with the scans it recorded
+#[derive(Debug)]
+pub struct GateThroughput {
+    pub device: ScannerDevice,
+    pub counts: Option<DeviceScanCounts>,
+}
+
+impl GateThroughput {
+    pub fn total(&self) -> i64 {
+        self.counts.as_ref().map_or(0, |c| c.total)
+    }
+
+    pub fn successful(&self) -> i64 {
+        self.counts.as_ref().map_or(0, |c| c.successful)
+    }
+
+    /// Scans per minute over the last `GATE_RATE_WINDOW_MINUTES`
+    pub fn rate_label(&self) -> String {
+        let recent = self.counts.as_ref().map_or(0, |c| c.recent);
+        format!("{:.1}", recent as f64 / GATE_RATE_WINDOW_MINUTES as f64)
+    }
+
+    pub fn last_scan_label(&self) -> Option<String> {
+        self.counts
+            .as_ref()
+            .map(|c| c.last_scan_at.format("%m/%d %H:%M").to_string())
+    }
 }
 
 impl EventStats {
@@ -119,6 +428,200 @@ async fn is_authenticated(session: &Session) -> Result<bool, EventError> {
     Ok(member_id.is_some())
 }
 
+/// The signed-in member, if they are the event's organizer (signed in with the
+/// channel of the event's issuer)
+async fn require_organizer(
+    state: &AppState,
+    session: &Session,
+    event: &Event,
+) -> Result<AuthenticatedMember, EventError> {
+    require_issuer_organizer(state, session, event.issuer_id).await
+}
+
+/// The signed-in member, if they manage the issuer's channel and so may
+/// organize its events
+async fn require_issuer_organizer(
+    state: &AppState,
+    session: &Session,
+    issuer_id: Uuid,
+) -> Result<AuthenticatedMember, EventError> {
+    let member = get_authenticated_member(session)
+        .await
+        .map_err(EventError::AuthError)?;
+
+    let issuer = CardIssuer::find_by_id(&state.pool, issuer_id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+    if !issuer
+        .is_managed_by(&state.pool, member.member_id)
+        .await
+        .map_err(EventError::DatabaseError)?
+    {
+        return Err(EventError::AuthError(AuthError::Forbidden));
+    }
+
+    Ok(member)
+}
+
+/// A verifier account submitted with an event form; None when no token was entered
+struct VerifierAccountInput {
+    api_url: Option<String>,
+    access_token: String,
+}
+
+/// Validates an event's own verifier account before anything is saved
+fn parse_verifier_account(
+    state: &AppState,
+    api_url: Option<String>,
+    access_token: Option<String>,
+) -> Result<Option<VerifierAccountInput>, EventError> {
+    let Some(access_token) = access_token
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let api_url = api_url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty());
+    if let Some(url) = &api_url {
+        wallet_accounts::validate_api_url(url).map_err(EventError::ValidationError)?;
+    }
+
+    wallet_accounts::ensure_can_store_tokens(&state.config)
+        .map_err(|e| EventError::ValidationError(e.to_string()))?;
+
+    Ok(Some(VerifierAccountInput {
+        api_url,
+        access_token,
+    }))
+}
+
+/// Validates a submitted re-entry policy; None when no policy was given
+fn parse_reentry_policy(
+    kind: Option<String>,
+    max_entries: Option<i32>,
+) -> Result<Option<ReentryPolicy>, EventError> {
+    match kind.as_deref().map(str::trim).filter(|k| !k.is_empty()) {
+        Some(kind) => ReentryPolicy::parse(kind, max_entries)
+            .map(Some)
+            .map_err(EventError::ValidationError),
+        None => Ok(None),
+    }
+}
+
+/// Times submitted with an event form, in the event's time zone
+struct ScheduleInput {
+    timezone: Option<String>,
+    event_date: Option<NaiveDate>,
+    starts_at: Option<NaiveDateTime>,
+    ends_at: Option<NaiveDateTime>,
+    checkin_opens_at: Option<NaiveDateTime>,
+    checkin_closes_at: Option<NaiveDateTime>,
+}
+
+/// Validates submitted event times; None when no start, end or date was given
+fn parse_schedule(
+    input: ScheduleInput,
+    default_timezone: Tz,
+) -> Result<Option<EventSchedule>, EventError> {
+    let timezone = match input.timezone.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => {
+            EventSchedule::parse_timezone(name).map_err(EventError::ValidationError)?
+        }
+        _ => default_timezone,
+    };
+
+    let (starts_at, ends_at) = match (input.starts_at, input.ends_at, input.event_date) {
+        (Some(starts_at), Some(ends_at), _) => (starts_at, ends_at),
+        (Some(_), None, _) | (None, Some(_), _) => {
+            return Err(EventError::ValidationError(
+                "Both the start and the end time are required".to_string(),
+            ))
+        }
+        (None, None, Some(date)) => {
+            let start = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+            (start, start + Duration::days(1))
+        }
+        (None, None, None) => return Ok(None),
+    };
+
+    EventSchedule::from_local(
+        timezone,
+        starts_at,
+        ends_at,
+        input.checkin_opens_at,
+        input.checkin_closes_at,
+    )
+    .map(Some)
+    .map_err(EventError::ValidationError)
+}
+
+/// Validates a submitted capacity: None leaves it unchanged, 0 removes the limit
+fn parse_capacity(capacity: Option<i32>) -> Result<Option<Option<i32>>, EventError> {
+    match capacity {
+        None => Ok(None),
+        Some(0) => Ok(Some(None)),
+        Some(n) if n > 0 => Ok(Some(Some(n))),
+        Some(_) => Err(EventError::ValidationError(
+            "Capacity cannot be negative".to_string(),
+        )),
+    }
+}
+
+/// Whether an event form asks for a recurring series
+fn is_recurring(recurrence: &Option<String>) -> bool {
+    recurrence
+        .as_deref()
+        .map(str::trim)
+        .is_some_and(|kind| !kind.is_empty() && kind != "none")
+}
+
+/// Validates a submitted recurrence, starting on the first occurrence's local date
+fn parse_recurrence(
+    kind: &str,
+    interval: Option<i32>,
+    repeat_until: Option<NaiveDate>,
+    first: &EventSchedule,
+) -> Result<RecurrenceRule, EventError> {
+    let recurrence = Recurrence::parse(kind.trim()).map_err(EventError::ValidationError)?;
+    let interval = u32::try_from(interval.unwrap_or(1)).map_err(|_| {
+        EventError::ValidationError("Repeat interval cannot be negative".to_string())
+    })?;
+
+    RecurrenceRule::new(recurrence, interval, first.event_date(), repeat_until)
+        .map_err(EventError::ValidationError)
+}
+
+/// Encrypts and stores an event's own verifier account
+async fn store_verifier_account(
+    state: &AppState,
+    event_id: Uuid,
+    account: VerifierAccountInput,
+) -> Result<(), EventError> {
+    let token_encrypted = wallet_accounts::encrypt_token(
+        &state.config,
+        &wallet_accounts::event_verifier_token_context(event_id),
+        &account.access_token,
+    )
+    .map_err(|e| EventError::ValidationError(e.to_string()))?;
+
+    Event::update_verifier_account(
+        &state.pool,
+        event_id,
+        account.api_url.as_deref(),
+        Some(&token_encrypted),
+    )
+    .await
+    .map_err(EventError::DatabaseError)?;
+
+    tracing::info!(event_id = %event_id, "Event verifier account updated");
+
+    Ok(())
+}
+
 // Handlers
 
 /// List events (HTML)
@@ -180,11 +683,22 @@ async fn new_event_page(
     })
 }
 
-/// Create event (HTML form)
+/// Create event (HTML form); a recurrence creates a series instead
 async fn create_event_form(
     State(state): State<AppState>,
+    session: Session,
     Form(req): Form<CreateEventRequest>,
 ) -> Result<axum::response::Redirect, EventError> {
+    require_issuer_organizer(&state, &session, req.issuer_id).await?;
+
+    if is_recurring(&req.recurrence) {
+        let (series, _) = create_series(&state, req).await?;
+        return Ok(axum::response::Redirect::to(&format!(
+            "/events/series/{}",
+            series.id
+        )));
+    }
+
     // Validate
     if req.event_name.trim().is_empty() {
         return Err(EventError::ValidationError(
@@ -196,6 +710,23 @@ async fn create_event_form(
             "Verifier reference is required".to_string(),
         ));
     }
+    let verifier_account =
+        parse_verifier_account(&state, req.verifier_api_url, req.verifier_access_token)?;
+    let reentry_policy = parse_reentry_policy(req.reentry_policy, req.max_entries)?
+        .unwrap_or(ReentryPolicy::Unlimited);
+    let capacity = parse_capacity(req.capacity)?.flatten();
+    let schedule = parse_schedule(
+        ScheduleInput {
+            timezone: req.timezone,
+            event_date: req.event_date,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+            checkin_opens_at: req.checkin_opens_at,
+            checkin_closes_at: req.checkin_closes_at,
+        },
+        DEFAULT_TIMEZONE,
+    )?
+    .ok_or_else(|| EventError::ValidationError("Event start and end are required".to_string()))?;
 
     let event = Event::create(
         &state.pool,
@@ -203,14 +734,20 @@ async fn create_event_form(
             issuer_id: req.issuer_id,
             event_name: req.event_name,
             event_description: req.event_description,
-            event_date: req.event_date,
+            schedule,
             event_location: req.event_location,
             verifier_ref: req.verifier_ref,
+            reentry_policy,
+            capacity,
         },
     )
     .await
     .map_err(EventError::DatabaseError)?;
 
+    if let Some(account) = verifier_account {
+        store_verifier_account(&state, event.id, account).await?;
+    }
+
     tracing::info!(event_id = %event.id, event_name = %event.event_name, "Event created");
 
     Ok(axum::response::Redirect::to(&format!(
@@ -222,8 +759,17 @@ async fn create_event_form(
 /// Create event (JSON API)
 async fn create_event_json(
     State(state): State<AppState>,
+    session: Session,
     Json(req): Json<CreateEventRequest>,
 ) -> Result<(StatusCode, Json<Event>), EventError> {
+    require_issuer_organizer(&state, &session, req.issuer_id).await?;
+
+    if is_recurring(&req.recurrence) {
+        return Err(EventError::ValidationError(
+            "Create recurring events through /api/event-series".to_string(),
+        ));
+    }
+
     // Validate
     if req.event_name.trim().is_empty() {
         return Err(EventError::ValidationError(
@@ -235,6 +781,23 @@ async fn create_event_json(
             "Verifier reference is required".to_string(),
         ));
     }
+    let verifier_account =
+        parse_verifier_account(&state, req.verifier_api_url, req.verifier_access_token)?;
+    let reentry_policy = parse_reentry_policy(req.reentry_policy, req.max_entries)?
+        .unwrap_or(ReentryPolicy::Unlimited);
+    let capacity = parse_capacity(req.capacity)?.flatten();
+    let schedule = parse_schedule(
+        ScheduleInput {
+            timezone: req.timezone,
+            event_date: req.event_date,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+            checkin_opens_at: req.checkin_opens_at,
+            checkin_closes_at: req.checkin_closes_at,
+        },
+        DEFAULT_TIMEZONE,
+    )?
+    .ok_or_else(|| EventError::ValidationError("Event start and end are required".to_string()))?;
 
     let event = Event::create(
         &state.pool,
@@ -242,99 +805,990 @@ async fn create_event_json(
             issuer_id: req.issuer_id,
             event_name: req.event_name,
             event_description: req.event_description,
-            event_date: req.event_date,
+            schedule,
             event_location: req.event_location,
             verifier_ref: req.verifier_ref,
+            reentry_policy,
+            capacity,
         },
     )
     .await
     .map_err(EventError::DatabaseError)?;
 
+    if let Some(account) = verifier_account {
+        store_verifier_account(&state, event.id, account).await?;
+    }
+
     tracing::info!(event_id = %event.id, event_name = %event.event_name, "Event created");
 
     Ok((StatusCode::CREATED, Json(event)))
 }
 
-/// Get event details
-async fn show_event(
-    State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    session: Session,
-) -> Result<ShowEventTemplate, EventError> {
-    let event = Event::find_by_id(&state.pool, id)
+/// Creates a recurring series from an event form and materializes its
+/// upcoming occurrences
+async fn create_series(
+    state: &AppState,
+    req: CreateEventRequest,
+) -> Result<(EventSeries, Vec<Event>), EventError> {
+    if req.event_name.trim().is_empty() {
+        return Err(EventError::ValidationError(
+            "Event name is required".to_string(),
+        ));
+    }
+    if req.verifier_ref.trim().is_empty() {
+        return Err(EventError::ValidationError(
+            "Verifier reference is required".to_string(),
+        ));
+    }
+    // Own verifier tokens are encrypted per event, so occurrences cannot share one
+    if parse_verifier_account(state, req.verifier_api_url, req.verifier_access_token)?.is_some() {
+        return Err(EventError::ValidationError(
+            "A recurring series uses the issuer's verifier account; set an own account on each occurrence".to_string(),
+        ));
+    }
+    let reentry_policy = parse_reentry_policy(req.reentry_policy, req.max_entries)?
+        .unwrap_or(ReentryPolicy::Unlimited);
+    let capacity = parse_capacity(req.capacity)?.flatten();
+    let first = parse_schedule(
+        ScheduleInput {
+            timezone: req.timezone,
+            event_date: req.event_date,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+            checkin_opens_at: req.checkin_opens_at,
+            checkin_closes_at: req.checkin_closes_at,
+        },
+        DEFAULT_TIMEZONE,
+    )?
+    .ok_or_else(|| EventError::ValidationError("Event start and end are required".to_string()))?;
+    let rule = parse_recurrence(
+        req.recurrence.as_deref().unwrap_or_default(),
+        req.recurrence_interval,
+        req.repeat_until,
+        &first,
+    )?;
+
+    let today = Utc::now().with_timezone(&first.timezone).date_naive();
+    if rule.repeat_until.is_some_and(|until| until < today) {
+        return Err(EventError::ValidationError(
+            "The series ends before today".to_string(),
+        ));
+    }
+
+    let series = EventSeries::create(
+        &state.pool,
+        CreateEventSeriesData {
+            issuer_id: req.issuer_id,
+            series_name: req.event_name,
+            event_description: req.event_description,
+            event_location: req.event_location,
+            verifier_ref: req.verifier_ref,
+            reentry_policy,
+            capacity,
+            first,
+            rule,
+        },
+    )
+    .await
+    .map_err(EventError::DatabaseError)?;
+
+    let occurrences = event_series::materialize(&state.pool, &series, Utc::now())
         .await
-        .map_err(EventError::DatabaseError)?
-        .ok_or(EventError::NotFound)?;
+        .map_err(EventError::DatabaseError)?;
 
-    let issuer = crate::models::issuer::CardIssuer::find_by_id(&state.pool, event.issuer_id)
+    tracing::info!(
+        series_id = %series.id,
+        series_name = %series.series_name,
+        occurrences = occurrences.len(),
+        "Event series created"
+    );
+
+    Ok((series, occurrences))
+}
+
+/// Loads a series with its occurrences and the cards admitted to each
+async fn series_occurrences(
+    state: &AppState,
+    series_id: Uuid,
+) -> Result<(EventSeries, Vec<OccurrenceSummary>, SeriesAttendance), EventError> {
+    let series = EventSeries::find_by_id(&state.pool, series_id)
         .await
         .map_err(EventError::DatabaseError)?
         .ok_or(EventError::NotFound)?;
-
-    // Calculate stats
-    let total_scans =
-        crate::models::verification_event::VerificationEvent::count_by_event_and_result(
-            &state.pool,
-            id,
-            None,
-        )
+    let events = Event::list_by_series(&state.pool, series_id)
         .await
         .map_err(EventError::DatabaseError)?;
-
-    let successful_scans =
-        crate::models::verification_event::VerificationEvent::count_by_event_and_result(
-            &state.pool,
-            id,
-            Some("success"),
-        )
+    let counts = VerificationEvent::attendance_by_occurrence(&state.pool, series_id)
         .await
         .map_err(EventError::DatabaseError)?;
-
-    let unique_cards =
-        crate::models::verification_event::VerificationEvent::count_unique_cards_by_event(
-            &state.pool,
-            id,
-        )
+    let attendance = VerificationEvent::series_attendance(&state.pool, series_id)
         .await
         .map_err(EventError::DatabaseError)?;
 
-    let stats = EventStats {
-        total_scans,
-        successful_scans,
-        failed_scans: total_scans - successful_scans,
-        unique_cards,
-    };
-
-    let is_authenticated = is_authenticated(&session).await?;
+    let occurrences = events
+        .into_iter()
+        .map(|event| OccurrenceSummary {
+            attendees: counts
+                .iter()
+                .find(|c| c.event_id == event.id)
+                .map_or(0, |c| c.attendees),
+            event,
+        })
+        .collect();
 
-    Ok(ShowEventTemplate {
-        event,
-        issuer,
-        stats,
-        is_authenticated,
-    })
+    Ok((series, occurrences, attendance))
 }
 
-/// Get event (JSON)
-async fn get_event_json(
+/// Series page: occurrences, attendance across them and shared access rules
+async fn show_series(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Event>, EventError> {
-    let event = Event::find_by_id(&state.pool, id)
+    session: Session,
+) -> Result<SeriesTemplate, EventError> {
+    let (series, occurrences, attendance) = series_occurrences(&state, id).await?;
+
+    let issuer = CardIssuer::find_by_id(&state.pool, series.issuer_id)
         .await
         .map_err(EventError::DatabaseError)?
         .ok_or(EventError::NotFound)?;
 
-    Ok(Json(event))
+    let is_authenticated = is_authenticated(&session).await?;
+    let access_rows = if is_authenticated {
+        let rules = SeriesAccessRule::list_by_series(&state.pool, id)
+            .await
+            .map_err(EventError::DatabaseError)?
+            .iter()
+            .map(SeriesAccessRule::data)
+            .collect();
+        access_rule_rows(&state, series.issuer_id, rules).await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(SeriesTemplate {
+        series,
+        issuer,
+        occurrences: occurrences
+            .into_iter()
+            .map(|o| OccurrenceRow {
+                event: o.event,
+                attendees: o.attendees,
+            })
+            .collect(),
+        attendance,
+        access_rows,
+        is_authenticated,
+    })
 }
 
-/// Update event
-async fn update_event(
+/// Create a recurring series (JSON API)
+async fn create_series_json(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Json(req): Json<UpdateEventRequest>,
-) -> Result<Json<Event>, EventError> {
-    // Validate verifier_ref if provided
+    Json(req): Json<CreateEventRequest>,
+) -> Result<(StatusCode, Json<EventSeriesResponse>), EventError> {
+    if !is_recurring(&req.recurrence) {
+        return Err(EventError::ValidationError(
+            "A recurrence is required".to_string(),
+        ));
+    }
+
+    let (series, _) = create_series(&state, req).await?;
+    let (series, occurrences, attendance) = series_occurrences(&state, series.id).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(EventSeriesResponse {
+            series,
+            occurrences,
+            attendance,
+        }),
+    ))
+}
+
+/// Get a series with its occurrences and attendance (JSON API)
+async fn get_series_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EventSeriesResponse>, EventError> {
+    let (series, occurrences, attendance) = series_occurrences(&state, id).await?;
+
+    Ok(Json(EventSeriesResponse {
+        series,
+        occurrences,
+        attendance,
+    }))
+}
+
+/// Update a series' shared details (JSON API)
+/// Upcoming occurrences that were not edited on their own follow the change.
+async fn update_series_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Json(req): Json<UpdateEventSeriesRequest>,
+) -> Result<Json<EventSeries>, EventError> {
+    let series = EventSeries::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+    require_issuer_organizer(&state, &session, series.issuer_id).await?;
+
+    if req
+        .series_name
+        .as_deref()
+        .is_some_and(|name| name.trim().is_empty())
+    {
+        return Err(EventError::ValidationError(
+            "Series name cannot be empty".to_string(),
+        ));
+    }
+    if req
+        .verifier_ref
+        .as_deref()
+        .is_some_and(|verifier_ref| verifier_ref.trim().is_empty())
+    {
+        return Err(EventError::ValidationError(
+            "Verifier reference cannot be empty".to_string(),
+        ));
+    }
+    let reentry_policy = parse_reentry_policy(req.reentry_policy, req.max_entries)?;
+    let capacity = parse_capacity(req.capacity)?;
+
+    let series = EventSeries::update(
+        &state.pool,
+        id,
+        UpdateEventSeriesData {
+            series_name: req.series_name,
+            event_description: req.event_description,
+            event_location: req.event_location,
+            verifier_ref: req.verifier_ref,
+            reentry_policy,
+            capacity,
+        },
+    )
+    .await
+    .map_err(EventError::DatabaseError)?
+    .ok_or(EventError::NotFound)?;
+
+    let updated = event_series::apply_details(&state.pool, &series, capacity.is_some())
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    tracing::info!(series_id = %series.id, occurrences = updated, "Event series updated");
+
+    Ok(Json(series))
+}
+
+/// End a series, cancelling occurrences that have not started
+async fn end_series(state: &AppState, session: &Session, id: Uuid) -> Result<(), EventError> {
+    let series = EventSeries::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    require_issuer_organizer(state, session, series.issuer_id).await?;
+
+    event_series::end(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// End series (HTML form)
+async fn end_series_form(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<axum::response::Redirect, EventError> {
+    end_series(&state, &session, id).await?;
+
+    Ok(axum::response::Redirect::to(&format!(
+        "/events/series/{}",
+        id
+    )))
+}
+
+/// End series (JSON API)
+async fn end_series_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<StatusCode, EventError> {
+    end_series(&state, &session, id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Cancel a single event or occurrence (HTML form)
+async fn cancel_event_form(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<axum::response::Redirect, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    require_organizer(&state, &session, &event).await?;
+
+    Event::deactivate(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    tracing::info!(event_id = %id, series_id = ?event.series_id, "Event cancelled");
+
+    Ok(axum::response::Redirect::to(&match event.series_id {
+        Some(series_id) => format!("/events/series/{}", series_id),
+        None => format!("/events/{}", id),
+    }))
+}
+
+/// Get event details
+async fn show_event(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<ShowEventTemplate, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    let issuer = crate::models::issuer::CardIssuer::find_by_id(&state.pool, event.issuer_id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    // Calculate stats
+    let total_scans =
+        crate::models::verification_event::VerificationEvent::count_by_event_and_result(
+            &state.pool,
+            id,
+            None,
+        )
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    let successful_scans =
+        crate::models::verification_event::VerificationEvent::count_by_event_and_result(
+            &state.pool,
+            id,
+            Some("success"),
+        )
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    let unique_cards =
+        crate::models::verification_event::VerificationEvent::count_unique_cards_by_event(
+            &state.pool,
+            id,
+        )
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    let claimed_credentials = AttendanceCredential::count_claimed_by_event(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    let stats = EventStats {
+        total_scans,
+        successful_scans,
+        failed_scans: total_scans - successful_scans,
+        unique_cards,
+        claimed_credentials,
+    };
+
+    let spots = event_spots(&state, &event).await?;
+
+    let member_id: Option<Uuid> = session
+        .get(SESSION_KEY_MEMBER_ID)
+        .await
+        .map_err(|e| EventError::SessionError(e.to_string()))?;
+    let is_authenticated = member_id.is_some();
+    let rsvp = match member_id {
+        Some(member_id) => Some(rsvp_panel(&state, &event, member_id).await?),
+        None => None,
+    };
+
+    // Scanner devices and access rules are managed by signed-in organizers only
+    let (gates, access_rows) = if is_authenticated {
+        (
+            gate_throughput(&state, id).await?,
+            event_access_rule_rows(&state, &event).await?,
+        )
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    Ok(ShowEventTemplate {
+        event,
+        issuer,
+        stats,
+        gates,
+        access_rows,
+        spots,
+        rsvp,
+        is_authenticated,
+    })
+}
+
+async fn event_spots(state: &AppState, event: &Event) -> Result<EventSpots, EventError> {
+    let occupancy = EventReservation::occupancy(&state.pool, event.id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+    let counts = EventReservation::counts(&state.pool, event.id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(EventSpots {
+        capacity: event.capacity,
+        occupancy,
+        counts,
+    })
+}
+
+async fn rsvp_panel(
+    state: &AppState,
+    event: &Event,
+    member_id: Uuid,
+) -> Result<RsvpPanel, EventError> {
+    let reservation = EventReservation::find_open_for_member(&state.pool, event.id, member_id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    let waitlist_position = match &reservation {
+        Some(reservation) if reservation.is_waitlisted() => Some(
+            EventReservation::waitlist_position(&state.pool, reservation)
+                .await
+                .map_err(EventError::DatabaseError)?,
+        ),
+        _ => None,
+    };
+
+    let has_eligible_card = reservation.is_some()
+        || reservations::eligible_card(&state.pool, event, member_id)
+            .await
+            .map_err(EventError::DatabaseError)?
+            .is_some();
+
+    Ok(RsvpPanel {
+        reservation,
+        waitlist_position,
+        has_eligible_card,
+    })
+}
+
+/// A member's RSVP with their place on the waitlist
+#[derive(Debug, Serialize)]
+pub struct RsvpResponse {
+    pub reservation: EventReservation,
+    pub waitlist_position: Option<i64>,
+}
+
+fn reservation_error(error: ReservationError) -> EventError {
+    match error {
+        ReservationError::DatabaseError(e) => EventError::DatabaseError(e),
+        e @ (ReservationError::EventInactive | ReservationError::NoEligibleCard) => {
+            EventError::ValidationError(e.to_string())
+        }
+    }
+}
+
+/// Reserve a spot, or join the waitlist of a full event
+async fn rsvp(
+    state: &AppState,
+    session: &Session,
+    event_id: Uuid,
+) -> Result<RsvpResponse, EventError> {
+    let member = get_authenticated_member(session)
+        .await
+        .map_err(EventError::AuthError)?;
+
+    let event = Event::find_by_id(&state.pool, event_id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    let reservation = reservations::reserve(&state.pool, &event, member.member_id)
+        .await
+        .map_err(reservation_error)?;
+
+    let waitlist_position = if reservation.is_waitlisted() {
+        Some(
+            EventReservation::waitlist_position(&state.pool, &reservation)
+                .await
+                .map_err(EventError::DatabaseError)?,
+        )
+    } else {
+        None
+    };
+
+    Ok(RsvpResponse {
+        reservation,
+        waitlist_position,
+    })
+}
+
+/// Cancel the member's RSVP; false if they had none
+async fn cancel_rsvp(
+    state: &AppState,
+    session: &Session,
+    event_id: Uuid,
+) -> Result<bool, EventError> {
+    let member = get_authenticated_member(session)
+        .await
+        .map_err(EventError::AuthError)?;
+
+    let event = Event::find_by_id(&state.pool, event_id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    reservations::cancel(&state.pool, &event, member.member_id)
+        .await
+        .map_err(EventError::DatabaseError)
+}
+
+/// RSVP (HTML form)
+async fn rsvp_form(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<axum::response::Redirect, EventError> {
+    rsvp(&state, &session, id).await?;
+
+    Ok(axum::response::Redirect::to(&format!("/events/{}", id)))
+}
+
+/// Cancel RSVP (HTML form)
+async fn cancel_rsvp_form(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<axum::response::Redirect, EventError> {
+    cancel_rsvp(&state, &session, id).await?;
+
+    Ok(axum::response::Redirect::to(&format!("/events/{}", id)))
+}
+
+/// RSVP (JSON API)
+async fn rsvp_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<Json<RsvpResponse>, EventError> {
+    Ok(Json(rsvp(&state, &session, id).await?))
+}
+
+/// Cancel RSVP (JSON API)
+async fn cancel_rsvp_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<StatusCode, EventError> {
+    if cancel_rsvp(&state, &session, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(EventError::NotFound)
+    }
+}
+
+/// List the event's open RSVPs, reserved first, then the waitlist (JSON API)
+async fn list_reservations_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<Json<Vec<EventReservation>>, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    require_organizer(&state, &session, &event).await?;
+
+    let reservations = EventReservation::list_open_by_event(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(Json(reservations))
+}
+
+/// Active issuers with whether and from which tier an event (or series) of
+/// `own_issuer_id` accepts their cards under `rules`
+async fn access_rule_rows(
+    state: &AppState,
+    own_issuer_id: Uuid,
+    rules: Vec<AccessRuleData>,
+) -> Result<Vec<AccessRuleRow>, EventError> {
+    let issuers = CardIssuer::list_active(&state.pool)
+        .await
+        .map_err(EventError::DatabaseError)?;
+    let issuer_ids: Vec<Uuid> = issuers.iter().map(|issuer| issuer.id).collect();
+    let tiers = MembershipTier::list_by_issuers(&state.pool, &issuer_ids)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(issuers
+        .into_iter()
+        .map(|issuer| {
+            let rule = rules.iter().find(|rule| rule.issuer_id == issuer.id);
+            AccessRuleRow {
+                // Without rules the event accepts its own issuer
+                accepted: rule.is_some() || (rules.is_empty() && issuer.id == own_issuer_id),
+                min_tier: rule
+                    .and_then(|rule| rule.min_tier.clone())
+                    .unwrap_or_default(),
+                tiers: tiers
+                    .iter()
+                    .filter(|tier| tier.issuer_id == issuer.id)
+                    .map(|tier| tier.label.clone())
+                    .collect(),
+                issuer_id: issuer.id,
+                channel_name: issuer.channel_name,
+            }
+        })
+        .collect())
+}
+
+async fn event_access_rule_rows(
+    state: &AppState,
+    event: &Event,
+) -> Result<Vec<AccessRuleRow>, EventError> {
+    let rules = EventAccessRule::list_by_event(&state.pool, event.id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .into_iter()
+        .map(|rule| AccessRuleData {
+            issuer_id: rule.issuer_id,
+            min_tier: rule.min_tier_label,
+        })
+        .collect();
+
+    access_rule_rows(state, event.issuer_id, rules).await
+}
+
+/// Reads access rules from the form: `accept_<issuer_id>` for each accepted
+/// issuer and its `min_tier_<issuer_id>`
+fn access_rules_from_form(form: &HashMap<String, String>) -> Vec<AccessRuleData> {
+    form.keys()
+        .filter_map(|key| key.strip_prefix("accept_"))
+        .filter_map(|issuer_id| Uuid::parse_str(issuer_id).ok())
+        .map(|issuer_id| AccessRuleData {
+            issuer_id,
+            min_tier: form.get(&format!("min_tier_{}", issuer_id)).cloned(),
+        })
+        .collect()
+}
+
+/// Validates submitted access rules
+async fn validate_access_rules(
+    state: &AppState,
+    rules: Vec<AccessRuleData>,
+) -> Result<Vec<AccessRuleData>, EventError> {
+    if rules.is_empty() {
+        return Err(EventError::ValidationError(
+            "Accept the cards of at least one issuer".to_string(),
+        ));
+    }
+
+    event_access::validate_rules(&state.pool, rules)
+        .await
+        .map_err(|e| match e {
+            AccessRuleError::DatabaseError(e) => EventError::DatabaseError(e),
+            AccessRuleError::Invalid(msg) => EventError::ValidationError(msg),
+        })
+}
+
+/// Validates and stores an event's access rules
+async fn store_access_rules(
+    state: &AppState,
+    event_id: Uuid,
+    rules: Vec<AccessRuleData>,
+) -> Result<(), EventError> {
+    let rules = validate_access_rules(state, rules).await?;
+
+    // A series occurrence with its own rules keeps them when the series' rules change
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(EventError::DatabaseError)?;
+    EventAccessRule::write_for_event(&mut tx, event_id, &rules)
+        .await
+        .map_err(EventError::DatabaseError)?;
+    Event::mark_series_overridden(&mut *tx, event_id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+    tx.commit().await.map_err(EventError::DatabaseError)?;
+
+    tracing::info!(event_id = %event_id, issuers = rules.len(), "Updated event access rules");
+
+    Ok(())
+}
+
+/// Replace the event's access rules (HTML form)
+async fn update_access_rules_form(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<axum::response::Redirect, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    require_organizer(&state, &session, &event).await?;
+
+    store_access_rules(&state, id, access_rules_from_form(&form)).await?;
+
+    Ok(axum::response::Redirect::to(&format!("/events/{}", id)))
+}
+
+/// Get the event's access rules (JSON API); empty when only its own issuer is accepted
+async fn get_access_rules_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EventAccessRule>>, EventError> {
+    let rules = EventAccessRule::list_by_event(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(Json(rules))
+}
+
+/// Replace the event's access rules (JSON API)
+async fn update_access_rules_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Json(rules): Json<Vec<AccessRuleData>>,
+) -> Result<Json<Vec<EventAccessRule>>, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    require_organizer(&state, &session, &event).await?;
+
+    store_access_rules(&state, id, rules).await?;
+
+    let rules = EventAccessRule::list_by_event(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(Json(rules))
+}
+
+/// Validates and stores a series' access rules, replacing those of its
+/// upcoming occurrences
+async fn store_series_access_rules(
+    state: &AppState,
+    session: &Session,
+    series_id: Uuid,
+    rules: Vec<AccessRuleData>,
+) -> Result<(), EventError> {
+    let series = EventSeries::find_by_id(&state.pool, series_id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    require_issuer_organizer(state, session, series.issuer_id).await?;
+
+    let rules = validate_access_rules(state, rules).await?;
+
+    let occurrences = event_series::replace_access_rules(&state.pool, series_id, &rules)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    tracing::info!(
+        series_id = %series_id,
+        issuers = rules.len(),
+        occurrences,
+        "Updated series access rules"
+    );
+
+    Ok(())
+}
+
+/// Replace the series' access rules (HTML form)
+async fn update_series_access_rules_form(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<axum::response::Redirect, EventError> {
+    store_series_access_rules(&state, &session, id, access_rules_from_form(&form)).await?;
+
+    Ok(axum::response::Redirect::to(&format!(
+        "/events/series/{}",
+        id
+    )))
+}
+
+/// Get the series' access rules (JSON API); empty when only its own issuer is accepted
+async fn get_series_access_rules_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<SeriesAccessRule>>, EventError> {
+    let rules = SeriesAccessRule::list_by_series(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(Json(rules))
+}
+
+/// Replace the series' access rules (JSON API)
+async fn update_series_access_rules_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Json(rules): Json<Vec<AccessRuleData>>,
+) -> Result<Json<Vec<SeriesAccessRule>>, EventError> {
+    store_series_access_rules(&state, &session, id, rules).await?;
+
+    let rules = SeriesAccessRule::list_by_series(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    Ok(Json(rules))
+}
+
+/// An event's scanner devices with the scans each recorded
+async fn gate_throughput(
+    state: &AppState,
+    event_id: Uuid,
+) -> Result<Vec<GateThroughput>, EventError> {
+    let devices = ScannerDevice::list_by_event(&state.pool, event_id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
+    let mut counts = VerificationEvent::count_by_device(
+        &state.pool,
+        event_id,
+        Utc::now() - Duration::minutes(GATE_RATE_WINDOW_MINUTES),
+    )
+    .await
+    .map_err(EventError::DatabaseError)?;
+
+    Ok(devices
+        .into_iter()
+        .map(|device| {
+            let counts = counts
+                .iter()
+                .position(|c| c.device_id == device.id)
+                .map(|i| counts.swap_remove(i));
+            GateThroughput { device, counts }
+        })
+        .collect())
+}
+
+/// Register a scanner device (gate) for an event
+///
+/// The device token is only shown on the resulting page, as a pairing link and
+/// QR code that the scanner opens once.
+async fn register_scanner(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Form(form): Form<RegisterScannerForm>,
+) -> Result<ScannerRegisteredTemplate, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    let member = require_organizer(&state, &session, &event).await?;
+
+    let name = form.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > 100 {
+        return Err(EventError::ValidationError(
+            "Scanner name is required (at most 100 characters)".to_string(),
+        ));
+    }
+    let operator_name = form
+        .operator_name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    let registered = scanner_devices::register(
+        &state.pool,
+        event.id,
+        name,
+        operator_name,
+        Some(member.member_id),
+    )
+    .await
+    .map_err(EventError::DatabaseError)?;
+
+    let pairing_url = format!(
+        "{}/verify/{}/scanner#device={}",
+        state.config.base_url.trim_end_matches('/'),
+        event.id,
+        registered.token
+    );
+    let pairing_qr_svg = qr_render::render_qr_svg(&pairing_url, QrRenderOptions::default())
+        .map_err(EventError::RenderError)?;
+    let pairing_qr_svg = STANDARD.encode(pairing_qr_svg);
+
+    Ok(ScannerRegisteredTemplate {
+        event,
+        device: registered.device,
+        pairing_url,
+        pairing_qr_svg,
+        is_authenticated: true,
+    })
+}
+
+/// Revoke a scanner device; its token stops working immediately
+async fn revoke_scanner(
+    State(state): State<AppState>,
+    Path((id, device_id)): Path<(Uuid, Uuid)>,
+    session: Session,
+) -> Result<axum::response::Redirect, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    require_organizer(&state, &session, &event).await?;
+
+    // Only the event's own devices are revoked
+    if !ScannerDevice::revoke(&state.pool, event.id, device_id)
+        .await
+        .map_err(EventError::DatabaseError)?
+    {
+        return Err(EventError::NotFound);
+    }
+
+    tracing::info!(event_id = %id, device_id = %device_id, "Scanner device revoked");
+
+    Ok(axum::response::Redirect::to(&format!("/events/{}", id)))
+}
+
+/// Get event (JSON)
+async fn get_event_json(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Event>, EventError> {
+    let event = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+
+    Ok(Json(event))
+}
+
+/// Update event
+async fn update_event(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Json(req): Json<UpdateEventRequest>,
+) -> Result<Json<Event>, EventError> {
+    // Validate verifier_ref if provided
     if let Some(ref verifier_ref) = req.verifier_ref {
         if verifier_ref.trim().is_empty() {
             return Err(EventError::ValidationError(
@@ -342,6 +1796,27 @@ async fn update_event(
             ));
         }
     }
+    let verifier_account =
+        parse_verifier_account(&state, req.verifier_api_url, req.verifier_access_token)?;
+    let reentry_policy = parse_reentry_policy(req.reentry_policy, req.max_entries)?;
+    let capacity = parse_capacity(req.capacity)?;
+
+    let existing = Event::find_by_id(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?
+        .ok_or(EventError::NotFound)?;
+    require_organizer(&state, &session, &existing).await?;
+    let schedule = parse_schedule(
+        ScheduleInput {
+            timezone: req.timezone,
+            event_date: req.event_date,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+            checkin_opens_at: req.checkin_opens_at,
+            checkin_closes_at: req.checkin_closes_at,
+        },
+        existing.timezone(),
+    )?;
 
     let event = Event::update(
         &state.pool,
@@ -349,14 +1824,27 @@ async fn update_event(
         UpdateEventData {
             event_name: req.event_name,
             event_description: req.event_description,
-            event_date: req.event_date,
+            schedule,
             event_location: req.event_location,
             verifier_ref: req.verifier_ref,
+            reentry_policy,
+            capacity,
         },
     )
     .await
     .map_err(EventError::DatabaseError)?;
 
+    if let Some(account) = verifier_account {
+        store_verifier_account(&state, event.id, account).await?;
+    }
+
+    // Spots added (or the limit removed) go to the waitlist
+    if capacity.is_some() {
+        reservations::capacity_changed(&state.pool, &event)
+            .await
+            .map_err(EventError::DatabaseError)?;
+    }
+
     tracing::info!(event_id = %event.id, "Event updated");
 
     Ok(Json(event))
@@ -413,30 +1901,73 @@ async fn event_stats(
         .await
         .map_err(EventError::DatabaseError)?;
 
+    let claimed_credentials = AttendanceCredential::count_claimed_by_event(&state.pool, id)
+        .await
+        .map_err(EventError::DatabaseError)?;
+
     let stats = EventStats {
         total_scans,
         successful_scans,
         failed_scans: total_scans - successful_scans,
         unique_cards,
+        claimed_credentials,
     };
 
     Ok(Json(stats))
 }
 
 pub fn router() -> Router<AppState> {
+    let auth_routes = Router::new()
+        .route("/events/:id/scanners", post(register_scanner))
+        .route(
+            "/events/:id/scanners/:device_id/revoke",
+            post(revoke_scanner),
+        )
+        .route("/events/:id/access-rules", post(update_access_rules_form))
+        .route(
+            "/api/events/:id/access-rules",
+            get(get_access_rules_json).put(update_access_rules_json),
+        )
+        .route("/events/:id/rsvp", post(rsvp_form))
+        .route("/events/:id/rsvp/cancel", post(cancel_rsvp_form))
+        .route(
+            "/api/events/:id/rsvp",
+            post(rsvp_json).delete(cancel_rsvp_json),
+        )
+        .route("/api/events/:id/reservations", get(list_reservations_json))
+        .route("/events/:id/cancel", post(cancel_event_form))
+        .route("/events/series/:id/end", post(end_series_form))
+        .route(
+            "/events/series/:id/access-rules",
+            post(update_series_access_rules_form),
+        )
+        .route("/events/create", post(create_event_form))
+        .route("/api/events", post(create_event_json))
+        .route("/api/events/:id", axum::routing::put(update_event))
+        .route("/api/event-series", post(create_series_json))
+        .route(
+            "/api/event-series/:id",
+            axum::routing::put(update_series_json).delete(end_series_json),
+        )
+        .route(
+            "/api/event-series/:id/access-rules",
+            get(get_series_access_rules_json).put(update_series_access_rules_json),
+        )
+        .layer(middleware::from_fn(require_auth));
+
     Router::new()
         // HTML routes
         .route("/events", get(list_events_page))
         .route("/events/new", get(new_event_page))
-        .route("/events/create", post(create_event_form))
         .route("/events/:id", get(show_event))
+        .route("/events/series/:id", get(show_series))
         // JSON API routes
-        .route("/api/events", get(list_events_json).post(create_event_json))
+        .route("/api/events", get(list_events_json))
         .route(
             "/api/events/:id",
-            get(get_event_json)
-                .put(update_event)
-                .delete(deactivate_event),
+            get(get_event_json).delete(deactivate_event),
         )
         .route("/api/events/:id/stats", get(event_stats))
+        .route("/api/event-series/:id", get(get_series_json))
+        .merge(auth_routes)
 }
@@ -1,36 +1,59 @@
+use std::collections::HashMap;
+
 use askama::Template;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
+    middleware,
     response::{IntoResponse, Response},
     routing::{get, post},
     Form, Json, Router,
 };
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
 use uuid::Uuid;
 
-use crate::api::middleware::session::{AppState, SESSION_KEY_MEMBER_ID};
-use crate::models::issuer::{CardIssuer, CreateIssuerData};
-use crate::services::youtube_channel;
+use crate::api::middleware::{
+    auth::{get_authenticated_member, require_auth, AuthError, AuthenticatedMember},
+    session::{AppState, SESSION_KEY_MEMBER_ID},
+};
+use crate::models::{
+    card::MembershipCard,
+    credential_field::{CredentialFieldSource, IssuerCredentialField},
+    issuer::{CardIssuer, CreateIssuerData, LifecyclePolicy, WalletProviderKind},
+    member::Member,
+    membership_tier::MembershipTier,
+    signing_key::SigningKey,
+    suspension::{CardSuspension, CreateSuspensionData},
+};
+use crate::services::{
+    credential_schema, credential_verifier, signing_keys, wallet_accounts, youtube_channel,
+};
 
 #[derive(Debug)]
 pub enum IssuersError {
+    AuthError(AuthError),
     DatabaseError(sqlx::Error),
     NotFound,
+    CardNotFound,
     ValidationError(String),
     YouTubeApiError(youtube_channel::YouTubeChannelError),
     SessionError(String),
+    WalletAccountError(wallet_accounts::WalletAccountError),
+    SigningKeyError(signing_keys::SigningKeyError),
 }
 
 impl IntoResponse for IssuersError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
+            IssuersError::AuthError(e) => return e.into_response(),
             IssuersError::DatabaseError(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Database error: {}", e),
             ),
             IssuersError::NotFound => (StatusCode::NOT_FOUND, "Issuer not found".to_string()),
+            IssuersError::CardNotFound => (StatusCode::NOT_FOUND, "Card not found".to_string()),
             IssuersError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
             IssuersError::YouTubeApiError(e) => {
                 (StatusCode::BAD_REQUEST, format!("YouTube API error: {}", e))
@@ -39,6 +62,14 @@ impl IntoResponse for IssuersError {
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Session error: {}", msg),
             ),
+            IssuersError::WalletAccountError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Wallet account error: {}", e),
+            ),
+            IssuersError::SigningKeyError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Signing key error: {}", e),
+            ),
         };
 
         (status, message).into_response()
@@ -63,9 +94,36 @@ struct NewIssuerTemplate {
 #[template(path = "issuers/edit.html")]
 struct EditIssuerTemplate {
     issuer: CardIssuer,
+    credential_rows: Vec<CredentialFieldRow>,
+    sources: Vec<CredentialFieldSource>,
+    wallet_providers: Vec<WalletProviderKind>,
+    tiers_text: String, // ranked tiers, one per line, lowest first
+    issuer_did: String,
+    signing_keys: Vec<SigningKey>,
     is_authenticated: bool,
 }
 
+/// One row of the credential schema form; unused slots have an empty ename
+struct CredentialFieldRow {
+    index: usize,
+    ename: String,
+    source: String,
+}
+
+#[derive(Template)]
+#[template(path = "issuers/cards.html")]
+struct IssuerCardsTemplate {
+    issuer: CardIssuer,
+    cards: Vec<IssuerCardRow>,
+    is_authenticated: bool,
+}
+
+struct IssuerCardRow {
+    card: MembershipCard,
+    member: Option<Member>,
+    suspension: Option<CardSuspension>,
+}
+
 async fn is_authenticated(session: &Session) -> Result<bool, IssuersError> {
     let member_id: Option<Uuid> = session
         .get(SESSION_KEY_MEMBER_ID)
@@ -180,8 +238,44 @@ async fn edit_issuer_form(
 
     let is_authenticated = is_authenticated(&session).await?;
 
+    let schema = IssuerCredentialField::list_by_issuer(&state.pool, issuer.id)
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+    let mappings = credential_schema::mappings_for_issuer(&schema);
+    let credential_rows = (0..credential_schema::MAX_CREDENTIAL_FIELDS)
+        .map(|index| CredentialFieldRow {
+            index,
+            ename: mappings
+                .get(index)
+                .map(|m| m.ename.clone())
+                .unwrap_or_default(),
+            source: mappings
+                .get(index)
+                .map(|m| m.source.as_str().to_string())
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let signing_keys = signing_keys::published_keys(&state.pool, Some(issuer.id))
+        .await
+        .map_err(IssuersError::SigningKeyError)?;
+
+    let tiers_text = MembershipTier::list_by_issuer(&state.pool, issuer.id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .into_iter()
+        .map(|tier| tier.label)
+        .collect::<Vec<_>>()
+        .join("\n");
+
     Ok(EditIssuerTemplate {
+        issuer_did: signing_keys::issuer_did(&state.config, issuer.id),
         issuer,
+        credential_rows,
+        sources: CredentialFieldSource::ALL.to_vec(),
+        wallet_providers: WalletProviderKind::ALL.to_vec(),
+        tiers_text,
+        signing_keys,
         is_authenticated,
     })
 }
@@ -193,6 +287,22 @@ struct UpdateIssuerForm {
     verification_video_id: Option<String>,
     default_membership_label: Option<String>,
     vc_uid: Option<String>,
+    attendance_vc_uid: Option<String>,
+    initial_validity_days: Option<String>,
+    extension_days: Option<String>,
+    recheck_interval_hours: Option<String>,
+    failure_threshold: Option<String>,
+    grace_period_days: Option<String>,
+}
+
+/// Parses an optional numeric form field, falling back to the current value when blank
+fn parse_policy_field(value: Option<&str>, current: i32, name: &str) -> Result<i32, IssuersError> {
+    match value.map(str::trim) {
+        Some(v) if !v.is_empty() => v
+            .parse()
+            .map_err(|_| IssuersError::ValidationError(format!("{} must be a number", name))),
+        _ => Ok(current),
+    }
 }
 
 /// Update an existing issuer
@@ -207,6 +317,37 @@ async fn update_issuer(
         .map_err(IssuersError::DatabaseError)?
         .ok_or(IssuersError::NotFound)?;
 
+    // Validate the lifecycle policy before touching anything
+    let current_policy = issuer.lifecycle_policy();
+    let policy = LifecyclePolicy {
+        initial_validity_days: parse_policy_field(
+            form.initial_validity_days.as_deref(),
+            current_policy.initial_validity_days,
+            "Initial validity",
+        )?,
+        extension_days: parse_policy_field(
+            form.extension_days.as_deref(),
+            current_policy.extension_days,
+            "Extension length",
+        )?,
+        recheck_interval_hours: parse_policy_field(
+            form.recheck_interval_hours.as_deref(),
+            current_policy.recheck_interval_hours,
+            "Recheck interval",
+        )?,
+        failure_threshold: parse_policy_field(
+            form.failure_threshold.as_deref(),
+            current_policy.failure_threshold,
+            "Failure threshold",
+        )?,
+        grace_period_days: parse_policy_field(
+            form.grace_period_days.as_deref(),
+            current_policy.grace_period_days,
+            "Grace period",
+        )?,
+    };
+    policy.validate().map_err(IssuersError::ValidationError)?;
+
     // Update channel info if provided
     let channel_name = form.channel_name.filter(|s| !s.trim().is_empty());
     let channel_handle = form.channel_handle.filter(|s| !s.trim().is_empty());
@@ -226,6 +367,16 @@ async fn update_issuer(
     .await
     .map_err(IssuersError::DatabaseError)?;
 
+    // A blank attendance template stops offering attendance credentials
+    if let Some(attendance_vc_uid) = form.attendance_vc_uid {
+        let attendance_vc_uid = Some(attendance_vc_uid.trim()).filter(|s| !s.is_empty());
+        if attendance_vc_uid != issuer.attendance_vc_uid.as_deref() {
+            CardIssuer::update_attendance_vc_uid(&state.pool, id, attendance_vc_uid)
+                .await
+                .map_err(IssuersError::DatabaseError)?;
+        }
+    }
+
     // Update verification video if provided
     if let Some(video_id) = form.verification_video_id.filter(|s| !s.trim().is_empty()) {
         CardIssuer::update_verification_video(&state.pool, id, &video_id)
@@ -233,11 +384,381 @@ async fn update_issuer(
             .map_err(IssuersError::DatabaseError)?;
     }
 
+    if policy != current_policy {
+        CardIssuer::update_lifecycle_policy(&state.pool, id, &policy)
+            .await
+            .map_err(IssuersError::DatabaseError)?;
+
+        tracing::info!(issuer_id = %issuer.id, ?policy, "Updated card lifecycle policy");
+    }
+
     tracing::info!(issuer_id = %issuer.id, "Updated issuer");
 
     Ok(axum::response::Redirect::to("/issuers").into_response())
 }
 
+/// Replaces the issuer's credential schema
+/// The form posts `ename_<n>` / `source_<n>` pairs; rows with an empty field name are dropped.
+async fn update_credential_fields(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Response, IssuersError> {
+    let issuer = CardIssuer::find_by_id(&state.pool, id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .ok_or(IssuersError::NotFound)?;
+
+    require_issuer_admin(&state, &session, &issuer).await?;
+
+    let rows: Vec<(String, String)> = (0..credential_schema::MAX_CREDENTIAL_FIELDS)
+        .map(|i| {
+            (
+                form.get(&format!("ename_{}", i))
+                    .cloned()
+                    .unwrap_or_default(),
+                form.get(&format!("source_{}", i))
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+        })
+        .collect();
+
+    let mappings = credential_schema::parse_mappings(&rows)
+        .map_err(|e| IssuersError::ValidationError(e.to_string()))?;
+
+    IssuerCredentialField::replace_for_issuer(&state.pool, issuer.id, &mappings)
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+    tracing::info!(
+        issuer_id = %issuer.id,
+        fields = mappings.len(),
+        "Updated credential schema"
+    );
+
+    Ok(axum::response::Redirect::to(&format!("/issuers/{}/edit", issuer.id)).into_response())
+}
+
+#[derive(Deserialize)]
+struct MembershipTiersForm {
+    #[serde(default)]
+    tiers: String,
+}
+
+/// Replaces the issuer's ranked membership tiers (one label per line, lowest first)
+async fn update_membership_tiers(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Form(form): Form<MembershipTiersForm>,
+) -> Result<Response, IssuersError> {
+    let issuer = CardIssuer::find_by_id(&state.pool, id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .ok_or(IssuersError::NotFound)?;
+
+    require_issuer_admin(&state, &session, &issuer).await?;
+
+    let labels =
+        MembershipTier::parse_labels(&form.tiers).map_err(IssuersError::ValidationError)?;
+
+    MembershipTier::replace_for_issuer(&state.pool, issuer.id, &labels)
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+    tracing::info!(issuer_id = %issuer.id, tiers = labels.len(), "Updated membership tiers");
+
+    Ok(axum::response::Redirect::to(&format!("/issuers/{}/edit", issuer.id)).into_response())
+}
+
+#[derive(Deserialize)]
+struct WalletAccountsForm {
+    wallet_provider: Option<String>,
+    issuer_api_url: Option<String>,
+    issuer_access_token: Option<String>,
+    issuer_credential_issuer: Option<String>,
+    clear_issuer_account: Option<String>,
+    verifier_api_url: Option<String>,
+    verifier_access_token: Option<String>,
+    clear_verifier_account: Option<String>,
+}
+
+/// Change to one of the issuer's wallet accounts requested by the form
+enum AccountChange {
+    Unchanged,
+    /// Go back to the global account
+    Clear,
+    /// Store the URL, and the new token if one was entered (None keeps the stored token)
+    Update {
+        api_url: Option<String>,
+        access_token: Option<String>,
+    },
+}
+
+fn parse_account_change(
+    api_url: Option<String>,
+    access_token: Option<String>,
+    clear: bool,
+    stored_url: Option<&str>,
+    has_stored_token: bool,
+) -> Result<AccountChange, IssuersError> {
+    if clear {
+        return Ok(AccountChange::Clear);
+    }
+
+    let api_url = api_url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty());
+    if let Some(url) = &api_url {
+        wallet_accounts::validate_api_url(url).map_err(IssuersError::ValidationError)?;
+    }
+
+    let access_token = access_token
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+
+    match (access_token, has_stored_token) {
+        (Some(token), _) => Ok(AccountChange::Update {
+            api_url,
+            access_token: Some(token),
+        }),
+        (None, true) if api_url.as_deref() != stored_url => Ok(AccountChange::Update {
+            api_url,
+            access_token: None,
+        }),
+        // The global token is never sent to another URL, so a URL alone is not enough
+        (None, false) if api_url.is_some() => Err(IssuersError::ValidationError(
+            "An access token is required to use the channel's own wallet account".to_string(),
+        )),
+        (None, _) => Ok(AccountChange::Unchanged),
+    }
+}
+
+/// Column values stored for a wallet account
+struct AccountColumns {
+    api_url: Option<String>,
+    token_encrypted: Option<String>,
+}
+
+/// Returns the columns to store for an account change, or None if nothing changes
+fn account_columns(
+    config: &crate::config::Config,
+    change: AccountChange,
+    context: &str,
+    stored_token: Option<&str>,
+) -> Result<Option<AccountColumns>, IssuersError> {
+    match change {
+        AccountChange::Unchanged => Ok(None),
+        AccountChange::Clear => Ok(Some(AccountColumns {
+            api_url: None,
+            token_encrypted: None,
+        })),
+        AccountChange::Update {
+            api_url,
+            access_token,
+        } => {
+            let token_encrypted = match access_token {
+                Some(token) => Some(
+                    wallet_accounts::encrypt_token(config, context, &token)
+                        .map_err(IssuersError::WalletAccountError)?,
+                ),
+                None => stored_token.map(str::to_string),
+            };
+            Ok(Some(AccountColumns {
+                api_url,
+                token_encrypted,
+            }))
+        }
+    }
+}
+
+/// The signed-in member, if they manage the issuer (signed in with its channel)
+async fn require_issuer_admin(
+    state: &AppState,
+    session: &Session,
+    issuer: &CardIssuer,
+) -> Result<AuthenticatedMember, IssuersError> {
+    let member = get_authenticated_member(session)
+        .await
+        .map_err(IssuersError::AuthError)?;
+
+    if !issuer
+        .is_managed_by(&state.pool, member.member_id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+    {
+        return Err(IssuersError::AuthError(AuthError::Forbidden));
+    }
+
+    Ok(member)
+}
+
+/// Sets the issuer's wallet provider and its own wallet issuer/verifier accounts
+///
+/// Only the channel's own account may change them: the stored token is sent to
+/// whatever API URL is saved here.
+async fn update_wallet_accounts(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+    Form(form): Form<WalletAccountsForm>,
+) -> Result<Response, IssuersError> {
+    let issuer = CardIssuer::find_by_id(&state.pool, id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .ok_or(IssuersError::NotFound)?;
+
+    let admin = require_issuer_admin(&state, &session, &issuer).await?;
+
+    let wallet_provider = form
+        .wallet_provider
+        .as_deref()
+        .map(|value| {
+            WalletProviderKind::parse(value).ok_or_else(|| {
+                IssuersError::ValidationError(format!("Unknown wallet provider: {}", value))
+            })
+        })
+        .transpose()?;
+
+    // Validate both accounts before saving either
+    let credential_issuer = form
+        .issuer_credential_issuer
+        .map(|i| i.trim().to_string())
+        .filter(|i| !i.is_empty() && form.clear_issuer_account.is_none());
+    if let Some(credential_issuer) = &credential_issuer {
+        credential_verifier::validate_issuer_identifier(credential_issuer)
+            .map_err(IssuersError::ValidationError)?;
+    }
+    let issuer_change = parse_account_change(
+        form.issuer_api_url,
+        form.issuer_access_token,
+        form.clear_issuer_account.is_some(),
+        issuer.wallet_issuer_api_url.as_deref(),
+        issuer.has_own_issuer_account(),
+    )?;
+    let own_issuer_account = match &issuer_change {
+        AccountChange::Unchanged => issuer.has_own_issuer_account(),
+        AccountChange::Clear => false,
+        AccountChange::Update { .. } => true,
+    };
+    if own_issuer_account && credential_issuer.is_none() {
+        return Err(IssuersError::ValidationError(
+            "The credential issuer is required to verify credentials from the channel's own wallet account".to_string(),
+        ));
+    }
+    let verifier_change = parse_account_change(
+        form.verifier_api_url,
+        form.verifier_access_token,
+        form.clear_verifier_account.is_some(),
+        issuer.wallet_verifier_api_url.as_deref(),
+        issuer.has_own_verifier_account(),
+    )?;
+
+    let issuer_columns = account_columns(
+        &state.config,
+        issuer_change,
+        &wallet_accounts::issuer_token_context(issuer.id),
+        issuer.wallet_issuer_token_encrypted.as_deref(),
+    )?;
+    let verifier_columns = account_columns(
+        &state.config,
+        verifier_change,
+        &wallet_accounts::verifier_token_context(issuer.id),
+        issuer.wallet_verifier_token_encrypted.as_deref(),
+    )?;
+
+    if let Some(kind) = wallet_provider.filter(|kind| *kind != issuer.wallet_provider_kind()) {
+        CardIssuer::update_wallet_provider(&state.pool, issuer.id, kind.as_str())
+            .await
+            .map_err(IssuersError::DatabaseError)?;
+
+        tracing::info!(
+            issuer_id = %issuer.id,
+            admin_id = %admin.member_id,
+            wallet_provider = kind.as_str(),
+            "Updated wallet provider"
+        );
+    }
+
+    if let Some(columns) = issuer_columns {
+        CardIssuer::update_wallet_issuer_account(
+            &state.pool,
+            issuer.id,
+            columns.api_url.as_deref(),
+            columns.token_encrypted.as_deref(),
+        )
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+        tracing::info!(
+            issuer_id = %issuer.id,
+            admin_id = %admin.member_id,
+            own_account = columns.token_encrypted.is_some(),
+            "Updated wallet issuer account"
+        );
+    }
+
+    if credential_issuer != issuer.wallet_credential_issuer {
+        CardIssuer::update_wallet_credential_issuer(
+            &state.pool,
+            issuer.id,
+            credential_issuer.as_deref(),
+        )
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+        tracing::info!(
+            issuer_id = %issuer.id,
+            admin_id = %admin.member_id,
+            credential_issuer = ?credential_issuer,
+            "Updated wallet credential issuer"
+        );
+    }
+
+    if let Some(columns) = verifier_columns {
+        CardIssuer::update_wallet_verifier_account(
+            &state.pool,
+            issuer.id,
+            columns.api_url.as_deref(),
+            columns.token_encrypted.as_deref(),
+        )
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+        tracing::info!(
+            issuer_id = %issuer.id,
+            admin_id = %admin.member_id,
+            own_account = columns.token_encrypted.is_some(),
+            "Updated wallet verifier account"
+        );
+    }
+
+    Ok(axum::response::Redirect::to(&format!("/issuers/{}/edit", issuer.id)).into_response())
+}
+
+/// Retires the issuer's signing key and generates a new one
+/// Credentials signed with the old key stay verifiable; it remains published.
+async fn rotate_signing_key(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<Response, IssuersError> {
+    let issuer = CardIssuer::find_by_id(&state.pool, id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .ok_or(IssuersError::NotFound)?;
+
+    require_issuer_admin(&state, &session, &issuer).await?;
+
+    signing_keys::rotate_key(&state.pool, &state.config, issuer.id)
+        .await
+        .map_err(IssuersError::SigningKeyError)?;
+
+    Ok(axum::response::Redirect::to(&format!("/issuers/{}/edit", issuer.id)).into_response())
+}
+
 /// Toggle issuer active status
 async fn toggle_issuer_status(
     State(state): State<AppState>,
@@ -263,6 +784,245 @@ async fn toggle_issuer_status(
     Ok(axum::response::Redirect::to("/issuers").into_response())
 }
 
+/// List cards issued by an issuer, with suspension controls
+async fn issuer_cards_page(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    session: Session,
+) -> Result<IssuerCardsTemplate, IssuersError> {
+    let issuer = CardIssuer::find_by_id(&state.pool, id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .ok_or(IssuersError::NotFound)?;
+
+    require_issuer_admin(&state, &session, &issuer).await?;
+
+    let cards = MembershipCard::list_by_issuer(&state.pool, id)
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+    let mut open_suspensions = CardSuspension::list_open_by_issuer(&state.pool, id)
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+    let mut rows = Vec::with_capacity(cards.len());
+    for card in cards {
+        let member = Member::find_by_id(&state.pool, card.member_id)
+            .await
+            .map_err(IssuersError::DatabaseError)?;
+
+        let suspension = open_suspensions
+            .iter()
+            .position(|s| s.card_id == card.id)
+            .map(|index| open_suspensions.swap_remove(index));
+
+        rows.push(IssuerCardRow {
+            card,
+            member,
+            suspension,
+        });
+    }
+
+    Ok(IssuerCardsTemplate {
+        issuer,
+        cards: rows,
+        is_authenticated: true,
+    })
+}
+
+/// The signed-in member, if they manage the issuer with the given ID
+async fn require_admin_of(
+    state: &AppState,
+    session: &Session,
+    issuer_id: Uuid,
+) -> Result<AuthenticatedMember, IssuersError> {
+    let issuer = CardIssuer::find_by_id(&state.pool, issuer_id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .ok_or(IssuersError::NotFound)?;
+
+    require_issuer_admin(state, session, &issuer).await
+}
+
+/// Loads a card and checks that it was issued by the given issuer
+async fn find_issuer_card(
+    state: &AppState,
+    issuer_id: Uuid,
+    card_id: Uuid,
+) -> Result<MembershipCard, IssuersError> {
+    let card = MembershipCard::find_by_id(&state.pool, card_id)
+        .await
+        .map_err(IssuersError::DatabaseError)?
+        .ok_or(IssuersError::CardNotFound)?;
+
+    if card.issuer_id != issuer_id {
+        return Err(IssuersError::CardNotFound);
+    }
+
+    Ok(card)
+}
+
+/// Suspends a card and returns the new suspension record
+async fn suspend_card(
+    state: &AppState,
+    session: &Session,
+    issuer_id: Uuid,
+    card_id: Uuid,
+    reason: String,
+    ends_at: Option<DateTime<Utc>>,
+) -> Result<CardSuspension, IssuersError> {
+    let admin = require_admin_of(state, session, issuer_id).await?;
+
+    let reason = reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(IssuersError::ValidationError(
+            "Suspension reason is required".to_string(),
+        ));
+    }
+    if let Some(ends_at) = ends_at {
+        if ends_at <= Utc::now() {
+            return Err(IssuersError::ValidationError(
+                "Suspension end must be in the future".to_string(),
+            ));
+        }
+    }
+
+    let card = find_issuer_card(state, issuer_id, card_id).await?;
+
+    let suspension = CardSuspension::create(
+        &state.pool,
+        CreateSuspensionData {
+            card_id: card.id,
+            reason,
+            suspended_by: Some(admin.member_id),
+            ends_at,
+        },
+    )
+    .await
+    .map_err(IssuersError::DatabaseError)?
+    .ok_or_else(|| {
+        IssuersError::ValidationError("Only active cards can be suspended".to_string())
+    })?;
+
+    tracing::info!(
+        issuer_id = %issuer_id,
+        card_id = %card.id,
+        suspended_by = %admin.member_id,
+        ends_at = ?suspension.ends_at,
+        "Card suspended"
+    );
+
+    Ok(suspension)
+}
+
+/// Lifts the open suspension on a card
+async fn reinstate_card(
+    state: &AppState,
+    session: &Session,
+    issuer_id: Uuid,
+    card_id: Uuid,
+) -> Result<(), IssuersError> {
+    let admin = require_admin_of(state, session, issuer_id).await?;
+
+    let card = find_issuer_card(state, issuer_id, card_id).await?;
+
+    let lifted = CardSuspension::lift(&state.pool, card.id, Some(admin.member_id))
+        .await
+        .map_err(IssuersError::DatabaseError)?;
+
+    if !lifted {
+        return Err(IssuersError::ValidationError(
+            "Card is not suspended".to_string(),
+        ));
+    }
+
+    tracing::info!(
+        issuer_id = %issuer_id,
+        card_id = %card.id,
+        lifted_by = %admin.member_id,
+        "Card reinstated"
+    );
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct SuspendCardForm {
+    reason: String,
+    ends_at: Option<String>, // datetime-local value, interpreted as UTC
+}
+
+/// Suspend a card (HTML form)
+async fn suspend_card_form(
+    State(state): State<AppState>,
+    Path((issuer_id, card_id)): Path<(Uuid, Uuid)>,
+    session: Session,
+    Form(form): Form<SuspendCardForm>,
+) -> Result<Response, IssuersError> {
+    let ends_at = match form.ends_at.as_deref().map(str::trim) {
+        Some(value) if !value.is_empty() => Some(
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+                .map_err(|_| {
+                    IssuersError::ValidationError("Invalid suspension end time".to_string())
+                })?
+                .and_utc(),
+        ),
+        _ => None,
+    };
+
+    suspend_card(&state, &session, issuer_id, card_id, form.reason, ends_at).await?;
+
+    Ok(axum::response::Redirect::to(&format!("/issuers/{}/cards", issuer_id)).into_response())
+}
+
+/// Reinstate a suspended card (HTML form)
+async fn reinstate_card_form(
+    State(state): State<AppState>,
+    Path((issuer_id, card_id)): Path<(Uuid, Uuid)>,
+    session: Session,
+) -> Result<Response, IssuersError> {
+    reinstate_card(&state, &session, issuer_id, card_id).await?;
+
+    Ok(axum::response::Redirect::to(&format!("/issuers/{}/cards", issuer_id)).into_response())
+}
+
+#[derive(Deserialize)]
+struct SuspendCardRequest {
+    reason: String,
+    ends_at: Option<DateTime<Utc>>,
+}
+
+/// Suspend a card (JSON API)
+async fn suspend_card_json(
+    State(state): State<AppState>,
+    Path((issuer_id, card_id)): Path<(Uuid, Uuid)>,
+    session: Session,
+    Json(req): Json<SuspendCardRequest>,
+) -> Result<(StatusCode, Json<CardSuspension>), IssuersError> {
+    let suspension = suspend_card(
+        &state,
+        &session,
+        issuer_id,
+        card_id,
+        req.reason,
+        req.ends_at,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(suspension)))
+}
+
+/// Reinstate a suspended card (JSON API)
+async fn reinstate_card_json(
+    State(state): State<AppState>,
+    Path((issuer_id, card_id)): Path<(Uuid, Uuid)>,
+    session: Session,
+) -> Result<StatusCode, IssuersError> {
+    reinstate_card(&state, &session, issuer_id, card_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[derive(Deserialize)]
 struct AutoFillQuery {
     url: String,
@@ -299,6 +1059,30 @@ async fn autofill_channel(
 }
 
 pub fn router() -> Router<AppState> {
+    // Card moderation and credential schema routes - authentication required
+    let moderation_routes = Router::new()
+        .route("/issuers/:id/cards", get(issuer_cards_page))
+        .route(
+            "/issuers/:id/cards/:card_id/suspend",
+            post(suspend_card_form),
+        )
+        .route(
+            "/issuers/:id/cards/:card_id/reinstate",
+            post(reinstate_card_form),
+        )
+        .route(
+            "/api/issuers/:id/cards/:card_id/suspension",
+            post(suspend_card_json).delete(reinstate_card_json),
+        )
+        .route(
+            "/issuers/:id/credential-fields",
+            post(update_credential_fields),
+        )
+        .route("/issuers/:id/tiers", post(update_membership_tiers))
+        .route("/issuers/:id/wallet-accounts", post(update_wallet_accounts))
+        .route("/issuers/:id/signing-keys/rotate", post(rotate_signing_key))
+        .layer(middleware::from_fn(require_auth));
+
     Router::new()
         .route("/issuers", get(list_issuers).post(create_issuer))
         .route("/issuers/new", get(new_issuer_form))
@@ -306,4 +1090,5 @@ pub fn router() -> Router<AppState> {
         .route("/issuers/:id/edit", get(edit_issuer_form))
         .route("/issuers/:id", post(update_issuer))
         .route("/issuers/:id/toggle", post(toggle_issuer_status))
+        .merge(moderation_routes)
 }
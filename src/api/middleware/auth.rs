@@ -13,6 +13,7 @@ use super::session::{SESSION_KEY_MEMBER_ID, SESSION_KEY_RETURN_URL};
 #[derive(Debug)]
 pub enum AuthError {
     Unauthorized(String), // Store the requested path
+    Forbidden,            // Signed in, but not the channel's own account
     SessionError,
 }
 
@@ -24,6 +25,11 @@ impl IntoResponse for AuthError {
                 // The return URL is already stored in the session by the middleware
                 Redirect::to("/auth/youtube/login").into_response()
             }
+            AuthError::Forbidden => (
+                StatusCode::FORBIDDEN,
+                "Only the channel's own account can manage this.",
+            )
+                .into_response(),
             AuthError::SessionError => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Session error occurred.").into_response()
             }
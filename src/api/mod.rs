@@ -6,4 +6,6 @@ pub mod events;
 pub mod health;
 pub mod issuers;
 pub mod middleware;
+pub mod openid;
+pub mod tasks;
 pub mod verification;
@@ -0,0 +1,253 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::api::middleware::session::AppState;
+use crate::models::{credential_status_list::CredentialStatusList, issuer::CardIssuer};
+use crate::services::openid_wallet::{self, OpenIdError};
+use crate::services::{signing_keys, status_list};
+
+/// Errors are returned to wallets in OAuth form (`{"error": ..., "error_description": ...}`)
+#[derive(Debug)]
+pub enum OpenIdApiError {
+    ProtocolError(OpenIdError),
+}
+
+impl IntoResponse for OpenIdApiError {
+    fn into_response(self) -> Response {
+        let OpenIdApiError::ProtocolError(e) = self;
+
+        let status = match &e {
+            OpenIdError::DatabaseError(_)
+            | OpenIdError::SigningKey(_)
+            | OpenIdError::Signing(_) => {
+                tracing::error!(error = %e, "OpenID wallet endpoint failed");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            OpenIdError::InvalidToken => StatusCode::UNAUTHORIZED,
+            OpenIdError::UnknownPresentation
+            | OpenIdError::UnknownStatusList
+            | OpenIdError::UnknownIssuer => StatusCode::NOT_FOUND,
+            _ => StatusCode::BAD_REQUEST,
+        };
+
+        (
+            status,
+            Json(json!({
+                "error": e.error_code(),
+                "error_description": e.to_string(),
+            })),
+        )
+            .into_response()
+    }
+}
+
+impl From<OpenIdError> for OpenIdApiError {
+    fn from(e: OpenIdError) -> Self {
+        OpenIdApiError::ProtocolError(e)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenRequest {
+    grant_type: String,
+    #[serde(rename = "pre-authorized_code")]
+    pre_authorized_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PresentationResponseForm {
+    vp_token: String,
+    state: Option<String>,
+}
+
+async fn credential_issuer_metadata(State(state): State<AppState>) -> Json<Value> {
+    Json(openid_wallet::issuer_metadata(&state.config))
+}
+
+async fn authorization_server_metadata(State(state): State<AppState>) -> Json<Value> {
+    Json(openid_wallet::authorization_server_metadata(&state.config))
+}
+
+async fn jwt_vc_issuer_metadata(
+    State(state): State<AppState>,
+) -> Result<Json<Value>, OpenIdApiError> {
+    Ok(Json(
+        openid_wallet::jwt_vc_issuer_metadata(&state.pool, &state.config).await?,
+    ))
+}
+
+/// VPass's own `did:web` document
+async fn vpass_did_document(State(state): State<AppState>) -> Result<Json<Value>, OpenIdApiError> {
+    let document = signing_keys::did_document(&state.pool, &state.config, None)
+        .await
+        .map_err(OpenIdError::SigningKey)?;
+
+    Ok(Json(document))
+}
+
+async fn find_issuer(state: &AppState, issuer_id: Uuid) -> Result<CardIssuer, OpenIdApiError> {
+    Ok(CardIssuer::find_by_id(&state.pool, issuer_id)
+        .await
+        .map_err(OpenIdError::DatabaseError)?
+        .ok_or(OpenIdError::UnknownIssuer)?)
+}
+
+/// An issuer's `did:web` document (`did:web:<host>:issuers:<id>`)
+async fn issuer_did_document(
+    State(state): State<AppState>,
+    Path(issuer_id): Path<Uuid>,
+) -> Result<Json<Value>, OpenIdApiError> {
+    let issuer = find_issuer(&state, issuer_id).await?;
+    let document = signing_keys::did_document(&state.pool, &state.config, Some(issuer.id))
+        .await
+        .map_err(OpenIdError::SigningKey)?;
+
+    Ok(Json(document))
+}
+
+/// An issuer's published keys as a JWK Set
+async fn issuer_jwks(
+    State(state): State<AppState>,
+    Path(issuer_id): Path<Uuid>,
+) -> Result<Json<Value>, OpenIdApiError> {
+    let issuer = find_issuer(&state, issuer_id).await?;
+    let jwks = signing_keys::jwks(&state.pool, Some(issuer.id))
+        .await
+        .map_err(OpenIdError::SigningKey)?;
+
+    Ok(Json(jwks))
+}
+
+/// OpenID4VCI token endpoint (pre-authorized code grant)
+async fn token(
+    State(state): State<AppState>,
+    Form(request): Form<TokenRequest>,
+) -> Result<Response, OpenIdApiError> {
+    let response = openid_wallet::exchange_code(
+        &state.pool,
+        &request.grant_type,
+        request.pre_authorized_code.as_deref(),
+    )
+    .await?;
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)).into_response())
+}
+
+/// OpenID4VCI credential endpoint
+async fn credential(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Result<Json<Value>, OpenIdApiError> {
+    let access_token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(OpenIdError::InvalidToken)?;
+
+    Ok(Json(
+        openid_wallet::issue_credential(&state.pool, &state.config, access_token, &request).await?,
+    ))
+}
+
+/// OpenID4VP response endpoint (direct_post)
+async fn presentation_response(
+    State(state): State<AppState>,
+    Path(presentation_id): Path<Uuid>,
+    Form(form): Form<PresentationResponseForm>,
+) -> Result<Json<Value>, OpenIdApiError> {
+    openid_wallet::receive_presentation(
+        &state.pool,
+        &state.config,
+        presentation_id,
+        &form.vp_token,
+        form.state.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(json!({})))
+}
+
+/// Token status list of an issuer's VPass-signed credentials
+///
+/// Verifiers cache it for the token's `ttl`; conditional requests are answered
+/// with 304 while no card status changed.
+async fn credential_status_list(
+    State(state): State<AppState>,
+    Path(issuer_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, OpenIdApiError> {
+    let list = CredentialStatusList::find_by_issuer(&state.pool, issuer_id)
+        .await
+        .map_err(OpenIdError::DatabaseError)?
+        .ok_or(OpenIdError::UnknownStatusList)?;
+
+    let etag = status_list::etag(&list);
+    let cache_control = format!("public, max-age={}", status_list::STATUS_LIST_TTL_SECS);
+    let last_modified = list
+        .updated_at
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    if not_modified {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::CACHE_CONTROL, cache_control)],
+        )
+            .into_response());
+    }
+
+    let token = status_list::sign_status_list(&state.pool, &state.config, &list).await?;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                status_list::STATUS_LIST_MEDIA_TYPE.to_string(),
+            ),
+            (header::CACHE_CONTROL, cache_control),
+            (header::ETAG, etag),
+            (header::LAST_MODIFIED, last_modified),
+        ],
+        token,
+    )
+        .into_response())
+}
+
+/// Endpoints wallets and verifiers call; none of them use the VPass session
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/.well-known/openid-credential-issuer",
+            get(credential_issuer_metadata),
+        )
+        .route(
+            "/.well-known/oauth-authorization-server",
+            get(authorization_server_metadata),
+        )
+        .route("/.well-known/jwt-vc-issuer", get(jwt_vc_issuer_metadata))
+        .route("/.well-known/did.json", get(vpass_did_document))
+        // VPass's DID when the base URL has a path (`did:web:<host>:<path>`)
+        .route("/did.json", get(vpass_did_document))
+        .route("/issuers/:issuer_id/did.json", get(issuer_did_document))
+        .route("/issuers/:issuer_id/jwks.json", get(issuer_jwks))
+        .route("/oid4vci/token", post(token))
+        .route("/oid4vci/credential", post(credential))
+        .route(
+            "/oid4vp/responses/:presentation_id",
+            post(presentation_response),
+        )
+        .route("/status-lists/:issuer_id", get(credential_status_list))
+}
@@ -0,0 +1,88 @@
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Router,
+};
+use uuid::Uuid;
+
+use crate::api::middleware::{auth::require_auth, session::AppState};
+use crate::models::background_task::BackgroundTask;
+
+/// Maximum number of tasks shown on the admin page
+const ATTENTION_LIST_LIMIT: i64 = 200;
+
+#[derive(Debug)]
+pub enum TasksError {
+    DatabaseError(sqlx::Error),
+    NotFound,
+}
+
+impl IntoResponse for TasksError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            TasksError::DatabaseError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Database error: {}", e),
+            ),
+            TasksError::NotFound => (
+                StatusCode::NOT_FOUND,
+                "Task not found or not retryable".to_string(),
+            ),
+        };
+
+        (status, message).into_response()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/tasks.html")]
+struct TasksTemplate {
+    tasks: Vec<BackgroundTask>,
+    status_counts: Vec<(String, i64)>,
+    is_authenticated: bool,
+}
+
+/// Lists dead-lettered, retrying and stuck background tasks
+async fn tasks_page(State(state): State<AppState>) -> Result<TasksTemplate, TasksError> {
+    let tasks = BackgroundTask::list_needing_attention(&state.pool, ATTENTION_LIST_LIMIT)
+        .await
+        .map_err(TasksError::DatabaseError)?;
+
+    let status_counts = BackgroundTask::count_by_status(&state.pool)
+        .await
+        .map_err(TasksError::DatabaseError)?;
+
+    Ok(TasksTemplate {
+        tasks,
+        status_counts,
+        is_authenticated: true,
+    })
+}
+
+/// Puts a task back in the queue to run on the worker's next tick
+async fn retry_task(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Redirect, TasksError> {
+    if !BackgroundTask::retry_now(&state.pool, id)
+        .await
+        .map_err(TasksError::DatabaseError)?
+    {
+        return Err(TasksError::NotFound);
+    }
+
+    tracing::info!(task_id = %id, "Task requeued manually");
+
+    Ok(Redirect::to("/admin/tasks"))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/tasks", get(tasks_page))
+        .route("/admin/tasks/:id/retry", post(retry_task))
+        .layer(middleware::from_fn(require_auth))
+}
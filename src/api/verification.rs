@@ -1,28 +1,42 @@
 use askama::Template;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
-use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
 use uuid::Uuid;
 
 use crate::api::middleware::session::{AppState, SESSION_KEY_MEMBER_ID};
 use crate::models::{
+    attendance_credential::AttendanceCredential,
+    card::MembershipCard,
     event::Event,
+    issuer::CardIssuer,
+    member::Member,
     verification_event::{CreateVerificationEventData, VerificationEvent},
 };
-use crate::services::oidvp_verifier;
+use crate::services::{
+    admission::{self, AdmissionDecision, Attendance, ScanDirection},
+    card_verifier,
+    event_access::{AccessDecision, EventAccess},
+    offline_scanning, oidvp_verifier,
+    scanner_devices::{self, ScannerIdentity, DEVICE_TOKEN_HEADER},
+    wallet_provider::{self, PresentationRequest, WalletProvider},
+};
 
 #[derive(Debug)]
 pub enum VerificationApiError {
     DatabaseError(sqlx::Error),
     OidvpError(oidvp_verifier::OidvpError),
+    CardVerificationError(card_verifier::VerificationError),
+    OfflineScanError(offline_scanning::OfflineScanError),
     EventNotFound,
+    ScannerUnauthorized,
+    OwnCardScanned,
     ValidationError(String),
     ConfigError(String),
     SessionError(String),
@@ -39,9 +53,29 @@ impl IntoResponse for VerificationApiError {
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("OIDVP error: {}", e),
             ),
+            VerificationApiError::CardVerificationError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Card verification error: {}", e),
+            ),
+            VerificationApiError::OfflineScanError(
+                e @ offline_scanning::OfflineScanError::TooManyScans(_),
+            ) => (StatusCode::BAD_REQUEST, e.to_string()),
+            VerificationApiError::OfflineScanError(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Offline scan error: {}", e),
+            ),
             VerificationApiError::EventNotFound => {
                 (StatusCode::NOT_FOUND, "Event not found".to_string())
             }
+            VerificationApiError::ScannerUnauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "Scanner is not registered for this event; sign in as the organizer or pair this device"
+                    .to_string(),
+            ),
+            VerificationApiError::OwnCardScanned => (
+                StatusCode::FORBIDDEN,
+                "A scanner cannot admit its operator's own card".to_string(),
+            ),
             VerificationApiError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
             VerificationApiError::ConfigError(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -109,10 +143,46 @@ pub struct CheckResultResponse {
     pub status: String, // "pending", "completed", "expired"
     pub verify_result: Option<bool>,
     pub result_description: Option<String>,
+    pub card_status: Option<String>, // card_verifier result type when the credential names a VPass card
     pub member_info: Option<serde_json::Value>,
+    /// "reserved" or "walk_in" for an admitted card
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attendance: Option<Attendance>,
     pub message: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ScanCardRequest {
+    pub token: String,
+    #[serde(default)]
+    pub direction: ScanDirection,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckResultParams {
+    #[serde(default)]
+    pub direction: ScanDirection,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventBundleResponse {
+    pub bundle_id: String,
+    pub bundle: String, // signed JWT
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OfflineScanBatch {
+    pub bundle_id: Option<String>,
+    pub scans: Vec<offline_scanning::OfflineScan>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScannerDeviceResponse {
+    pub device: Option<serde_json::Value>, // {id, name, operator_name} of a paired device
+    pub signed_in: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
     pub page: Option<i64>,
@@ -128,6 +198,116 @@ async fn is_authenticated(session: &Session) -> Result<bool, VerificationApiErro
     Ok(member_id.is_some())
 }
 
+/// Identifies who is scanning for an event
+///
+/// A scanner either sends the token of a device registered for the event, or is
+/// operated by the organizer, i.e. a member signed in with the channel of the
+/// event's issuer. A device token that is revoked or belongs to another event is
+/// rejected even if the organizer is signed in, so a revoked gate stops scanning.
+async fn scanner_identity(
+    state: &AppState,
+    session: &Session,
+    headers: &HeaderMap,
+    event: &Event,
+) -> Result<ScannerIdentity, VerificationApiError> {
+    let member_id: Option<Uuid> = session
+        .get(SESSION_KEY_MEMBER_ID)
+        .await
+        .map_err(|e| VerificationApiError::SessionError(e.to_string()))?;
+
+    let device = match headers.get(DEVICE_TOKEN_HEADER) {
+        Some(token) => {
+            let token = token
+                .to_str()
+                .map_err(|_| VerificationApiError::ScannerUnauthorized)?;
+            Some(
+                scanner_devices::authenticate(&state.pool, event.id, token)
+                    .await
+                    .map_err(VerificationApiError::DatabaseError)?
+                    .ok_or(VerificationApiError::ScannerUnauthorized)?,
+            )
+        }
+        None => None,
+    };
+
+    if device.is_none() {
+        let Some(member_id) = member_id else {
+            return Err(VerificationApiError::ScannerUnauthorized);
+        };
+        let issuer = CardIssuer::find_by_id(&state.pool, event.issuer_id)
+            .await
+            .map_err(VerificationApiError::DatabaseError)?
+            .ok_or(VerificationApiError::EventNotFound)?;
+        if !issuer
+            .is_managed_by(&state.pool, member_id)
+            .await
+            .map_err(VerificationApiError::DatabaseError)?
+        {
+            return Err(VerificationApiError::ScannerUnauthorized);
+        }
+    }
+
+    Ok(ScannerIdentity { device, member_id })
+}
+
+/// Records the scan of a card VPass found valid, as an admission, a denied
+/// re-entry or walk-in, or a check-out according to the event's re-entry policy
+/// and capacity; an admission comes with whether the card holder reserved
+async fn record_admission(
+    state: &AppState,
+    event: &Event,
+    card: &MembershipCard,
+    direction: ScanDirection,
+    mut data: CreateVerificationEventData,
+) -> Result<(AdmissionDecision, Option<Attendance>), VerificationApiError> {
+    let now = chrono::Utc::now();
+    let (mut tx, decision, attendance) = admission::check(&state.pool, event, card, direction, now)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?;
+
+    let attendance = (decision == AdmissionDecision::Admit).then_some(attendance);
+    data.verification_result = decision.result_type().to_string();
+    if let (Some(attendance), Some(context)) = (
+        attendance,
+        data.verification_context
+            .as_mut()
+            .and_then(|c| c.as_object_mut()),
+    ) {
+        context.insert("attendance".to_string(), attendance.as_str().into());
+    }
+    VerificationEvent::create_event(&mut *tx, data)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?;
+    // The member can claim an attendance credential from their cards page
+    if decision == AdmissionDecision::Admit {
+        AttendanceCredential::record(&mut *tx, event.id, card.id, now)
+            .await
+            .map_err(VerificationApiError::DatabaseError)?;
+    }
+    tx.commit()
+        .await
+        .map_err(VerificationApiError::DatabaseError)?;
+
+    Ok((decision, attendance))
+}
+
+/// Whether the event accepts a card that verified; other results are refused anyway
+fn card_access(access: &EventAccess, result: &card_verifier::VerificationResult) -> AccessDecision {
+    match result {
+        card_verifier::VerificationResult::Success { card, .. } => access.check(card),
+        _ => AccessDecision::Allowed,
+    }
+}
+
+/// Description shown for a valid card that was admitted or checked out
+fn admission_description(event: &Event, decision: Option<&AdmissionDecision>) -> Option<String> {
+    match decision {
+        Some(AdmissionDecision::CheckOut) => Some("已登記離場".to_string()),
+        Some(decision) => decision.denial_reason(event.timezone()),
+        None => None,
+    }
+}
+
 // Handlers
 
 /// Verification home page - shows list of active events
@@ -135,7 +315,7 @@ async fn verification_home(
     State(state): State<AppState>,
     session: Session,
 ) -> Result<VerificationHomeTemplate, VerificationApiError> {
-    let events = Event::list_active(&state.pool)
+    let events = Event::list_open_for_checkin(&state.pool)
         .await
         .map_err(VerificationApiError::DatabaseError)?;
 
@@ -172,12 +352,36 @@ async fn scanner_page(
     })
 }
 
+/// Resolves the wallet provider an event verifies with: the issuer's provider, and
+/// for the Taiwan wallet the verifier account (event, then issuer, then global)
+async fn event_verifier(
+    state: &AppState,
+    event: &Event,
+) -> Result<(CardIssuer, Box<dyn WalletProvider>), VerificationApiError> {
+    let issuer = CardIssuer::find_by_id(&state.pool, event.issuer_id)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?
+        .ok_or(VerificationApiError::EventNotFound)?;
+
+    let verifier = wallet_provider::verifier_provider(&state.pool, &state.config, &issuer, event)
+        .map_err(|e| VerificationApiError::ConfigError(e.to_string()))?
+        .ok_or_else(|| {
+            VerificationApiError::ConfigError(
+                "VERIFIER_API_URL / VERIFIER_ACCESS_TOKEN not configured".to_string(),
+            )
+        })?;
+
+    Ok((issuer, verifier))
+}
+
 /// Request verification QR code
 ///
-/// Generates a new QR code via OIDVP API (no database storage - frontend manages state)
+/// Generates a new QR code via the event's wallet provider (frontend manages state)
 async fn request_qr(
     State(state): State<AppState>,
     Path(event_id): Path<Uuid>,
+    session: Session,
+    headers: HeaderMap,
 ) -> Result<Json<RequestQrResponse>, VerificationApiError> {
     // Verify event exists and get verifier_ref from event
     let event = Event::find_by_id(&state.pool, event_id)
@@ -185,29 +389,25 @@ async fn request_qr(
         .map_err(VerificationApiError::DatabaseError)?
         .ok_or(VerificationApiError::EventNotFound)?;
 
-    // Get verifier config
-    let verifier_api_url = state
-        .config
-        .verifier_api_url
-        .as_ref()
-        .ok_or_else(|| VerificationApiError::ConfigError("VERIFIER_API_URL not configured".to_string()))?;
+    scanner_identity(&state, &session, &headers, &event).await?;
 
-    let verifier_access_token = state
-        .config
-        .verifier_access_token
-        .as_ref()
-        .ok_or_else(|| VerificationApiError::ConfigError("VERIFIER_ACCESS_TOKEN not configured".to_string()))?;
+    let (issuer, verifier) = event_verifier(&state, &event).await?;
 
-    tracing::info!(event_id = %event_id, verifier_ref = %event.verifier_ref, "Requesting verification QR code");
+    tracing::info!(
+        event_id = %event_id,
+        verifier_ref = %event.verifier_ref,
+        provider = verifier.kind().as_str(),
+        "Requesting verification QR code"
+    );
 
-    // Call OIDVP API to generate QR code using event's verifier_ref
-    let qr_response = oidvp_verifier::request_verification_qr(
-        verifier_api_url,
-        verifier_access_token.expose_secret(),
-        &event.verifier_ref,
-    )
-    .await
-    .map_err(VerificationApiError::OidvpError)?;
+    // Taiwan wallet: OIDVP API with the event's verifier_ref
+    let qr_response = verifier
+        .request_presentation(PresentationRequest {
+            event: &event,
+            issuer: &issuer,
+        })
+        .await
+        .map_err(VerificationApiError::OidvpError)?;
 
     // Strip data URL prefix if present, as frontend will add it
     let qrcode_image = qr_response
@@ -233,40 +433,27 @@ async fn request_qr(
 
 /// Check verification result
 ///
-/// Polls OIDVP API for verification result (frontend-managed state)
+/// Polls the event's wallet provider for the verification result (frontend-managed state)
 async fn check_result(
     State(state): State<AppState>,
     Path((event_id, transaction_id)): Path<(Uuid, String)>,
+    Query(params): Query<CheckResultParams>,
+    session: Session,
+    headers: HeaderMap,
 ) -> Result<Json<CheckResultResponse>, VerificationApiError> {
     // Verify event exists
-    Event::find_by_id(&state.pool, event_id)
+    let event = Event::find_by_id(&state.pool, event_id)
         .await
         .map_err(VerificationApiError::DatabaseError)?
         .ok_or(VerificationApiError::EventNotFound)?;
 
-    // Get verifier config
-    let verifier_api_url = state
-        .config
-        .verifier_api_url
-        .as_ref()
-        .ok_or_else(|| VerificationApiError::ConfigError("VERIFIER_API_URL not configured".to_string()))?;
+    let scanner = scanner_identity(&state, &session, &headers, &event).await?;
 
-    let verifier_access_token = state
-        .config
-        .verifier_access_token
-        .as_ref()
-        .ok_or_else(|| VerificationApiError::ConfigError("VERIFIER_ACCESS_TOKEN not configured".to_string()))?;
+    let (_, verifier) = event_verifier(&state, &event).await?;
 
-    tracing::debug!(transaction_id = %transaction_id, "Polling OIDVP result");
+    tracing::debug!(transaction_id = %transaction_id, "Polling verification result");
 
-    // Poll OIDVP API directly (no database session tracking)
-    match oidvp_verifier::poll_verification_result(
-        verifier_api_url,
-        verifier_access_token.expose_secret(),
-        &transaction_id,
-    )
-    .await
-    {
+    match verifier.fetch_result(&transaction_id).await {
         Ok(result) => {
             // Extract member info from claims
             let member_info = if let Some(ref data) = result.data {
@@ -275,47 +462,120 @@ async fn check_result(
                 None
             };
 
-            // If successful, create verification event record (audit log)
+            // The wallet only vouches for the credential itself; the card it names
+            // may since have been suspended, revoked or deleted in VPass. A
+            // credential naming no card is not admitted.
+            let card_check = if result.verify_result {
+                Some(match card_verifier::wallet_card_id(member_info.as_ref()) {
+                    Some(card_id) => card_verifier::verify_card(&state.pool, card_id)
+                        .await
+                        .map_err(VerificationApiError::CardVerificationError)?,
+                    None => card_verifier::missing_wallet_card(),
+                })
+            } else {
+                None
+            };
+
+            if let Some(card_verifier::VerificationResult::Success { card, .. }) = &card_check {
+                if scanner.is_card_holder(card) {
+                    return Err(VerificationApiError::OwnCardScanned);
+                }
+            }
+
+            // A wallet credential of a channel or tier the event does not accept
+            let access_decision = match &card_check {
+                Some(check) => {
+                    let access = EventAccess::load(&state.pool, &event)
+                        .await
+                        .map_err(VerificationApiError::DatabaseError)?;
+                    card_access(&access, check)
+                }
+                None => AccessDecision::Allowed,
+            };
+
+            // If the credential verified, create verification event record (audit log)
+            let mut admission = None;
+            let mut attendance = None;
             if result.verify_result {
-                // Try to extract card_id from member_info if available
-                let card_id = member_info
-                    .as_ref()
-                    .and_then(|info| info.get("cardId"))
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| Uuid::parse_str(s).ok());
-
-                let _ = VerificationEvent::create_event(
-                    &state.pool,
-                    CreateVerificationEventData {
-                        event_id,
-                        card_id,
-                        verification_result: "success".to_string(),
-                        verification_context: Some(serde_json::json!({
-                            "transaction_id": transaction_id,
-                            "method": "oidvp"
-                        })),
-                        raw_payload: Some(serde_json::to_string(&result).unwrap_or_default()),
-                    },
-                )
-                .await
-                .map_err(VerificationApiError::DatabaseError)?;
+                let data = CreateVerificationEventData {
+                    event_id,
+                    // An unknown card ID cannot be stored (foreign key)
+                    card_id: card_check
+                        .as_ref()
+                        .filter(|c| {
+                            !matches!(c, card_verifier::VerificationResult::CardNotFound { .. })
+                        })
+                        .and_then(|c| c.card_id()),
+                    verification_result: access_decision
+                        .result_type()
+                        .or_else(|| card_check.as_ref().map(|c| c.result_type()))
+                        .unwrap_or("success")
+                        .to_string(),
+                    verification_context: Some(scanner.annotate(serde_json::json!({
+                        "transaction_id": transaction_id,
+                        "method": "oidvp",
+                        "provider": verifier.kind().as_str(),
+                        "direction": params.direction,
+                    }))),
+                    raw_payload: Some(serde_json::to_string(&result).unwrap_or_default()),
+                };
+
+                match &card_check {
+                    Some(card_verifier::VerificationResult::Success { card, .. })
+                        if access_decision == AccessDecision::Allowed =>
+                    {
+                        let (decision, admitted) =
+                            record_admission(&state, &event, card, params.direction, data).await?;
+                        admission = Some(decision);
+                        attendance = admitted;
+                    }
+                    _ => {
+                        VerificationEvent::create_event(&state.pool, data)
+                            .await
+                            .map_err(VerificationApiError::DatabaseError)?;
+                    }
+                }
             }
 
+            let card_failure = card_check
+                .as_ref()
+                .and_then(|c| c.failure_reason())
+                .or_else(|| access_decision.failure_reason())
+                .or_else(|| {
+                    admission
+                        .as_ref()
+                        .and_then(|a| a.denial_reason(event.timezone()))
+                });
+            let verified = result.verify_result && card_failure.is_none();
+
             tracing::info!(
                 transaction_id = %transaction_id,
                 verify_result = result.verify_result,
+                card_status = ?card_check.as_ref().map(|c| c.result_type()),
+                admission = ?admission.as_ref().map(|a| a.result_type()),
                 "Verification completed"
             );
 
+            let result_description = card_failure
+                .or_else(|| admission_description(&event, admission.as_ref()))
+                .unwrap_or_else(|| result.result_description.clone());
+
             Ok(Json(CheckResultResponse {
                 status: "completed".to_string(),
-                verify_result: Some(result.verify_result),
-                result_description: Some(result.result_description.clone()),
+                verify_result: Some(verified),
+                result_description: Some(result_description.clone()),
+                card_status: admission
+                    .as_ref()
+                    .map(|a| a.result_type())
+                    .or(access_decision.result_type())
+                    .or_else(|| card_check.as_ref().map(|c| c.result_type()))
+                    .map(str::to_string),
                 member_info,
-                message: if result.verify_result {
+                attendance,
+                message: if verified {
                     "Verification successful!".to_string()
                 } else {
-                    format!("Verification failed: {}", result.result_description)
+                    format!("Verification failed: {}", result_description)
                 },
             }))
         }
@@ -325,10 +585,21 @@ async fn check_result(
                 status: "pending".to_string(),
                 verify_result: None,
                 result_description: None,
+                card_status: None,
                 member_info: None,
+                attendance: None,
                 message: "Waiting for user to scan QR code...".to_string(),
             }))
         }
+        Err(oidvp_verifier::OidvpError::Expired) => Ok(Json(CheckResultResponse {
+            status: "expired".to_string(),
+            verify_result: None,
+            result_description: None,
+            card_status: None,
+            member_info: None,
+            attendance: None,
+            message: "QR code expired, please generate a new one".to_string(),
+        })),
         Err(e) => {
             tracing::error!(error = ?e, "Failed to poll verification result");
             Err(VerificationApiError::OidvpError(e))
@@ -336,6 +607,219 @@ async fn check_result(
     }
 }
 
+/// Verify a VPass card QR code
+///
+/// Members without the wallet app show a rotating QR from their card page; it
+/// carries a signed token that is valid for 60 seconds and accepted once.
+async fn scan_card(
+    State(state): State<AppState>,
+    Path(event_id): Path<Uuid>,
+    session: Session,
+    headers: HeaderMap,
+    Json(request): Json<ScanCardRequest>,
+) -> Result<Json<CheckResultResponse>, VerificationApiError> {
+    let event = Event::find_by_id(&state.pool, event_id)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?
+        .ok_or(VerificationApiError::EventNotFound)?;
+
+    let scanner = scanner_identity(&state, &session, &headers, &event).await?;
+
+    let access = EventAccess::load(&state.pool, &event)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?;
+
+    let result = card_verifier::verify_presentation_token(
+        &state.pool,
+        &request.token,
+        &access.accepted_issuers(),
+        event.id,
+    )
+    .await
+    .map_err(VerificationApiError::CardVerificationError)?;
+    let access_decision = card_access(&access, &result);
+
+    let card = match &result {
+        card_verifier::VerificationResult::Success { card, .. }
+        | card_verifier::VerificationResult::CardExpired { card, .. }
+        | card_verifier::VerificationResult::CardRevoked { card, .. }
+        | card_verifier::VerificationResult::CardSuspended { card, .. }
+        | card_verifier::VerificationResult::CardDeleted { card, .. } => Some(card),
+        _ => None,
+    };
+
+    if card.is_some_and(|card| scanner.is_card_holder(card)) {
+        return Err(VerificationApiError::OwnCardScanned);
+    }
+
+    // Only tokens that named an existing card are worth an audit log entry
+    let mut admission = None;
+    let mut attendance = None;
+    if let Some(card) = card {
+        let data = CreateVerificationEventData {
+            event_id,
+            card_id: Some(card.id),
+            verification_result: access_decision
+                .result_type()
+                .unwrap_or(result.result_type())
+                .to_string(),
+            verification_context: Some(scanner.annotate(serde_json::json!({
+                "method": "vpass_card_qr",
+                "direction": request.direction,
+            }))),
+            raw_payload: None,
+        };
+
+        if data.verification_result == "success" {
+            let (decision, admitted) =
+                record_admission(&state, &event, card, request.direction, data).await?;
+            admission = Some(decision);
+            attendance = admitted;
+        } else {
+            VerificationEvent::create_event(&state.pool, data)
+                .await
+                .map_err(VerificationApiError::DatabaseError)?;
+        }
+    }
+
+    let member_info = match card {
+        Some(card) => {
+            let member = Member::find_by_id(&state.pool, card.member_id)
+                .await
+                .map_err(VerificationApiError::DatabaseError)?;
+            Some(serde_json::json!({
+                "name": member.map(|m| m.default_display_name).unwrap_or_default(),
+                "membershipLevel": card.membership_level_label,
+                "cardId": card.id,
+            }))
+        }
+        None => None,
+    };
+
+    let failure = result
+        .failure_reason()
+        .or_else(|| access_decision.failure_reason())
+        .or_else(|| {
+            admission
+                .as_ref()
+                .and_then(|a| a.denial_reason(event.timezone()))
+        });
+    let verified = failure.is_none();
+    let card_status = admission
+        .as_ref()
+        .map(|a| a.result_type())
+        .or(access_decision.result_type())
+        .unwrap_or(result.result_type());
+
+    tracing::info!(
+        event_id = %event_id,
+        card_status,
+        "VPass card QR verified"
+    );
+
+    let result_description = failure
+        .or_else(|| admission_description(&event, admission.as_ref()))
+        .unwrap_or_else(|| "會員卡有效".to_string());
+
+    Ok(Json(CheckResultResponse {
+        status: "completed".to_string(),
+        verify_result: Some(verified),
+        result_description: Some(result_description.clone()),
+        card_status: Some(card_status.to_string()),
+        member_info,
+        attendance,
+        message: if verified {
+            "Verification successful!".to_string()
+        } else {
+            format!("Verification failed: {}", result_description)
+        },
+    }))
+}
+
+/// Signed bundle for verifying VPass card QR codes offline
+async fn event_bundle(
+    State(state): State<AppState>,
+    Path(event_id): Path<Uuid>,
+    session: Session,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, VerificationApiError> {
+    let event = Event::find_by_id(&state.pool, event_id)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?
+        .ok_or(VerificationApiError::EventNotFound)?;
+
+    scanner_identity(&state, &session, &headers, &event).await?;
+
+    let bundle = offline_scanning::issue_bundle(&state.pool, &state.config, &event)
+        .await
+        .map_err(VerificationApiError::OfflineScanError)?;
+
+    tracing::info!(event_id = %event_id, bundle_id = %bundle.bundle_id, "Issued offline bundle");
+
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(EventBundleResponse {
+            bundle_id: bundle.bundle_id,
+            bundle: bundle.token,
+            expires_at: bundle.expires_at,
+        }),
+    ))
+}
+
+/// Upload scans an offline scanner verified against a bundle
+async fn upload_offline_scans(
+    State(state): State<AppState>,
+    Path(event_id): Path<Uuid>,
+    session: Session,
+    headers: HeaderMap,
+    Json(batch): Json<OfflineScanBatch>,
+) -> Result<Json<offline_scanning::IngestSummary>, VerificationApiError> {
+    let event = Event::find_by_id(&state.pool, event_id)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?
+        .ok_or(VerificationApiError::EventNotFound)?;
+
+    let scanner = scanner_identity(&state, &session, &headers, &event).await?;
+
+    let summary = offline_scanning::ingest_scans(
+        &state.pool,
+        &event,
+        &scanner,
+        batch.bundle_id.as_deref(),
+        batch.scans,
+    )
+    .await
+    .map_err(VerificationApiError::OfflineScanError)?;
+
+    Ok(Json(summary))
+}
+
+/// The device a scanner is paired as, for the scanner page to show
+async fn scanner_device(
+    State(state): State<AppState>,
+    Path(event_id): Path<Uuid>,
+    session: Session,
+    headers: HeaderMap,
+) -> Result<Json<ScannerDeviceResponse>, VerificationApiError> {
+    let event = Event::find_by_id(&state.pool, event_id)
+        .await
+        .map_err(VerificationApiError::DatabaseError)?
+        .ok_or(VerificationApiError::EventNotFound)?;
+
+    let scanner = scanner_identity(&state, &session, &headers, &event).await?;
+
+    Ok(Json(ScannerDeviceResponse {
+        device: scanner.device.map(|device| {
+            serde_json::json!({
+                "id": device.id,
+                "name": device.name,
+                "operator_name": device.operator_name,
+            })
+        }),
+        signed_in: scanner.member_id.is_some(),
+    }))
+}
+
 /// Verification history for an event
 async fn verification_history(
     State(state): State<AppState>,
@@ -419,10 +903,22 @@ async fn verification_history(
 }
 
 pub fn router() -> Router<AppState> {
+    // Scanning endpoints check for a paired device or a signed-in member themselves
+    // (see `scanner_identity`); the offline bundle lists revoked and suspended cards
     Router::new()
         .route("/verify", get(verification_home))
         .route("/verify/:event_id/scanner", get(scanner_page))
+        .route("/verify/:event_id/device", get(scanner_device))
         .route("/verify/:event_id/request-qr", post(request_qr))
-        .route("/verify/:event_id/check-result/:transaction_id", get(check_result))
+        .route(
+            "/verify/:event_id/check-result/:transaction_id",
+            get(check_result),
+        )
+        .route("/verify/:event_id/scan-card", post(scan_card))
+        .route("/verify/:event_id/bundle", get(event_bundle))
+        .route(
+            "/verify/:event_id/offline-scans",
+            post(upload_offline_scans),
+        )
         .route("/verify/:event_id/history", get(verification_history))
 }
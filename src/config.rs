@@ -1,6 +1,8 @@
-use secrecy::Secret;
+use secrecy::{ExposeSecret, Secret};
 use serde::Deserialize;
 
+use crate::services::credential_cipher::{CipherError, CredentialCipher};
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub database_url: String,
@@ -15,6 +17,10 @@ pub struct Config {
     // YouTube Data API (for channel info lookup)
     pub youtube_api_key: Option<String>,
 
+    // Background membership checks
+    pub youtube_daily_quota: i32,
+    pub subscription_check_concurrency: usize,
+
     // Taiwan Digital Wallet Issuer API
     pub issuer_api_url: Option<String>,
     pub issuer_access_token: Option<Secret<String>>,
@@ -23,8 +29,18 @@ pub struct Config {
     pub verifier_api_url: Option<String>,
     pub verifier_access_token: Option<Secret<String>>,
 
+    // Taiwan Digital Wallet credential verification
+    /// Expected `iss` of issued credentials (DID or URL)
+    pub wallet_credential_issuer: Option<String>,
+    /// Where the issuer's keys are published (JWKS, DID document or JWT VC issuer metadata)
+    pub wallet_issuer_jwks_url: Option<String>,
+    /// Local JWKS or DID document used instead of fetching the keys
+    pub wallet_issuer_jwks: Option<String>,
+
     // Security
     pub session_secret: Secret<String>,
+    /// Key for wallet API tokens stored in the database (base64, 32 bytes)
+    pub credential_encryption_key: Option<Secret<String>>,
 }
 
 impl Config {
@@ -47,6 +63,11 @@ impl Config {
 
             youtube_api_key: config.get("youtube_api_key").ok(),
 
+            youtube_daily_quota: config.get("youtube_daily_quota").unwrap_or(10_000),
+            subscription_check_concurrency: config
+                .get("subscription_check_concurrency")
+                .unwrap_or(4),
+
             issuer_api_url: config.get("issuer_api_url").ok(),
             issuer_access_token: config
                 .get::<String>("issuer_access_token")
@@ -59,7 +80,24 @@ impl Config {
                 .ok()
                 .map(Secret::new),
 
+            wallet_credential_issuer: config.get("wallet_credential_issuer").ok(),
+            wallet_issuer_jwks_url: config.get("wallet_issuer_jwks_url").ok(),
+            wallet_issuer_jwks: config.get("wallet_issuer_jwks").ok(),
+
             session_secret: Secret::new(config.get("session_secret")?),
+            credential_encryption_key: config
+                .get::<String>("credential_encryption_key")
+                .ok()
+                .filter(|key| !key.trim().is_empty())
+                .map(Secret::new),
         })
     }
+
+    /// Returns the cipher for wallet API tokens stored in the database, if a key is configured
+    pub fn credential_cipher(&self) -> Result<Option<CredentialCipher>, CipherError> {
+        self.credential_encryption_key
+            .as_ref()
+            .map(|key| CredentialCipher::from_base64_key(key.expose_secret()))
+            .transpose()
+    }
 }
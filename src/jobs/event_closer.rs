@@ -0,0 +1,26 @@
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::models::event::Event;
+
+/// Background job that closes events whose check-in window has ended
+///
+/// Closed events are deactivated, so they drop off the `/verify` home page and
+/// stop accepting scans. Returns the number of events closed.
+pub async fn close_ended_events(pool: &PgPool, batch_size: i64) -> Result<usize, sqlx::Error> {
+    let closed = Event::close_ended(pool, Utc::now(), batch_size).await?;
+
+    for event in &closed {
+        tracing::info!(
+            event_id = %event.id,
+            event_name = %event.event_name,
+            "Check-in window ended, event closed"
+        );
+    }
+
+    if !closed.is_empty() {
+        tracing::info!(closed = closed.len(), "Event closing job completed");
+    }
+
+    Ok(closed.len())
+}
@@ -0,0 +1,112 @@
+use std::future::Future;
+
+use chrono::Duration;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::models::job_lease::JobLease;
+
+/// Aborts a spawned task when dropped, including while unwinding from a panic
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Runs a job only if this instance wins its lease
+///
+/// The lease is renewed in the background every third of `ttl` while the job runs
+/// and released when it finishes. If the lease is lost mid-run (it expired and
+/// another instance took it over), the job is cancelled so two runs never
+/// overlap. If this instance dies mid-run, the lease simply expires and another
+/// instance takes over on its next tick.
+///
+/// Returns None when another run already holds the lease, or took it over.
+pub async fn run_exclusive<F, T>(
+    pool: &PgPool,
+    holder_id: Uuid,
+    job_name: &'static str,
+    ttl: Duration,
+    job: F,
+) -> Result<Option<T>, sqlx::Error>
+where
+    F: Future<Output = T>,
+{
+    if JobLease::try_acquire(pool, job_name, holder_id, ttl)
+        .await?
+        .is_none()
+    {
+        tracing::debug!(job_name, "Job lease held elsewhere, skipping run");
+        return Ok(None);
+    }
+
+    let renew_every = (ttl / 3)
+        .to_std()
+        .unwrap_or(std::time::Duration::from_secs(60));
+    let renew_pool = pool.clone();
+    // Finishes only when the lease is lost
+    let mut heartbeat = AbortOnDrop(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(renew_every);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match JobLease::renew(&renew_pool, job_name, holder_id, ttl).await {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(e) => tracing::error!(job_name, error = %e, "Failed to renew job lease"),
+            }
+        }
+    }));
+
+    let output = tokio::select! {
+        output = job => output,
+        _ = &mut heartbeat.0 => {
+            tracing::warn!(job_name, "Job lease lost while running, cancelling the run");
+            return Ok(None);
+        }
+    };
+
+    drop(heartbeat);
+    JobLease::release(pool, job_name, holder_id).await?;
+
+    Ok(Some(output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_lost_lease_cancels_the_job() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+
+        let job_name: &'static str = Box::leak(format!("test-{}", Uuid::new_v4()).into_boxed_str());
+        let result = run_exclusive(
+            &pool,
+            Uuid::new_v4(),
+            job_name,
+            Duration::milliseconds(300),
+            async {
+                // Another instance takes the lease over, as after an expiry
+                sqlx::query("UPDATE job_leases SET holder_id = $2 WHERE job_name = $1")
+                    .bind(job_name)
+                    .bind(Uuid::new_v4())
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+                std::future::pending::<()>().await
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(result.is_none());
+    }
+}
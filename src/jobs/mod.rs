@@ -1,3 +1,9 @@
 // Jobs module - Background tasks
 
+pub mod event_closer;
+pub mod lease;
+pub mod scheduler;
+pub mod series_materializer;
 pub mod subscription_checker;
+pub mod suspension_reinstater;
+pub mod task_worker;
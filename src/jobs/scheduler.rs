@@ -0,0 +1,195 @@
+use chrono::Duration;
+use sqlx::PgPool;
+use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::jobs::{
+    event_closer, lease, series_materializer, subscription_checker, suspension_reinstater,
+    task_worker,
+};
+
+/// Every five minutes, at second 0
+const SUSPENSION_REINSTATEMENT_SCHEDULE: &str = "0 */5 * * * *";
+const SUSPENSION_REINSTATEMENT_BATCH_SIZE: i64 = 100;
+const SUSPENSION_REINSTATEMENT_LEASE_MINUTES: i64 = 5;
+
+/// Every five minutes, at second 30
+const EVENT_CLOSING_SCHEDULE: &str = "30 */5 * * * *";
+const EVENT_CLOSING_BATCH_SIZE: i64 = 100;
+const EVENT_CLOSING_LEASE_MINUTES: i64 = 5;
+
+/// Every hour, at minute 30
+const SERIES_MATERIALIZATION_SCHEDULE: &str = "0 30 * * * *";
+const SERIES_MATERIALIZATION_BATCH_SIZE: i64 = 100;
+const SERIES_MATERIALIZATION_LEASE_MINUTES: i64 = 10;
+
+/// Every hour, on the hour
+const MEMBERSHIP_VERIFICATION_SCHEDULE: &str = "0 0 * * * *";
+const MEMBERSHIP_VERIFICATION_BATCH_SIZE: i64 = 500;
+const MEMBERSHIP_VERIFICATION_LEASE_MINUTES: i64 = 15;
+
+/// Every ten seconds
+const TASK_WORKER_SCHEDULE: &str = "*/10 * * * * *";
+const TASK_WORKER_BATCH_SIZE: i64 = 20;
+
+/// Registers background jobs and starts the scheduler
+///
+/// Every instance runs the scheduler, but each job run is guarded by a lease in
+/// `job_leases`, so it executes on exactly one instance at a time. The task worker
+/// runs everywhere: task claiming already skips rows other workers hold.
+///
+/// The returned scheduler must be kept alive for the jobs to keep running.
+pub async fn start(pool: PgPool, config: Config) -> Result<JobScheduler, JobSchedulerError> {
+    let scheduler = JobScheduler::new().await?;
+    let instance_id = Uuid::new_v4();
+
+    tracing::info!(instance_id = %instance_id, "Registering background jobs");
+
+    let reinstatement_pool = pool.clone();
+    scheduler
+        .add(Job::new_async(
+            SUSPENSION_REINSTATEMENT_SCHEDULE,
+            move |_uuid, _lock| {
+                let pool = reinstatement_pool.clone();
+                Box::pin(async move {
+                    let result = lease::run_exclusive(
+                        &pool,
+                        instance_id,
+                        "suspension_reinstatement",
+                        Duration::minutes(SUSPENSION_REINSTATEMENT_LEASE_MINUTES),
+                        suspension_reinstater::reinstate_due_suspensions(
+                            &pool,
+                            SUSPENSION_REINSTATEMENT_BATCH_SIZE,
+                        ),
+                    )
+                    .await;
+
+                    match result {
+                        Ok(Some(Err(e))) | Err(e) => {
+                            tracing::error!(error = %e, "Suspension reinstatement job failed");
+                        }
+                        Ok(_) => {}
+                    }
+                })
+            },
+        )?)
+        .await?;
+
+    let closing_pool = pool.clone();
+    scheduler
+        .add(Job::new_async(
+            EVENT_CLOSING_SCHEDULE,
+            move |_uuid, _lock| {
+                let pool = closing_pool.clone();
+                Box::pin(async move {
+                    let result = lease::run_exclusive(
+                        &pool,
+                        instance_id,
+                        "event_closing",
+                        Duration::minutes(EVENT_CLOSING_LEASE_MINUTES),
+                        event_closer::close_ended_events(&pool, EVENT_CLOSING_BATCH_SIZE),
+                    )
+                    .await;
+
+                    match result {
+                        Ok(Some(Err(e))) | Err(e) => {
+                            tracing::error!(error = %e, "Event closing job failed");
+                        }
+                        Ok(_) => {}
+                    }
+                })
+            },
+        )?)
+        .await?;
+
+    let series_pool = pool.clone();
+    scheduler
+        .add(Job::new_async(
+            SERIES_MATERIALIZATION_SCHEDULE,
+            move |_uuid, _lock| {
+                let pool = series_pool.clone();
+                Box::pin(async move {
+                    let result = lease::run_exclusive(
+                        &pool,
+                        instance_id,
+                        "series_materialization",
+                        Duration::minutes(SERIES_MATERIALIZATION_LEASE_MINUTES),
+                        series_materializer::materialize_series(
+                            &pool,
+                            SERIES_MATERIALIZATION_BATCH_SIZE,
+                        ),
+                    )
+                    .await;
+
+                    match result {
+                        Ok(Some(Err(e))) | Err(e) => {
+                            tracing::error!(error = %e, "Series materialization job failed");
+                        }
+                        Ok(_) => {}
+                    }
+                })
+            },
+        )?)
+        .await?;
+
+    let task_pool = pool.clone();
+    let task_config = config.clone();
+    scheduler
+        .add(Job::new_async(
+            TASK_WORKER_SCHEDULE,
+            move |_uuid, _lock| {
+                let pool = task_pool.clone();
+                let config = task_config.clone();
+                Box::pin(async move {
+                    if let Err(e) = task_worker::run_due_tasks(
+                        &pool,
+                        &config,
+                        instance_id,
+                        TASK_WORKER_BATCH_SIZE,
+                    )
+                    .await
+                    {
+                        tracing::error!(error = %e, "Task worker run failed");
+                    }
+                })
+            },
+        )?)
+        .await?;
+
+    let verification_pool = pool.clone();
+    scheduler
+        .add(Job::new_async(
+            MEMBERSHIP_VERIFICATION_SCHEDULE,
+            move |_uuid, _lock| {
+                let pool = verification_pool.clone();
+                let config = config.clone();
+                Box::pin(async move {
+                    let result = lease::run_exclusive(
+                        &pool,
+                        instance_id,
+                        "membership_verification",
+                        Duration::minutes(MEMBERSHIP_VERIFICATION_LEASE_MINUTES),
+                        subscription_checker::verify_membership_cards(
+                            &pool,
+                            &config,
+                            MEMBERSHIP_VERIFICATION_BATCH_SIZE,
+                        ),
+                    )
+                    .await;
+
+                    match result {
+                        Ok(Some(Err(e))) | Err(e) => {
+                            tracing::error!(error = %e, "Membership verification job failed");
+                        }
+                        Ok(_) => {}
+                    }
+                })
+            },
+        )?)
+        .await?;
+
+    scheduler.start().await?;
+
+    Ok(scheduler)
+}
@@ -0,0 +1,27 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+
+use crate::models::event_series::EventSeries;
+use crate::services::event_series::{self, MATERIALIZE_AHEAD_DAYS};
+
+/// Background job that materializes the upcoming occurrences of recurring event series
+///
+/// Each active series gets its occurrences created up to `MATERIALIZE_AHEAD_DAYS`
+/// ahead, so they can be opened, reserved and scanned like any other event.
+/// Returns the number of occurrences created.
+pub async fn materialize_series(pool: &PgPool, batch_size: i64) -> Result<usize, sqlx::Error> {
+    let now = Utc::now();
+    let through = now.date_naive() + Duration::days(MATERIALIZE_AHEAD_DAYS);
+    let due = EventSeries::list_to_materialize(pool, through, batch_size).await?;
+    let mut created = 0;
+
+    for series in due {
+        created += event_series::materialize(pool, &series, now).await?.len();
+    }
+
+    if created > 0 {
+        tracing::info!(created, "Series materialization job completed");
+    }
+
+    Ok(created)
+}
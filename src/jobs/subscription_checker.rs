@@ -1,66 +1,147 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 use sqlx::PgPool;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use uuid::Uuid;
 
+use crate::config::Config;
 use crate::models::{
     card::{CardStatus, MembershipCard},
     issuer::CardIssuer,
     oauth_session::OAuthSession,
+    youtube_quota::YoutubeQuotaUsage,
+};
+use crate::services::{
+    membership_checker::{self, MembershipCheckError},
+    oauth::youtube,
 };
-use crate::services::{membership_checker, oauth::youtube};
 
-const EXPIRATION_EXTENSION_DAYS: i64 = 30;
-const FAILURE_THRESHOLD: i32 = 3;
+/// Backoff before each retry after a rate-limit response
+const RATE_LIMIT_BACKOFF: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(4),
+];
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct VerificationStats {
     pub total_checked: usize,
     pub still_members: usize,
     pub expired_memberships: usize,
     pub token_refresh_failures: usize,
     pub api_errors: usize,
+    /// Cards left for the next run (quota exhausted or rate limited)
+    pub deferred: usize,
 }
 
 /// Background job that verifies active membership cards
 ///
-/// For each active card that hasn't been verified in 24 hours:
+/// For each active card that hasn't been verified within its issuer's recheck interval:
 /// 1. Get member's OAuth session and refresh token if needed
-/// 2. Check video access using members-only video ID
-/// 3. If still a member: extend card expiration by 30 days
-/// 4. If not a member: increment failure count, expire after 3 failures
+/// 2. Reserve quota from the daily budget and check video access
+/// 3. If still a member: extend card expiration by the issuer's extension length
+/// 4. If not a member: increment failure count; once the issuer's failure threshold
+///    is reached, expire the card (after the grace period, if one is configured)
+///
+/// Cards are grouped by issuer and checked with bounded parallelism. When the daily
+/// quota runs out or YouTube keeps rate limiting, the remaining cards are left
+/// untouched and picked up first by the next run.
 pub async fn verify_membership_cards(
     pool: &PgPool,
+    config: &Config,
     batch_size: i64,
-) -> Result<VerificationStats, Box<dyn std::error::Error>> {
-    let mut stats = VerificationStats {
-        total_checked: 0,
-        still_members: 0,
-        expired_memberships: 0,
-        token_refresh_failures: 0,
-        api_errors: 0,
-    };
+) -> Result<VerificationStats, sqlx::Error> {
+    let mut stats = VerificationStats::default();
 
-    // Get cards that need verification
+    // Get cards that need verification, grouped by issuer
     let cards = MembershipCard::find_cards_needing_verification(pool, batch_size).await?;
     stats.total_checked = cards.len();
 
+    let mut cards_by_issuer: HashMap<Uuid, Vec<MembershipCard>> = HashMap::new();
+    for card in cards {
+        cards_by_issuer
+            .entry(card.issuer_id)
+            .or_default()
+            .push(card);
+    }
+
     tracing::info!(
         total_cards = stats.total_checked,
+        issuers = cards_by_issuer.len(),
         "Starting membership verification job"
     );
 
-    for card in cards {
-        match verify_single_card(pool, &card).await {
+    let config = Arc::new(config.clone());
+    let semaphore = Arc::new(Semaphore::new(config.subscription_check_concurrency.max(1)));
+    let quota_exhausted = Arc::new(AtomicBool::new(false));
+    let mut tasks = JoinSet::new();
+
+    for (issuer_id, cards) in cards_by_issuer {
+        let issuer = match CardIssuer::find_by_id(pool, issuer_id).await? {
+            Some(issuer) => Arc::new(issuer),
+            None => {
+                tracing::error!(issuer_id = %issuer_id, "Issuer not found for cards");
+                stats.api_errors += cards.len();
+                continue;
+            }
+        };
+
+        for card in cards {
+            let permit = semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+
+            if quota_exhausted.load(Ordering::Relaxed) {
+                stats.deferred += 1;
+                continue;
+            }
+
+            let pool = pool.clone();
+            let config = config.clone();
+            let issuer = issuer.clone();
+            let quota_exhausted = quota_exhausted.clone();
+
+            tasks.spawn(async move {
+                let _permit = permit;
+                let result =
+                    verify_single_card(&pool, &config, &issuer, &card, &quota_exhausted).await;
+                (card.id, result)
+            });
+        }
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (card_id, result) = match joined {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::error!(error = %e, "Membership verification task panicked");
+                stats.api_errors += 1;
+                continue;
+            }
+        };
+
+        match result {
             Ok(VerificationResult::StillMember) => {
                 stats.still_members += 1;
             }
             Ok(VerificationResult::MembershipExpired) => {
                 stats.expired_memberships += 1;
             }
+            Ok(VerificationResult::Deferred) => {
+                stats.deferred += 1;
+            }
             Err(VerificationError::TokenRefreshFailed) => {
                 stats.token_refresh_failures += 1;
             }
             Err(VerificationError::ApiError(e)) => {
                 tracing::error!(
-                    card_id = %card.id,
+                    card_id = %card_id,
                     error = %e,
                     "API error during verification"
                 );
@@ -68,7 +149,7 @@ pub async fn verify_membership_cards(
             }
             Err(VerificationError::DatabaseError(e)) => {
                 tracing::error!(
-                    card_id = %card.id,
+                    card_id = %card_id,
                     error = %e,
                     "Database error during verification"
                 );
@@ -85,6 +166,8 @@ pub async fn verify_membership_cards(
 enum VerificationResult {
     StillMember,
     MembershipExpired,
+    /// Not checked this run; the card is left as-is for the next run
+    Deferred,
 }
 
 enum VerificationError {
@@ -95,21 +178,22 @@ enum VerificationError {
 
 async fn verify_single_card(
     pool: &PgPool,
+    config: &Config,
+    issuer: &CardIssuer,
     card: &MembershipCard,
+    quota_exhausted: &AtomicBool,
 ) -> Result<VerificationResult, VerificationError> {
-    // 1. Load issuer configuration
-    let issuer = CardIssuer::find_by_id(pool, card.issuer_id)
-        .await
-        .map_err(VerificationError::DatabaseError)?
-        .ok_or_else(|| VerificationError::ApiError("Issuer not found".to_string()))?;
+    if quota_exhausted.load(Ordering::Relaxed) {
+        return Ok(VerificationResult::Deferred);
+    }
 
-    // 2. Load member's OAuth session
+    // 1. Load member's OAuth session
     let oauth_session = OAuthSession::find_by_member_id(pool, card.member_id)
         .await
         .map_err(VerificationError::DatabaseError)?
         .ok_or_else(|| VerificationError::ApiError("OAuth session not found".to_string()))?;
 
-    // 3. Refresh token if expired
+    // 2. Refresh token if expired
     let access_token = if oauth_session.is_expired() {
         tracing::info!(
             card_id = %card.id,
@@ -123,19 +207,11 @@ async fn verify_single_card(
             .and_then(|t| String::from_utf8(t.clone()).ok())
             .ok_or(VerificationError::TokenRefreshFailed)?;
 
-        // Get config from environment (in a real implementation, pass this in)
-        let youtube_client_id = std::env::var("YOUTUBE_CLIENT_ID")
-            .map_err(|_| VerificationError::TokenRefreshFailed)?;
-        let youtube_client_secret = std::env::var("YOUTUBE_CLIENT_SECRET")
-            .map_err(|_| VerificationError::TokenRefreshFailed)?;
-        let base_url =
-            std::env::var("BASE_URL").map_err(|_| VerificationError::TokenRefreshFailed)?;
-
         let token_data = youtube::refresh_access_token(
             &refresh_token,
-            &youtube_client_id,
-            &secrecy::Secret::new(youtube_client_secret),
-            &format!("{}/auth/youtube/callback", base_url),
+            &config.youtube_client_id,
+            &config.youtube_client_secret,
+            &format!("{}/auth/youtube/callback", config.base_url),
         )
         .await
         .map_err(|e| {
@@ -160,20 +236,63 @@ async fn verify_single_card(
             .map_err(|_| VerificationError::ApiError("Invalid token encoding".to_string()))?
     };
 
-    // 4. Check membership access
+    // 3. Check membership access, within the daily quota budget
     let video_id = issuer
         .members_only_video_id
         .as_ref()
         .unwrap_or(&issuer.verification_video_id);
 
-    let is_still_member = membership_checker::check_video_access(&access_token, video_id)
+    let mut backoff = RATE_LIMIT_BACKOFF.iter();
+    let is_still_member = loop {
+        let reserved = YoutubeQuotaUsage::try_reserve(
+            pool,
+            membership_checker::LIST_CALL_QUOTA_COST,
+            config.youtube_daily_quota,
+        )
         .await
-        .map_err(|e| VerificationError::ApiError(e.to_string()))?;
+        .map_err(VerificationError::DatabaseError)?;
+
+        if !reserved {
+            if !quota_exhausted.swap(true, Ordering::Relaxed) {
+                tracing::warn!(
+                    daily_quota = config.youtube_daily_quota,
+                    "Daily YouTube quota budget spent, deferring remaining cards"
+                );
+            }
+            return Ok(VerificationResult::Deferred);
+        }
+
+        match membership_checker::check_video_access(&access_token, video_id).await {
+            Ok(is_member) => break is_member,
+            Err(MembershipCheckError::QuotaExceeded) => {
+                YoutubeQuotaUsage::mark_exhausted(pool)
+                    .await
+                    .map_err(VerificationError::DatabaseError)?;
+                if !quota_exhausted.swap(true, Ordering::Relaxed) {
+                    tracing::warn!("YouTube reported quotaExceeded, deferring remaining cards");
+                }
+                return Ok(VerificationResult::Deferred);
+            }
+            Err(MembershipCheckError::RateLimited) => match backoff.next() {
+                Some(delay) => {
+                    tracing::debug!(card_id = %card.id, ?delay, "Rate limited, backing off");
+                    tokio::time::sleep(*delay).await;
+                }
+                None => {
+                    tracing::warn!(card_id = %card.id, "Still rate limited, deferring card");
+                    return Ok(VerificationResult::Deferred);
+                }
+            },
+            Err(e) => return Err(VerificationError::ApiError(e.to_string())),
+        }
+    };
+
+    // 4. Update card based on result
+    let policy = issuer.lifecycle_policy();
 
-    // 5. Update card based on result
     if is_still_member {
         // Extend expiration and reset failures
-        MembershipCard::extend_expiration(pool, card.id, EXPIRATION_EXTENSION_DAYS)
+        MembershipCard::extend_expiration(pool, card.id, i64::from(policy.extension_days))
             .await
             .map_err(VerificationError::DatabaseError)?;
 
@@ -197,22 +316,41 @@ async fn verify_single_card(
             "Membership verification failed"
         );
 
-        // Mark as expired if threshold reached
-        if failures >= FAILURE_THRESHOLD {
-            MembershipCard::set_status(pool, card.id, CardStatus::Expired)
+        if failures < policy.failure_threshold {
+            return Ok(VerificationResult::MembershipExpired);
+        }
+
+        // Threshold reached: keep the card valid through the grace period, if any
+        if policy.grace_period_days > 0 && !card.is_expired() {
+            let grace_ends_at =
+                chrono::Utc::now() + chrono::Duration::days(i64::from(policy.grace_period_days));
+
+            MembershipCard::cap_expiration(pool, card.id, grace_ends_at)
                 .await
                 .map_err(VerificationError::DatabaseError)?;
 
             tracing::info!(
                 card_id = %card.id,
                 member_id = %card.member_id,
-                "Card marked as expired after {} failures",
-                failures
+                failures = failures,
+                grace_period_days = policy.grace_period_days,
+                "Failure threshold reached, card in grace period"
             );
 
-            Ok(VerificationResult::MembershipExpired)
-        } else {
-            Ok(VerificationResult::MembershipExpired)
+            return Ok(VerificationResult::MembershipExpired);
         }
+
+        MembershipCard::set_status(pool, card.id, CardStatus::Expired)
+            .await
+            .map_err(VerificationError::DatabaseError)?;
+
+        tracing::info!(
+            card_id = %card.id,
+            member_id = %card.member_id,
+            "Card marked as expired after {} failures",
+            failures
+        );
+
+        Ok(VerificationResult::MembershipExpired)
     }
 }
@@ -0,0 +1,32 @@
+use sqlx::PgPool;
+
+use crate::models::suspension::CardSuspension;
+
+/// Background job that reinstates cards whose suspension has ended
+///
+/// For each open suspension with an `ends_at` in the past, lifts the suspension
+/// and flips the card back to active. Returns the number of cards reinstated.
+pub async fn reinstate_due_suspensions(
+    pool: &PgPool,
+    batch_size: i64,
+) -> Result<usize, sqlx::Error> {
+    let due = CardSuspension::find_due(pool, batch_size).await?;
+    let mut reinstated = 0;
+
+    for suspension in due {
+        if CardSuspension::lift(pool, suspension.card_id, None).await? {
+            tracing::info!(
+                card_id = %suspension.card_id,
+                suspension_id = %suspension.id,
+                "Suspension ended, card reinstated"
+            );
+            reinstated += 1;
+        }
+    }
+
+    if reinstated > 0 {
+        tracing::info!(reinstated, "Suspension reinstatement job completed");
+    }
+
+    Ok(reinstated)
+}
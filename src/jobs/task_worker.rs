@@ -0,0 +1,47 @@
+use chrono::Duration;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::background_task::BackgroundTask;
+use crate::services::task_queue;
+
+/// Background job that executes due tasks from the task queue
+///
+/// Claims up to `batch_size` due tasks (plus tasks whose worker lease expired) and
+/// runs them one by one. Claiming skips rows locked by other workers, so every
+/// instance can run this job concurrently. Returns the number of tasks that succeeded.
+pub async fn run_due_tasks(
+    pool: &PgPool,
+    config: &Config,
+    worker_id: Uuid,
+    batch_size: i64,
+) -> Result<usize, sqlx::Error> {
+    let tasks = BackgroundTask::claim_due(
+        pool,
+        worker_id,
+        Duration::minutes(task_queue::TASK_LEASE_MINUTES),
+        batch_size,
+    )
+    .await?;
+
+    if tasks.is_empty() {
+        return Ok(0);
+    }
+
+    let mut succeeded = 0;
+
+    for task in &tasks {
+        if task_queue::run(pool, config, task).await? {
+            succeeded += 1;
+        }
+    }
+
+    tracing::info!(
+        claimed = tasks.len(),
+        succeeded,
+        "Task worker run completed"
+    );
+
+    Ok(succeeded)
+}
@@ -26,6 +26,8 @@ async fn main() -> anyhow::Result<()> {
 
     // Load configuration
     let config = Config::from_env()?;
+    // Fail fast on a malformed encryption key rather than on first use
+    config.credential_cipher()?;
     tracing::info!("Configuration loaded successfully");
 
     // Create database pool
@@ -36,6 +38,13 @@ async fn main() -> anyhow::Result<()> {
     db::run_migrations(&pool).await?;
     tracing::info!("Database migrations completed");
 
+    // Start background jobs (kept alive for the lifetime of the server)
+    let _scheduler = vpass::jobs::scheduler::start(pool.clone(), config.clone()).await?;
+    tracing::info!("Background job scheduler started");
+
+    // Scan the system fonts for printable cards before the first print request
+    tokio::task::spawn_blocking(vpass::services::qr_render::preload_fonts);
+
     // Create session layer
     let session_secret = config.session_secret.expose_secret().as_bytes();
     let session_layer =
@@ -62,6 +71,8 @@ async fn main() -> anyhow::Result<()> {
         .merge(vpass::api::issuers::router())
         .merge(vpass::api::events::router())
         .merge(vpass::api::verification::router())
+        .merge(vpass::api::tasks::router())
+        .merge(vpass::api::openid::router())
         .merge(static_routes)
         .layer(session_layer)
         .layer(TraceLayer::new_for_http())
@@ -0,0 +1,294 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// A card's attendance at an event, claimable as a wallet credential
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AttendanceCredential {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub card_id: Uuid,
+    pub admitted_at: DateTime<Utc>,
+    pub transaction_id: Option<String>,
+    pub wallet_qr_code: Option<String>,
+    pub wallet_deep_link: Option<String>,
+    pub offered_at: Option<DateTime<Utc>>,
+    pub cid: Option<String>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One entry of a member's attendance history
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AttendanceRecord {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub event_name: String,
+    pub event_date: NaiveDate, // local date of the event
+    pub issuer_id: Uuid,       // the event's issuer
+    pub issuer_name: String,
+    pub card_id: Uuid,
+    pub membership_level_label: String,
+    pub admitted_at: DateTime<Utc>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    /// Whether the credential can be claimed; the issuer's template is checked
+    /// here, its wallet account by `attendance_credentials::list_for_member`
+    pub claimable: bool,
+}
+
+impl AttendanceCredential {
+    /// Records a card's admission to an event; later admissions keep the first
+    pub async fn record<'e>(
+        executor: impl PgExecutor<'e>,
+        event_id: Uuid,
+        card_id: Uuid,
+        admitted_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO attendance_credentials (event_id, card_id, admitted_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (event_id, card_id)
+            DO UPDATE SET admitted_at = LEAST(attendance_credentials.admitted_at, EXCLUDED.admitted_at)
+            "#,
+        )
+        .bind(event_id)
+        .bind(card_id)
+        .bind(admitted_at)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let attendance = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM attendance_credentials
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(attendance)
+    }
+
+    /// Stores a new wallet offer, replacing an earlier unclaimed one
+    pub async fn set_offer(
+        pool: &PgPool,
+        id: Uuid,
+        transaction_id: &str,
+        qr_code: &str,
+        deep_link: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE attendance_credentials
+            SET transaction_id = $2, wallet_qr_code = $3, wallet_deep_link = $4, offered_at = NOW()
+            WHERE id = $1 AND claimed_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(transaction_id)
+        .bind(qr_code)
+        .bind(deep_link)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records that the member accepted the credential into their wallet
+    pub async fn mark_claimed(pool: &PgPool, id: Uuid, cid: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE attendance_credentials
+            SET cid = $2, claimed_at = NOW()
+            WHERE id = $1 AND claimed_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(cid)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists the events a member's cards were admitted to, most recent first
+    ///
+    /// Use `attendance_credentials::list_for_member` for which are claimable.
+    pub async fn list_by_member(
+        pool: &PgPool,
+        member_id: Uuid,
+    ) -> Result<Vec<AttendanceRecord>, sqlx::Error> {
+        let records = sqlx::query_as::<_, AttendanceRecord>(
+            r#"
+            SELECT
+                a.id,
+                a.event_id,
+                e.event_name,
+                e.event_date,
+                i.id AS issuer_id,
+                i.channel_name AS issuer_name,
+                a.card_id,
+                c.membership_level_label,
+                a.admitted_at,
+                a.claimed_at,
+                i.attendance_vc_uid IS NOT NULL AS claimable
+            FROM attendance_credentials a
+            JOIN events e ON e.id = a.event_id
+            JOIN card_issuers i ON i.id = e.issuer_id
+            JOIN membership_cards c ON c.id = a.card_id
+            WHERE c.member_id = $1
+            ORDER BY a.admitted_at DESC
+            "#,
+        )
+        .bind(member_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Counts the event's attendees who claimed their attendance credential
+    pub async fn count_claimed_by_event(pool: &PgPool, event_id: Uuid) -> Result<i64, sqlx::Error> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM attendance_credentials
+            WHERE event_id = $1 AND claimed_at IS NOT NULL
+            "#,
+        )
+        .bind(event_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        card::{CreateCardData, MembershipCard},
+        event::{CreateEventData, Event, EventSchedule, ReentryPolicy, DEFAULT_TIMEZONE},
+        issuer::{CardIssuer, CreateIssuerData},
+        member::{CreateMemberData, Member},
+    };
+
+    /// An admitted card with an attendance to claim
+    async fn admitted(pool: &PgPool) -> AttendanceCredential {
+        let channel_id = format!("UC{}", Uuid::new_v4().simple());
+        let issuer = CardIssuer::create(
+            pool,
+            CreateIssuerData {
+                youtube_channel_id: channel_id.clone(),
+                channel_handle: None,
+                channel_name: "星詠".to_string(),
+                verification_video_id: "video".to_string(),
+                default_membership_label: "會員".to_string(),
+                vc_uid: None,
+            },
+        )
+        .await
+        .unwrap();
+        let member = Member::create(
+            pool,
+            CreateMemberData {
+                youtube_user_id: format!("member-{}", channel_id),
+                default_display_name: "小明".to_string(),
+                avatar_url: None,
+                locale: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let card = MembershipCard::create(
+            &mut conn,
+            CreateCardData {
+                issuer_id: issuer.id,
+                member_id: member.id,
+                membership_level_label: "會員".to_string(),
+                membership_confirmed_at: Utc::now(),
+                verification_comment_id: "comment".to_string(),
+                verification_video_id: "video".to_string(),
+                snapshot_json: serde_json::json!({}),
+                validity_days: 30,
+            },
+        )
+        .await
+        .unwrap();
+        let now = Utc::now();
+        let event = Event::create(
+            pool,
+            CreateEventData {
+                issuer_id: issuer.id,
+                event_name: "見面會".to_string(),
+                event_description: None,
+                schedule: EventSchedule {
+                    timezone: DEFAULT_TIMEZONE,
+                    starts_at: now,
+                    ends_at: now + chrono::Duration::hours(2),
+                    checkin_opens_at: None,
+                    checkin_closes_at: None,
+                },
+                event_location: None,
+                verifier_ref: "verifier".to_string(),
+                reentry_policy: ReentryPolicy::SingleEntry,
+                capacity: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        AttendanceCredential::record(pool, event.id, card.id, now)
+            .await
+            .unwrap();
+        sqlx::query_as::<_, AttendanceCredential>(
+            "SELECT * FROM attendance_credentials WHERE event_id = $1 AND card_id = $2",
+        )
+        .bind(event.id)
+        .bind(card.id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_claimed_attendance_keeps_its_credential() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+        let attendance = admitted(&pool).await;
+
+        AttendanceCredential::set_offer(&pool, attendance.id, "tx-1", "qr-1", "link-1")
+            .await
+            .unwrap();
+        AttendanceCredential::mark_claimed(&pool, attendance.id, "cid-1")
+            .await
+            .unwrap();
+
+        // Neither a new offer nor a second claim replaces the claimed credential
+        AttendanceCredential::set_offer(&pool, attendance.id, "tx-2", "qr-2", "link-2")
+            .await
+            .unwrap();
+        AttendanceCredential::mark_claimed(&pool, attendance.id, "cid-2")
+            .await
+            .unwrap();
+
+        let claimed = AttendanceCredential::find_by_id(&pool, attendance.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.transaction_id.as_deref(), Some("tx-1"));
+        assert_eq!(claimed.wallet_qr_code.as_deref(), Some("qr-1"));
+        assert_eq!(claimed.cid.as_deref(), Some("cid-1"));
+    }
+}
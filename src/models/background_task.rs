@@ -0,0 +1,304 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// Work executed by the task worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskPayload {
+    /// Generate the wallet QR code for a newly issued card
+    GenerateWalletQr { card_id: Uuid },
+    /// Revoke a wallet credential after its card was deleted
+    RevokeWalletCredential { card_id: Uuid, cid: String },
+}
+
+impl TaskPayload {
+    /// Returns the task kind as stored in the `kind` column
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TaskPayload::GenerateWalletQr { .. } => "generate_wallet_qr",
+            TaskPayload::RevokeWalletCredential { .. } => "revoke_wallet_credential",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BackgroundTask {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: JsonValue,
+    pub status: String, // "pending", "running", "succeeded", "dead"
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    pub locked_by: Option<Uuid>,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BackgroundTask {
+    /// Enqueues a task
+    /// Pass the transaction that performs the related state change so the task
+    /// is only recorded if that change commits.
+    pub async fn enqueue<'e>(
+        executor: impl PgExecutor<'e>,
+        payload: &TaskPayload,
+    ) -> Result<Self, sqlx::Error> {
+        let payload_json = serde_json::to_value(payload)
+            .map_err(|e| sqlx::Error::Protocol(format!("Invalid task payload: {}", e)))?;
+
+        let task = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO background_tasks (kind, payload)
+            VALUES ($1, $2)
+            RETURNING *
+            "#,
+        )
+        .bind(payload.kind())
+        .bind(payload_json)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(task)
+    }
+
+    /// Claims up to `limit` due tasks for a worker
+    /// Also reclaims running tasks whose lease expired (the worker died mid-run).
+    /// Rows locked by another worker's claim are skipped, so concurrent workers
+    /// never receive the same task.
+    pub async fn claim_due(
+        pool: &PgPool,
+        worker_id: Uuid,
+        lease: Duration,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let tasks = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE background_tasks
+            SET status = 'running',
+                attempts = attempts + 1,
+                locked_by = $1,
+                locked_until = NOW() + $2
+            WHERE id IN (
+                SELECT id FROM background_tasks
+                WHERE (status = 'pending' AND run_at <= NOW())
+                   OR (status = 'running' AND locked_until < NOW())
+                ORDER BY run_at ASC
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(worker_id)
+        .bind(lease)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Claims a specific pending task (used to run a task right after enqueueing it)
+    /// Returns None if a worker already picked it up.
+    pub async fn claim(
+        pool: &PgPool,
+        id: Uuid,
+        worker_id: Uuid,
+        lease: Duration,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let task = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE background_tasks
+            SET status = 'running',
+                attempts = attempts + 1,
+                locked_by = $2,
+                locked_until = NOW() + $3
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(worker_id)
+        .bind(lease)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(task)
+    }
+
+    /// Marks a task as succeeded
+    pub async fn complete(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE background_tasks
+            SET status = 'succeeded',
+                completed_at = NOW(),
+                locked_by = NULL,
+                locked_until = NULL,
+                last_error = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a failed attempt
+    /// The task is retried after `retry_in`, or dead-lettered once it has used
+    /// all its attempts (or immediately when `retry_in` is None).
+    /// Returns true if the task was dead-lettered.
+    pub async fn fail(
+        pool: &PgPool,
+        id: Uuid,
+        error: &str,
+        retry_in: Option<Duration>,
+    ) -> Result<bool, sqlx::Error> {
+        let (status,): (String,) = sqlx::query_as(
+            r#"
+            UPDATE background_tasks
+            SET status = CASE
+                    WHEN $3::interval IS NULL OR attempts >= max_attempts THEN 'dead'
+                    ELSE 'pending'
+                END,
+                run_at = NOW() + COALESCE($3::interval, INTERVAL '0'),
+                locked_by = NULL,
+                locked_until = NULL,
+                last_error = $2
+            WHERE id = $1
+            RETURNING status
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_in)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(status == "dead")
+    }
+
+    /// Puts a task back in the queue without counting the attempt
+    /// Used when the failure says nothing about the task itself (e.g. the upstream
+    /// service is down), so an outage cannot exhaust a task's attempts.
+    pub async fn defer(
+        pool: &PgPool,
+        id: Uuid,
+        error: &str,
+        delay: Duration,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE background_tasks
+            SET status = 'pending',
+                attempts = GREATEST(attempts - 1, 0),
+                run_at = NOW() + $3,
+                locked_by = NULL,
+                locked_until = NULL,
+                last_error = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(delay)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists tasks that need an operator's attention:
+    /// dead-lettered tasks, tasks retrying after a failure, and running tasks past their lease
+    pub async fn list_needing_attention(
+        pool: &PgPool,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let tasks = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM background_tasks
+            WHERE status = 'dead'
+               OR (status = 'pending' AND last_error IS NOT NULL)
+               OR (status = 'running' AND locked_until < NOW())
+            ORDER BY updated_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+
+    /// Counts tasks by status
+    pub async fn count_by_status(pool: &PgPool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        let counts = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT status, COUNT(*) FROM background_tasks
+            GROUP BY status
+            ORDER BY status
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    /// Puts a dead-lettered or retrying task back in the queue with a fresh set of attempts
+    /// Returns false if the task is not in a retryable state.
+    pub async fn retry_now(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE background_tasks
+            SET status = 'pending',
+                attempts = 0,
+                run_at = NOW()
+            WHERE id = $1 AND status IN ('dead', 'pending')
+            "#,
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Parses the stored payload
+    pub fn task_payload(&self) -> Result<TaskPayload, serde_json::Error> {
+        serde_json::from_value(self.payload.clone())
+    }
+
+    /// Checks if the task has been dead-lettered
+    pub fn is_dead(&self) -> bool {
+        self.status == "dead"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_round_trip() {
+        let payload = TaskPayload::RevokeWalletCredential {
+            card_id: Uuid::new_v4(),
+            cid: "abc".to_string(),
+        };
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["kind"], payload.kind());
+
+        let parsed: TaskPayload = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, payload);
+    }
+}
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use sqlx::{FromRow, PgPool, Type};
+use sqlx::{FromRow, PgConnection, PgExecutor, PgPool, Type};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
@@ -14,6 +14,19 @@ pub enum CardStatus {
     Deleted,
 }
 
+impl CardStatus {
+    /// Returns the database/string representation of the status
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CardStatus::Active => "active",
+            CardStatus::Expired => "expired",
+            CardStatus::Revoked => "revoked",
+            CardStatus::Suspended => "suspended",
+            CardStatus::Deleted => "deleted",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct MembershipCard {
     pub id: Uuid,
@@ -25,6 +38,7 @@ pub struct MembershipCard {
     pub verification_video_id: String,
     pub snapshot_json: JsonValue,
     pub status: CardStatus,
+    pub status_changed_at: DateTime<Utc>, // maintained by a trigger on status updates
     pub expires_at: Option<DateTime<Utc>>,
     pub last_verified_at: Option<DateTime<Utc>>,
     pub verification_failures: i32,
@@ -39,6 +53,13 @@ pub struct MembershipCard {
     pub wallet_scanned_at: Option<DateTime<Utc>>,
 }
 
+/// A revoked or suspended card, as listed in offline scanning bundles
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DeniedCard {
+    pub id: Uuid,
+    pub status: CardStatus,
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateCardData {
     pub issuer_id: Uuid,
@@ -48,18 +69,21 @@ pub struct CreateCardData {
     pub verification_comment_id: String,
     pub verification_video_id: String,
     pub snapshot_json: JsonValue,
+    pub validity_days: i64, // issuer's initial validity
 }
 
 impl MembershipCard {
     /// Creates a new membership card
     /// Automatically marks existing cards as deleted for the same issuer/member pair
-    /// and sets expiration (30 days from now)
-    pub async fn create(pool: &PgPool, data: CreateCardData) -> Result<Self, sqlx::Error> {
+    /// and sets expiration (`validity_days` from now).
+    /// Runs on the caller's transaction so follow-up work (e.g. background tasks)
+    /// can be recorded atomically with the card.
+    pub async fn create(
+        conn: &mut PgConnection,
+        data: CreateCardData,
+    ) -> Result<Self, sqlx::Error> {
         use chrono::Duration;
 
-        // Start a transaction
-        let mut tx = pool.begin().await?;
-
         // Mark any existing non-deleted cards as deleted for this issuer/member combination
         sqlx::query(
             r#"
@@ -70,11 +94,11 @@ impl MembershipCard {
         )
         .bind(data.issuer_id)
         .bind(data.member_id)
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await?;
 
-        // Calculate initial expiration (30 days from now)
-        let expires_at = chrono::Utc::now() + Duration::days(30);
+        // Calculate initial expiration from the issuer's policy
+        let expires_at = chrono::Utc::now() + Duration::days(data.validity_days);
 
         // Insert the new card
         let card = sqlx::query_as::<_, Self>(
@@ -96,15 +120,18 @@ impl MembershipCard {
         .bind(&data.verification_video_id)
         .bind(&data.snapshot_json)
         .bind(expires_at)
-        .fetch_one(&mut *tx)
+        .fetch_one(&mut *conn)
         .await?;
 
-        // Commit the transaction
-        tx.commit().await?;
-
         Ok(card)
     }
 
+    /// Checks if the card is still waiting for its wallet QR code
+    /// (issued while the wallet API was unavailable; the task worker attaches it later)
+    pub fn is_wallet_pending(&self) -> bool {
+        self.status == CardStatus::Active && self.wallet_transaction_id.is_none()
+    }
+
     /// Checks if the card has expired
     /// Returns true if expires_at exists and is in the past
     pub fn is_expired(&self) -> bool {
@@ -115,14 +142,17 @@ impl MembershipCard {
     }
 
     /// Finds a card by its ID
-    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+    pub async fn find_by_id<'e>(
+        executor: impl PgExecutor<'e>,
+        id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
         let card = sqlx::query_as::<_, Self>(
             r#"
             SELECT * FROM membership_cards WHERE id = $1
             "#,
         )
         .bind(id)
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await?;
 
         Ok(card)
@@ -151,10 +181,26 @@ impl MembershipCard {
         Ok(card)
     }
 
+    /// Takes the transaction-scoped issuance lock for a member/issuer pair
+    /// Held until the transaction ends, so concurrent claims for the same pair
+    /// run their duplicate check and insert one after the other.
+    pub async fn lock_issuance(
+        conn: &mut PgConnection,
+        issuer_id: Uuid,
+        member_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("card_issuance:{}:{}", issuer_id, member_id))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
     /// Finds active AND unexpired cards for a member at a specific issuer
     /// Used for duplicate card prevention (FR-006 + FR-006a)
-    pub async fn find_active_unexpired_cards(
-        pool: &PgPool,
+    pub async fn find_active_unexpired_cards<'e>(
+        executor: impl PgExecutor<'e>,
         issuer_id: Uuid,
         member_id: Uuid,
     ) -> Result<Vec<Self>, sqlx::Error> {
@@ -169,12 +215,36 @@ impl MembershipCard {
         )
         .bind(issuer_id)
         .bind(member_id)
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await?;
 
         Ok(cards)
     }
 
+    /// Finds a suspended card for a member at a specific issuer
+    /// Used to stop members from side-stepping a suspension by claiming a new card
+    pub async fn find_suspended_for_member<'e>(
+        executor: impl PgExecutor<'e>,
+        issuer_id: Uuid,
+        member_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let card = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM membership_cards
+            WHERE issuer_id = $1 AND member_id = $2
+              AND status = 'suspended'
+            ORDER BY issued_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(issuer_id)
+        .bind(member_id)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(card)
+    }
+
     /// Lists all non-deleted cards for a member (across all issuers)
     pub async fn list_by_member(pool: &PgPool, member_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
         let cards = sqlx::query_as::<_, Self>(
@@ -191,6 +261,28 @@ impl MembershipCard {
         Ok(cards)
     }
 
+    /// Lists every card a member has held with an issuer, deleted and replaced
+    /// ones included, most recently issued first
+    pub async fn list_history(
+        pool: &PgPool,
+        member_id: Uuid,
+        issuer_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let cards = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM membership_cards
+            WHERE member_id = $1 AND issuer_id = $2
+            ORDER BY issued_at DESC
+            "#,
+        )
+        .bind(member_id)
+        .bind(issuer_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(cards)
+    }
+
     /// Lists all non-deleted cards issued by a specific issuer
     pub async fn list_by_issuer(pool: &PgPool, issuer_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
         let cards = sqlx::query_as::<_, Self>(
@@ -229,7 +321,10 @@ impl MembershipCard {
     }
 
     /// Soft deletes a card by setting status to 'deleted' and recording deletion timestamp
-    pub async fn soft_delete(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+    pub async fn soft_delete<'e>(
+        executor: impl PgExecutor<'e>,
+        id: Uuid,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
             UPDATE membership_cards
@@ -238,7 +333,7 @@ impl MembershipCard {
             "#,
         )
         .bind(id)
-        .execute(pool)
+        .execute(executor)
         .await?;
 
         Ok(())
@@ -288,6 +383,69 @@ impl MembershipCard {
         Ok(result.0)
     }
 
+    /// Caps card expiration at the given time without ever extending it
+    /// Used to start the grace period once the failure threshold is reached
+    pub async fn cap_expiration(
+        pool: &PgPool,
+        id: Uuid,
+        latest: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE membership_cards
+            SET expires_at = LEAST(COALESCE(expires_at, $2), $2)
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(latest)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists the revoked and suspended cards of the given issuers
+    pub async fn list_denied(
+        pool: &PgPool,
+        issuer_ids: &[Uuid],
+    ) -> Result<Vec<DeniedCard>, sqlx::Error> {
+        let cards = sqlx::query_as::<_, DeniedCard>(
+            r#"
+            SELECT id, status FROM membership_cards
+            WHERE issuer_id = ANY($1) AND status IN ('revoked', 'suspended')
+            ORDER BY id
+            "#,
+        )
+        .bind(issuer_ids)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    /// Lists an issuer's active cards whose tier is not among `accepted_tiers`
+    pub async fn list_below_tier(
+        pool: &PgPool,
+        issuer_id: Uuid,
+        accepted_tiers: &[String],
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let cards = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM membership_cards
+            WHERE issuer_id = $1 AND status = 'active'
+              AND NOT (membership_level_label = ANY($2))
+            ORDER BY id
+            "#,
+        )
+        .bind(issuer_id)
+        .bind(accepted_tiers)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(cards)
+    }
+
     /// Counts total active cards issued by an issuer
     pub async fn count_by_issuer(pool: &PgPool, issuer_id: Uuid) -> Result<i64, sqlx::Error> {
         let result: (i64,) = sqlx::query_as(
@@ -303,17 +461,22 @@ impl MembershipCard {
         Ok(result.0)
     }
 
-    /// Finds cards that need verification (active cards not verified in last 24 hours)
+    /// Finds cards that need verification
+    /// (active cards not verified within their issuer's recheck interval)
     pub async fn find_cards_needing_verification(
         pool: &PgPool,
         limit: i64,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let cards = sqlx::query_as::<_, Self>(
             r#"
-            SELECT * FROM membership_cards
-            WHERE status = 'active'
-              AND (last_verified_at IS NULL OR last_verified_at < NOW() - INTERVAL '24 hours')
-            ORDER BY last_verified_at ASC NULLS FIRST
+            SELECT c.* FROM membership_cards c
+            JOIN card_issuers i ON i.id = c.issuer_id
+            WHERE c.status = 'active'
+              AND (
+                c.last_verified_at IS NULL
+                OR c.last_verified_at < NOW() - make_interval(hours => i.recheck_interval_hours)
+              )
+            ORDER BY c.last_verified_at ASC NULLS FIRST
             LIMIT $1
             "#,
         )
@@ -353,6 +516,29 @@ impl MembershipCard {
         Ok(())
     }
 
+    /// Clears the card's wallet offer and credential so a new offer can be generated
+    pub async fn clear_wallet<'e>(
+        executor: impl PgExecutor<'e>,
+        card_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE membership_cards
+            SET wallet_transaction_id = NULL,
+                wallet_qr_code = NULL,
+                wallet_deep_link = NULL,
+                wallet_cid = NULL,
+                wallet_scanned_at = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(card_id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
     /// Finds a card by wallet transaction ID
     pub async fn find_by_wallet_transaction_id(
         pool: &PgPool,
@@ -393,3 +579,95 @@ impl MembershipCard {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        issuer::{CardIssuer, CreateIssuerData},
+        member::{CreateMemberData, Member},
+    };
+
+    /// The duplicate check and insert of `card_issuer::issue_card`, slowed down
+    /// so that concurrent claims overlap
+    async fn claim(pool: &PgPool, issuer_id: Uuid, member_id: Uuid) -> bool {
+        let mut tx = pool.begin().await.unwrap();
+        MembershipCard::lock_issuance(&mut tx, issuer_id, member_id)
+            .await
+            .unwrap();
+
+        let existing = MembershipCard::find_active_unexpired_cards(&mut *tx, issuer_id, member_id)
+            .await
+            .unwrap();
+        if !existing.is_empty() {
+            tx.rollback().await.unwrap();
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        MembershipCard::create(
+            &mut tx,
+            CreateCardData {
+                issuer_id,
+                member_id,
+                membership_level_label: "會員".to_string(),
+                membership_confirmed_at: Utc::now(),
+                verification_comment_id: "comment".to_string(),
+                verification_video_id: "video".to_string(),
+                snapshot_json: serde_json::json!({}),
+                validity_days: 30,
+            },
+        )
+        .await
+        .unwrap();
+        tx.commit().await.unwrap();
+
+        true
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_concurrent_issuance_creates_one_card() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+
+        let channel_id = format!("UC{}", Uuid::new_v4().simple());
+        let issuer = CardIssuer::create(
+            &pool,
+            CreateIssuerData {
+                youtube_channel_id: channel_id.clone(),
+                channel_handle: None,
+                channel_name: "星詠".to_string(),
+                verification_video_id: "video".to_string(),
+                default_membership_label: "會員".to_string(),
+                vc_uid: None,
+            },
+        )
+        .await
+        .unwrap();
+        let member = Member::create(
+            &pool,
+            CreateMemberData {
+                youtube_user_id: format!("member-{}", channel_id),
+                default_display_name: "小明".to_string(),
+                avatar_url: None,
+                locale: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let (first, second) = tokio::join!(
+            claim(&pool, issuer.id, member.id),
+            claim(&pool, issuer.id, member.id)
+        );
+
+        assert!(first ^ second, "exactly one claim issues a card");
+        let cards = MembershipCard::find_active_unexpired_cards(&pool, issuer.id, member.id)
+            .await
+            .unwrap();
+        assert_eq!(cards.len(), 1);
+    }
+}
@@ -0,0 +1,142 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// Card data a credential field is filled from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialFieldSource {
+    DisplayName,
+    TierLabel,
+    ChannelName,
+    MemberSince,
+    CardId,
+    ExpiresAt,
+    TenureMonths,
+    TenureSince,
+    EventsAttended,
+}
+
+impl CredentialFieldSource {
+    pub const ALL: [CredentialFieldSource; 9] = [
+        CredentialFieldSource::DisplayName,
+        CredentialFieldSource::TierLabel,
+        CredentialFieldSource::ChannelName,
+        CredentialFieldSource::MemberSince,
+        CredentialFieldSource::CardId,
+        CredentialFieldSource::ExpiresAt,
+        CredentialFieldSource::TenureMonths,
+        CredentialFieldSource::TenureSince,
+        CredentialFieldSource::EventsAttended,
+    ];
+
+    /// Returns the database/string representation of the source
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CredentialFieldSource::DisplayName => "display_name",
+            CredentialFieldSource::TierLabel => "tier_label",
+            CredentialFieldSource::ChannelName => "channel_name",
+            CredentialFieldSource::MemberSince => "member_since",
+            CredentialFieldSource::CardId => "card_id",
+            CredentialFieldSource::ExpiresAt => "expires_at",
+            CredentialFieldSource::TenureMonths => "tenure_months",
+            CredentialFieldSource::TenureSince => "tenure_since",
+            CredentialFieldSource::EventsAttended => "events_attended",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|source| source.as_str() == value)
+    }
+
+    /// Human-readable label for the admin UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            CredentialFieldSource::DisplayName => "會員名稱",
+            CredentialFieldSource::TierLabel => "會員等級",
+            CredentialFieldSource::ChannelName => "頻道名稱",
+            CredentialFieldSource::MemberSince => "會員資格確認日（YYYYMMDD）",
+            CredentialFieldSource::CardId => "卡片 ID",
+            CredentialFieldSource::ExpiresAt => "到期日（YYYYMMDD）",
+            CredentialFieldSource::TenureMonths => "連續會員月數",
+            CredentialFieldSource::TenureSince => "連續會員起始日（YYYYMMDD）",
+            CredentialFieldSource::EventsAttended => "參加活動場數",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct IssuerCredentialField {
+    pub id: Uuid,
+    pub issuer_id: Uuid,
+    pub ename: String,
+    pub source: String,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialFieldMapping {
+    pub ename: String,
+    pub source: CredentialFieldSource,
+}
+
+impl IssuerCredentialField {
+    /// Lists an issuer's credential fields in template order
+    pub async fn list_by_issuer(pool: &PgPool, issuer_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let fields = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM issuer_credential_fields
+            WHERE issuer_id = $1
+            ORDER BY position ASC, created_at ASC
+            "#,
+        )
+        .bind(issuer_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(fields)
+    }
+
+    /// Replaces an issuer's credential fields
+    pub async fn replace_for_issuer(
+        pool: &PgPool,
+        issuer_id: Uuid,
+        mappings: &[CredentialFieldMapping],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM issuer_credential_fields WHERE issuer_id = $1")
+            .bind(issuer_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (position, mapping) in mappings.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO issuer_credential_fields (issuer_id, ename, source, position)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(issuer_id)
+            .bind(&mapping.ename)
+            .bind(mapping.source.as_str())
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Parses the stored source; rows are constrained by the database, so this only
+    /// fails if the schema and code disagree
+    pub fn field_source(&self) -> Option<CredentialFieldSource> {
+        CredentialFieldSource::parse(&self.source)
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// Bytes the list grows by, so its size only hints at the number of cards
+const GROWTH_BYTES: i32 = 1024;
+
+/// An issuer's token status list: 2 bits per credential, kept in sync with card
+/// statuses and credential revocations by database triggers
+#[derive(Debug, Clone, FromRow)]
+pub struct CredentialStatusList {
+    pub issuer_id: Uuid,
+    pub statuses: Vec<u8>,
+    pub next_index: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CredentialStatusList {
+    pub async fn find_by_issuer(
+        pool: &PgPool,
+        issuer_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let list = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM credential_status_lists
+            WHERE issuer_id = $1
+            "#,
+        )
+        .bind(issuer_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(list)
+    }
+
+    /// Returns the issued credential's index in its issuer's list, assigning the
+    /// next free one the first time
+    ///
+    /// Each credential has its own index, so revoking one (e.g. the credential on
+    /// a lost device) leaves the card's other credentials valid; cards only keep
+    /// the index of credentials signed before that.
+    pub async fn assign_index(
+        pool: &PgPool,
+        issuer_id: Uuid,
+        offer_id: Uuid,
+    ) -> Result<i32, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO credential_status_lists (issuer_id)
+            VALUES ($1)
+            ON CONFLICT (issuer_id) DO NOTHING
+            "#,
+        )
+        .bind(issuer_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let assigned: Option<i32> = sqlx::query_scalar(
+            r#"
+            SELECT status_list_index FROM openid_credential_offers
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(offer_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if let Some(index) = assigned {
+            tx.commit().await?;
+            return Ok(index);
+        }
+
+        // 4 credentials per byte; the list row lock serializes concurrent assignments
+        let index: i32 = sqlx::query_scalar(
+            r#"
+            UPDATE credential_status_lists
+            SET next_index = next_index + 1,
+                statuses = CASE
+                    WHEN length(statuses) * 4 <= next_index
+                    THEN statuses || decode(repeat('00', $2), 'hex')
+                    ELSE statuses
+                END,
+                updated_at = NOW()
+            WHERE issuer_id = $1
+            RETURNING next_index - 1
+            "#,
+        )
+        .bind(issuer_id)
+        .bind(GROWTH_BYTES)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // The trigger writes the card's current status into the list
+        sqlx::query(
+            r#"
+            UPDATE openid_credential_offers
+            SET status_list_index = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(offer_id)
+        .bind(index)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(index)
+    }
+}
@@ -1,8 +1,11 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgExecutor, PgPool};
 use uuid::Uuid;
 
+use crate::models::event_series::EventSeries;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Event {
     pub id: Uuid,
@@ -13,51 +16,391 @@ pub struct Event {
     pub event_location: Option<String>,
     pub verifier_ref: String,
     pub is_active: bool,
+    // Own verifier account for this event (NULL = issuer/global); token is encrypted
+    pub verifier_api_url: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub verifier_token_encrypted: Option<String>,
+    pub reentry_policy: String, // "single_entry", "unlimited", "after_checkout" or "max_entries"
+    pub max_entries: Option<i32>,
+    pub capacity: Option<i32>, // None = no limit
+    pub timezone: String,      // IANA name, e.g. "Asia/Taipei"
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub checkin_opens_at: Option<DateTime<Utc>>, // None = DEFAULT_CHECKIN_LEAD_MINUTES before start
+    pub checkin_closes_at: Option<DateTime<Utc>>, // None = ends_at
+    pub closed_at: Option<DateTime<Utc>>,        // set when closed automatically
+    pub series_id: Option<Uuid>,                 // set for occurrences of a recurring series
+    pub occurrence_date: Option<NaiveDate>,      // the series date this occurrence stands for
+    pub series_overridden: bool,                 // edited on its own; series changes skip it
+    pub cancelled_at: Option<DateTime<Utc>>,     // set when an organizer cancelled it
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Time zone of events created without one
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Asia::Taipei;
+
+/// Check-in opens this long before the start unless the event sets its own window
+pub const DEFAULT_CHECKIN_LEAD_MINUTES: i64 = 120;
+
+/// When an event takes place and when its cards are checked in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventSchedule {
+    pub timezone: Tz,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub checkin_opens_at: Option<DateTime<Utc>>,
+    pub checkin_closes_at: Option<DateTime<Utc>>,
+}
+
+impl EventSchedule {
+    /// Parses an IANA time zone name
+    pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+        name.trim()
+            .parse()
+            .map_err(|_| format!("Unknown time zone: {}", name.trim()))
+    }
+
+    /// Builds a schedule from wall-clock times in `timezone`
+    pub fn from_local(
+        timezone: Tz,
+        starts_at: NaiveDateTime,
+        ends_at: NaiveDateTime,
+        checkin_opens_at: Option<NaiveDateTime>,
+        checkin_closes_at: Option<NaiveDateTime>,
+    ) -> Result<Self, String> {
+        let schedule = Self {
+            timezone,
+            starts_at: local_to_utc(timezone, starts_at)?,
+            ends_at: local_to_utc(timezone, ends_at)?,
+            checkin_opens_at: checkin_opens_at
+                .map(|t| local_to_utc(timezone, t))
+                .transpose()?,
+            checkin_closes_at: checkin_closes_at
+                .map(|t| local_to_utc(timezone, t))
+                .transpose()?,
+        };
+        schedule.validate()?;
+
+        Ok(schedule)
+    }
+
+    /// A schedule covering the whole of `date` in `timezone`
+    pub fn all_day(timezone: Tz, date: NaiveDate) -> Result<Self, String> {
+        let start = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        Self::from_local(timezone, start, start + Duration::days(1), None, None)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.ends_at <= self.starts_at {
+            return Err("The event must end after it starts".to_string());
+        }
+        if self.checkin_closes() <= self.checkin_opens() {
+            return Err("Check-in must close after it opens".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn checkin_opens(&self) -> DateTime<Utc> {
+        self.checkin_opens_at
+            .unwrap_or(self.starts_at - Duration::minutes(DEFAULT_CHECKIN_LEAD_MINUTES))
+    }
+
+    fn checkin_closes(&self) -> DateTime<Utc> {
+        self.checkin_closes_at.unwrap_or(self.ends_at)
+    }
+
+    /// The local date the event starts on
+    pub fn event_date(&self) -> NaiveDate {
+        self.starts_at.with_timezone(&self.timezone).date_naive()
+    }
+}
+
+/// Resolves a wall-clock time in `timezone`; the earlier instant when clocks go back
+fn local_to_utc(timezone: Tz, local: NaiveDateTime) -> Result<DateTime<Utc>, String> {
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| format!("{} does not exist in {}", local, timezone))
+}
+
+/// Where a scan time falls relative to an event's check-in window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckinState {
+    NotOpen { opens_at: DateTime<Utc> },
+    Open,
+    Closed { closed_at: DateTime<Utc> },
+}
+
+/// Whether a card that was already admitted to an event may enter again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReentryPolicy {
+    /// Each card is admitted once
+    SingleEntry,
+    /// Every valid scan admits
+    Unlimited,
+    /// A card is admitted again only after a check-out scan
+    AfterCheckout,
+    /// Each card is admitted at most this many times
+    MaxEntries(i32),
+}
+
+impl ReentryPolicy {
+    /// Largest `max_entries` the database accepts
+    pub const MAX_ENTRIES_LIMIT: i32 = 100;
+
+    /// Returns the database representation of the policy kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReentryPolicy::SingleEntry => "single_entry",
+            ReentryPolicy::Unlimited => "unlimited",
+            ReentryPolicy::AfterCheckout => "after_checkout",
+            ReentryPolicy::MaxEntries(_) => "max_entries",
+        }
+    }
+
+    /// Parses a policy kind; `max_entries` is required for "max_entries" only
+    pub fn parse(kind: &str, max_entries: Option<i32>) -> Result<Self, String> {
+        match kind {
+            "single_entry" => Ok(ReentryPolicy::SingleEntry),
+            "unlimited" => Ok(ReentryPolicy::Unlimited),
+            "after_checkout" => Ok(ReentryPolicy::AfterCheckout),
+            "max_entries" => match max_entries {
+                Some(n) if (1..=Self::MAX_ENTRIES_LIMIT).contains(&n) => {
+                    Ok(ReentryPolicy::MaxEntries(n))
+                }
+                _ => Err(format!(
+                    "Maximum entries must be between 1 and {}",
+                    Self::MAX_ENTRIES_LIMIT
+                )),
+            },
+            other => Err(format!("Unknown re-entry policy: {}", other)),
+        }
+    }
+
+    pub fn max_entries(&self) -> Option<i32> {
+        match self {
+            ReentryPolicy::MaxEntries(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Whether scanners offer a check-out mode
+    pub fn uses_checkout(&self) -> bool {
+        matches!(self, ReentryPolicy::AfterCheckout)
+    }
+
+    /// Human-readable label for the admin UI
+    pub fn label(&self) -> String {
+        match self {
+            ReentryPolicy::SingleEntry => "僅限入場一次".to_string(),
+            ReentryPolicy::Unlimited => "不限入場次數".to_string(),
+            ReentryPolicy::AfterCheckout => "離場登記後可再入場".to_string(),
+            ReentryPolicy::MaxEntries(n) => format!("最多入場 {} 次", n),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateEventData {
     pub issuer_id: Uuid,
     pub event_name: String,
     pub event_description: Option<String>,
-    pub event_date: NaiveDate,
+    pub schedule: EventSchedule,
     pub event_location: Option<String>,
     pub verifier_ref: String,
+    pub reentry_policy: ReentryPolicy,
+    pub capacity: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateEventData {
     pub event_name: Option<String>,
     pub event_description: Option<String>,
-    pub event_date: Option<NaiveDate>,
+    pub schedule: Option<EventSchedule>,
     pub event_location: Option<String>,
     pub verifier_ref: Option<String>,
+    pub reentry_policy: Option<ReentryPolicy>,
+    /// Some(None) removes the capacity limit
+    pub capacity: Option<Option<i32>>,
 }
 
 impl Event {
+    /// Returns the event's time zone
+    pub fn timezone(&self) -> Tz {
+        self.timezone.parse().unwrap_or(DEFAULT_TIMEZONE)
+    }
+
+    /// Returns the event's times as a schedule
+    pub fn schedule(&self) -> EventSchedule {
+        EventSchedule {
+            timezone: self.timezone(),
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            checkin_opens_at: self.checkin_opens_at,
+            checkin_closes_at: self.checkin_closes_at,
+        }
+    }
+
+    /// Start of the check-in window
+    pub fn checkin_opens(&self) -> DateTime<Utc> {
+        self.schedule().checkin_opens()
+    }
+
+    /// End of the check-in window
+    pub fn checkin_closes(&self) -> DateTime<Utc> {
+        self.schedule().checkin_closes()
+    }
+
+    /// Whether cards can be checked in at `at`
+    pub fn checkin_state(&self, at: DateTime<Utc>) -> CheckinState {
+        let (opens_at, closes_at) = (self.checkin_opens(), self.checkin_closes());
+        if at < opens_at {
+            CheckinState::NotOpen { opens_at }
+        } else if at >= closes_at {
+            CheckinState::Closed {
+                closed_at: closes_at,
+            }
+        } else {
+            CheckinState::Open
+        }
+    }
+
+    /// Formats a time in the event's time zone
+    pub fn local_time(&self, at: DateTime<Utc>, format: &str) -> String {
+        at.with_timezone(&self.timezone())
+            .format(format)
+            .to_string()
+    }
+
+    /// Start and end in the event's time zone, e.g. "2025年12月20日 19:00 – 22:00"
+    pub fn schedule_label(&self) -> String {
+        let tz = self.timezone();
+        let (starts, ends) = (
+            self.starts_at.with_timezone(&tz),
+            self.ends_at.with_timezone(&tz),
+        );
+        // An all-day event ends at the next midnight
+        let end_format = if ends.date_naive() == starts.date_naive()
+            || (ends - starts == Duration::days(1) && ends.format("%H:%M").to_string() == "00:00")
+        {
+            "%H:%M"
+        } else {
+            "%Y年%m月%d日 %H:%M"
+        };
+
+        format!(
+            "{} – {}",
+            starts.format("%Y年%m月%d日 %H:%M"),
+            ends.format(end_format)
+        )
+    }
+
+    /// The check-in window in the event's time zone
+    pub fn checkin_label(&self) -> String {
+        format!(
+            "{} – {}",
+            self.local_time(self.checkin_opens(), "%m/%d %H:%M"),
+            self.local_time(self.checkin_closes(), "%m/%d %H:%M")
+        )
+    }
+
+    /// Returns the event's re-entry policy
+    pub fn reentry_policy(&self) -> ReentryPolicy {
+        ReentryPolicy::parse(&self.reentry_policy, self.max_entries)
+            .unwrap_or(ReentryPolicy::Unlimited)
+    }
+
     /// Create a new event
     pub async fn create(pool: &PgPool, data: CreateEventData) -> Result<Self, sqlx::Error> {
         let event = sqlx::query_as::<_, Event>(
             r#"
-            INSERT INTO events (issuer_id, event_name, event_description, event_date, event_location, verifier_ref)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO events (issuer_id, event_name, event_description, event_date, event_location, verifier_ref, reentry_policy, max_entries, capacity,
+                                timezone, starts_at, ends_at, checkin_opens_at, checkin_closes_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             RETURNING *
             "#,
         )
         .bind(data.issuer_id)
         .bind(data.event_name)
         .bind(data.event_description)
-        .bind(data.event_date)
+        .bind(data.schedule.event_date())
         .bind(data.event_location)
         .bind(data.verifier_ref)
+        .bind(data.reentry_policy.as_str())
+        .bind(data.reentry_policy.max_entries())
+        .bind(data.capacity)
+        .bind(data.schedule.timezone.name())
+        .bind(data.schedule.starts_at)
+        .bind(data.schedule.ends_at)
+        .bind(data.schedule.checkin_opens_at)
+        .bind(data.schedule.checkin_closes_at)
         .fetch_one(pool)
         .await?;
 
         Ok(event)
     }
 
+    /// Create the occurrence of a series on `occurrence_date`
+    /// Returns None if the series already has one for that date, even a cancelled one.
+    pub async fn create_occurrence<'e>(
+        executor: impl PgExecutor<'e>,
+        series: &EventSeries,
+        occurrence_date: NaiveDate,
+        schedule: &EventSchedule,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let event = sqlx::query_as::<_, Event>(
+            r#"
+            INSERT INTO events (issuer_id, event_name, event_description, event_date, event_location, verifier_ref, reentry_policy, max_entries, capacity,
+                                timezone, starts_at, ends_at, checkin_opens_at, checkin_closes_at, series_id, occurrence_date)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            ON CONFLICT (series_id, occurrence_date) WHERE series_id IS NOT NULL DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(series.issuer_id)
+        .bind(&series.series_name)
+        .bind(&series.event_description)
+        .bind(schedule.event_date())
+        .bind(&series.event_location)
+        .bind(&series.verifier_ref)
+        .bind(&series.reentry_policy)
+        .bind(series.max_entries)
+        .bind(series.capacity)
+        .bind(schedule.timezone.name())
+        .bind(schedule.starts_at)
+        .bind(schedule.ends_at)
+        .bind(schedule.checkin_opens_at)
+        .bind(schedule.checkin_closes_at)
+        .bind(series.id)
+        .bind(occurrence_date)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(event)
+    }
+
+    /// Marks a series occurrence as edited on its own, so series changes skip it
+    pub async fn mark_series_overridden<'e>(
+        executor: impl PgExecutor<'e>,
+        id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE events
+            SET series_overridden = TRUE, updated_at = NOW()
+            WHERE id = $1 AND series_id IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
     /// Find event by ID
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         let event = sqlx::query_as::<_, Event>(
@@ -115,6 +458,94 @@ impl Event {
         Ok(events)
     }
 
+    /// List active events for the scanner, soonest first
+    /// Events are closed automatically once their check-in window ends.
+    pub async fn list_open_for_checkin(pool: &PgPool) -> Result<Vec<Self>, sqlx::Error> {
+        let events = sqlx::query_as::<_, Event>(
+            r#"
+            SELECT * FROM events
+            WHERE is_active = TRUE
+            ORDER BY starts_at ASC
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// List a series' occurrences, cancelled ones included, in date order
+    pub async fn list_by_series(pool: &PgPool, series_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let events = sqlx::query_as::<_, Event>(
+            r#"
+            SELECT * FROM events
+            WHERE series_id = $1
+            ORDER BY starts_at ASC
+            "#,
+        )
+        .bind(series_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Copies a series' shared details to its upcoming occurrences, except
+    /// those edited on their own or cancelled
+    pub async fn apply_series_details(
+        pool: &PgPool,
+        series: &EventSeries,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let events = sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events
+            SET event_name = $2,
+                event_description = $3,
+                event_location = $4,
+                verifier_ref = $5,
+                reentry_policy = $6,
+                max_entries = $7,
+                capacity = $8
+            WHERE series_id = $1
+              AND series_overridden = FALSE
+              AND is_active = TRUE
+              AND starts_at > NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(series.id)
+        .bind(&series.series_name)
+        .bind(&series.event_description)
+        .bind(&series.event_location)
+        .bind(&series.verifier_ref)
+        .bind(&series.reentry_policy)
+        .bind(series.max_entries)
+        .bind(series.capacity)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Cancels a series' occurrences that have not started yet
+    pub async fn cancel_upcoming_in_series(
+        pool: &PgPool,
+        series_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE events
+            SET is_active = FALSE, cancelled_at = NOW()
+            WHERE series_id = $1 AND is_active = TRUE AND starts_at > NOW()
+            "#,
+        )
+        .bind(series_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     /// List upcoming events for an issuer
     pub async fn list_upcoming(pool: &PgPool, issuer_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
         let events = sqlx::query_as::<_, Event>(
@@ -122,8 +553,8 @@ impl Event {
             SELECT * FROM events
             WHERE issuer_id = $1
               AND is_active = TRUE
-              AND event_date >= CURRENT_DATE
-            ORDER BY event_date ASC
+              AND ends_at > NOW()
+            ORDER BY starts_at ASC
             "#,
         )
         .bind(issuer_id)
@@ -152,9 +583,18 @@ impl Event {
             updates.push(format!("event_description = ${}", bind_count));
             bind_count += 1;
         }
-        if data.event_date.is_some() {
-            updates.push(format!("event_date = ${}", bind_count));
-            bind_count += 1;
+        if data.schedule.is_some() {
+            for column in [
+                "event_date",
+                "timezone",
+                "starts_at",
+                "ends_at",
+                "checkin_opens_at",
+                "checkin_closes_at",
+            ] {
+                updates.push(format!("{} = ${}", column, bind_count));
+                bind_count += 1;
+            }
         }
         if data.event_location.is_some() {
             updates.push(format!("event_location = ${}", bind_count));
@@ -164,6 +604,15 @@ impl Event {
             updates.push(format!("verifier_ref = ${}", bind_count));
             bind_count += 1;
         }
+        if data.reentry_policy.is_some() {
+            updates.push(format!("reentry_policy = ${}", bind_count));
+            updates.push(format!("max_entries = ${}", bind_count + 1));
+            bind_count += 2;
+        }
+        if data.capacity.is_some() {
+            updates.push(format!("capacity = ${}", bind_count));
+            bind_count += 1;
+        }
 
         if updates.is_empty() {
             // No fields to update, just return existing event
@@ -172,6 +621,9 @@ impl Event {
                 .ok_or(sqlx::Error::RowNotFound);
         }
 
+        // An occurrence edited on its own keeps its edits when the series changes
+        updates.push("series_overridden = (series_id IS NOT NULL)".to_string());
+
         query.push_str(&updates.join(", "));
         query.push_str(&format!(" WHERE id = ${} RETURNING *", bind_count));
 
@@ -183,8 +635,14 @@ impl Event {
         if let Some(desc) = data.event_description {
             query_builder = query_builder.bind(desc);
         }
-        if let Some(date) = data.event_date {
-            query_builder = query_builder.bind(date);
+        if let Some(schedule) = data.schedule {
+            query_builder = query_builder
+                .bind(schedule.event_date())
+                .bind(schedule.timezone.name())
+                .bind(schedule.starts_at)
+                .bind(schedule.ends_at)
+                .bind(schedule.checkin_opens_at)
+                .bind(schedule.checkin_closes_at);
         }
         if let Some(location) = data.event_location {
             query_builder = query_builder.bind(location);
@@ -192,6 +650,14 @@ impl Event {
         if let Some(verifier_ref) = data.verifier_ref {
             query_builder = query_builder.bind(verifier_ref);
         }
+        if let Some(policy) = data.reentry_policy {
+            query_builder = query_builder
+                .bind(policy.as_str())
+                .bind(policy.max_entries());
+        }
+        if let Some(capacity) = data.capacity {
+            query_builder = query_builder.bind(capacity);
+        }
 
         query_builder = query_builder.bind(id);
 
@@ -200,12 +666,67 @@ impl Event {
         Ok(event)
     }
 
+    /// Sets the event's own wallet verifier account
+    /// `token_encrypted` must already be encrypted; None reverts to the issuer/global account.
+    pub async fn update_verifier_account(
+        pool: &PgPool,
+        id: Uuid,
+        api_url: Option<&str>,
+        token_encrypted: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE events
+            SET verifier_api_url = $2,
+                verifier_token_encrypted = $3,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(api_url)
+        .bind(token_encrypted)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Closes active events whose check-in window ended before `now`, oldest first
+    pub async fn close_ended(
+        pool: &PgPool,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let events = sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events
+            SET is_active = FALSE, closed_at = NOW(), updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM events
+                WHERE is_active = TRUE
+                  AND COALESCE(checkin_closes_at, ends_at) < $1
+                ORDER BY ends_at
+                LIMIT $2
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
     /// Deactivate an event (soft delete)
+    /// For an occurrence of a series this cancels it; it is not materialized again.
     pub async fn deactivate(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
             UPDATE events
-            SET is_active = FALSE
+            SET is_active = FALSE, cancelled_at = COALESCE(cancelled_at, NOW())
             WHERE id = $1
             "#,
         )
@@ -216,3 +737,78 @@ impl Event {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reentry_policy_round_trip() {
+        for policy in [
+            ReentryPolicy::SingleEntry,
+            ReentryPolicy::Unlimited,
+            ReentryPolicy::AfterCheckout,
+            ReentryPolicy::MaxEntries(3),
+        ] {
+            assert_eq!(
+                ReentryPolicy::parse(policy.as_str(), policy.max_entries()),
+                Ok(policy)
+            );
+        }
+    }
+
+    #[test]
+    fn test_schedule_from_local_time() {
+        let tz = EventSchedule::parse_timezone("Asia/Taipei").unwrap();
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap();
+        let schedule = EventSchedule::from_local(
+            tz,
+            local("2025-12-20T19:00"),
+            local("2025-12-20T22:00"),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(schedule.starts_at.to_rfc3339(), "2025-12-20T11:00:00+00:00");
+        assert_eq!(schedule.event_date().to_string(), "2025-12-20");
+        assert_eq!(
+            schedule.checkin_opens().to_rfc3339(),
+            "2025-12-20T09:00:00+00:00"
+        );
+
+        assert!(EventSchedule::from_local(
+            tz,
+            local("2025-12-20T19:00"),
+            local("2025-12-20T18:00"),
+            None,
+            None
+        )
+        .is_err());
+        assert!(EventSchedule::parse_timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_schedule_skips_nonexistent_local_time() {
+        // Clocks in New York jump from 02:00 to 03:00 on 2025-03-09
+        let tz = EventSchedule::parse_timezone("America/New_York").unwrap();
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap();
+
+        assert!(EventSchedule::from_local(
+            tz,
+            local("2025-03-09T02:30"),
+            local("2025-03-09T05:00"),
+            None,
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_reentry_policy_max_entries_bounds() {
+        assert!(ReentryPolicy::parse("max_entries", None).is_err());
+        assert!(ReentryPolicy::parse("max_entries", Some(0)).is_err());
+        assert!(ReentryPolicy::parse("max_entries", Some(101)).is_err());
+        assert!(ReentryPolicy::parse("twice", None).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+/// An issuer whose cards an event accepts, with the lowest accepted tier
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EventAccessRule {
+    pub event_id: Uuid,
+    pub issuer_id: Uuid,
+    pub min_tier_label: Option<String>, // None = any tier
+    pub created_at: DateTime<Utc>,
+}
+
+/// An access rule shared by a series' occurrences
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SeriesAccessRule {
+    pub series_id: Uuid,
+    pub issuer_id: Uuid,
+    pub min_tier_label: Option<String>, // None = any tier
+    pub created_at: DateTime<Utc>,
+}
+
+/// An access rule as submitted by an organizer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessRuleData {
+    pub issuer_id: Uuid,
+    #[serde(default)]
+    pub min_tier: Option<String>,
+}
+
+impl EventAccessRule {
+    /// Lists an event's access rules
+    pub async fn list_by_event(pool: &PgPool, event_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let rules = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM event_access_rules
+            WHERE event_id = $1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// Replaces an event's access rules; no rules accepts the event's own issuer only
+    pub async fn replace_for_event(
+        pool: &PgPool,
+        event_id: Uuid,
+        rules: &[AccessRuleData],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        Self::write_for_event(&mut tx, event_id, rules).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Replaces the event's access rules on the caller's transaction, so they can
+    /// be written atomically with the event
+    pub async fn write_for_event(
+        conn: &mut PgConnection,
+        event_id: Uuid,
+        rules: &[AccessRuleData],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM event_access_rules WHERE event_id = $1")
+            .bind(event_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for rule in rules {
+            sqlx::query(
+                r#"
+                INSERT INTO event_access_rules (event_id, issuer_id, min_tier_label)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(event_id)
+            .bind(rule.issuer_id)
+            .bind(&rule.min_tier)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SeriesAccessRule {
+    /// The rule as copied to an occurrence
+    pub fn data(&self) -> AccessRuleData {
+        AccessRuleData {
+            issuer_id: self.issuer_id,
+            min_tier: self.min_tier_label.clone(),
+        }
+    }
+
+    /// Lists a series' access rules
+    pub async fn list_by_series(pool: &PgPool, series_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let rules = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM event_series_access_rules
+            WHERE series_id = $1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(series_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// Replaces a series' access rules; no rules accepts the series' own issuer only
+    pub async fn replace_for_series(
+        pool: &PgPool,
+        series_id: Uuid,
+        rules: &[AccessRuleData],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM event_series_access_rules WHERE series_id = $1")
+            .bind(series_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for rule in rules {
+            sqlx::query(
+                r#"
+                INSERT INTO event_series_access_rules (series_id, issuer_id, min_tier_label)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(series_id)
+            .bind(rule.issuer_id)
+            .bind(&rule.min_tier)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,384 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// A member's RSVP for an event
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EventReservation {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub member_id: Uuid,
+    pub card_id: Uuid,  // the card the member reserved with
+    pub status: String, // "reserved", "waitlisted" or "cancelled"
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+/// Open RSVPs of an event
+#[derive(Debug, Clone, Default, Serialize, FromRow)]
+pub struct ReservationCounts {
+    pub reserved: i64,
+    pub waitlisted: i64,
+}
+
+impl EventReservation {
+    pub fn is_reserved(&self) -> bool {
+        self.status == "reserved"
+    }
+
+    pub fn is_waitlisted(&self) -> bool {
+        self.status == "waitlisted"
+    }
+
+    /// Records an RSVP as reserved or waitlisted
+    pub async fn create<'e>(
+        executor: impl PgExecutor<'e>,
+        event_id: Uuid,
+        member_id: Uuid,
+        card_id: Uuid,
+        status: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let reservation = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO event_reservations (event_id, member_id, card_id, status)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(event_id)
+        .bind(member_id)
+        .bind(card_id)
+        .bind(status)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(reservation)
+    }
+
+    /// Finds a member's open (reserved or waitlisted) RSVP for an event
+    pub async fn find_open_for_member<'e>(
+        executor: impl PgExecutor<'e>,
+        event_id: Uuid,
+        member_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let reservation = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM event_reservations
+            WHERE event_id = $1 AND member_id = $2 AND status <> 'cancelled'
+            "#,
+        )
+        .bind(event_id)
+        .bind(member_id)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(reservation)
+    }
+
+    /// Whether a member holds a reserved spot at an event, whichever card they
+    /// reserved with
+    pub async fn is_member_reserved<'e>(
+        executor: impl PgExecutor<'e>,
+        event_id: Uuid,
+        member_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let reserved: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM event_reservations
+                WHERE event_id = $1 AND member_id = $2 AND status = 'reserved'
+            )
+            "#,
+        )
+        .bind(event_id)
+        .bind(member_id)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(reserved)
+    }
+
+    /// Active cards of the members holding a reserved spot at an event, so a
+    /// reissued card is admitted as reserved too
+    pub async fn list_reserved_cards(
+        pool: &PgPool,
+        event_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let cards = sqlx::query_scalar(
+            r#"
+            SELECT c.id FROM event_reservations r
+            JOIN membership_cards c ON c.member_id = r.member_id
+            WHERE r.event_id = $1 AND r.status = 'reserved' AND c.status = 'active'
+            ORDER BY c.id
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    /// Lists an event's open RSVPs, reserved first, then the waitlist in order
+    pub async fn list_open_by_event(
+        pool: &PgPool,
+        event_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let reservations = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM event_reservations
+            WHERE event_id = $1 AND status <> 'cancelled'
+            ORDER BY status = 'waitlisted', created_at
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(reservations)
+    }
+
+    /// Cancels an open RSVP
+    pub async fn cancel<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE event_reservations
+            SET status = 'cancelled', cancelled_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status <> 'cancelled'
+            "#,
+        )
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Moves the longest-waiting RSVP off the waitlist; None when it is empty
+    pub async fn promote_next<'e>(
+        executor: impl PgExecutor<'e>,
+        event_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let reservation = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE event_reservations
+            SET status = 'reserved', updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM event_reservations
+                WHERE event_id = $1 AND status = 'waitlisted'
+                ORDER BY created_at
+                LIMIT 1
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(event_id)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(reservation)
+    }
+
+    /// 1-based place of a waitlisted RSVP in the event's waitlist
+    pub async fn waitlist_position<'e>(
+        executor: impl PgExecutor<'e>,
+        reservation: &Self,
+    ) -> Result<i64, sqlx::Error> {
+        let ahead: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM event_reservations
+            WHERE event_id = $1 AND status = 'waitlisted' AND created_at < $2
+            "#,
+        )
+        .bind(reservation.event_id)
+        .bind(reservation.created_at)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(ahead + 1)
+    }
+
+    /// Counts an event's reserved and waitlisted RSVPs
+    pub async fn counts(pool: &PgPool, event_id: Uuid) -> Result<ReservationCounts, sqlx::Error> {
+        let counts = sqlx::query_as::<_, ReservationCounts>(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE status = 'reserved') AS reserved,
+                COUNT(*) FILTER (WHERE status = 'waitlisted') AS waitlisted
+            FROM event_reservations
+            WHERE event_id = $1
+            "#,
+        )
+        .bind(event_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    /// Spots taken at an event: reserved spots plus walk-in members admitted
+    pub async fn occupancy<'e>(
+        executor: impl PgExecutor<'e>,
+        event_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let taken: i64 = sqlx::query_scalar(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM event_reservations
+                 WHERE event_id = $1 AND status = 'reserved')
+                +
+                (SELECT COUNT(DISTINCT c.member_id) FROM verification_events v
+                 JOIN membership_cards c ON c.id = v.card_id
+                 WHERE v.event_id = $1
+                   AND v.verification_result = 'success'
+                   AND NOT EXISTS (
+                       SELECT 1 FROM event_reservations r
+                       WHERE r.event_id = v.event_id AND r.member_id = c.member_id
+                         AND r.status = 'reserved'
+                   ))
+            "#,
+        )
+        .bind(event_id)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(taken)
+    }
+
+    /// Takes the transaction-scoped capacity lock for an event
+    /// Held until the transaction ends, so RSVPs, cancellations and walk-in
+    /// admissions count the event's spots and take one one after the other.
+    pub async fn lock_capacity(conn: &mut PgConnection, event_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("event_capacity:{}", event_id))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        card::{CreateCardData, MembershipCard},
+        event::{CreateEventData, Event, EventSchedule, ReentryPolicy, DEFAULT_TIMEZONE},
+        issuer::{CardIssuer, CreateIssuerData},
+        member::{CreateMemberData, Member},
+        verification_event::{CreateVerificationEventData, VerificationEvent},
+    };
+
+    async fn issue_card(pool: &PgPool, issuer_id: Uuid, member_id: Uuid) -> MembershipCard {
+        let mut conn = pool.acquire().await.unwrap();
+        MembershipCard::create(
+            &mut conn,
+            CreateCardData {
+                issuer_id,
+                member_id,
+                membership_level_label: "會員".to_string(),
+                membership_confirmed_at: Utc::now(),
+                verification_comment_id: "comment".to_string(),
+                verification_video_id: "video".to_string(),
+                snapshot_json: serde_json::json!({}),
+                validity_days: 30,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_reissued_card_keeps_the_reservation() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+
+        let channel_id = format!("UC{}", Uuid::new_v4().simple());
+        let issuer = CardIssuer::create(
+            &pool,
+            CreateIssuerData {
+                youtube_channel_id: channel_id.clone(),
+                channel_handle: None,
+                channel_name: "星詠".to_string(),
+                verification_video_id: "video".to_string(),
+                default_membership_label: "會員".to_string(),
+                vc_uid: None,
+            },
+        )
+        .await
+        .unwrap();
+        let member = Member::create(
+            &pool,
+            CreateMemberData {
+                youtube_user_id: format!("member-{}", channel_id),
+                default_display_name: "小明".to_string(),
+                avatar_url: None,
+                locale: None,
+            },
+        )
+        .await
+        .unwrap();
+        let now = Utc::now();
+        let event = Event::create(
+            &pool,
+            CreateEventData {
+                issuer_id: issuer.id,
+                event_name: "見面會".to_string(),
+                event_description: None,
+                schedule: EventSchedule {
+                    timezone: DEFAULT_TIMEZONE,
+                    starts_at: now,
+                    ends_at: now + chrono::Duration::hours(2),
+                    checkin_opens_at: None,
+                    checkin_closes_at: None,
+                },
+                event_location: None,
+                verifier_ref: "verifier".to_string(),
+                reentry_policy: ReentryPolicy::SingleEntry,
+                capacity: Some(10),
+            },
+        )
+        .await
+        .unwrap();
+
+        let reserved_with = issue_card(&pool, issuer.id, member.id).await;
+        EventReservation::create(&pool, event.id, member.id, reserved_with.id, "reserved")
+            .await
+            .unwrap();
+        // Reissued after reserving, then admitted with the new card
+        let reissued = issue_card(&pool, issuer.id, member.id).await;
+        VerificationEvent::create_event(
+            &pool,
+            CreateVerificationEventData {
+                event_id: event.id,
+                card_id: Some(reissued.id),
+                verification_result: "success".to_string(),
+                verification_context: None,
+                raw_payload: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(
+            EventReservation::is_member_reserved(&pool, event.id, member.id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            EventReservation::list_reserved_cards(&pool, event.id)
+                .await
+                .unwrap(),
+            vec![reissued.id]
+        );
+        assert_eq!(
+            EventReservation::occupancy(&pool, event.id).await.unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,506 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::models::event::{EventSchedule, ReentryPolicy, DEFAULT_TIMEZONE};
+
+/// A recurring event whose occurrences are materialized into `events` ahead of time
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EventSeries {
+    pub id: Uuid,
+    pub issuer_id: Uuid,
+    pub series_name: String,
+    pub event_description: Option<String>,
+    pub event_location: Option<String>,
+    pub verifier_ref: String,
+    pub reentry_policy: String, // "single_entry", "unlimited", "after_checkout" or "max_entries"
+    pub max_entries: Option<i32>,
+    pub capacity: Option<i32>, // None = no limit
+    pub timezone: String,      // IANA name, e.g. "Asia/Taipei"
+    pub start_time: NaiveTime, // local time each occurrence starts
+    pub duration_minutes: i32,
+    pub checkin_opens_before_minutes: Option<i32>, // None = the event default
+    pub checkin_closes_after_minutes: Option<i32>, // after the start; None = at the end
+    pub recurrence: String, // "weekly", "monthly_by_day" or "monthly_by_weekday"
+    pub recurrence_interval: i32,
+    pub starts_on: NaiveDate,
+    pub repeat_until: Option<NaiveDate>, // None = no end
+    pub materialized_until: Option<NaiveDate>,
+    pub is_active: bool,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How often a series repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recurrence {
+    /// On the weekday of the first occurrence
+    Weekly,
+    /// On the day of month of the first occurrence; months without that day are skipped
+    MonthlyByDay,
+    /// On the same weekday of the month, e.g. the second Saturday; months
+    /// without a fifth such weekday are skipped
+    MonthlyByWeekday,
+}
+
+impl Recurrence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recurrence::Weekly => "weekly",
+            Recurrence::MonthlyByDay => "monthly_by_day",
+            Recurrence::MonthlyByWeekday => "monthly_by_weekday",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Result<Self, String> {
+        match kind {
+            "weekly" => Ok(Recurrence::Weekly),
+            "monthly_by_day" => Ok(Recurrence::MonthlyByDay),
+            "monthly_by_weekday" => Ok(Recurrence::MonthlyByWeekday),
+            other => Err(format!("Unknown recurrence: {}", other)),
+        }
+    }
+}
+
+/// When a series' occurrences fall, as local dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurrenceRule {
+    pub recurrence: Recurrence,
+    pub interval: u32, // every n weeks or months
+    pub starts_on: NaiveDate,
+    pub repeat_until: Option<NaiveDate>,
+}
+
+impl RecurrenceRule {
+    /// Largest interval the database accepts
+    pub const MAX_INTERVAL: u32 = 12;
+
+    pub fn new(
+        recurrence: Recurrence,
+        interval: u32,
+        starts_on: NaiveDate,
+        repeat_until: Option<NaiveDate>,
+    ) -> Result<Self, String> {
+        if !(1..=Self::MAX_INTERVAL).contains(&interval) {
+            return Err(format!(
+                "Repeat interval must be between 1 and {}",
+                Self::MAX_INTERVAL
+            ));
+        }
+        if repeat_until.is_some_and(|until| until < starts_on) {
+            return Err("The series must repeat until on or after its first date".to_string());
+        }
+
+        Ok(Self {
+            recurrence,
+            interval,
+            starts_on,
+            repeat_until,
+        })
+    }
+
+    /// Occurrence dates from `from` through `to`, in order
+    pub fn dates_between(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let from = from.max(self.starts_on);
+        let to = self.repeat_until.map_or(to, |until| to.min(until));
+        if from > to {
+            return Vec::new();
+        }
+
+        match self.recurrence {
+            Recurrence::Weekly => {
+                let step = 7 * i64::from(self.interval);
+                // First occurrence on or after `from`
+                let skipped = ((from - self.starts_on).num_days() + step - 1) / step;
+                (skipped..)
+                    .map(|n| self.starts_on + Duration::days(n * step))
+                    .take_while(|date| *date <= to)
+                    .collect()
+            }
+            Recurrence::MonthlyByDay | Recurrence::MonthlyByWeekday => {
+                let first_month = self.starts_on.with_day(1).expect("day 1 exists");
+                let months_to_from = (from.year() - self.starts_on.year()) * 12
+                    + from.month() as i32
+                    - self.starts_on.month() as i32;
+                let skipped = months_to_from.max(0) as u32 / self.interval;
+
+                (skipped..)
+                    .map(|n| first_month + Months::new(n * self.interval))
+                    .take_while(|month| *month <= to)
+                    .filter_map(|month| self.date_in_month(month))
+                    .filter(|date| (from..=to).contains(date))
+                    .collect()
+            }
+        }
+    }
+
+    /// The occurrence in the month starting at `month`, if that month has one
+    fn date_in_month(&self, month: NaiveDate) -> Option<NaiveDate> {
+        match self.recurrence {
+            Recurrence::Weekly => None,
+            Recurrence::MonthlyByDay => month.with_day(self.starts_on.day()),
+            Recurrence::MonthlyByWeekday => NaiveDate::from_weekday_of_month_opt(
+                month.year(),
+                month.month(),
+                self.starts_on.weekday(),
+                self.week_of_month(),
+            ),
+        }
+    }
+
+    /// Which of its weekday in the month the first occurrence is (1 to 5)
+    fn week_of_month(&self) -> u8 {
+        ((self.starts_on.day() - 1) / 7 + 1) as u8
+    }
+
+    /// Human-readable rule, e.g. "每週六", "每 2 週的週六" or "每月第二個週六"
+    pub fn label(&self) -> String {
+        let every = match (self.recurrence, self.interval) {
+            (Recurrence::Weekly, 1) => "每".to_string(),
+            (Recurrence::Weekly, n) => format!("每 {} 週的", n),
+            (_, 1) => "每月".to_string(),
+            (_, n) => format!("每 {} 個月的", n),
+        };
+        let weekday = weekday_label(self.starts_on.weekday());
+
+        let rule = match self.recurrence {
+            Recurrence::Weekly => format!("{}{}", every, weekday),
+            Recurrence::MonthlyByDay => format!("{} {} 日", every, self.starts_on.day()),
+            Recurrence::MonthlyByWeekday => format!(
+                "{}第{}個{}",
+                every,
+                ["一", "二", "三", "四", "五"][usize::from(self.week_of_month()) - 1],
+                weekday
+            ),
+        };
+
+        match self.repeat_until {
+            Some(until) => format!("{}，至 {}", rule, until.format("%Y/%m/%d")),
+            None => rule,
+        }
+    }
+}
+
+fn weekday_label(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "週一",
+        Weekday::Tue => "週二",
+        Weekday::Wed => "週三",
+        Weekday::Thu => "週四",
+        Weekday::Fri => "週五",
+        Weekday::Sat => "週六",
+        Weekday::Sun => "週日",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateEventSeriesData {
+    pub issuer_id: Uuid,
+    pub series_name: String,
+    pub event_description: Option<String>,
+    pub event_location: Option<String>,
+    pub verifier_ref: String,
+    pub reentry_policy: ReentryPolicy,
+    pub capacity: Option<i32>,
+    /// Times of the first occurrence; later ones keep its local times
+    pub first: EventSchedule,
+    pub rule: RecurrenceRule,
+}
+
+/// Details shared by a series' occurrences; None leaves a field unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateEventSeriesData {
+    pub series_name: Option<String>,
+    pub event_description: Option<String>,
+    pub event_location: Option<String>,
+    pub verifier_ref: Option<String>,
+    pub reentry_policy: Option<ReentryPolicy>,
+    /// Some(None) removes the capacity limit
+    pub capacity: Option<Option<i32>>,
+}
+
+impl EventSeries {
+    /// Returns the series' time zone
+    pub fn timezone(&self) -> Tz {
+        self.timezone.parse().unwrap_or(DEFAULT_TIMEZONE)
+    }
+
+    /// Returns the series' re-entry policy
+    pub fn reentry_policy(&self) -> ReentryPolicy {
+        ReentryPolicy::parse(&self.reentry_policy, self.max_entries)
+            .unwrap_or(ReentryPolicy::Unlimited)
+    }
+
+    /// Returns the series' recurrence rule
+    pub fn rule(&self) -> RecurrenceRule {
+        RecurrenceRule {
+            recurrence: Recurrence::parse(&self.recurrence).unwrap_or(Recurrence::Weekly),
+            interval: self.recurrence_interval.max(1) as u32,
+            starts_on: self.starts_on,
+            repeat_until: self.repeat_until,
+        }
+    }
+
+    /// Local start and end time of each occurrence, e.g. "19:00 – 22:00"
+    pub fn time_label(&self) -> String {
+        let ends = self.start_time + Duration::minutes(i64::from(self.duration_minutes));
+        format!(
+            "{} – {}",
+            self.start_time.format("%H:%M"),
+            ends.format("%H:%M")
+        )
+    }
+
+    /// The occurrence's times on `date`, at the series' local start time
+    pub fn occurrence_schedule(&self, date: NaiveDate) -> Result<EventSchedule, String> {
+        let starts_at = date.and_time(self.start_time);
+        let minutes = |m: i32| Duration::minutes(i64::from(m));
+
+        EventSchedule::from_local(
+            self.timezone(),
+            starts_at,
+            starts_at + minutes(self.duration_minutes),
+            self.checkin_opens_before_minutes
+                .map(|m| starts_at - minutes(m)),
+            self.checkin_closes_after_minutes
+                .map(|m| starts_at + minutes(m)),
+        )
+    }
+
+    /// Create a new series; no occurrences exist until it is materialized
+    pub async fn create(pool: &PgPool, data: CreateEventSeriesData) -> Result<Self, sqlx::Error> {
+        let first = &data.first;
+        let local_start = first.starts_at.with_timezone(&first.timezone).time();
+        let minutes_between =
+            |from: DateTime<Utc>, to: DateTime<Utc>| (to - from).num_minutes() as i32;
+
+        let series = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO event_series (issuer_id, series_name, event_description, event_location, verifier_ref,
+                                      reentry_policy, max_entries, capacity, timezone, start_time, duration_minutes,
+                                      checkin_opens_before_minutes, checkin_closes_after_minutes,
+                                      recurrence, recurrence_interval, starts_on, repeat_until)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            RETURNING *
+            "#,
+        )
+        .bind(data.issuer_id)
+        .bind(data.series_name)
+        .bind(data.event_description)
+        .bind(data.event_location)
+        .bind(data.verifier_ref)
+        .bind(data.reentry_policy.as_str())
+        .bind(data.reentry_policy.max_entries())
+        .bind(data.capacity)
+        .bind(first.timezone.name())
+        .bind(local_start)
+        .bind(minutes_between(first.starts_at, first.ends_at))
+        .bind(
+            first
+                .checkin_opens_at
+                .map(|opens| minutes_between(opens, first.starts_at)),
+        )
+        .bind(
+            first
+                .checkin_closes_at
+                .map(|closes| minutes_between(first.starts_at, closes)),
+        )
+        .bind(data.rule.recurrence.as_str())
+        .bind(data.rule.interval as i32)
+        .bind(data.rule.starts_on)
+        .bind(data.rule.repeat_until)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(series)
+    }
+
+    /// Find a series by ID
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let series = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM event_series WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(series)
+    }
+
+    /// Active series not yet materialized through `through`, least materialized first
+    pub async fn list_to_materialize(
+        pool: &PgPool,
+        through: NaiveDate,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let series = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM event_series
+            WHERE is_active = TRUE
+              AND (materialized_until IS NULL OR materialized_until < $1)
+              AND (repeat_until IS NULL OR materialized_until IS NULL OR materialized_until < repeat_until)
+            ORDER BY materialized_until ASC NULLS FIRST
+            LIMIT $2
+            "#,
+        )
+        .bind(through)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(series)
+    }
+
+    /// Records that occurrences exist up to `date`
+    pub async fn set_materialized_until(
+        pool: &PgPool,
+        id: Uuid,
+        date: NaiveDate,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE event_series
+            SET materialized_until = GREATEST(materialized_until, $2)
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(date)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Update the details shared by the series' occurrences
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        data: UpdateEventSeriesData,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let (reentry_policy, max_entries) = match data.reentry_policy {
+            Some(policy) => (Some(policy.as_str()), policy.max_entries()),
+            None => (None, None),
+        };
+
+        let series = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE event_series
+            SET series_name = COALESCE($2, series_name),
+                event_description = COALESCE($3, event_description),
+                event_location = COALESCE($4, event_location),
+                verifier_ref = COALESCE($5, verifier_ref),
+                reentry_policy = COALESCE($6, reentry_policy),
+                max_entries = CASE WHEN $6 IS NULL THEN max_entries ELSE $7 END,
+                capacity = CASE WHEN $8 THEN $9 ELSE capacity END
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(data.series_name)
+        .bind(data.event_description)
+        .bind(data.event_location)
+        .bind(data.verifier_ref)
+        .bind(reentry_policy)
+        .bind(max_entries)
+        .bind(data.capacity.is_some())
+        .bind(data.capacity.flatten())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(series)
+    }
+
+    /// Ends the series: no further occurrences are materialized
+    pub async fn end(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE event_series
+            SET is_active = FALSE, ended_at = NOW()
+            WHERE id = $1 AND is_active = TRUE
+            "#,
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(rule: &RecurrenceRule, from: &str, to: &str) -> Vec<String> {
+        rule.dates_between(date(from), date(to))
+            .into_iter()
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_weekly_every_other_week() {
+        // 2025-12-06 is a Saturday
+        let rule = RecurrenceRule::new(Recurrence::Weekly, 2, date("2025-12-06"), None).unwrap();
+
+        assert_eq!(
+            dates(&rule, "2025-12-10", "2026-01-31"),
+            ["2025-12-20", "2026-01-03", "2026-01-17", "2026-01-31"]
+        );
+        assert_eq!(dates(&rule, "2025-11-01", "2025-12-06"), ["2025-12-06"]);
+        assert_eq!(rule.label(), "每 2 週的週六");
+    }
+
+    #[test]
+    fn test_monthly_by_day_skips_short_months() {
+        let rule = RecurrenceRule::new(
+            Recurrence::MonthlyByDay,
+            1,
+            date("2026-01-31"),
+            Some(date("2026-05-31")),
+        )
+        .unwrap();
+
+        assert_eq!(
+            dates(&rule, "2026-01-01", "2026-12-31"),
+            ["2026-01-31", "2026-03-31", "2026-05-31"]
+        );
+    }
+
+    #[test]
+    fn test_monthly_by_weekday() {
+        // The second Saturday of December 2025
+        let rule =
+            RecurrenceRule::new(Recurrence::MonthlyByWeekday, 1, date("2025-12-13"), None).unwrap();
+
+        assert_eq!(
+            dates(&rule, "2026-01-01", "2026-03-31"),
+            ["2026-01-10", "2026-02-14", "2026-03-14"]
+        );
+        assert_eq!(rule.label(), "每月第二個週六");
+    }
+
+    #[test]
+    fn test_rule_bounds() {
+        assert!(RecurrenceRule::new(Recurrence::Weekly, 0, date("2025-12-06"), None).is_err());
+        assert!(RecurrenceRule::new(
+            Recurrence::Weekly,
+            1,
+            date("2025-12-06"),
+            Some(date("2025-12-05"))
+        )
+        .is_err());
+        assert!(Recurrence::parse("daily").is_err());
+    }
+}
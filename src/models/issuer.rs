@@ -16,10 +16,104 @@ pub struct CardIssuer {
     pub members_only_video_id: Option<String>, // For membership verification
     pub verification_method: String, // "video" or "comment"
     pub is_active: bool,
+    pub initial_validity_days: i32,
+    pub extension_days: i32,
+    pub recheck_interval_hours: i32,
+    pub failure_threshold: i32,
+    pub grace_period_days: i32,
+    // Own wallet accounts (NULL = global config); tokens are encrypted
+    pub wallet_issuer_api_url: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub wallet_issuer_token_encrypted: Option<String>,
+    pub wallet_credential_issuer: Option<String>, // `iss` of credentials from the own issuer account
+    pub wallet_verifier_api_url: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub wallet_verifier_token_encrypted: Option<String>,
+    pub wallet_provider: String,           // "taiwan_wallet" or "openid"
+    pub attendance_vc_uid: Option<String>, // Taiwan Digital Wallet VC UID of attendance credentials
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Wallet an issuer's credentials are offered to and verified with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletProviderKind {
+    /// Taiwan Digital Wallet (issuer/verifier REST APIs)
+    TaiwanWallet,
+    /// VPass's own OpenID4VCI issuer and OpenID4VP verifier (SD-JWT VCs)
+    OpenId,
+}
+
+impl WalletProviderKind {
+    pub const ALL: [WalletProviderKind; 2] =
+        [WalletProviderKind::TaiwanWallet, WalletProviderKind::OpenId];
+
+    /// Returns the database/string representation of the provider
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletProviderKind::TaiwanWallet => "taiwan_wallet",
+            WalletProviderKind::OpenId => "openid",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|provider| provider.as_str() == value)
+    }
+
+    /// Human-readable label for the admin UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            WalletProviderKind::TaiwanWallet => "台灣數位皮夾",
+            WalletProviderKind::OpenId => "OpenID4VC 標準皮夾（VPass 自建）",
+        }
+    }
+}
+
+/// Card lifecycle settings for an issuer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LifecyclePolicy {
+    pub initial_validity_days: i32,
+    pub extension_days: i32,
+    pub recheck_interval_hours: i32,
+    pub failure_threshold: i32,
+    pub grace_period_days: i32,
+}
+
+impl Default for LifecyclePolicy {
+    fn default() -> Self {
+        Self {
+            initial_validity_days: 30,
+            extension_days: 30,
+            recheck_interval_hours: 24,
+            failure_threshold: 3,
+            grace_period_days: 0,
+        }
+    }
+}
+
+impl LifecyclePolicy {
+    /// Validates the policy against the bounds enforced by the database
+    pub fn validate(&self) -> Result<(), String> {
+        let checks = [
+            ("Initial validity", self.initial_validity_days, 1, 366),
+            ("Extension length", self.extension_days, 1, 366),
+            ("Recheck interval", self.recheck_interval_hours, 1, 720),
+            ("Failure threshold", self.failure_threshold, 1, 30),
+            ("Grace period", self.grace_period_days, 0, 90),
+        ];
+
+        for (name, value, min, max) in checks {
+            if value < min || value > max {
+                return Err(format!("{} must be between {} and {}", name, min, max));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateIssuerData {
     pub youtube_channel_id: String,
@@ -31,6 +125,17 @@ pub struct CreateIssuerData {
 }
 
 impl CardIssuer {
+    /// Returns the issuer's card lifecycle policy
+    pub fn lifecycle_policy(&self) -> LifecyclePolicy {
+        LifecyclePolicy {
+            initial_validity_days: self.initial_validity_days,
+            extension_days: self.extension_days,
+            recheck_interval_hours: self.recheck_interval_hours,
+            failure_threshold: self.failure_threshold,
+            grace_period_days: self.grace_period_days,
+        }
+    }
+
     /// Creates a new card issuer (YouTube channel)
     pub async fn create(pool: &PgPool, data: CreateIssuerData) -> Result<Self, sqlx::Error> {
         let issuer = sqlx::query_as::<_, Self>(
@@ -178,6 +283,186 @@ impl CardIssuer {
         Ok(())
     }
 
+    /// Sets (or with None, clears) the attendance credential template
+    pub async fn update_attendance_vc_uid(
+        pool: &PgPool,
+        id: Uuid,
+        attendance_vc_uid: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE card_issuers
+            SET attendance_vc_uid = $2, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(attendance_vc_uid)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Updates the card lifecycle policy
+    pub async fn update_lifecycle_policy(
+        pool: &PgPool,
+        id: Uuid,
+        policy: &LifecyclePolicy,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE card_issuers
+            SET
+                initial_validity_days = $2,
+                extension_days = $3,
+                recheck_interval_hours = $4,
+                failure_threshold = $5,
+                grace_period_days = $6,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(policy.initial_validity_days)
+        .bind(policy.extension_days)
+        .bind(policy.recheck_interval_hours)
+        .bind(policy.failure_threshold)
+        .bind(policy.grace_period_days)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sets the issuer's own wallet issuer API account
+    /// `token_encrypted` must already be encrypted; None reverts to the global account.
+    pub async fn update_wallet_issuer_account(
+        pool: &PgPool,
+        id: Uuid,
+        api_url: Option<&str>,
+        token_encrypted: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE card_issuers
+            SET wallet_issuer_api_url = $2,
+                wallet_issuer_token_encrypted = $3,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(api_url)
+        .bind(token_encrypted)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sets the issuer identifier credentials from the issuer's own account are checked against
+    pub async fn update_wallet_credential_issuer(
+        pool: &PgPool,
+        id: Uuid,
+        credential_issuer: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE card_issuers
+            SET wallet_credential_issuer = $2, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(credential_issuer)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sets the wallet verifier API account used for the issuer's events
+    /// `token_encrypted` must already be encrypted; None reverts to the global account.
+    pub async fn update_wallet_verifier_account(
+        pool: &PgPool,
+        id: Uuid,
+        api_url: Option<&str>,
+        token_encrypted: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE card_issuers
+            SET wallet_verifier_api_url = $2,
+                wallet_verifier_token_encrypted = $3,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(api_url)
+        .bind(token_encrypted)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Wallet provider new credentials are offered through
+    pub fn wallet_provider_kind(&self) -> WalletProviderKind {
+        WalletProviderKind::parse(&self.wallet_provider).unwrap_or(WalletProviderKind::TaiwanWallet)
+    }
+
+    /// Sets the wallet provider new credentials are offered through
+    pub async fn update_wallet_provider(
+        pool: &PgPool,
+        id: Uuid,
+        wallet_provider: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE card_issuers
+            SET wallet_provider = $2, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(wallet_provider)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether the member signed in with the issuer's own YouTube channel, and so
+    /// manages the issuer and its events
+    pub async fn is_managed_by(&self, pool: &PgPool, member_id: Uuid) -> Result<bool, sqlx::Error> {
+        let managed: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM members
+                WHERE id = $1 AND youtube_user_id = $2
+            )
+            "#,
+        )
+        .bind(member_id)
+        .bind(&self.youtube_channel_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(managed.0)
+    }
+
+    /// Whether the issuer uses its own wallet issuer account
+    pub fn has_own_issuer_account(&self) -> bool {
+        self.wallet_issuer_token_encrypted.is_some()
+    }
+
+    /// Whether the issuer's events use its own wallet verifier account
+    pub fn has_own_verifier_account(&self) -> bool {
+        self.wallet_verifier_token_encrypted.is_some()
+    }
+
     /// Updates members-only video ID for background verification
     pub async fn update_members_only_video(
         pool: &PgPool,
@@ -199,3 +484,29 @@ impl CardIssuer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_is_valid() {
+        assert!(LifecyclePolicy::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_policy_bounds() {
+        let policy = LifecyclePolicy {
+            failure_threshold: 0,
+            ..LifecyclePolicy::default()
+        };
+        assert!(policy.validate().is_err());
+
+        let policy = LifecyclePolicy {
+            grace_period_days: 0,
+            recheck_interval_hours: 721,
+            ..LifecyclePolicy::default()
+        };
+        assert!(policy.validate().is_err());
+    }
+}
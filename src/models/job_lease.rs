@@ -0,0 +1,168 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JobLease {
+    pub job_name: String,
+    pub holder_id: Uuid,
+    pub acquired_at: DateTime<Utc>,
+    pub leased_until: DateTime<Utc>,
+}
+
+impl JobLease {
+    /// Acquires the lease for a job
+    /// Succeeds only if nobody holds the lease or the previous lease has expired.
+    /// Returns None if another run (on any instance) still holds it.
+    pub async fn try_acquire(
+        pool: &PgPool,
+        job_name: &str,
+        holder_id: Uuid,
+        ttl: Duration,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let lease = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO job_leases (job_name, holder_id, leased_until)
+            VALUES ($1, $2, NOW() + $3)
+            ON CONFLICT (job_name) DO UPDATE
+            SET holder_id = EXCLUDED.holder_id,
+                acquired_at = NOW(),
+                leased_until = EXCLUDED.leased_until
+            WHERE job_leases.leased_until < NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(job_name)
+        .bind(holder_id)
+        .bind(ttl)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(lease)
+    }
+
+    /// Extends a lease still held by `holder_id`
+    /// Returns false if the lease was lost (expired and taken over by another instance).
+    pub async fn renew(
+        pool: &PgPool,
+        job_name: &str,
+        holder_id: Uuid,
+        ttl: Duration,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE job_leases
+            SET leased_until = NOW() + $3
+            WHERE job_name = $1 AND holder_id = $2
+            "#,
+        )
+        .bind(job_name)
+        .bind(holder_id)
+        .bind(ttl)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Releases a lease so the next scheduled run can start immediately
+    pub async fn release(
+        pool: &PgPool,
+        job_name: &str,
+        holder_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE job_leases
+            SET leased_until = NOW()
+            WHERE job_name = $1 AND holder_id = $2
+            "#,
+        )
+        .bind(job_name)
+        .bind(holder_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> PgPool {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_acquire_renew_release() {
+        let pool = test_pool().await;
+        let job_name = format!("test-{}", Uuid::new_v4());
+        let (holder, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let ttl = Duration::minutes(5);
+
+        let lease = JobLease::try_acquire(&pool, &job_name, holder, ttl)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lease.holder_id, holder);
+        assert!(JobLease::try_acquire(&pool, &job_name, other, ttl)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert!(JobLease::renew(&pool, &job_name, holder, ttl)
+            .await
+            .unwrap());
+        assert!(!JobLease::renew(&pool, &job_name, other, ttl).await.unwrap());
+
+        // Releasing as another holder leaves the lease in place
+        JobLease::release(&pool, &job_name, other).await.unwrap();
+        assert!(JobLease::try_acquire(&pool, &job_name, other, ttl)
+            .await
+            .unwrap()
+            .is_none());
+
+        JobLease::release(&pool, &job_name, holder).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let next = JobLease::try_acquire(&pool, &job_name, other, ttl)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.holder_id, other);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_expired_lease_is_taken_over() {
+        let pool = test_pool().await;
+        let job_name = format!("test-{}", Uuid::new_v4());
+        let (holder, other) = (Uuid::new_v4(), Uuid::new_v4());
+
+        JobLease::try_acquire(&pool, &job_name, holder, Duration::milliseconds(50))
+            .await
+            .unwrap()
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let taken = JobLease::try_acquire(&pool, &job_name, other, Duration::minutes(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(taken.holder_id, other);
+
+        // The previous holder finds out on its next renewal
+        assert!(
+            !JobLease::renew(&pool, &job_name, holder, Duration::minutes(5))
+                .await
+                .unwrap()
+        );
+    }
+}
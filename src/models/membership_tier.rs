@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// A membership tier of an issuer; higher ranks are higher tiers
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MembershipTier {
+    pub id: Uuid,
+    pub issuer_id: Uuid,
+    pub label: String,
+    pub rank: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MembershipTier {
+    /// Most tiers an issuer can rank
+    pub const MAX_TIERS: usize = 20;
+
+    /// Parses tier labels entered one per line, lowest first; blank lines are skipped
+    pub fn parse_labels(text: &str) -> Result<Vec<String>, String> {
+        let mut labels: Vec<String> = Vec::new();
+        for label in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if label.chars().count() > 50 {
+                return Err(format!("Tier \"{}\" is longer than 50 characters", label));
+            }
+            if labels.iter().any(|l| l == label) {
+                return Err(format!("Tier \"{}\" is listed twice", label));
+            }
+            labels.push(label.to_string());
+        }
+
+        if labels.len() > Self::MAX_TIERS {
+            return Err(format!("At most {} tiers can be ranked", Self::MAX_TIERS));
+        }
+
+        Ok(labels)
+    }
+
+    /// Lists an issuer's tiers, lowest first
+    pub async fn list_by_issuer(pool: &PgPool, issuer_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        Self::list_by_issuers(pool, &[issuer_id]).await
+    }
+
+    /// Lists the tiers of several issuers, lowest first per issuer
+    pub async fn list_by_issuers(
+        pool: &PgPool,
+        issuer_ids: &[Uuid],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let tiers = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM issuer_membership_tiers
+            WHERE issuer_id = ANY($1)
+            ORDER BY issuer_id, rank ASC
+            "#,
+        )
+        .bind(issuer_ids)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tiers)
+    }
+
+    /// Replaces an issuer's tiers; `labels` are ordered lowest first
+    pub async fn replace_for_issuer(
+        pool: &PgPool,
+        issuer_id: Uuid,
+        labels: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM issuer_membership_tiers WHERE issuer_id = $1")
+            .bind(issuer_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (rank, label) in labels.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO issuer_membership_tiers (issuer_id, label, rank)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(issuer_id)
+            .bind(label)
+            .bind(rank as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_labels() {
+        assert_eq!(
+            MembershipTier::parse_labels(" 一般\n\n白銀 \r\n黃金\n").unwrap(),
+            vec!["一般", "白銀", "黃金"]
+        );
+        assert!(MembershipTier::parse_labels("").unwrap().is_empty());
+        assert!(MembershipTier::parse_labels("白銀\n白銀").is_err());
+    }
+}
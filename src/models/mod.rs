@@ -1,17 +1,55 @@
 // Models module - Database entity representations
 
+pub mod attendance_credential;
+pub mod background_task;
 pub mod card;
+pub mod credential_field;
+pub mod credential_status_list;
 pub mod event;
+pub mod event_access_rule;
+pub mod event_reservation;
+pub mod event_series;
 pub mod issuer;
+pub mod job_lease;
 pub mod member;
+pub mod membership_tier;
 pub mod oauth_session;
+pub mod offline_bundle;
+pub mod openid_credential_offer;
+pub mod openid_presentation;
+pub mod presentation_token_nonce;
+pub mod printed_card_token;
 pub mod revocation;
+pub mod scanner_device;
+pub mod signing_key;
+pub mod suspension;
 pub mod verification_event;
+pub mod wallet_offer;
+pub mod youtube_quota;
 
+pub use attendance_credential::AttendanceCredential;
+pub use background_task::BackgroundTask;
 pub use card::MembershipCard;
+pub use credential_field::IssuerCredentialField;
+pub use credential_status_list::CredentialStatusList;
 pub use event::Event;
+pub use event_access_rule::EventAccessRule;
+pub use event_reservation::EventReservation;
+pub use event_series::EventSeries;
 pub use issuer::CardIssuer;
+pub use job_lease::JobLease;
 pub use member::Member;
+pub use membership_tier::MembershipTier;
 pub use oauth_session::OAuthSession;
+pub use offline_bundle::OfflineBundle;
+pub use openid_credential_offer::OpenIdCredentialOffer;
+pub use openid_presentation::OpenIdPresentation;
+pub use presentation_token_nonce::PresentationTokenNonce;
+pub use printed_card_token::PrintedCardToken;
 pub use revocation::Revocation;
+pub use scanner_device::ScannerDevice;
+pub use signing_key::SigningKey;
+pub use suspension::CardSuspension;
 pub use verification_event::VerificationEvent;
+pub use wallet_offer::WalletOffer;
+pub use youtube_quota::YoutubeQuotaUsage;
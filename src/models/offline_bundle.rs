@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// An event bundle issued to an offline scanner
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OfflineBundle {
+    pub id: String, // the bundle's `jti`
+    pub event_id: Uuid,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl OfflineBundle {
+    /// Records a bundle as signed
+    pub async fn create<'e>(
+        executor: impl PgExecutor<'e>,
+        id: &str,
+        event_id: Uuid,
+        issued_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let bundle = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO offline_bundles (id, event_id, issued_at, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(event_id)
+        .bind(issued_at)
+        .bind(expires_at)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(bundle)
+    }
+
+    /// Finds a bundle issued for an event
+    pub async fn find_for_event(
+        pool: &PgPool,
+        id: &str,
+        event_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let bundle = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM offline_bundles
+            WHERE id = $1 AND event_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(bundle)
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// A credential offer made through the self-hosted OpenID4VCI issuer
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OpenIdCredentialOffer {
+    pub id: Uuid,
+    pub card_id: Uuid,
+    pub issuer_id: Uuid,
+    pub claims: serde_json::Value,
+    #[serde(skip_serializing, default)]
+    pub pre_authorized_code_hash: String,
+    #[serde(skip_serializing, default)]
+    pub access_token_hash: Option<String>,
+    pub access_token_expires_at: Option<DateTime<Utc>>,
+    pub c_nonce: Option<String>,
+    pub credential_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub issued_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub status_list_index: Option<i32>, // index in the issuer's credential status list
+    pub created_at: DateTime<Utc>,
+}
+
+impl OpenIdCredentialOffer {
+    /// Records an offer; the pre-authorized code is only stored hashed
+    pub async fn create(
+        pool: &PgPool,
+        card_id: Uuid,
+        issuer_id: Uuid,
+        claims: &serde_json::Value,
+        pre_authorized_code_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let offer = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO openid_credential_offers
+                (card_id, issuer_id, claims, pre_authorized_code_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(card_id)
+        .bind(issuer_id)
+        .bind(claims)
+        .bind(pre_authorized_code_hash)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let offer = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM openid_credential_offers
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    pub async fn find_by_credential_id(
+        pool: &PgPool,
+        credential_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let offer = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM openid_credential_offers
+            WHERE credential_id = $1
+            "#,
+        )
+        .bind(credential_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Exchanges an unexpired, unused pre-authorized code for an access token
+    /// Returns None if the code is unknown, expired or already used.
+    pub async fn redeem_code(
+        pool: &PgPool,
+        pre_authorized_code_hash: &str,
+        access_token_hash: &str,
+        access_token_expires_at: DateTime<Utc>,
+        c_nonce: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let offer = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE openid_credential_offers
+            SET access_token_hash = $2,
+                access_token_expires_at = $3,
+                c_nonce = $4
+            WHERE pre_authorized_code_hash = $1
+              AND access_token_hash IS NULL
+              AND issued_at IS NULL
+              AND expires_at > NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(pre_authorized_code_hash)
+        .bind(access_token_hash)
+        .bind(access_token_expires_at)
+        .bind(c_nonce)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Finds the offer an unexpired access token was issued for
+    pub async fn find_by_access_token(
+        pool: &PgPool,
+        access_token_hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let offer = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM openid_credential_offers
+            WHERE access_token_hash = $1
+              AND access_token_expires_at > NOW()
+            "#,
+        )
+        .bind(access_token_hash)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Records the issued credential; the access token cannot be used again
+    /// Returns false if the credential was already issued for this offer.
+    pub async fn mark_issued(
+        pool: &PgPool,
+        id: Uuid,
+        credential_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE openid_credential_offers
+            SET credential_id = $2,
+                issued_at = NOW(),
+                access_token_expires_at = NOW(),
+                c_nonce = NULL
+            WHERE id = $1 AND issued_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(credential_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revokes an issued credential (idempotent); a trigger marks it invalid in
+    /// the issuer's status list
+    pub async fn revoke(pool: &PgPool, credential_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE openid_credential_offers
+            SET revoked_at = COALESCE(revoked_at, NOW())
+            WHERE credential_id = $1
+            "#,
+        )
+        .bind(credential_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
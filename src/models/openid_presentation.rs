@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// A presentation request made through the self-hosted OpenID4VP verifier
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OpenIdPresentation {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub issuer_id: Uuid,
+    pub nonce: String,
+    pub result: Option<serde_json::Value>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl OpenIdPresentation {
+    pub async fn create(
+        pool: &PgPool,
+        event_id: Uuid,
+        issuer_id: Uuid,
+        nonce: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let presentation = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO openid_presentations (event_id, issuer_id, nonce, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(event_id)
+        .bind(issuer_id)
+        .bind(nonce)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(presentation)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let presentation = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM openid_presentations
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(presentation)
+    }
+
+    /// Stores the verification result of the wallet's response
+    /// Returns false if the request was already answered or has expired.
+    pub async fn complete(
+        pool: &PgPool,
+        id: Uuid,
+        result: &serde_json::Value,
+    ) -> Result<bool, sqlx::Error> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE openid_presentations
+            SET result = $2, completed_at = NOW()
+            WHERE id = $1 AND completed_at IS NULL AND expires_at > NOW()
+            "#,
+        )
+        .bind(id)
+        .bind(result)
+        .execute(pool)
+        .await?;
+
+        Ok(updated.rows_affected() > 0)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.completed_at.is_none() && self.expires_at <= Utc::now()
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// Nonce of a card presentation token that has been scanned
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PresentationTokenNonce {
+    pub nonce: String,
+    pub card_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: DateTime<Utc>,
+}
+
+impl PresentationTokenNonce {
+    /// Records a token's nonce as used
+    /// Returns false if it was already used. Nonces of tokens that expired before
+    /// `purge_before` are purged first.
+    pub async fn consume(
+        pool: &PgPool,
+        nonce: &str,
+        card_id: Uuid,
+        expires_at: DateTime<Utc>,
+        purge_before: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM presentation_token_nonces
+            WHERE expires_at < $1
+            "#,
+        )
+        .bind(purge_before)
+        .execute(pool)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO presentation_token_nonces (nonce, card_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (nonce) DO NOTHING
+            "#,
+        )
+        .bind(nonce)
+        .bind(card_id)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
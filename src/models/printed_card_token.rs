@@ -0,0 +1,224 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Serial of the token printed on a card; only the card's latest print is valid
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PrintedCardToken {
+    pub serial: String,
+    pub card_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of scanning a printed card's token at an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrintedTokenUse {
+    Accepted,
+    /// Already scanned at this event
+    AlreadyUsed,
+    /// The card was printed again since, or the serial is unknown
+    Revoked,
+}
+
+impl PrintedCardToken {
+    /// Records a new print of a card, revoking the serials printed before
+    ///
+    /// Runs on the caller's transaction so the previous print stays valid if
+    /// the new one is not stored.
+    pub async fn create(
+        conn: &mut PgConnection,
+        card_id: Uuid,
+        serial: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE printed_card_tokens
+            SET revoked_at = NOW()
+            WHERE card_id = $1 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(card_id)
+        .execute(&mut *conn)
+        .await?;
+
+        let token = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO printed_card_tokens (serial, card_id)
+            VALUES ($1, $2)
+            RETURNING *
+            "#,
+        )
+        .bind(serial)
+        .bind(card_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Records a scan of a card's printed serial at an event
+    pub async fn use_at_event(
+        pool: &PgPool,
+        serial: &str,
+        card_id: Uuid,
+        event_id: Uuid,
+    ) -> Result<PrintedTokenUse, sqlx::Error> {
+        let (active, used): (bool, bool) = sqlx::query_as(
+            r#"
+            WITH token AS (
+                SELECT serial FROM printed_card_tokens
+                WHERE serial = $1 AND card_id = $2 AND revoked_at IS NULL
+            ),
+            inserted AS (
+                INSERT INTO printed_card_token_uses (serial, event_id)
+                SELECT serial, $3 FROM token
+                ON CONFLICT (serial, event_id) DO NOTHING
+                RETURNING serial
+            )
+            SELECT EXISTS (SELECT 1 FROM token), EXISTS (SELECT 1 FROM inserted)
+            "#,
+        )
+        .bind(serial)
+        .bind(card_id)
+        .bind(event_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(match (active, used) {
+            (false, _) => PrintedTokenUse::Revoked,
+            (true, false) => PrintedTokenUse::AlreadyUsed,
+            (true, true) => PrintedTokenUse::Accepted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        card::{CreateCardData, MembershipCard},
+        event::{CreateEventData, Event, EventSchedule, ReentryPolicy, DEFAULT_TIMEZONE},
+        issuer::{CardIssuer, CreateIssuerData},
+        member::{CreateMemberData, Member},
+    };
+
+    async fn create_event(pool: &PgPool, issuer_id: Uuid) -> Event {
+        let now = Utc::now();
+        Event::create(
+            pool,
+            CreateEventData {
+                issuer_id,
+                event_name: "見面會".to_string(),
+                event_description: None,
+                schedule: EventSchedule {
+                    timezone: DEFAULT_TIMEZONE,
+                    starts_at: now,
+                    ends_at: now + chrono::Duration::hours(2),
+                    checkin_opens_at: None,
+                    checkin_closes_at: None,
+                },
+                event_location: None,
+                verifier_ref: "verifier".to_string(),
+                reentry_policy: ReentryPolicy::Unlimited,
+                capacity: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_printed_token_cannot_be_replayed() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+
+        let channel_id = format!("UC{}", Uuid::new_v4().simple());
+        let issuer = CardIssuer::create(
+            &pool,
+            CreateIssuerData {
+                youtube_channel_id: channel_id.clone(),
+                channel_handle: None,
+                channel_name: "星詠".to_string(),
+                verification_video_id: "video".to_string(),
+                default_membership_label: "會員".to_string(),
+                vc_uid: None,
+            },
+        )
+        .await
+        .unwrap();
+        let member = Member::create(
+            &pool,
+            CreateMemberData {
+                youtube_user_id: format!("member-{}", channel_id),
+                default_display_name: "小明".to_string(),
+                avatar_url: None,
+                locale: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let card = MembershipCard::create(
+            &mut conn,
+            CreateCardData {
+                issuer_id: issuer.id,
+                member_id: member.id,
+                membership_level_label: "會員".to_string(),
+                membership_confirmed_at: Utc::now(),
+                verification_comment_id: "comment".to_string(),
+                verification_video_id: "video".to_string(),
+                snapshot_json: serde_json::json!({}),
+                validity_days: 30,
+            },
+        )
+        .await
+        .unwrap();
+        let (first_event, second_event) = (
+            create_event(&pool, issuer.id).await,
+            create_event(&pool, issuer.id).await,
+        );
+
+        let serial = format!("serial-{}", card.id);
+        PrintedCardToken::create(&mut conn, card.id, &serial)
+            .await
+            .unwrap();
+        let use_at = |event_id| PrintedCardToken::use_at_event(&pool, &serial, card.id, event_id);
+
+        assert_eq!(
+            use_at(first_event.id).await.unwrap(),
+            PrintedTokenUse::Accepted
+        );
+        // A photo of the card scanned again at the same event
+        assert_eq!(
+            use_at(first_event.id).await.unwrap(),
+            PrintedTokenUse::AlreadyUsed
+        );
+        assert_eq!(
+            use_at(second_event.id).await.unwrap(),
+            PrintedTokenUse::Accepted
+        );
+
+        // Printing the card again revokes the old serial
+        PrintedCardToken::create(&mut conn, card.id, &format!("reprint-{}", card.id))
+            .await
+            .unwrap();
+        let third_event = create_event(&pool, issuer.id).await;
+        assert_eq!(
+            use_at(third_event.id).await.unwrap(),
+            PrintedTokenUse::Revoked
+        );
+
+        // A serial only counts for the card it was printed for
+        assert_eq!(
+            PrintedCardToken::use_at_event(&pool, &serial, Uuid::new_v4(), third_event.id)
+                .await
+                .unwrap(),
+            PrintedTokenUse::Revoked
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScannerDevice {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub name: String, // gate or device label, e.g. "A 入口"
+    pub operator_name: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub token_hash: String,
+    pub registered_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateScannerDeviceData {
+    pub event_id: Uuid,
+    pub name: String,
+    pub operator_name: Option<String>,
+    pub token_hash: String,
+    pub registered_by: Option<Uuid>,
+}
+
+impl ScannerDevice {
+    pub async fn create(pool: &PgPool, data: CreateScannerDeviceData) -> Result<Self, sqlx::Error> {
+        let device = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO scanner_devices (event_id, name, operator_name, token_hash, registered_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(data.event_id)
+        .bind(data.name)
+        .bind(data.operator_name)
+        .bind(data.token_hash)
+        .bind(data.registered_by)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(device)
+    }
+
+    /// Finds the event's unrevoked device with this token hash, recording that it was seen
+    pub async fn authenticate(
+        pool: &PgPool,
+        event_id: Uuid,
+        token_hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let device = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE scanner_devices
+            SET last_seen_at = NOW()
+            WHERE token_hash = $1 AND event_id = $2 AND revoked_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(token_hash)
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(device)
+    }
+
+    /// Lists an event's devices, revoked ones included
+    pub async fn list_by_event(pool: &PgPool, event_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let devices = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM scanner_devices
+            WHERE event_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(devices)
+    }
+
+    /// Revokes a device; its token stops working immediately
+    /// Returns false if the event has no such unrevoked device.
+    pub async fn revoke(pool: &PgPool, event_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE scanner_devices
+            SET revoked_at = NOW()
+            WHERE id = $1 AND event_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SigningKey {
+    pub id: Uuid,
+    pub kid: String,
+    pub algorithm: String, // "ES256"
+    #[serde(skip_serializing, default)]
+    pub private_key_encrypted: String,
+    pub public_jwk: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+    /// None for VPass's own key
+    pub issuer_id: Option<Uuid>,
+    pub signed_until: Option<DateTime<Utc>>, // latest expiry of what the key signed
+    pub signed_unexpiring: bool,             // signed a credential without expiry
+}
+
+impl SigningKey {
+    /// Stores a new key; `private_key_encrypted` must already be encrypted
+    /// Returns None if the issuer got an active key concurrently.
+    pub async fn create(
+        executor: impl PgExecutor<'_>,
+        issuer_id: Option<Uuid>,
+        kid: &str,
+        private_key_encrypted: &str,
+        public_jwk: &serde_json::Value,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let key = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO signing_keys (issuer_id, kid, private_key_encrypted, public_jwk)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (issuer_id) WHERE retired_at IS NULL AND issuer_id IS NOT NULL
+            DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(issuer_id)
+        .bind(kid)
+        .bind(private_key_encrypted)
+        .bind(public_jwk)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(key)
+    }
+
+    /// Returns the newest key of an issuer (None: VPass's own) that is not retired
+    pub async fn find_active(
+        pool: &PgPool,
+        issuer_id: Option<Uuid>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let key = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM signing_keys
+            WHERE issuer_id IS NOT DISTINCT FROM $1 AND retired_at IS NULL
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(issuer_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    /// Retires the active key of an issuer; it keeps verifying what it signed
+    pub async fn retire_active(
+        executor: impl PgExecutor<'_>,
+        issuer_id: Option<Uuid>,
+    ) -> Result<u64, sqlx::Error> {
+        let retired = sqlx::query(
+            r#"
+            UPDATE signing_keys
+            SET retired_at = NOW()
+            WHERE issuer_id IS NOT DISTINCT FROM $1 AND retired_at IS NULL
+            "#,
+        )
+        .bind(issuer_id)
+        .execute(executor)
+        .await?;
+
+        Ok(retired.rows_affected())
+    }
+
+    pub async fn find_by_kid(pool: &PgPool, kid: &str) -> Result<Option<Self>, sqlx::Error> {
+        let key = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM signing_keys
+            WHERE kid = $1
+            "#,
+        )
+        .bind(kid)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    /// Records that the key signed something valid until `expires_at` (None: for good)
+    pub async fn record_signed(
+        executor: impl PgExecutor<'_>,
+        id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE signing_keys
+            SET signed_until = GREATEST(signed_until, $2),
+                signed_unexpiring = signed_unexpiring OR $2 IS NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(expires_at)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists an issuer's keys (None: VPass's own), newest first
+    /// Retired keys are left out once everything they signed expired before
+    /// `signed_since`.
+    pub async fn list_by_issuer(
+        pool: &PgPool,
+        issuer_id: Option<Uuid>,
+        signed_since: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let keys = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM signing_keys
+            WHERE issuer_id IS NOT DISTINCT FROM $1
+              AND (retired_at IS NULL OR signed_unexpiring OR signed_until >= $2)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(issuer_id)
+        .bind(signed_since)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(keys)
+    }
+}
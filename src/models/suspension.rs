@@ -0,0 +1,192 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CardSuspension {
+    pub id: Uuid,
+    pub card_id: Uuid,
+    pub reason: String,
+    pub suspended_by: Option<Uuid>,
+    pub suspended_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>, // None = until lifted manually
+    pub lifted_at: Option<DateTime<Utc>>,
+    pub lifted_by: Option<Uuid>, // None when reinstated by the scheduler
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateSuspensionData {
+    pub card_id: Uuid,
+    pub reason: String,
+    pub suspended_by: Option<Uuid>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl CardSuspension {
+    /// Suspends a card
+    /// Records the suspension and flips the card to 'suspended' in one transaction.
+    /// Returns None if the card is not currently active (nothing to suspend).
+    pub async fn create(
+        pool: &PgPool,
+        data: CreateSuspensionData,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE membership_cards
+            SET status = 'suspended'
+            WHERE id = $1 AND status = 'active'
+            "#,
+        )
+        .bind(data.card_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let suspension = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO card_suspensions (card_id, reason, suspended_by, ends_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(data.card_id)
+        .bind(&data.reason)
+        .bind(data.suspended_by)
+        .bind(data.ends_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(suspension))
+    }
+
+    /// Finds the open (not yet lifted) suspension for a card
+    pub async fn find_open_for_card(
+        pool: &PgPool,
+        card_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let suspension = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM card_suspensions
+            WHERE card_id = $1 AND lifted_at IS NULL
+            "#,
+        )
+        .bind(card_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(suspension)
+    }
+
+    /// Lists all suspensions for a card, most recent first
+    pub async fn list_by_card(pool: &PgPool, card_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let suspensions = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM card_suspensions
+            WHERE card_id = $1
+            ORDER BY suspended_at DESC
+            "#,
+        )
+        .bind(card_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(suspensions)
+    }
+
+    /// Lists open suspensions for all cards of an issuer
+    pub async fn list_open_by_issuer(
+        pool: &PgPool,
+        issuer_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let suspensions = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT s.* FROM card_suspensions s
+            JOIN membership_cards c ON c.id = s.card_id
+            WHERE c.issuer_id = $1 AND s.lifted_at IS NULL
+            ORDER BY s.suspended_at DESC
+            "#,
+        )
+        .bind(issuer_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(suspensions)
+    }
+
+    /// Lifts the open suspension for a card and reinstates it
+    /// `lifted_by` is None when the scheduler reinstates the card automatically.
+    /// Returns false if the card had no open suspension.
+    pub async fn lift(
+        pool: &PgPool,
+        card_id: Uuid,
+        lifted_by: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let lifted = sqlx::query(
+            r#"
+            UPDATE card_suspensions
+            SET lifted_at = NOW(), lifted_by = $2
+            WHERE card_id = $1 AND lifted_at IS NULL
+            "#,
+        )
+        .bind(card_id)
+        .bind(lifted_by)
+        .execute(&mut *tx)
+        .await?;
+
+        if lifted.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        // Only reinstate cards that are still suspended (a deleted card stays deleted)
+        sqlx::query(
+            r#"
+            UPDATE membership_cards
+            SET status = 'active'
+            WHERE id = $1 AND status = 'suspended'
+            "#,
+        )
+        .bind(card_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Finds open suspensions whose end date has passed
+    pub async fn find_due(pool: &PgPool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        let suspensions = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM card_suspensions
+            WHERE lifted_at IS NULL
+              AND ends_at IS NOT NULL
+              AND ends_at <= NOW()
+            ORDER BY ends_at ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(suspensions)
+    }
+
+    /// Checks if the suspension is still in effect
+    pub fn is_open(&self) -> bool {
+        self.lifted_at.is_none()
+    }
+}
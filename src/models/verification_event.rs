@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgExecutor, PgPool};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -13,6 +13,40 @@ pub struct VerificationEvent {
     pub verification_context: Option<JsonValue>, // JSONB field for extra metadata
     pub raw_payload: Option<String>, // Original QR payload for debugging
     pub verified_at: DateTime<Utc>,
+    pub client_scan_id: Option<Uuid>, // set for scans uploaded by an offline scanner
+}
+
+/// Scans recorded by one scanner device
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DeviceScanCounts {
+    pub device_id: Uuid,
+    pub total: i64,
+    pub successful: i64,
+    pub recent: i64, // scans since the `recent_since` passed in
+    pub last_scan_at: DateTime<Utc>,
+}
+
+/// An earlier admission (`success`) or check-out (`checked_out`) of a member at an event
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AdmissionScan {
+    pub verification_result: String,
+    pub verified_at: DateTime<Utc>,
+    pub gate_name: Option<String>, // `verification_context.device.name`
+}
+
+/// Members admitted at one occurrence of a series
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OccurrenceAttendance {
+    pub event_id: Uuid,
+    pub attendees: i64,
+}
+
+/// Attendance across all occurrences of a series
+#[derive(Debug, Clone, Default, Serialize, FromRow)]
+pub struct SeriesAttendance {
+    pub unique_attendees: i64,    // members admitted at least once
+    pub total_attendances: i64,   // sum of each occurrence's admitted members
+    pub returning_attendees: i64, // members admitted at two or more occurrences
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,8 +60,8 @@ pub struct CreateVerificationEventData {
 
 impl VerificationEvent {
     /// Create a new verification event
-    pub async fn create_event(
-        pool: &PgPool,
+    pub async fn create_event<'e>(
+        executor: impl PgExecutor<'e>,
         data: CreateVerificationEventData,
     ) -> Result<Self, sqlx::Error> {
         let event = sqlx::query_as::<_, VerificationEvent>(
@@ -42,12 +76,105 @@ impl VerificationEvent {
         .bind(data.verification_result)
         .bind(data.verification_context)
         .bind(data.raw_payload)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(event)
     }
 
+    /// Lists a member's admissions and check-outs at an event before `before`, with
+    /// any of their cards, oldest first
+    pub async fn admission_history<'e>(
+        executor: impl PgExecutor<'e>,
+        event_id: Uuid,
+        member_id: Uuid,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<AdmissionScan>, sqlx::Error> {
+        let scans = sqlx::query_as::<_, AdmissionScan>(
+            r#"
+            SELECT
+                v.verification_result,
+                v.verified_at,
+                v.verification_context->'device'->>'name' AS gate_name
+            FROM verification_events v
+            JOIN membership_cards c ON c.id = v.card_id
+            WHERE v.event_id = $1 AND c.member_id = $2
+              AND v.verification_result IN ('success', 'checked_out')
+              AND v.verified_at < $3
+            ORDER BY v.verified_at
+            "#,
+        )
+        .bind(event_id)
+        .bind(member_id)
+        .bind(before)
+        .fetch_all(executor)
+        .await?;
+
+        Ok(scans)
+    }
+
+    /// Takes the transaction-scoped admission lock for a member at an event
+    /// Held until the transaction ends, so two gates scanning the member's cards
+    /// check their admission history and record their scan one after the other.
+    pub async fn lock_admission(
+        conn: &mut PgConnection,
+        event_id: Uuid,
+        member_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(format!("event_admission:{}:{}", event_id, member_id))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records a scan uploaded by an offline scanner, dated when it was scanned
+    /// Returns None if a scan with this ID was already uploaded.
+    pub async fn create_offline_scan<'e>(
+        executor: impl PgExecutor<'e>,
+        data: CreateVerificationEventData,
+        client_scan_id: Uuid,
+        scanned_at: DateTime<Utc>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let event = sqlx::query_as::<_, VerificationEvent>(
+            r#"
+            INSERT INTO verification_events (event_id, card_id, verification_result, verification_context, raw_payload, client_scan_id, verified_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (client_scan_id) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(data.event_id)
+        .bind(data.card_id)
+        .bind(data.verification_result)
+        .bind(data.verification_context)
+        .bind(data.raw_payload)
+        .bind(client_scan_id)
+        .bind(scanned_at)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(event)
+    }
+
+    /// Checks whether an offline scan was already uploaded
+    pub async fn client_scan_exists(
+        pool: &PgPool,
+        client_scan_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM verification_events WHERE client_scan_id = $1)
+            "#,
+        )
+        .bind(client_scan_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exists)
+    }
+
     /// List verification events for a specific event
     pub async fn list_by_event(
         pool: &PgPool,
@@ -105,6 +232,33 @@ impl VerificationEvent {
     }
 
     /// Count unique cards verified at an event
+    /// Counts an event's scans per scanner device (`verification_context.device.id`)
+    pub async fn count_by_device(
+        pool: &PgPool,
+        event_id: Uuid,
+        recent_since: DateTime<Utc>,
+    ) -> Result<Vec<DeviceScanCounts>, sqlx::Error> {
+        let counts = sqlx::query_as::<_, DeviceScanCounts>(
+            r#"
+            SELECT
+                (verification_context->'device'->>'id')::uuid AS device_id,
+                COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE verification_result = 'success') AS successful,
+                COUNT(*) FILTER (WHERE verified_at >= $2) AS recent,
+                MAX(verified_at) AS last_scan_at
+            FROM verification_events
+            WHERE event_id = $1 AND verification_context->'device'->>'id' IS NOT NULL
+            GROUP BY 1
+            "#,
+        )
+        .bind(event_id)
+        .bind(recent_since)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(counts)
+    }
+
     pub async fn count_unique_cards_by_event(
         pool: &PgPool,
         event_id: Uuid,
@@ -123,6 +277,83 @@ impl VerificationEvent {
         Ok(count)
     }
 
+    /// Counts the events a member was admitted to with any of their cards of an issuer
+    pub async fn count_events_attended(
+        pool: &PgPool,
+        member_id: Uuid,
+        issuer_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(DISTINCT v.event_id)
+            FROM verification_events v
+            JOIN membership_cards c ON c.id = v.card_id
+            WHERE c.member_id = $1 AND c.issuer_id = $2
+              AND v.verification_result = 'success'
+            "#,
+        )
+        .bind(member_id)
+        .bind(issuer_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Counts the members admitted at each occurrence of a series
+    pub async fn attendance_by_occurrence(
+        pool: &PgPool,
+        series_id: Uuid,
+    ) -> Result<Vec<OccurrenceAttendance>, sqlx::Error> {
+        let attendance = sqlx::query_as::<_, OccurrenceAttendance>(
+            r#"
+            SELECT e.id AS event_id, COUNT(DISTINCT c.member_id) AS attendees
+            FROM events e
+            LEFT JOIN verification_events v
+              ON v.event_id = e.id AND v.verification_result = 'success'
+            LEFT JOIN membership_cards c ON c.id = v.card_id
+            WHERE e.series_id = $1
+            GROUP BY e.id
+            "#,
+        )
+        .bind(series_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(attendance)
+    }
+
+    /// Aggregates the members admitted across a series' occurrences, whichever
+    /// of their cards they used
+    pub async fn series_attendance(
+        pool: &PgPool,
+        series_id: Uuid,
+    ) -> Result<SeriesAttendance, sqlx::Error> {
+        let attendance = sqlx::query_as::<_, SeriesAttendance>(
+            r#"
+            WITH per_member AS (
+                SELECT c.member_id, COUNT(DISTINCT v.event_id) AS occurrences
+                FROM verification_events v
+                JOIN events e ON e.id = v.event_id
+                JOIN membership_cards c ON c.id = v.card_id
+                WHERE e.series_id = $1
+                  AND v.verification_result = 'success'
+                GROUP BY c.member_id
+            )
+            SELECT
+                COUNT(*) AS unique_attendees,
+                COALESCE(SUM(occurrences), 0)::BIGINT AS total_attendances,
+                COUNT(*) FILTER (WHERE occurrences > 1) AS returning_attendees
+            FROM per_member
+            "#,
+        )
+        .bind(series_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(attendance)
+    }
+
     /// List verification events for a specific card
     pub async fn list_by_card(pool: &PgPool, card_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
         let events = sqlx::query_as::<_, VerificationEvent>(
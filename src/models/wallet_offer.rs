@@ -0,0 +1,231 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WalletOffer {
+    pub id: Uuid,
+    pub card_id: Uuid,
+    pub reason: String, // "initial", "expired", "lost_device"
+    pub replaced_cid: Option<String>,
+    pub transaction_id: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub offered_at: Option<DateTime<Utc>>,
+}
+
+impl WalletOffer {
+    /// Records a requested offer (the QR code itself is generated afterwards)
+    pub async fn create<'e>(
+        executor: impl PgExecutor<'e>,
+        card_id: Uuid,
+        reason: &str,
+        replaced_cid: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        let offer = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO wallet_offers (card_id, reason, replaced_cid)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+        )
+        .bind(card_id)
+        .bind(reason)
+        .bind(replaced_cid)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Records the wallet transaction on the card's latest outstanding offer
+    pub async fn mark_offered(
+        pool: &PgPool,
+        card_id: Uuid,
+        transaction_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE wallet_offers
+            SET transaction_id = $2, offered_at = NOW()
+            WHERE id = (
+                SELECT id FROM wallet_offers
+                WHERE card_id = $1 AND offered_at IS NULL
+                ORDER BY requested_at DESC
+                LIMIT 1
+            )
+            "#,
+        )
+        .bind(card_id)
+        .bind(transaction_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists all offers for a card, most recent first
+    pub async fn list_by_card(pool: &PgPool, card_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let offers = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM wallet_offers
+            WHERE card_id = $1
+            ORDER BY requested_at DESC
+            "#,
+        )
+        .bind(card_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(offers)
+    }
+
+    /// Counts re-offers (offers after the initial one) requested in the last 24 hours
+    pub async fn count_recent_reoffers<'e>(
+        executor: impl PgExecutor<'e>,
+        card_id: Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM wallet_offers
+            WHERE card_id = $1
+              AND reason != 'initial'
+              AND requested_at > NOW() - INTERVAL '24 hours'
+            "#,
+        )
+        .bind(card_id)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Returns a human-readable label for the offer reason
+    pub fn reason_label(&self) -> &'static str {
+        match self.reason.as_str() {
+            "initial" => "首次發行",
+            "expired" => "重新產生 QR Code",
+            "lost_device" => "裝置遺失重新發行",
+            _ => "其他",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        card::{CreateCardData, MembershipCard},
+        issuer::{CardIssuer, CreateIssuerData},
+        member::{CreateMemberData, Member},
+    };
+
+    #[test]
+    fn test_reason_label() {
+        let offer = |reason: &str| WalletOffer {
+            id: Uuid::new_v4(),
+            card_id: Uuid::new_v4(),
+            reason: reason.to_string(),
+            replaced_cid: None,
+            transaction_id: None,
+            requested_at: Utc::now(),
+            offered_at: None,
+        };
+
+        assert_eq!(offer("initial").reason_label(), "首次發行");
+        assert_eq!(offer("lost_device").reason_label(), "裝置遺失重新發行");
+        assert_eq!(offer("unknown").reason_label(), "其他");
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires a database (DATABASE_URL)
+    async fn test_reoffers_are_counted_and_marked() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+
+        let channel_id = format!("UC{}", Uuid::new_v4().simple());
+        let issuer = CardIssuer::create(
+            &pool,
+            CreateIssuerData {
+                youtube_channel_id: channel_id.clone(),
+                channel_handle: None,
+                channel_name: "星詠".to_string(),
+                verification_video_id: "video".to_string(),
+                default_membership_label: "會員".to_string(),
+                vc_uid: None,
+            },
+        )
+        .await
+        .unwrap();
+        let member = Member::create(
+            &pool,
+            CreateMemberData {
+                youtube_user_id: format!("member-{}", channel_id),
+                default_display_name: "小明".to_string(),
+                avatar_url: None,
+                locale: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let card = MembershipCard::create(
+            &mut conn,
+            CreateCardData {
+                issuer_id: issuer.id,
+                member_id: member.id,
+                membership_level_label: "會員".to_string(),
+                membership_confirmed_at: Utc::now(),
+                verification_comment_id: "comment".to_string(),
+                verification_video_id: "video".to_string(),
+                snapshot_json: serde_json::json!({}),
+                validity_days: 30,
+            },
+        )
+        .await
+        .unwrap();
+
+        WalletOffer::create(&pool, card.id, "initial", None)
+            .await
+            .unwrap();
+        WalletOffer::mark_offered(&pool, card.id, "tx-initial")
+            .await
+            .unwrap();
+        WalletOffer::create(&pool, card.id, "expired", None)
+            .await
+            .unwrap();
+        WalletOffer::create(&pool, card.id, "lost_device", Some("old-cid"))
+            .await
+            .unwrap();
+        WalletOffer::mark_offered(&pool, card.id, "tx-reissue")
+            .await
+            .unwrap();
+
+        // The initial offer does not count against the daily re-offer limit
+        assert_eq!(
+            WalletOffer::count_recent_reoffers(&pool, card.id)
+                .await
+                .unwrap(),
+            2
+        );
+
+        let offers = WalletOffer::list_by_card(&pool, card.id).await.unwrap();
+        let transaction = |reason: &str| {
+            offers
+                .iter()
+                .find(|offer| offer.reason == reason)
+                .unwrap()
+                .transaction_id
+                .clone()
+        };
+        assert_eq!(offers.len(), 3);
+        assert_eq!(offers[0].reason, "lost_device");
+        assert_eq!(offers[0].replaced_cid.as_deref(), Some("old-cid"));
+        // Only the latest outstanding offer gets the new transaction
+        assert_eq!(transaction("lost_device").as_deref(), Some("tx-reissue"));
+        assert_eq!(transaction("expired"), None);
+        assert_eq!(transaction("initial").as_deref(), Some("tx-initial"));
+    }
+}
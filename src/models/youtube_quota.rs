@@ -0,0 +1,91 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+/// YouTube quotas reset at midnight Pacific time
+const QUOTA_DAY: &str = "(NOW() AT TIME ZONE 'America/Los_Angeles')::date";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct YoutubeQuotaUsage {
+    pub usage_date: NaiveDate,
+    pub units_spent: i32,
+    pub exhausted_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl YoutubeQuotaUsage {
+    /// Reserves quota units for today if the daily budget allows it
+    /// Returns false (and reserves nothing) if the budget would be exceeded
+    /// or the API already reported the quota as exhausted today.
+    pub async fn try_reserve(
+        pool: &PgPool,
+        units: i32,
+        daily_budget: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let reserved: Option<(i32,)> = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO youtube_quota_ledger (usage_date, units_spent)
+            SELECT {QUOTA_DAY}, $1
+            WHERE $1 <= $2
+            ON CONFLICT (usage_date) DO UPDATE
+            SET units_spent = youtube_quota_ledger.units_spent + EXCLUDED.units_spent,
+                updated_at = NOW()
+            WHERE youtube_quota_ledger.exhausted_at IS NULL
+              AND youtube_quota_ledger.units_spent + EXCLUDED.units_spent <= $2
+            RETURNING units_spent
+            "#
+        ))
+        .bind(units)
+        .bind(daily_budget)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(reserved.is_some())
+    }
+
+    /// Records quota units spent outside the budget check (e.g. interactive card issuance)
+    pub async fn record(pool: &PgPool, units: i32) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO youtube_quota_ledger (usage_date, units_spent)
+            VALUES ({QUOTA_DAY}, $1)
+            ON CONFLICT (usage_date) DO UPDATE
+            SET units_spent = youtube_quota_ledger.units_spent + EXCLUDED.units_spent,
+                updated_at = NOW()
+            "#
+        ))
+        .bind(units)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks today's quota as exhausted after the API returned quotaExceeded
+    pub async fn mark_exhausted(pool: &PgPool) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO youtube_quota_ledger (usage_date, exhausted_at)
+            VALUES ({QUOTA_DAY}, NOW())
+            ON CONFLICT (usage_date) DO UPDATE
+            SET exhausted_at = COALESCE(youtube_quota_ledger.exhausted_at, NOW()),
+                updated_at = NOW()
+            "#
+        ))
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets today's usage, if any quota has been spent
+    pub async fn today(pool: &PgPool) -> Result<Option<Self>, sqlx::Error> {
+        let usage = sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM youtube_quota_ledger WHERE usage_date = {QUOTA_DAY}"
+        ))
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(usage)
+    }
+}
@@ -0,0 +1,368 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::models::{
+    card::MembershipCard,
+    event::{CheckinState, Event, ReentryPolicy},
+    event_reservation::EventReservation,
+    verification_event::{AdmissionScan, VerificationEvent},
+};
+use crate::services::reservations;
+
+/// Which way a card is scanned at the gate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanDirection {
+    #[default]
+    Entry,
+    Exit,
+}
+
+/// Whether a card holder reserved a spot or walked in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Attendance {
+    Reserved,
+    WalkIn,
+}
+
+impl Attendance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Attendance::Reserved => "reserved",
+            Attendance::WalkIn => "walk_in",
+        }
+    }
+
+    /// Shown to gate staff with an admitted card
+    pub fn label(&self) -> &'static str {
+        match self {
+            Attendance::Reserved => "已預約",
+            Attendance::WalkIn => "現場入場",
+        }
+    }
+}
+
+/// Outcome of checking a valid card against the event's check-in window,
+/// re-entry policy and capacity
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdmissionDecision {
+    Admit,
+    CheckOut,
+    /// The card was admitted before and the policy does not let it in again
+    AlreadyCheckedIn {
+        at: DateTime<Utc>,
+        gate: Option<String>,
+        entries: usize,
+        max_entries: Option<i32>,
+    },
+    /// A walk-in at an event whose spots are all taken
+    CapacityReached {
+        capacity: i32,
+    },
+    /// Scanned before the event's check-in window opens
+    CheckinNotOpen {
+        opens_at: DateTime<Utc>,
+    },
+    /// Scanned after the event's check-in window closed
+    CheckinClosed {
+        closed_at: DateTime<Utc>,
+    },
+}
+
+impl AdmissionDecision {
+    /// `verification_result` recorded for the scan
+    pub fn result_type(&self) -> &'static str {
+        match self {
+            AdmissionDecision::Admit => "success",
+            AdmissionDecision::CheckOut => "checked_out",
+            AdmissionDecision::AlreadyCheckedIn { .. } => "already_checked_in",
+            AdmissionDecision::CapacityReached { .. } => "capacity_reached",
+            AdmissionDecision::CheckinNotOpen { .. } => "checkin_not_open",
+            AdmissionDecision::CheckinClosed { .. } => "checkin_closed",
+        }
+    }
+
+    /// Explains to gate staff why the card is not admitted, with times in the
+    /// event's time zone
+    pub fn denial_reason(&self, timezone: Tz) -> Option<String> {
+        match self {
+            AdmissionDecision::CapacityReached { capacity } => {
+                return Some(format!(
+                    "活動已額滿（{} 人），僅限已預約的會員入場",
+                    capacity
+                ))
+            }
+            AdmissionDecision::CheckinNotOpen { opens_at } => {
+                return Some(format!(
+                    "尚未開放入場，{} 起開始報到",
+                    display_time(*opens_at, timezone)
+                ))
+            }
+            AdmissionDecision::CheckinClosed { closed_at } => {
+                return Some(format!(
+                    "報到已於 {} 截止",
+                    display_time(*closed_at, timezone)
+                ))
+            }
+            _ => {}
+        }
+
+        let AdmissionDecision::AlreadyCheckedIn {
+            at,
+            gate,
+            entries,
+            max_entries,
+        } = self
+        else {
+            return None;
+        };
+
+        let time = display_time(*at, timezone);
+        let place = gate
+            .as_ref()
+            .map(|gate| format!("在 {} ", gate))
+            .unwrap_or_default();
+
+        Some(match max_entries {
+            Some(max) => format!(
+                "已入場 {} 次（上限 {} 次），最後一次於 {} {}入場",
+                entries, max, time, place
+            ),
+            None => format!("已於 {} {}入場", time, place),
+        })
+    }
+}
+
+/// Formats a time for gate staff: the time of day, with the date unless it is today
+fn display_time(at: DateTime<Utc>, timezone: Tz) -> String {
+    let local = at.with_timezone(&timezone);
+    if local.date_naive() == Utc::now().with_timezone(&timezone).date_naive() {
+        local.format("%H:%M").to_string()
+    } else {
+        local.format("%m/%d %H:%M").to_string()
+    }
+}
+
+/// Decides whether a valid card is admitted, given its earlier admissions and
+/// check-outs at the event (oldest first)
+pub fn evaluate(
+    policy: ReentryPolicy,
+    direction: ScanDirection,
+    history: &[AdmissionScan],
+) -> AdmissionDecision {
+    if direction == ScanDirection::Exit {
+        return AdmissionDecision::CheckOut;
+    }
+
+    let admissions: Vec<&AdmissionScan> = history
+        .iter()
+        .filter(|scan| scan.verification_result == "success")
+        .collect();
+    let Some(last_admission) = admissions.last() else {
+        return AdmissionDecision::Admit;
+    };
+
+    let admitted = match policy {
+        ReentryPolicy::Unlimited => true,
+        ReentryPolicy::SingleEntry => false,
+        // Inside unless the latest scan was a check-out
+        ReentryPolicy::AfterCheckout => history
+            .last()
+            .is_some_and(|scan| scan.verification_result == "checked_out"),
+        ReentryPolicy::MaxEntries(max) => admissions.len() < max as usize,
+    };
+
+    if admitted {
+        AdmissionDecision::Admit
+    } else {
+        AdmissionDecision::AlreadyCheckedIn {
+            at: last_admission.verified_at,
+            gate: last_admission.gate_name.clone(),
+            entries: admissions.len(),
+            max_entries: policy.max_entries(),
+        }
+    }
+}
+
+/// Checks a valid card's entry against the event's check-in window and re-entry
+/// policy as of `at`, and a walk-in's first entry against the event's capacity
+///
+/// Admission is per member: entries with any of the holder's cards (e.g. one
+/// reissued since they reserved) count towards the re-entry policy and capacity.
+/// Returns a transaction holding the member's admission lock for the event (and
+/// the event's capacity lock for a walk-in at a limited event); the caller
+/// records the scan with the decided result in it and commits, so gates scanning
+/// the same pass at once cannot both admit it, nor two walk-ins take the last spot.
+pub async fn check(
+    pool: &PgPool,
+    event: &Event,
+    card: &MembershipCard,
+    direction: ScanDirection,
+    at: DateTime<Utc>,
+) -> Result<
+    (
+        Transaction<'static, Postgres>,
+        AdmissionDecision,
+        Attendance,
+    ),
+    sqlx::Error,
+> {
+    let mut tx = pool.begin().await?;
+    VerificationEvent::lock_admission(&mut tx, event.id, card.member_id).await?;
+
+    let history =
+        VerificationEvent::admission_history(&mut *tx, event.id, card.member_id, at).await?;
+    // Check-outs are recorded whenever they happen
+    let mut decision = match (direction, event.checkin_state(at)) {
+        (ScanDirection::Entry, CheckinState::NotOpen { opens_at }) => {
+            AdmissionDecision::CheckinNotOpen { opens_at }
+        }
+        (ScanDirection::Entry, CheckinState::Closed { closed_at }) => {
+            AdmissionDecision::CheckinClosed { closed_at }
+        }
+        _ => evaluate(event.reentry_policy(), direction, &history),
+    };
+
+    let attendance =
+        if EventReservation::is_member_reserved(&mut *tx, event.id, card.member_id).await? {
+            Attendance::Reserved
+        } else {
+            Attendance::WalkIn
+        };
+
+    // Reserved spots are held; a walk-in takes a spot on first entry
+    let first_entry = !history
+        .iter()
+        .any(|scan| scan.verification_result == "success");
+    if let (AdmissionDecision::Admit, Attendance::WalkIn, Some(capacity), true) =
+        (&decision, attendance, event.capacity, first_entry)
+    {
+        EventReservation::lock_capacity(&mut tx, event.id).await?;
+        let occupancy = EventReservation::occupancy(&mut *tx, event.id).await?;
+        if !reservations::has_room(Some(capacity), occupancy) {
+            tracing::info!(event_id = %event.id, card_id = %card.id, capacity, "Event full, walk-in denied");
+            decision = AdmissionDecision::CapacityReached { capacity };
+        }
+    }
+
+    if let AdmissionDecision::AlreadyCheckedIn { entries, .. } = &decision {
+        tracing::info!(
+            event_id = %event.id,
+            card_id = %card.id,
+            entries,
+            policy = event.reentry_policy().as_str(),
+            "Denied re-entry"
+        );
+    }
+
+    Ok((tx, decision, attendance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn scan(result: &str, minutes_ago: i64, gate: &str) -> AdmissionScan {
+        AdmissionScan {
+            verification_result: result.to_string(),
+            verified_at: Utc::now() - Duration::minutes(minutes_ago),
+            gate_name: Some(gate.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_first_entry_is_admitted() {
+        for policy in [
+            ReentryPolicy::SingleEntry,
+            ReentryPolicy::AfterCheckout,
+            ReentryPolicy::MaxEntries(1),
+        ] {
+            assert_eq!(
+                evaluate(policy, ScanDirection::Entry, &[]),
+                AdmissionDecision::Admit
+            );
+        }
+    }
+
+    #[test]
+    fn test_single_entry_denies_second_scan() {
+        let history = [scan("success", 30, "B 入口")];
+        let decision = evaluate(ReentryPolicy::SingleEntry, ScanDirection::Entry, &history);
+
+        assert_eq!(decision.result_type(), "already_checked_in");
+        assert!(decision
+            .denial_reason(chrono_tz::Asia::Taipei)
+            .unwrap()
+            .contains("在 B 入口"));
+        assert_eq!(
+            evaluate(ReentryPolicy::Unlimited, ScanDirection::Entry, &history),
+            AdmissionDecision::Admit
+        );
+    }
+
+    #[test]
+    fn test_after_checkout() {
+        let inside = [scan("success", 30, "A 入口")];
+        assert_eq!(
+            evaluate(ReentryPolicy::AfterCheckout, ScanDirection::Entry, &inside).result_type(),
+            "already_checked_in"
+        );
+
+        let left = [
+            scan("success", 30, "A 入口"),
+            scan("checked_out", 10, "A 入口"),
+        ];
+        assert_eq!(
+            evaluate(ReentryPolicy::AfterCheckout, ScanDirection::Entry, &left),
+            AdmissionDecision::Admit
+        );
+        assert_eq!(
+            evaluate(ReentryPolicy::AfterCheckout, ScanDirection::Exit, &inside),
+            AdmissionDecision::CheckOut
+        );
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let history = [scan("success", 30, "A 入口"), scan("success", 10, "B 入口")];
+        assert_eq!(
+            evaluate(ReentryPolicy::MaxEntries(3), ScanDirection::Entry, &history),
+            AdmissionDecision::Admit
+        );
+
+        let decision = evaluate(ReentryPolicy::MaxEntries(2), ScanDirection::Entry, &history);
+        let AdmissionDecision::AlreadyCheckedIn { gate, entries, .. } = &decision else {
+            panic!("expected a denial, got {:?}", decision);
+        };
+        assert_eq!(gate.as_deref(), Some("B 入口"));
+        assert_eq!(*entries, 2);
+    }
+
+    #[test]
+    fn test_capacity_reached_reason() {
+        let decision = AdmissionDecision::CapacityReached { capacity: 200 };
+        assert_eq!(decision.result_type(), "capacity_reached");
+        assert!(decision
+            .denial_reason(chrono_tz::Asia::Taipei)
+            .unwrap()
+            .contains("200 人"));
+    }
+
+    #[test]
+    fn test_times_shown_in_event_timezone() {
+        let at = "2025-12-20T11:00:00Z".parse().unwrap();
+
+        assert_eq!(display_time(at, chrono_tz::Asia::Taipei), "12/20 19:00");
+        assert_eq!(display_time(at, chrono_tz::Asia::Tokyo), "12/20 20:00");
+        assert_eq!(
+            AdmissionDecision::CheckinClosed { closed_at: at }
+                .denial_reason(chrono_tz::Asia::Taipei)
+                .as_deref(),
+            Some("報到已於 12/20 19:00 截止")
+        );
+    }
+}
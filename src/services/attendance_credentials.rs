@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{
+    attendance_credential::{AttendanceCredential, AttendanceRecord},
+    card::MembershipCard,
+    event::Event,
+    issuer::CardIssuer,
+};
+use crate::services::{
+    credential_schema::{self, CredentialFieldError},
+    wallet_accounts::WalletAccountError,
+    wallet_provider::{self, CredentialOffer, WalletProvider},
+    wallet_qr::WalletQrError,
+};
+
+/// An unclaimed offer younger than this is shown again instead of requesting a new one
+const OFFER_REUSE_MINUTES: i64 = 5;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AttendanceCredentialError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("Attendance not found")]
+    NotFound,
+
+    #[error("The event's channel does not offer attendance credentials")]
+    NotOffered,
+
+    #[error("Attendance credential has not been offered yet")]
+    NoOffer,
+
+    #[error("Invalid credential data: {0}")]
+    InvalidCredentialField(#[from] CredentialFieldError),
+
+    #[error("Wallet QR generation failed: {0}")]
+    WalletQr(#[from] WalletQrError),
+
+    #[error("Wallet account error: {0}")]
+    WalletAccount(#[from] WalletAccountError),
+}
+
+/// Whether the attendance is returned as it is instead of offered again: its
+/// credential was claimed, or an offer is still fresh
+fn keeps_offer(attendance: &AttendanceCredential, now: DateTime<Utc>) -> bool {
+    let recently_offered = attendance
+        .offered_at
+        .is_some_and(|at| at > now - Duration::minutes(OFFER_REUSE_MINUTES));
+
+    attendance.claimed_at.is_some() || recently_offered
+}
+
+/// Whether attendance credentials of the issuer's events can be claimed: the same
+/// check `offer` makes, without contacting the wallet
+pub fn is_offered(config: &Config, issuer: &CardIssuer) -> bool {
+    matches!(
+        wallet_provider::attendance_provider(config, issuer),
+        Ok(Some(_))
+    )
+}
+
+/// A member's attendance history, most recent first, with which attendances can
+/// be claimed as credentials
+pub async fn list_for_member(
+    pool: &PgPool,
+    config: &Config,
+    member_id: Uuid,
+) -> Result<Vec<AttendanceRecord>, sqlx::Error> {
+    let mut records = AttendanceCredential::list_by_member(pool, member_id).await?;
+
+    let mut offered_by_issuer = HashMap::new();
+    for record in records.iter_mut().filter(|r| r.claimable) {
+        record.claimable = match offered_by_issuer.get(&record.issuer_id) {
+            Some(&offered) => offered,
+            None => {
+                let offered = CardIssuer::find_by_id(pool, record.issuer_id)
+                    .await?
+                    .is_some_and(|issuer| is_offered(config, &issuer));
+                offered_by_issuer.insert(record.issuer_id, offered);
+                offered
+            }
+        };
+    }
+
+    Ok(records)
+}
+
+/// The event an attendance belongs to and the provider its credential is offered through
+async fn attendance_wallet(
+    pool: &PgPool,
+    config: &Config,
+    attendance: &AttendanceCredential,
+) -> Result<(Event, CardIssuer, Box<dyn WalletProvider>), AttendanceCredentialError> {
+    let event = Event::find_by_id(pool, attendance.event_id)
+        .await?
+        .ok_or(AttendanceCredentialError::NotFound)?;
+    let issuer = CardIssuer::find_by_id(pool, event.issuer_id)
+        .await?
+        .ok_or(AttendanceCredentialError::NotFound)?;
+    let wallet = wallet_provider::attendance_provider(config, &issuer)?
+        .ok_or(AttendanceCredentialError::NotOffered)?;
+
+    Ok((event, issuer, wallet))
+}
+
+/// Offers an attendance credential to the member's wallet
+///
+/// Claimed attendances and offers made within the last few minutes are returned
+/// as they are, so reloading the claim page does not request a new QR code.
+#[tracing::instrument(skip(pool, config))]
+pub async fn offer(
+    pool: &PgPool,
+    config: &Config,
+    attendance_id: Uuid,
+) -> Result<AttendanceCredential, AttendanceCredentialError> {
+    let attendance = AttendanceCredential::find_by_id(pool, attendance_id)
+        .await?
+        .ok_or(AttendanceCredentialError::NotFound)?;
+
+    if keeps_offer(&attendance, Utc::now()) {
+        return Ok(attendance);
+    }
+
+    let (event, issuer, wallet) = attendance_wallet(pool, config, &attendance).await?;
+    let card = MembershipCard::find_by_id(pool, attendance.card_id)
+        .await?
+        .ok_or(AttendanceCredentialError::NotFound)?;
+
+    let response = wallet
+        .offer_credential(CredentialOffer {
+            card: &card,
+            issuer: &issuer,
+            fields: credential_schema::build_attendance_fields(&event, &issuer)?,
+        })
+        .await?;
+
+    AttendanceCredential::set_offer(
+        pool,
+        attendance.id,
+        &response.transaction_id,
+        &response.qr_code,
+        &response.deep_link,
+    )
+    .await?;
+
+    tracing::info!(
+        attendance_id = %attendance.id,
+        event_id = %event.id,
+        transaction_id = %response.transaction_id,
+        "Attendance credential offered"
+    );
+
+    AttendanceCredential::find_by_id(pool, attendance.id)
+        .await?
+        .ok_or(AttendanceCredentialError::NotFound)
+}
+
+/// Checks whether the member accepted the offered credential and records the claim
+///
+/// Returns the credential ID; `WalletQr(CredentialNotReady)` until the offer is scanned.
+#[tracing::instrument(skip(pool, config, attendance), fields(attendance_id = %attendance.id))]
+pub async fn poll_claim(
+    pool: &PgPool,
+    config: &Config,
+    attendance: &AttendanceCredential,
+) -> Result<String, AttendanceCredentialError> {
+    if let Some(cid) = &attendance.cid {
+        return Ok(cid.clone());
+    }
+
+    let transaction_id = attendance
+        .transaction_id
+        .as_deref()
+        .ok_or(AttendanceCredentialError::NoOffer)?;
+    let (_, _, wallet) = attendance_wallet(pool, config, attendance).await?;
+
+    let cid = wallet.poll_issuance(transaction_id).await?.cid;
+    AttendanceCredential::mark_claimed(pool, attendance.id, &cid).await?;
+
+    tracing::info!(
+        attendance_id = %attendance.id,
+        event_id = %attendance.event_id,
+        "Attendance credential claimed"
+    );
+
+    Ok(cid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn attendance(
+        offered_at: Option<DateTime<Utc>>,
+        claimed_at: Option<DateTime<Utc>>,
+    ) -> AttendanceCredential {
+        let admitted_at = Utc.with_ymd_and_hms(2026, 1, 9, 11, 0, 0).unwrap();
+        AttendanceCredential {
+            id: Uuid::new_v4(),
+            event_id: Uuid::new_v4(),
+            card_id: Uuid::new_v4(),
+            admitted_at,
+            transaction_id: offered_at.map(|_| "tx-1".to_string()),
+            wallet_qr_code: offered_at.map(|_| "data:image/png;base64,".to_string()),
+            wallet_deep_link: offered_at.map(|_| "modadigitalwallet://".to_string()),
+            offered_at,
+            cid: claimed_at.map(|_| "cid-1".to_string()),
+            claimed_at,
+            created_at: admitted_at,
+        }
+    }
+
+    #[test]
+    fn test_keeps_recent_offers() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 9, 12, 0, 0).unwrap();
+        let minutes_ago = |m| Some(now - Duration::minutes(m));
+
+        assert!(!keeps_offer(&attendance(None, None), now));
+        assert!(keeps_offer(&attendance(minutes_ago(1), None), now));
+        assert!(!keeps_offer(
+            &attendance(minutes_ago(OFFER_REUSE_MINUTES), None),
+            now
+        ));
+        assert!(!keeps_offer(&attendance(minutes_ago(30), None), now));
+    }
+
+    #[test]
+    fn test_claimed_attendance_is_never_offered_again() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 9, 12, 0, 0).unwrap();
+        let long_ago = Some(now - Duration::days(30));
+
+        assert!(keeps_offer(&attendance(long_ago, long_ago), now));
+    }
+}
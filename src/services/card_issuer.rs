@@ -1,13 +1,30 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::config::Config;
 use crate::models::{
-    card::{CreateCardData, MembershipCard},
-    issuer::CardIssuer,
+    background_task::{BackgroundTask, TaskPayload},
+    card::{CardStatus, CreateCardData, MembershipCard},
+    credential_field::IssuerCredentialField,
+    issuer::{CardIssuer, WalletProviderKind},
     member::{CreateMemberData, Member},
+    suspension::CardSuspension,
+    wallet_offer::WalletOffer,
+    youtube_quota::YoutubeQuotaUsage,
 };
-use crate::services::membership_checker;
+use crate::services::{
+    credential_schema::{self, CredentialFieldError, CredentialSubject},
+    loyalty, membership_checker, task_queue,
+    wallet_accounts::WalletAccountError,
+    wallet_provider::{self, CredentialOffer},
+};
+
+/// Window in which a repeated claim returns the card just issued instead of an error
+const ISSUANCE_REPLAY_WINDOW_MINUTES: i64 = 10;
+
+/// Re-offers a member may request per card within 24 hours
+const MAX_REOFFERS_PER_DAY: i64 = 5;
 
 #[derive(thiserror::Error, Debug)]
 pub enum CardIssuanceError {
@@ -23,20 +40,35 @@ pub enum CardIssuanceError {
     #[error("Wallet QR generation failed: {0}")]
     WalletQrGeneration(#[from] crate::services::wallet_qr::WalletQrError),
 
-    #[error("Taiwan Digital Wallet service unavailable. Please try again later.")]
-    WalletServiceUnavailable,
+    #[error("Invalid credential data: {0}")]
+    InvalidCredentialField(#[from] CredentialFieldError),
 
     #[error("Issuer not found")]
     IssuerNotFound,
 
+    #[error("Card not found")]
+    CardNotFound,
+
+    #[error("Cannot re-offer card to wallet: {0}")]
+    ReofferNotAllowed(String),
+
+    #[error("Too many wallet re-offers for this card today. Please try again tomorrow.")]
+    ReofferLimitReached,
+
     #[error("Active card already exists. {0}")]
     DuplicateCard(String),
 
+    #[error("Card is suspended by the channel. {0}")]
+    CardSuspended(String),
+
     #[error("Issuer not configured for Taiwan Digital Wallet (missing vc_uid)")]
     MissingVcUid,
 
     #[error("Issuer API not configured")]
     IssuerApiNotConfigured,
+
+    #[error("Wallet account error: {0}")]
+    WalletAccount(#[from] WalletAccountError),
 }
 
 /// Request to issue a new membership card
@@ -53,21 +85,25 @@ pub struct IssueCardRequest {
 pub struct IssueCardResult {
     pub card: MembershipCard,
     pub member: Member,
+    /// True when a duplicate request got the card a moment-ago request issued
+    pub reused: bool,
 }
 
 /// Issues a new membership card
 ///
 /// Flow:
-/// 1. Validates issuer exists and wallet API health
+/// 1. Validates issuer exists and checks wallet API health (degraded mode if down)
 /// 2. Verifies membership by checking access to members-only video
 /// 3. Creates or updates member record
-/// 4. Stores the card in the database
-/// 5. Generates Taiwan Digital Wallet QR code
-/// 6. Returns the card with QR code
-#[tracing::instrument(skip(pool, issuer_api_config, request), fields(issuer_id = %request.issuer_id))]
+/// 4. Under a per-member/issuer lock: rejects duplicates (returning the card to a
+///    repeated request within a few minutes), then stores the card and a wallet QR
+///    task in one transaction
+/// 5. Runs the wallet QR task immediately (left to the task worker if it fails)
+/// 6. Returns the card, with QR code if it was generated (otherwise wallet pending)
+#[tracing::instrument(skip(pool, config, request), fields(issuer_id = %request.issuer_id))]
 pub async fn issue_card(
     pool: &PgPool,
-    issuer_api_config: Option<(&str, &str)>, // (api_base_url, access_token)
+    config: &Config,
     request: IssueCardRequest,
 ) -> Result<IssueCardResult, CardIssuanceError> {
     use std::time::Instant;
@@ -75,16 +111,6 @@ pub async fn issue_card(
 
     tracing::info!("Starting card issuance process");
 
-    // 0. Early wallet API health check (FR-008a: fail fast if wallet unavailable)
-    let (api_base_url, access_token) =
-        issuer_api_config.ok_or(CardIssuanceError::IssuerApiNotConfigured)?;
-
-    crate::services::wallet_qr::check_wallet_health(api_base_url, access_token)
-        .await
-        .map_err(|_| CardIssuanceError::WalletServiceUnavailable)?;
-
-    tracing::debug!("Wallet API health check passed");
-
     // 1. Load and validate issuer
     let issuer = CardIssuer::find_by_id(pool, request.issuer_id)
         .await?
@@ -100,6 +126,24 @@ pub async fn issue_card(
         "Loaded issuer"
     );
 
+    // Early wallet health check, against the issuer's wallet provider (and its own
+    // wallet account if it has one). If the wallet is down the card is still issued
+    // with its wallet QR pending, so the member does not have to verify their
+    // membership again; the task worker attaches the QR once the API is back.
+    let wallet = wallet_provider::issuer_provider(pool, config, &issuer)?
+        .ok_or(CardIssuanceError::IssuerApiNotConfigured)?;
+
+    let wallet_available = match wallet.check_health().await {
+        Ok(()) => {
+            tracing::debug!("Wallet API health check passed");
+            true
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Wallet API unavailable, issuing card with wallet pending");
+            false
+        }
+    };
+
     // 2. Verify membership by checking access to the members-only video
     let membership_video_id = issuer
         .members_only_video_id
@@ -107,10 +151,17 @@ pub async fn issue_card(
         .unwrap_or(&issuer.verification_video_id);
 
     let youtube_start = Instant::now();
-    let has_access =
-        membership_checker::check_video_access(&request.access_token, membership_video_id).await?;
+    let access_check =
+        membership_checker::check_video_access(&request.access_token, membership_video_id).await;
     let youtube_duration = youtube_start.elapsed();
 
+    // Interactive checks are never deferred, but still count against the daily budget
+    if let Err(e) = YoutubeQuotaUsage::record(pool, membership_checker::LIST_CALL_QUOTA_COST).await
+    {
+        tracing::warn!(error = %e, "Failed to record YouTube quota usage");
+    }
+    let has_access = access_check?;
+
     if !has_access {
         tracing::warn!(
             video_id = %membership_video_id,
@@ -137,10 +188,37 @@ pub async fn issue_card(
 
     tracing::debug!(member_id = %member.id, "Member record created/updated");
 
-    // 5. Check for duplicate active unexpired cards (FR-006 + FR-006a)
-    let existing_cards = MembershipCard::find_active_unexpired_cards(pool, issuer.id, member.id).await?;
+    // 5. The Taiwan wallet needs the issuer's credential template (vc_uid)
+    if wallet.kind() == WalletProviderKind::TaiwanWallet && issuer.vc_uid.is_none() {
+        return Err(CardIssuanceError::MissingVcUid);
+    }
+
+    // 6. Serialize issuance per member/issuer: a concurrent request for the same
+    // pair waits here until this transaction commits, then sees our card.
+    let mut tx = pool.begin().await?;
+    MembershipCard::lock_issuance(&mut tx, issuer.id, member.id).await?;
+
+    // 7. Check for duplicate active unexpired cards (FR-006 + FR-006a)
+    let existing_cards =
+        MembershipCard::find_active_unexpired_cards(&mut *tx, issuer.id, member.id).await?;
+
+    if let Some(existing_card) = existing_cards.into_iter().next() {
+        tx.rollback().await?;
+
+        // A double submit gets the card the first request just issued
+        let replay_cutoff = Utc::now() - Duration::minutes(ISSUANCE_REPLAY_WINDOW_MINUTES);
+        if existing_card.issued_at > replay_cutoff {
+            tracing::info!(
+                card_id = %existing_card.id,
+                "Card was just issued for this member, returning it"
+            );
+            return Ok(IssueCardResult {
+                card: existing_card,
+                member,
+                reused: true,
+            });
+        }
 
-    if let Some(existing_card) = existing_cards.first() {
         let expires_info = existing_card
             .expires_at
             .map(|e| format!("Expires: {}", e.format("%Y-%m-%d")))
@@ -149,7 +227,22 @@ pub async fn issue_card(
         return Err(CardIssuanceError::DuplicateCard(expires_info));
     }
 
-    // 6. Create snapshot for auditing
+    // A suspended member must wait for reinstatement rather than claim a fresh card
+    if let Some(suspended_card) =
+        MembershipCard::find_suspended_for_member(&mut *tx, issuer.id, member.id).await?
+    {
+        tx.rollback().await?;
+
+        let suspension = CardSuspension::find_open_for_card(pool, suspended_card.id).await?;
+        let until_info = suspension
+            .and_then(|s| s.ends_at)
+            .map(|e| format!("Suspended until: {}", e.format("%Y-%m-%d")))
+            .unwrap_or_else(|| "Suspended until further notice".to_string());
+
+        return Err(CardIssuanceError::CardSuspended(until_info));
+    }
+
+    // 8. Create snapshot for auditing
     let now = Utc::now();
     let snapshot = serde_json::json!({
         "verification": {
@@ -160,47 +253,10 @@ pub async fn issue_card(
         },
     });
 
-    // 7. Validate issuer has vc_uid configured (api_base_url and access_token already extracted)
-    let vc_uid = issuer
-        .vc_uid
-        .as_ref()
-        .ok_or(CardIssuanceError::MissingVcUid)?;
-
-    // 9. Generate Taiwan Digital Wallet QR code
-    tracing::debug!("Generating Taiwan Digital Wallet QR code");
-    let wallet_start = Instant::now();
-
-    // Sanitize display name: Taiwan Digital Wallet only allows Chinese, English, numbers, and underscore
-    let sanitized_name = request
-        .member_display_name
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '_' || (*c >= '\u{4e00}' && *c <= '\u{9fff}'))
-        .collect::<String>();
-
-    let display_name = if sanitized_name.is_empty() {
-        "Member".to_string()
-    } else {
-        sanitized_name
-    };
-
-    let fields = vec![crate::services::wallet_qr::WalletQrField {
-        ename: "name".to_string(),
-        content: display_name,
-    }];
-
-    let wallet_qr_response =
-        crate::services::wallet_qr::generate_wallet_qr(api_base_url, access_token, vc_uid, fields)
-            .await?;
-    let wallet_duration = wallet_start.elapsed();
-
-    tracing::info!(
-        wallet_api_duration_ms = wallet_duration.as_millis(),
-        "Wallet QR code generated successfully"
-    );
-
-    // 10. Store the card
+    // 9. Store the card, together with the task that generates its wallet QR code.
+    // If we crash before the QR is attached, the task worker finishes the job.
     let card = MembershipCard::create(
-        pool,
+        &mut tx,
         CreateCardData {
             issuer_id: issuer.id,
             member_id: member.id,
@@ -209,36 +265,46 @@ pub async fn issue_card(
             verification_comment_id: format!("membership-access:{}", membership_video_id),
             verification_video_id: membership_video_id.to_string(),
             snapshot_json: snapshot,
+            validity_days: i64::from(issuer.initial_validity_days),
         },
     )
     .await?;
 
+    WalletOffer::create(&mut *tx, card.id, "initial", None).await?;
+    let wallet_task = BackgroundTask::enqueue(
+        &mut *tx,
+        &TaskPayload::GenerateWalletQr { card_id: card.id },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     tracing::info!(
         card_id = %card.id,
         expires_at = %card.expires_at.map(|e| e.to_rfc3339()).unwrap_or_else(|| "never".to_string()),
         "Card created successfully"
     );
 
-    // 11. Store wallet QR data on the card
-    MembershipCard::set_wallet_qr(
-        pool,
-        card.id,
-        wallet_qr_response.transaction_id.clone(),
-        wallet_qr_response.qr_code,
-        Some(wallet_qr_response.deep_link),
-    )
-    .await?;
+    // 10. Generate Taiwan Digital Wallet QR code right away (the worker retries on failure)
+    let wallet_start = Instant::now();
+
+    if wallet_available {
+        tracing::debug!("Generating Taiwan Digital Wallet QR code");
+        task_queue::run_now(pool, config, wallet_task.id).await?;
+    } else {
+        tracing::info!(card_id = %card.id, "Wallet QR pending, left to the task worker");
+    }
+    let wallet_duration = wallet_start.elapsed();
 
     tracing::info!(
-        card_id = %card.id,
-        transaction_id = %wallet_qr_response.transaction_id,
-        "Wallet QR data stored on card"
+        wallet_api_duration_ms = wallet_duration.as_millis(),
+        "Wallet QR generation attempted"
     );
 
     // Reload card to get wallet fields
     let card = MembershipCard::find_by_id(pool, card.id)
         .await?
-        .expect("Card should exist after creation");
+        .ok_or(CardIssuanceError::CardNotFound)?;
 
     // NFR-001: Log performance metrics (5-second target)
     let total_duration = start_time.elapsed();
@@ -262,5 +328,168 @@ pub async fn issue_card(
         );
     }
 
-    Ok(IssueCardResult { card, member })
+    Ok(IssueCardResult {
+        card,
+        member,
+        reused: false,
+    })
+}
+
+/// Generates the wallet QR code for a card and stores it on the card
+///
+/// Idempotent: cards that already have a wallet transaction, or that were
+/// deleted in the meantime, are left untouched.
+#[tracing::instrument(skip(pool, config))]
+pub async fn attach_wallet_qr(
+    pool: &PgPool,
+    config: &Config,
+    card_id: Uuid,
+) -> Result<(), CardIssuanceError> {
+    let card = MembershipCard::find_by_id(pool, card_id)
+        .await?
+        .ok_or(CardIssuanceError::CardNotFound)?;
+
+    if card.wallet_transaction_id.is_some() || card.status == CardStatus::Deleted {
+        tracing::debug!(card_id = %card.id, "Wallet QR not needed, skipping");
+        return Ok(());
+    }
+
+    let issuer = CardIssuer::find_by_id(pool, card.issuer_id)
+        .await?
+        .ok_or(CardIssuanceError::IssuerNotFound)?;
+
+    let wallet = wallet_provider::issuer_provider(pool, config, &issuer)?
+        .ok_or(CardIssuanceError::IssuerApiNotConfigured)?;
+    if wallet.kind() == WalletProviderKind::TaiwanWallet && issuer.vc_uid.is_none() {
+        return Err(CardIssuanceError::MissingVcUid);
+    }
+
+    let member = Member::find_by_id(pool, card.member_id)
+        .await?
+        .ok_or(CardIssuanceError::CardNotFound)?;
+
+    // Tenure and activity are computed afresh for every offer, so a reissued
+    // card carries the member's current history
+    let loyalty = loyalty::for_card(pool, &card).await?;
+
+    let schema = IssuerCredentialField::list_by_issuer(pool, issuer.id).await?;
+    let fields = credential_schema::build_wallet_fields(
+        &credential_schema::mappings_for_issuer(&schema),
+        &CredentialSubject {
+            card: &card,
+            issuer: &issuer,
+            member: &member,
+            loyalty: &loyalty,
+        },
+    )?;
+
+    let wallet_qr_response = wallet
+        .offer_credential(CredentialOffer {
+            card: &card,
+            issuer: &issuer,
+            fields,
+        })
+        .await?;
+
+    MembershipCard::set_wallet_qr(
+        pool,
+        card.id,
+        wallet_qr_response.transaction_id.clone(),
+        wallet_qr_response.qr_code,
+        Some(wallet_qr_response.deep_link),
+    )
+    .await?;
+
+    WalletOffer::mark_offered(pool, card.id, &wallet_qr_response.transaction_id).await?;
+
+    tracing::info!(
+        card_id = %card.id,
+        transaction_id = %wallet_qr_response.transaction_id,
+        provider = wallet.kind().as_str(),
+        "Wallet QR data stored on card"
+    );
+
+    Ok(())
+}
+
+/// Offers an existing card to the member's wallet again
+///
+/// Used when the previous QR code expired before it was scanned, or - with
+/// `lost_device` - when the member lost the phone holding the credential. In the
+/// latter case the old credential is revoked (in the background) before the new
+/// offer is generated. Every offer is recorded in `wallet_offers`.
+#[tracing::instrument(skip(pool, config))]
+pub async fn reoffer_wallet(
+    pool: &PgPool,
+    config: &Config,
+    card_id: Uuid,
+    lost_device: bool,
+) -> Result<(), CardIssuanceError> {
+    let card = MembershipCard::find_by_id(pool, card_id)
+        .await?
+        .ok_or(CardIssuanceError::CardNotFound)?;
+
+    if card.status != CardStatus::Active || card.is_expired() {
+        return Err(CardIssuanceError::ReofferNotAllowed(
+            "Only active cards can be added to a wallet".to_string(),
+        ));
+    }
+
+    // Same lock as issuance, so a re-offer cannot race a claim or another re-offer
+    let mut tx = pool.begin().await?;
+    MembershipCard::lock_issuance(&mut tx, card.issuer_id, card.member_id).await?;
+
+    // Re-read under the lock: a concurrent request may already have re-offered
+    let card = MembershipCard::find_by_id(&mut *tx, card_id)
+        .await?
+        .ok_or(CardIssuanceError::CardNotFound)?;
+
+    if card.is_wallet_pending() {
+        tracing::debug!(card_id = %card.id, "Wallet offer already being generated");
+        tx.rollback().await?;
+        return Ok(());
+    }
+
+    let (reason, replaced_cid) = match (&card.wallet_cid, lost_device) {
+        (None, _) => ("expired", None),
+        (Some(cid), true) => ("lost_device", Some(cid.as_str())),
+        (Some(_), false) => {
+            return Err(CardIssuanceError::ReofferNotAllowed(
+                "Credential already in a wallet; confirm the device was lost to re-issue it"
+                    .to_string(),
+            ));
+        }
+    };
+
+    if WalletOffer::count_recent_reoffers(&mut *tx, card.id).await? >= MAX_REOFFERS_PER_DAY {
+        return Err(CardIssuanceError::ReofferLimitReached);
+    }
+
+    WalletOffer::create(&mut *tx, card.id, reason, replaced_cid).await?;
+
+    if let Some(cid) = replaced_cid {
+        BackgroundTask::enqueue(
+            &mut *tx,
+            &TaskPayload::RevokeWalletCredential {
+                card_id: card.id,
+                cid: cid.to_string(),
+            },
+        )
+        .await?;
+    }
+
+    MembershipCard::clear_wallet(&mut *tx, card.id).await?;
+    let wallet_task = BackgroundTask::enqueue(
+        &mut *tx,
+        &TaskPayload::GenerateWalletQr { card_id: card.id },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(card_id = %card.id, reason, "Wallet re-offer requested");
+
+    task_queue::run_now(pool, config, wallet_task.id).await?;
+
+    Ok(())
 }
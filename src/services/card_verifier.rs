@@ -5,7 +5,9 @@ use uuid::Uuid;
 use crate::models::{
     card::{CardStatus, MembershipCard},
     issuer::CardIssuer,
+    suspension::CardSuspension,
 };
+use crate::services::presentation_token::{self, PresentationTokenError};
 
 #[derive(thiserror::Error, Debug)]
 pub enum VerificationError {
@@ -17,11 +19,9 @@ pub enum VerificationError {
 
     #[error("Invalid UUID format: {0}")]
     InvalidUuid(#[from] uuid::Error),
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct QrPayload {
-    pub card_id: Uuid,
+    #[error("Presentation token error: {0}")]
+    PresentationToken(PresentationTokenError),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +44,7 @@ pub enum VerificationResult {
     CardSuspended {
         card: MembershipCard,
         issuer: CardIssuer,
+        suspension: Option<CardSuspension>,
     },
     CardDeleted {
         card: MembershipCard,
@@ -80,43 +81,115 @@ impl VerificationResult {
             VerificationResult::InvalidPayload { .. } => None,
         }
     }
+
+    /// Returns a human-readable reason shown to organizers when the card is not valid
+    pub fn failure_reason(&self) -> Option<String> {
+        match self {
+            VerificationResult::Success { .. } => None,
+            VerificationResult::CardNotFound { .. } => Some("找不到此會員卡".to_string()),
+            VerificationResult::CardExpired { .. } => Some("會員卡已過期".to_string()),
+            VerificationResult::CardRevoked { .. } => Some("會員卡已撤銷".to_string()),
+            VerificationResult::CardSuspended { suspension, .. } => Some(match suspension {
+                Some(s) => match s.ends_at {
+                    Some(ends_at) => format!(
+                        "會員卡已停權：{}（至 {}）",
+                        s.reason,
+                        ends_at.format("%Y-%m-%d %H:%M UTC")
+                    ),
+                    None => format!("會員卡已停權：{}", s.reason),
+                },
+                None => "會員卡已停權".to_string(),
+            }),
+            VerificationResult::CardDeleted { .. } => Some("會員卡已刪除".to_string()),
+            VerificationResult::InvalidPayload { error } => {
+                Some(format!("無效的 QR Code：{}", error))
+            }
+        }
+    }
 }
 
-/// Verifies a QR code payload
+/// Explains to organizers why a scanned presentation token was rejected
+fn token_rejection(error: &PresentationTokenError) -> &'static str {
+    match error {
+        PresentationTokenError::Expired => "QR Code 已過期，請會員重新整理頁面",
+        PresentationTokenError::AlreadyUsed => "QR Code 已使用過，請會員重新整理頁面",
+        PresentationTokenError::Revoked => "此列印會員卡已失效，請會員重新列印",
+        PresentationTokenError::WrongIssuer => "本活動不接受此頻道的會員卡",
+        PresentationTokenError::WrongType => "不是 VPass 會員卡 QR Code",
+        _ => "簽章無效",
+    }
+}
+
+/// The VPass card named by a wallet presentation's `cardId` field
+pub fn wallet_card_id(member_info: Option<&serde_json::Value>) -> Option<Uuid> {
+    member_info
+        .and_then(|info| info.get("cardId"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| Uuid::parse_str(s).ok())
+}
+
+/// Result of a wallet presentation that names no VPass card
+///
+/// A credential issued under a schema without `cardId` cannot be checked against
+/// VPass (suspension, revocation, access rules), so it is rejected rather than
+/// admitted on the wallet's word alone.
+pub fn missing_wallet_card() -> VerificationResult {
+    VerificationResult::InvalidPayload {
+        error: "憑證未包含 VPass 會員卡 ID，請設定憑證欄位 cardId 後重新發卡".to_string(),
+    }
+}
+
+/// Verifies a presentation token scanned from a member's "show my card" page
 ///
 /// This function:
-/// 1. Parses the QR payload (JSON with card_id)
-/// 2. Looks up the card in the database
-/// 3. Checks the card status (active, expired, revoked, suspended)
-/// 4. Returns verification result
-#[tracing::instrument(skip(pool))]
-pub async fn verify_qr_payload(
+/// 1. Checks the token's signature (a key of one of `issuer_ids`), freshness and single use
+///    (once per `event_id` for a printed card's token)
+/// 2. Verifies the referenced card (see `verify_card`)
+#[tracing::instrument(skip(pool, token))]
+pub async fn verify_presentation_token(
     pool: &PgPool,
-    qr_payload: &str,
+    token: &str,
+    issuer_ids: &[Uuid],
+    event_id: Uuid,
 ) -> Result<VerificationResult, VerificationError> {
-    tracing::debug!(payload_len = qr_payload.len(), "Parsing QR payload");
+    tracing::debug!(token_len = token.len(), "Verifying presentation token");
 
-    // 1. Parse the payload
-    let payload: QrPayload = match serde_json::from_str(qr_payload) {
-        Ok(p) => p,
+    // 1. Check the token
+    let claims = match presentation_token::verify(pool, token, issuer_ids, event_id).await {
+        Ok(claims) => claims,
+        Err(
+            e @ (PresentationTokenError::DatabaseError(_) | PresentationTokenError::SigningKey(_)),
+        ) => return Err(VerificationError::PresentationToken(e)),
         Err(e) => {
-            tracing::warn!(error = %e, "Failed to parse QR payload");
+            tracing::warn!(error = %e, "Rejected presentation token");
             return Ok(VerificationResult::InvalidPayload {
-                error: format!("Invalid JSON: {}", e),
+                error: token_rejection(&e).to_string(),
             });
         }
     };
 
-    tracing::info!(card_id = %payload.card_id, "Parsed QR payload");
+    tracing::info!(card_id = %claims.card_id, "Verified presentation token");
 
-    // 2. Look up the card
-    let card = match MembershipCard::find_by_id(pool, payload.card_id).await? {
+    verify_card(pool, claims.card_id).await
+}
+
+/// Verifies a card by ID
+///
+/// This function:
+/// 1. Looks up the card in the database
+/// 2. Checks the card status (active, expired, revoked, suspended)
+/// 3. Returns verification result
+#[tracing::instrument(skip(pool))]
+pub async fn verify_card(
+    pool: &PgPool,
+    card_id: Uuid,
+) -> Result<VerificationResult, VerificationError> {
+    // 1. Look up the card
+    let card = match MembershipCard::find_by_id(pool, card_id).await? {
         Some(c) => c,
         None => {
-            tracing::warn!(card_id = %payload.card_id, "Card not found");
-            return Ok(VerificationResult::CardNotFound {
-                card_id: payload.card_id,
-            });
+            tracing::warn!(card_id = %card_id, "Card not found");
+            return Ok(VerificationResult::CardNotFound { card_id });
         }
     };
 
@@ -127,7 +200,7 @@ pub async fn verify_qr_payload(
         "Found card"
     );
 
-    // 3. Load the issuer
+    // 2. Load the issuer
     let issuer = CardIssuer::find_by_id(pool, card.issuer_id)
         .await?
         .ok_or_else(|| {
@@ -135,7 +208,7 @@ pub async fn verify_qr_payload(
             sqlx::Error::RowNotFound
         })?;
 
-    // 4. Check card status
+    // 3. Check card status
     let result = match card.status {
         CardStatus::Active => {
             // Check if expired
@@ -161,8 +234,13 @@ pub async fn verify_qr_payload(
             VerificationResult::CardExpired { card, issuer }
         }
         CardStatus::Suspended => {
+            let suspension = CardSuspension::find_open_for_card(pool, card.id).await?;
             tracing::info!(card_id = %card.id, "Card suspended");
-            VerificationResult::CardSuspended { card, issuer }
+            VerificationResult::CardSuspended {
+                card,
+                issuer,
+                suspension,
+            }
         }
         CardStatus::Deleted => {
             tracing::info!(card_id = %card.id, "Card deleted");
@@ -178,17 +256,49 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_qr_payload_parsing() {
+    fn test_token_rejection() {
+        assert!(token_rejection(&PresentationTokenError::Expired).contains("過期"));
+        assert!(token_rejection(&PresentationTokenError::AlreadyUsed).contains("使用過"));
+        assert!(token_rejection(&PresentationTokenError::Revoked).contains("重新列印"));
+        assert_eq!(
+            token_rejection(&PresentationTokenError::UnknownKey),
+            token_rejection(&PresentationTokenError::InvalidClaim("sub"))
+        );
+    }
+
+    #[test]
+    fn test_failure_reason() {
+        let not_found = VerificationResult::CardNotFound {
+            card_id: Uuid::new_v4(),
+        };
+        assert_eq!(not_found.result_type(), "card_not_found");
+        assert!(not_found.failure_reason().is_some());
+
+        let invalid = VerificationResult::InvalidPayload {
+            error: "bad".to_string(),
+        };
+        assert!(invalid.failure_reason().unwrap().contains("bad"));
+    }
+
+    #[test]
+    fn test_wallet_card_id() {
         let card_id = Uuid::new_v4();
-        let payload = format!(r#"{{"card_id":"{}"}}"#, card_id);
-        let parsed: QrPayload = serde_json::from_str(&payload).unwrap();
-        assert_eq!(parsed.card_id, card_id);
+        let info = serde_json::json!({ "name": "小明", "cardId": card_id.simple().to_string() });
+        assert_eq!(wallet_card_id(Some(&info)), Some(card_id));
+
+        // The default credential schema only discloses the member's name
+        let info = serde_json::json!({ "name": "小明" });
+        assert_eq!(wallet_card_id(Some(&info)), None);
+        let malformed = serde_json::json!({ "cardId": "not-a-card" });
+        assert_eq!(wallet_card_id(Some(&malformed)), None);
+        assert_eq!(wallet_card_id(None), None);
     }
 
     #[test]
-    fn test_invalid_payload() {
-        let payload = r#"{"invalid":"data"}"#;
-        let result: Result<QrPayload, _> = serde_json::from_str(payload);
-        assert!(result.is_err());
+    fn test_missing_wallet_card_is_not_admitted() {
+        let rejected = missing_wallet_card();
+        assert_eq!(rejected.result_type(), "invalid_payload");
+        assert_eq!(rejected.card_id(), None);
+        assert!(rejected.failure_reason().is_some());
     }
 }
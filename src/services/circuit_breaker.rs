@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Circuit breakers around Taiwan Digital Wallet issuer APIs, one per API URL, so
+/// a channel whose own account is down does not stop calls for other channels
+pub static WALLET_ISSUER_APIS: CircuitBreakers =
+    CircuitBreakers::new("wallet_issuer_api", 5, Duration::from_secs(60));
+
+/// Stops calling a failing upstream service for a while
+///
+/// After `failure_threshold` consecutive failures the breaker opens and calls are
+/// refused for `open_for`. Once that elapses, calls go through again (half-open):
+/// the first success closes the breaker, another failure re-opens it.
+///
+/// State is per process; each instance learns about an outage on its own.
+pub struct CircuitBreaker {
+    name: Cow<'static, str>,
+    failure_threshold: u32,
+    open_for: Duration,
+    state: Mutex<BreakerState>,
+}
+
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub const fn new(name: &'static str, failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            failure_threshold,
+            open_for,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                open_until: None,
+            }),
+        }
+    }
+
+    /// Returns how long calls are still refused, or None if calls may go through
+    pub fn retry_after(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .open_until
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Checks if calls are currently refused
+    pub fn is_open(&self) -> bool {
+        self.retry_after().is_some()
+    }
+
+    /// Records a successful call, closing the breaker
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.open_until.is_some() {
+            tracing::info!(breaker = %self.name, "Circuit breaker closed");
+        }
+        state.consecutive_failures = 0;
+        state.open_until = None;
+    }
+
+    /// Records a failed call, opening the breaker once the threshold is reached
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+
+        if state.consecutive_failures >= self.failure_threshold {
+            state.open_until = Some(Instant::now() + self.open_for);
+            tracing::warn!(
+                breaker = %self.name,
+                failures = state.consecutive_failures,
+                open_for_secs = self.open_for.as_secs(),
+                "Circuit breaker opened"
+            );
+        }
+    }
+}
+
+/// Circuit breakers with the same settings, one per upstream (e.g. per API URL)
+pub struct CircuitBreakers {
+    name: &'static str,
+    failure_threshold: u32,
+    open_for: Duration,
+    breakers: Mutex<BTreeMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakers {
+    pub const fn new(name: &'static str, failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold,
+            open_for,
+            breakers: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the breaker of an upstream, closed the first time it is asked for
+    pub fn get(&self, upstream: &str) -> Arc<CircuitBreaker> {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        breakers
+            .entry(upstream.to_string())
+            .or_insert_with(|| {
+                Arc::new(CircuitBreaker {
+                    name: Cow::Owned(format!("{}:{}", self.name, upstream)),
+                    ..CircuitBreaker::new(self.name, self.failure_threshold, self.open_for)
+                })
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new("test", 2, Duration::from_secs(60));
+        assert!(!breaker.is_open());
+
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        breaker.record_failure();
+        assert!(breaker.is_open());
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_half_open_after_cooldown() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::ZERO);
+
+        breaker.record_failure();
+        // Cooldown already elapsed: the next call is allowed through
+        assert!(!breaker.is_open());
+        assert!(breaker.retry_after().is_none());
+    }
+
+    #[test]
+    fn test_breakers_are_per_upstream() {
+        let breakers = CircuitBreakers::new("test", 1, Duration::from_secs(60));
+
+        breakers.get("https://broken.example").record_failure();
+        assert!(breakers.get("https://broken.example").is_open());
+        assert!(!breakers.get("https://working.example").is_open());
+    }
+}
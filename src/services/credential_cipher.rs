@@ -0,0 +1,140 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use secrecy::Secret;
+
+/// Prefix of stored ciphertexts, so the format can change without guessing
+const FORMAT_VERSION: &str = "v1";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum CipherError {
+    #[error("Encryption key must be 32 bytes, base64 encoded")]
+    InvalidKey,
+
+    #[error("Encrypted value is malformed")]
+    Malformed,
+
+    #[error("Encryption failed")]
+    EncryptionFailed,
+
+    #[error("Decryption failed (wrong key or tampered value)")]
+    DecryptionFailed,
+}
+
+/// Encrypts secrets stored in the database (e.g. per-issuer wallet API tokens)
+///
+/// Values are sealed with AES-256-GCM and stored as `v1:<base64(nonce || ciphertext)>`.
+/// Every value is bound to a context string naming where it is stored (table, column
+/// and row ID), so a ciphertext copied into another row or column fails to decrypt.
+pub struct CredentialCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl CredentialCipher {
+    /// Creates a cipher from a base64-encoded 32-byte key
+    pub fn from_base64_key(encoded_key: &str) -> Result<Self, CipherError> {
+        let key_bytes = STANDARD
+            .decode(encoded_key.trim())
+            .map_err(|_| CipherError::InvalidKey)?;
+        let key = UnboundKey::new(&AES_256_GCM, &key_bytes).map_err(|_| CipherError::InvalidKey)?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Encrypts `plaintext` for storage under `context`
+    pub fn encrypt(&self, context: &str, plaintext: &str) -> Result<String, CipherError> {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce_bytes)
+            .map_err(|_| CipherError::EncryptionFailed)?;
+
+        let mut sealed = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::from(context.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| CipherError::EncryptionFailed)?;
+
+        let mut payload = nonce_bytes.to_vec();
+        payload.extend_from_slice(&sealed);
+
+        Ok(format!("{}:{}", FORMAT_VERSION, STANDARD.encode(payload)))
+    }
+
+    /// Decrypts a value produced by [`CredentialCipher::encrypt`] with the same context
+    pub fn decrypt(&self, context: &str, stored: &str) -> Result<Secret<String>, CipherError> {
+        let encoded = stored
+            .strip_prefix(FORMAT_VERSION)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or(CipherError::Malformed)?;
+        let payload = STANDARD
+            .decode(encoded)
+            .map_err(|_| CipherError::Malformed)?;
+
+        if payload.len() < NONCE_LEN {
+            return Err(CipherError::Malformed);
+        }
+        let (nonce_bytes, sealed) = payload.split_at(NONCE_LEN);
+        let nonce =
+            Nonce::try_assume_unique_for_key(nonce_bytes).map_err(|_| CipherError::Malformed)?;
+
+        let mut sealed = sealed.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(context.as_bytes()), &mut sealed)
+            .map_err(|_| CipherError::DecryptionFailed)?;
+
+        String::from_utf8(plaintext.to_vec())
+            .map(Secret::new)
+            .map_err(|_| CipherError::Malformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secrecy::ExposeSecret;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+    #[test]
+    fn test_round_trip() {
+        let cipher = CredentialCipher::from_base64_key(KEY).unwrap();
+        let stored = cipher.encrypt("issuer:1", "token-value").unwrap();
+
+        assert!(stored.starts_with("v1:"));
+        assert!(!stored.contains("token-value"));
+        assert_eq!(
+            cipher.decrypt("issuer:1", &stored).unwrap().expose_secret(),
+            "token-value"
+        );
+    }
+
+    #[test]
+    fn test_context_is_bound() {
+        let cipher = CredentialCipher::from_base64_key(KEY).unwrap();
+        let stored = cipher.encrypt("issuer:1", "token-value").unwrap();
+
+        assert_eq!(
+            cipher.decrypt("issuer:2", &stored).unwrap_err(),
+            CipherError::DecryptionFailed
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(CredentialCipher::from_base64_key("c2hvcnQ=").is_err());
+
+        let cipher = CredentialCipher::from_base64_key(KEY).unwrap();
+        assert_eq!(
+            cipher.decrypt("issuer:1", "plaintext").unwrap_err(),
+            CipherError::Malformed
+        );
+    }
+}
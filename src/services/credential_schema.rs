@@ -0,0 +1,376 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+
+use crate::models::{
+    card::MembershipCard,
+    credential_field::{CredentialFieldMapping, CredentialFieldSource, IssuerCredentialField},
+    event::Event,
+    issuer::CardIssuer,
+    member::Member,
+};
+use crate::services::{loyalty::Loyalty, wallet_qr::WalletQrField};
+
+/// Most fields a credential template may map
+pub const MAX_CREDENTIAL_FIELDS: usize = 10;
+
+const MAX_ENAME_CHARS: usize = 50;
+const MAX_CONTENT_CHARS: usize = 100;
+
+/// Used when a member's display name has no characters the wallet accepts
+const FALLBACK_DISPLAY_NAME: &str = "Member";
+
+/// Used when a card's tier label has no characters the wallet accepts
+const FALLBACK_TIER_LABEL: &str = "Member";
+
+/// Used when a channel's name has no characters the wallet accepts
+const FALLBACK_CHANNEL_NAME: &str = "Channel";
+
+/// Dates are written in Taiwan time (no DST), matching the wallet's locale
+const WALLET_UTC_OFFSET_SECS: i32 = 8 * 3600;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+#[error("Credential field `{ename}`: {reason}")]
+pub struct CredentialFieldError {
+    pub ename: String,
+    pub reason: String,
+}
+
+impl CredentialFieldError {
+    fn new(ename: &str, reason: impl Into<String>) -> Self {
+        Self {
+            ename: ename.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// The card a credential is being issued for
+pub struct CredentialSubject<'a> {
+    pub card: &'a MembershipCard,
+    pub issuer: &'a CardIssuer,
+    pub member: &'a Member,
+    pub loyalty: &'a Loyalty,
+}
+
+/// Whether the Taiwan Digital Wallet accepts the character in field content:
+/// Chinese characters, English letters, digits and underscore
+pub fn is_wallet_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || c == '_'
+        || ('\u{4e00}'..='\u{9fff}').contains(&c) // CJK Unified Ideographs
+        || ('\u{3400}'..='\u{4dbf}').contains(&c) // CJK Extension A
+}
+
+/// Validates a credential template field name
+pub fn validate_ename(ename: &str) -> Result<(), CredentialFieldError> {
+    let starts_with_letter = ename
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic());
+    if !starts_with_letter {
+        return Err(CredentialFieldError::new(
+            ename,
+            "field name must start with an English letter",
+        ));
+    }
+
+    if ename.len() > MAX_ENAME_CHARS {
+        return Err(CredentialFieldError::new(
+            ename,
+            format!("field name must be at most {} characters", MAX_ENAME_CHARS),
+        ));
+    }
+
+    if !ename.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(CredentialFieldError::new(
+            ename,
+            "field name may only contain English letters, digits and underscore",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates field content against the wallet's character rules
+pub fn validate_content(ename: &str, content: &str) -> Result<(), CredentialFieldError> {
+    if content.is_empty() {
+        return Err(CredentialFieldError::new(ename, "value is empty"));
+    }
+
+    if content.chars().count() > MAX_CONTENT_CHARS {
+        return Err(CredentialFieldError::new(
+            ename,
+            format!("value must be at most {} characters", MAX_CONTENT_CHARS),
+        ));
+    }
+
+    if let Some(c) = content.chars().find(|c| !is_wallet_char(*c)) {
+        return Err(CredentialFieldError::new(
+            ename,
+            format!(
+                "character {:?} is not allowed (only Chinese, English, digits and underscore)",
+                c
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Drops characters the wallet rejects from free text such as names
+fn conform_text(text: &str) -> String {
+    text.chars().filter(|c| is_wallet_char(*c)).collect()
+}
+
+/// `conform_text`, or `fallback` if nothing is left, so an unusual name or label
+/// (e.g. only emoji) does not keep the card from being issued
+fn conform_text_or(text: &str, fallback: &str) -> String {
+    let text = conform_text(text);
+    if text.is_empty() {
+        fallback.to_string()
+    } else {
+        text
+    }
+}
+
+fn format_date(at: DateTime<Utc>) -> String {
+    let offset = FixedOffset::east_opt(WALLET_UTC_OFFSET_SECS).expect("valid UTC offset");
+    at.with_timezone(&offset).format("%Y%m%d").to_string()
+}
+
+/// Fields sent for issuers that have not configured a schema
+pub fn default_mappings() -> Vec<CredentialFieldMapping> {
+    vec![CredentialFieldMapping {
+        ename: "name".to_string(),
+        source: CredentialFieldSource::DisplayName,
+    }]
+}
+
+/// Turns an issuer's stored fields into mappings, falling back to the default schema
+pub fn mappings_for_issuer(rows: &[IssuerCredentialField]) -> Vec<CredentialFieldMapping> {
+    let mappings: Vec<_> = rows
+        .iter()
+        .filter_map(|row| {
+            row.field_source().map(|source| CredentialFieldMapping {
+                ename: row.ename.clone(),
+                source,
+            })
+        })
+        .collect();
+
+    if mappings.is_empty() {
+        default_mappings()
+    } else {
+        mappings
+    }
+}
+
+/// Parses a schema entered by an admin as (ename, source) pairs
+/// Rows with an empty field name are ignored.
+pub fn parse_mappings(
+    rows: &[(String, String)],
+) -> Result<Vec<CredentialFieldMapping>, CredentialFieldError> {
+    let mut mappings: Vec<CredentialFieldMapping> = Vec::new();
+
+    for (ename, source) in rows {
+        let ename = ename.trim();
+        if ename.is_empty() {
+            continue;
+        }
+
+        validate_ename(ename)?;
+
+        let source = CredentialFieldSource::parse(source.trim())
+            .ok_or_else(|| CredentialFieldError::new(ename, "unknown source"))?;
+
+        if mappings.iter().any(|m| m.ename == ename) {
+            return Err(CredentialFieldError::new(ename, "field name is used twice"));
+        }
+
+        mappings.push(CredentialFieldMapping {
+            ename: ename.to_string(),
+            source,
+        });
+    }
+
+    if mappings.len() > MAX_CREDENTIAL_FIELDS {
+        return Err(CredentialFieldError::new(
+            &mappings[MAX_CREDENTIAL_FIELDS].ename,
+            format!("at most {} fields are allowed", MAX_CREDENTIAL_FIELDS),
+        ));
+    }
+
+    Ok(mappings)
+}
+
+/// Resolves a single field's content for a card
+fn resolve(
+    mapping: &CredentialFieldMapping,
+    subject: &CredentialSubject<'_>,
+) -> Result<String, CredentialFieldError> {
+    let content = match mapping.source {
+        CredentialFieldSource::DisplayName => {
+            conform_text_or(&subject.member.default_display_name, FALLBACK_DISPLAY_NAME)
+        }
+        CredentialFieldSource::TierLabel => {
+            conform_text_or(&subject.card.membership_level_label, FALLBACK_TIER_LABEL)
+        }
+        CredentialFieldSource::ChannelName => {
+            conform_text_or(&subject.issuer.channel_name, FALLBACK_CHANNEL_NAME)
+        }
+        CredentialFieldSource::MemberSince => format_date(subject.card.membership_confirmed_at),
+        CredentialFieldSource::CardId => subject.card.id.simple().to_string(),
+        CredentialFieldSource::ExpiresAt => subject
+            .card
+            .expires_at
+            .map(format_date)
+            .ok_or_else(|| CredentialFieldError::new(&mapping.ename, "card has no expiry"))?,
+        CredentialFieldSource::TenureMonths => subject.loyalty.tenure_months.to_string(),
+        CredentialFieldSource::TenureSince => format_date(subject.loyalty.member_since),
+        CredentialFieldSource::EventsAttended => subject.loyalty.events_attended.to_string(),
+    };
+
+    validate_content(&mapping.ename, &content)?;
+
+    Ok(content)
+}
+
+/// Builds the credential fields sent to the wallet for a card
+pub fn build_wallet_fields(
+    mappings: &[CredentialFieldMapping],
+    subject: &CredentialSubject<'_>,
+) -> Result<Vec<WalletQrField>, CredentialFieldError> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            Ok(WalletQrField {
+                ename: mapping.ename.clone(),
+                content: resolve(mapping, subject)?,
+            })
+        })
+        .collect()
+}
+
+/// Builds the fields of an attendance credential: the event's name, its local
+/// date and the issuer that held it
+///
+/// Attendance credential templates must define these three field names.
+pub fn build_attendance_fields(
+    event: &Event,
+    issuer: &CardIssuer,
+) -> Result<Vec<WalletQrField>, CredentialFieldError> {
+    attendance_fields(&event.event_name, event.event_date, &issuer.channel_name)
+}
+
+fn attendance_fields(
+    event_name: &str,
+    event_date: NaiveDate,
+    issuer_name: &str,
+) -> Result<Vec<WalletQrField>, CredentialFieldError> {
+    [
+        ("eventName", conform_text(event_name)),
+        ("eventDate", event_date.format("%Y%m%d").to_string()),
+        ("issuerName", conform_text(issuer_name)),
+    ]
+    .into_iter()
+    .map(|(ename, content)| {
+        validate_content(ename, &content)?;
+        Ok(WalletQrField {
+            ename: ename.to_string(),
+            content,
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_validate_content() {
+        assert!(validate_content("name", "星詠_Hoshi123").is_ok());
+        assert!(validate_content("name", "").is_err());
+        assert!(validate_content("name", "with space").is_err());
+        assert!(validate_content("name", "emoji🎉").is_err());
+    }
+
+    #[test]
+    fn test_validate_ename() {
+        assert!(validate_ename("member_since").is_ok());
+        assert!(validate_ename("1st").is_err());
+        assert!(validate_ename("tier-label").is_err());
+    }
+
+    #[test]
+    fn test_conform_text() {
+        assert_eq!(conform_text("Tom Smith ✨"), "TomSmith");
+        assert_eq!(conform_text("會員 Lv.2"), "會員Lv2");
+    }
+
+    #[test]
+    fn test_conform_text_or_falls_back() {
+        assert_eq!(conform_text_or("⭐⭐⭐", FALLBACK_TIER_LABEL), "Member");
+        assert_eq!(conform_text_or("🌙 ✨", FALLBACK_CHANNEL_NAME), "Channel");
+        assert_eq!(conform_text_or("會員 Lv.2", FALLBACK_TIER_LABEL), "會員Lv2");
+    }
+
+    #[test]
+    fn test_format_date_uses_taiwan_time() {
+        let at = Utc.with_ymd_and_hms(2025, 12, 31, 17, 0, 0).unwrap();
+        assert_eq!(format_date(at), "20260101");
+    }
+
+    #[test]
+    fn test_parse_mappings() {
+        let rows = vec![
+            ("name".to_string(), "display_name".to_string()),
+            ("".to_string(), "tier_label".to_string()),
+            ("tier".to_string(), "tier_label".to_string()),
+        ];
+        let mappings = parse_mappings(&rows).unwrap();
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[1].source, CredentialFieldSource::TierLabel);
+
+        let duplicate = vec![
+            ("name".to_string(), "display_name".to_string()),
+            ("name".to_string(), "tier_label".to_string()),
+        ];
+        assert!(parse_mappings(&duplicate).is_err());
+
+        let unknown = vec![("name".to_string(), "email".to_string())];
+        assert!(parse_mappings(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_attendance_fields() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 9).unwrap();
+
+        let fields = attendance_fields("冬季見面會 2026!", date, "星詠 Hoshi ✨").unwrap();
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|f| (f.ename.as_str(), f.content.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("eventName", "冬季見面會2026"),
+                ("eventDate", "20260109"),
+                ("issuerName", "星詠Hoshi"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_attendance_fields_reject_unusable_values() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 9).unwrap();
+
+        // Nothing left after dropping the characters the wallet rejects
+        let err = attendance_fields("🎉🎉", date, "星詠").unwrap_err();
+        assert_eq!(err.ename, "eventName");
+
+        let long_name = "長".repeat(MAX_CONTENT_CHARS + 1);
+        let err = attendance_fields("見面會", date, &long_name).unwrap_err();
+        assert_eq!(err.ename, "issuerName");
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::services::jose::{DecodedJwt, JoseError, Jwk};
+use crate::services::wallet_accounts::{AccountScope, WalletApiAccount};
+
+/// Allowed clock difference when checking `exp` and `nbf`
+const CLOCK_SKEW_SECS: i64 = 60;
+
+/// How long a fetched key set is reused
+const KEY_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// Minimum time between refetches triggered by an unknown `kid` (key rotation)
+const KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum CredentialVerifyError {
+    #[error("Cannot resolve the keys of issuer {0}; set WALLET_ISSUER_JWKS_URL")]
+    UnresolvableIssuer(String),
+
+    #[error("Failed to load wallet issuer keys: {0}")]
+    KeySet(String),
+
+    #[error("Credential signed with an unknown key: {0}")]
+    UnknownKey(String),
+
+    #[error(transparent)]
+    Jose(#[from] JoseError),
+
+    #[error("Credential issued by {0}, not the wallet account's issuer")]
+    WrongIssuer(String),
+
+    #[error("Credential has expired")]
+    Expired,
+
+    #[error("Credential is not valid yet")]
+    NotYetValid,
+
+    #[error("Credential is not of this issuer's credential type")]
+    WrongCredentialType,
+
+    #[error("Credential has no valid {0}")]
+    MissingClaim(&'static str),
+}
+
+/// Where the wallet issuer's public keys come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum KeySource {
+    /// A JWKS or DID document given in the configuration
+    Local(String),
+    /// A JWKS, DID document or JWT VC issuer metadata URL, fetched and cached
+    Remote(String),
+    /// Published by the issuer itself (`did:web` or HTTPS issuer identifier)
+    Issuer,
+}
+
+/// Verifies credential JWTs returned by the Taiwan Digital Wallet issuer API
+#[derive(Debug, Clone)]
+pub struct WalletCredentialVerifier {
+    issuer: String,
+    keys: KeySource,
+}
+
+impl WalletCredentialVerifier {
+    /// Verifier for the credentials an issuer API account issues; None if the
+    /// account's credential issuer is not configured
+    ///
+    /// The key set configured in the environment belongs to the global account;
+    /// an issuer's own account publishes its keys under its issuer identifier.
+    pub fn for_account(config: &Config, account: &WalletApiAccount) -> Option<Self> {
+        let issuer = account.credential_issuer.clone()?;
+
+        let keys = match (
+            account.scope,
+            &config.wallet_issuer_jwks,
+            &config.wallet_issuer_jwks_url,
+        ) {
+            (AccountScope::Global, Some(local), _) => KeySource::Local(local.clone()),
+            (AccountScope::Global, None, Some(url)) => KeySource::Remote(url.clone()),
+            _ => KeySource::Issuer,
+        };
+
+        Some(Self { issuer, keys })
+    }
+
+    /// Verifies a credential and returns its credential ID
+    ///
+    /// Checks the signature against the issuer's keys, `iss`, `exp`/`nbf`, and that
+    /// the credential is of type `vc_uid`. The credential may be an SD-JWT.
+    pub async fn verify(
+        &self,
+        credential: &str,
+        vc_uid: &str,
+    ) -> Result<String, CredentialVerifyError> {
+        let jwt = DecodedJwt::decode(credential.split('~').next().unwrap_or_default())?;
+        let now = chrono::Utc::now().timestamp();
+
+        let url = match &self.keys {
+            KeySource::Local(document) => {
+                let keys = parse_key_set(&parse_json(document)?);
+                return check_credential(&jwt, &keys, &self.issuer, vc_uid, now);
+            }
+            KeySource::Remote(url) => url.clone(),
+            KeySource::Issuer => key_set_url(&self.issuer)?,
+        };
+
+        let keys = cached_key_set(&url, false).await?;
+        match check_credential(&jwt, &keys, &self.issuer, vc_uid, now) {
+            // The issuer may have rotated its keys since they were cached
+            Err(CredentialVerifyError::UnknownKey(kid)) => {
+                tracing::info!(kid = %kid, url = %url, "Unknown wallet issuer key, refetching key set");
+                let keys = cached_key_set(&url, true).await?;
+                check_credential(&jwt, &keys, &self.issuer, vc_uid, now)
+            }
+            result => result,
+        }
+    }
+}
+
+fn parse_json(document: &str) -> Result<Value, CredentialVerifyError> {
+    serde_json::from_str(document)
+        .map_err(|e| CredentialVerifyError::KeySet(format!("invalid JSON: {}", e)))
+}
+
+/// Where the keys of an issuer identifier are published
+///
+/// `did:web` identifiers resolve to their DID document; HTTPS identifiers to their
+/// JWT VC issuer metadata. Other DID methods need an explicit key set URL.
+fn key_set_url(issuer: &str) -> Result<String, CredentialVerifyError> {
+    if let Some(did) = issuer.strip_prefix("did:web:") {
+        let mut segments = did.split(':').map(|s| s.replace("%3A", ":"));
+        let host = segments.next().unwrap_or_default();
+        let path: Vec<String> = segments.collect();
+
+        return Ok(if path.is_empty() {
+            format!("https://{}/.well-known/did.json", host)
+        } else {
+            format!("https://{}/{}/did.json", host, path.join("/"))
+        });
+    }
+
+    if issuer.starts_with("https://") {
+        return Ok(format!(
+            "{}/.well-known/jwt-vc-issuer",
+            issuer.trim_end_matches('/')
+        ));
+    }
+
+    Err(CredentialVerifyError::UnresolvableIssuer(
+        issuer.to_string(),
+    ))
+}
+
+/// Extracts the P-256 keys of a JWKS, DID document or JWT VC issuer metadata
+///
+/// Keys from a DID document get their verification method ID as `kid`. Keys of
+/// other types are skipped, since only ES256 credentials are accepted.
+fn parse_key_set(document: &Value) -> Vec<Jwk> {
+    let jwks = document.get("jwks").unwrap_or(document);
+
+    let mut keys: Vec<Jwk> = jwks
+        .get("keys")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|key| serde_json::from_value(key.clone()).ok())
+        .collect();
+
+    let methods = document
+        .get("verificationMethod")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    for method in methods {
+        let Some(mut key) = method
+            .get("publicKeyJwk")
+            .and_then(|jwk| serde_json::from_value::<Jwk>(jwk.clone()).ok())
+        else {
+            continue;
+        };
+        if let Some(id) = method.get("id").and_then(Value::as_str) {
+            key.kid = Some(id.to_string());
+        }
+        keys.push(key);
+    }
+
+    keys.retain(|key| key.kty == "EC" && key.crv == "P-256");
+    keys
+}
+
+struct CachedKeySet {
+    keys: Vec<Jwk>,
+    fetched_at: Instant,
+}
+
+fn key_cache() -> &'static Mutex<HashMap<String, CachedKeySet>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CachedKeySet>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns the key set at `url`, fetching it if not cached (or if `refresh`,
+/// unless it was fetched moments ago)
+async fn cached_key_set(url: &str, refresh: bool) -> Result<Vec<Jwk>, CredentialVerifyError> {
+    {
+        let cache = key_cache().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = cache.get(url) {
+            let age = cached.fetched_at.elapsed();
+            let max_age = if refresh {
+                KEY_REFRESH_INTERVAL
+            } else {
+                KEY_CACHE_TTL
+            };
+            if age < max_age {
+                return Ok(cached.keys.clone());
+            }
+        }
+    }
+
+    let keys = fetch_key_set(url).await?;
+
+    key_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(
+            url.to_string(),
+            CachedKeySet {
+                keys: keys.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+
+    Ok(keys)
+}
+
+async fn fetch_key_set(url: &str) -> Result<Vec<Jwk>, CredentialVerifyError> {
+    let response = Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| CredentialVerifyError::KeySet(format!("{}: {}", url, e)))?;
+
+    let document: Value = response
+        .json()
+        .await
+        .map_err(|e| CredentialVerifyError::KeySet(format!("{}: {}", url, e)))?;
+
+    let keys = parse_key_set(&document);
+    if keys.is_empty() {
+        return Err(CredentialVerifyError::KeySet(format!(
+            "{}: no P-256 keys published",
+            url
+        )));
+    }
+
+    tracing::info!(url = %url, keys = keys.len(), "Fetched wallet issuer keys");
+    Ok(keys)
+}
+
+/// Whether `key` is the one a JWT header's `kid` names
+///
+/// DID-based `kid`s may be given in full (`did:web:x#key-1`) or as the fragment alone.
+fn key_matches(key: &Jwk, kid: &str) -> bool {
+    let Some(key_id) = key.kid.as_deref() else {
+        return false;
+    };
+    let fragment = |id: &str| {
+        id.rsplit_once('#')
+            .map(|(_, f)| f)
+            .unwrap_or(id)
+            .to_string()
+    };
+
+    key_id == kid || fragment(key_id) == fragment(kid)
+}
+
+/// Checks a decoded credential against the issuer keys and returns its credential ID
+fn check_credential(
+    jwt: &DecodedJwt,
+    keys: &[Jwk],
+    issuer: &str,
+    vc_uid: &str,
+    now: i64,
+) -> Result<String, CredentialVerifyError> {
+    let candidates: Vec<&Jwk> = match jwt.header_str("kid") {
+        Some(kid) => keys.iter().filter(|key| key_matches(key, kid)).collect(),
+        None => keys.iter().collect(),
+    };
+    if candidates.is_empty() {
+        return Err(CredentialVerifyError::UnknownKey(
+            jwt.header_str("kid").unwrap_or("(none)").to_string(),
+        ));
+    }
+    if !candidates.iter().any(|key| jwt.verify(key).is_ok()) {
+        // Report why the (first) key failed: bad signature or unsupported alg
+        jwt.verify(candidates[0])?;
+    }
+
+    match jwt.claim_str("iss") {
+        Some(iss) if iss == issuer => {}
+        Some(iss) => return Err(CredentialVerifyError::WrongIssuer(iss.to_string())),
+        None => return Err(CredentialVerifyError::MissingClaim("iss")),
+    }
+
+    if let Some(exp) = jwt.claims.get("exp") {
+        let exp = exp
+            .as_i64()
+            .ok_or(CredentialVerifyError::MissingClaim("exp"))?;
+        if now > exp + CLOCK_SKEW_SECS {
+            return Err(CredentialVerifyError::Expired);
+        }
+    }
+    if let Some(nbf) = jwt.claims.get("nbf") {
+        let nbf = nbf
+            .as_i64()
+            .ok_or(CredentialVerifyError::MissingClaim("nbf"))?;
+        if now + CLOCK_SKEW_SECS < nbf {
+            return Err(CredentialVerifyError::NotYetValid);
+        }
+    }
+
+    if !is_credential_type(&jwt.claims, vc_uid) {
+        return Err(CredentialVerifyError::WrongCredentialType);
+    }
+
+    cid_from_jti(
+        jwt.claim_str("jti")
+            .ok_or(CredentialVerifyError::MissingClaim("jti"))?,
+    )
+}
+
+/// Checks that an issuer identifier entered by an admin can be resolved to its keys
+pub fn validate_issuer_identifier(issuer: &str) -> Result<(), String> {
+    key_set_url(issuer)
+        .map(|_| ())
+        .map_err(|_| format!("Unsupported credential issuer identifier: {}", issuer))
+}
+
+/// Whether a credential is of the type `vc_uid`: its SD-JWT `vct`, or one of its
+/// W3C `vc.type`s, is `vc_uid` (or a URL ending in it)
+fn is_credential_type(claims: &Value, vc_uid: &str) -> bool {
+    let matches = |value: &str| value == vc_uid || value.rsplit('/').next() == Some(vc_uid);
+
+    let vct = claims.get("vct").and_then(Value::as_str);
+    let vc_types = claims
+        .pointer("/vc/type")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str);
+
+    vct.into_iter().chain(vc_types).any(matches)
+}
+
+/// Extracts the CID from a credential's `jti`
+///
+/// Example jti: "https://issuer-vc.wallet.gov.tw/api/credential/a16187e9-755e-48ca-a9c0-622f76fe1360"
+/// The CID would be: "a16187e9-755e-48ca-a9c0-622f76fe1360"
+fn cid_from_jti(jti: &str) -> Result<String, CredentialVerifyError> {
+    match jti.rsplit('/').next() {
+        Some(cid) if !cid.is_empty() => Ok(cid.to_string()),
+        _ => Err(CredentialVerifyError::MissingClaim("jti")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::jose::Es256Key;
+    use serde_json::json;
+
+    const ISSUER: &str = "did:web:issuer-vc.wallet.gov.tw";
+    const NOW: i64 = 1_765_000_000;
+
+    fn new_key() -> Es256Key {
+        Es256Key::from_pkcs8(&Es256Key::generate_pkcs8().unwrap()).unwrap()
+    }
+
+    fn key_set(key: &Es256Key, kid: &str) -> Vec<Jwk> {
+        let mut jwk = key.public_jwk();
+        jwk.kid = Some(kid.to_string());
+        vec![jwk]
+    }
+
+    fn credential(key: &Es256Key, claims: Value) -> DecodedJwt {
+        let mut payload = json!({
+            "iss": ISSUER,
+            "vct": "https://issuer-vc.wallet.gov.tw/vct/00000000_vpass_member",
+            "jti": "https://issuer-vc.wallet.gov.tw/api/credential/a16187e9-755e-48ca-a9c0-622f76fe1360",
+            "iat": NOW - 10,
+            "exp": NOW + 86_400,
+        });
+        for (name, value) in claims.as_object().unwrap() {
+            payload[name] = value.clone();
+        }
+
+        let jwt = key
+            .sign(json!({"typ": "vc+sd-jwt", "kid": "#key-1"}), &payload)
+            .unwrap();
+        DecodedJwt::decode(&jwt).unwrap()
+    }
+
+    fn check(jwt: &DecodedJwt, keys: &[Jwk]) -> Result<String, CredentialVerifyError> {
+        check_credential(jwt, keys, ISSUER, "00000000_vpass_member", NOW)
+    }
+
+    #[test]
+    fn test_valid_credential_returns_cid() {
+        let key = new_key();
+        let keys = key_set(&key, &format!("{}#key-1", ISSUER));
+
+        assert_eq!(
+            check(&credential(&key, json!({})), &keys).unwrap(),
+            "a16187e9-755e-48ca-a9c0-622f76fe1360"
+        );
+    }
+
+    #[test]
+    fn test_rejects_untrusted_credentials() {
+        let key = new_key();
+        let keys = key_set(&key, "key-1");
+
+        // Signed by a key that is not the issuer's
+        let forged = credential(&new_key(), json!({}));
+        assert!(matches!(
+            check(&forged, &keys),
+            Err(CredentialVerifyError::Jose(JoseError::BadSignature))
+        ));
+        assert!(matches!(
+            check(&credential(&key, json!({})), &key_set(&key, "key-2")),
+            Err(CredentialVerifyError::UnknownKey(_))
+        ));
+
+        let cases = [
+            json!({"iss": "did:web:evil.example"}),
+            json!({"exp": NOW - 3600}),
+            json!({"nbf": NOW + 3600}),
+            json!({"vct": "https://issuer-vc.wallet.gov.tw/vct/00000000_other"}),
+        ];
+        for claims in cases {
+            assert!(
+                check(&credential(&key, claims.clone()), &keys).is_err(),
+                "{}",
+                claims
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_key_set() {
+        let key = new_key().public_jwk();
+
+        let jwks = json!({"keys": [key, {"kty": "RSA", "n": "AQAB", "e": "AQAB"}]});
+        assert_eq!(parse_key_set(&jwks), vec![key.clone()]);
+
+        let metadata = json!({"issuer": "https://issuer.example", "jwks": {"keys": [key]}});
+        assert_eq!(parse_key_set(&metadata).len(), 1);
+
+        let did_document = json!({
+            "id": ISSUER,
+            "verificationMethod": [{
+                "id": format!("{}#key-1", ISSUER),
+                "type": "JsonWebKey2020",
+                "publicKeyJwk": key,
+            }],
+        });
+        let keys = parse_key_set(&did_document);
+        assert_eq!(keys.len(), 1);
+        assert!(key_matches(&keys[0], "#key-1"));
+    }
+
+    #[test]
+    fn test_key_set_url() {
+        assert_eq!(
+            key_set_url(ISSUER).unwrap(),
+            "https://issuer-vc.wallet.gov.tw/.well-known/did.json"
+        );
+        assert_eq!(
+            key_set_url("did:web:example.com%3A8443:issuers:vpass").unwrap(),
+            "https://example.com:8443/issuers/vpass/did.json"
+        );
+        assert_eq!(
+            key_set_url("https://issuer.example/").unwrap(),
+            "https://issuer.example/.well-known/jwt-vc-issuer"
+        );
+        assert!(key_set_url("did:key:z6Mk").is_err());
+    }
+
+    #[test]
+    fn test_validate_issuer_identifier() {
+        assert!(validate_issuer_identifier(ISSUER).is_ok());
+        assert!(validate_issuer_identifier("https://issuer.example").is_ok());
+        assert!(validate_issuer_identifier("did:key:z6Mk").is_err());
+    }
+}
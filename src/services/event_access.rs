@@ -0,0 +1,285 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    card::MembershipCard,
+    event::Event,
+    event_access_rule::{AccessRuleData, EventAccessRule},
+    issuer::CardIssuer,
+    membership_tier::MembershipTier,
+};
+
+/// Whether an event accepts a valid card
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessDecision {
+    Allowed,
+    /// The card's channel is not on the event's allowlist
+    IssuerNotAccepted,
+    /// The card's tier ranks below the lowest tier the event accepts for its channel
+    TierTooLow {
+        required: String,
+        actual: String,
+    },
+}
+
+impl AccessDecision {
+    /// `verification_result` recorded for a refused card
+    pub fn result_type(&self) -> Option<&'static str> {
+        match self {
+            AccessDecision::Allowed => None,
+            AccessDecision::IssuerNotAccepted => Some("issuer_not_accepted"),
+            AccessDecision::TierTooLow { .. } => Some("tier_too_low"),
+        }
+    }
+
+    /// Explains to gate staff why the card is refused
+    pub fn failure_reason(&self) -> Option<String> {
+        match self {
+            AccessDecision::Allowed => None,
+            AccessDecision::IssuerNotAccepted => Some("本活動不接受此頻道的會員卡".to_string()),
+            AccessDecision::TierTooLow { required, actual } => Some(format!(
+                "會員等級不足：需要「{}」以上（此卡為「{}」）",
+                required, actual
+            )),
+        }
+    }
+}
+
+/// The issuers an event accepts and their tiers
+#[derive(Debug, Clone)]
+pub struct EventAccess {
+    event_issuer_id: Uuid,
+    rules: Vec<EventAccessRule>,
+    tiers: Vec<MembershipTier>,
+}
+
+impl EventAccess {
+    /// Loads an event's access rules with the tiers of the issuers they name
+    pub async fn load(pool: &PgPool, event: &Event) -> Result<Self, sqlx::Error> {
+        let rules = EventAccessRule::list_by_event(pool, event.id).await?;
+        let issuer_ids: Vec<Uuid> = rules.iter().map(|rule| rule.issuer_id).collect();
+        let tiers = if rules.iter().any(|rule| rule.min_tier_label.is_some()) {
+            MembershipTier::list_by_issuers(pool, &issuer_ids).await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            event_issuer_id: event.issuer_id,
+            rules,
+            tiers,
+        })
+    }
+
+    /// Issuers whose cards the event accepts; without rules, the event's own issuer
+    pub fn accepted_issuers(&self) -> Vec<Uuid> {
+        if self.rules.is_empty() {
+            vec![self.event_issuer_id]
+        } else {
+            self.rules.iter().map(|rule| rule.issuer_id).collect()
+        }
+    }
+
+    /// Lowest tier accepted for an issuer's cards; None accepts any tier
+    pub fn min_tier(&self, issuer_id: Uuid) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.issuer_id == issuer_id)
+            .and_then(|rule| rule.min_tier_label.as_deref())
+    }
+
+    /// Tier labels of an issuer the event accepts; None accepts any tier
+    pub fn accepted_tiers(&self, issuer_id: Uuid) -> Option<Vec<String>> {
+        let min_tier = self.min_tier(issuer_id)?;
+        let tiers = self.issuer_tiers(issuer_id);
+
+        Some(
+            tiers
+                .iter()
+                .filter(|tier| meets_min_tier(&tiers, min_tier, &tier.label))
+                .map(|tier| tier.label.clone())
+                .chain((!tiers.iter().any(|t| t.label == min_tier)).then(|| min_tier.to_string()))
+                .collect(),
+        )
+    }
+
+    fn issuer_tiers(&self, issuer_id: Uuid) -> Vec<MembershipTier> {
+        self.tiers
+            .iter()
+            .filter(|tier| tier.issuer_id == issuer_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Checks a valid card against the event's issuer allowlist and minimum tier
+    pub fn check(&self, card: &MembershipCard) -> AccessDecision {
+        if !self.accepted_issuers().contains(&card.issuer_id) {
+            return AccessDecision::IssuerNotAccepted;
+        }
+
+        match self.min_tier(card.issuer_id) {
+            Some(min_tier)
+                if !meets_min_tier(
+                    &self.issuer_tiers(card.issuer_id),
+                    min_tier,
+                    &card.membership_level_label,
+                ) =>
+            {
+                AccessDecision::TierTooLow {
+                    required: min_tier.to_string(),
+                    actual: card.membership_level_label.clone(),
+                }
+            }
+            _ => AccessDecision::Allowed,
+        }
+    }
+}
+
+/// Whether a card tier ranks at or above `min_tier` among an issuer's tiers
+///
+/// A tier the issuer has not ranked only meets a minimum with the same label.
+pub fn meets_min_tier(tiers: &[MembershipTier], min_tier: &str, label: &str) -> bool {
+    if label == min_tier {
+        return true;
+    }
+
+    let rank = |label: &str| tiers.iter().find(|t| t.label == label).map(|t| t.rank);
+    match (rank(label), rank(min_tier)) {
+        (Some(rank), Some(min_rank)) => rank >= min_rank,
+        _ => false,
+    }
+}
+
+/// Validates access rules submitted for an event: each issuer exists and once,
+/// and a minimum tier is one of the issuer's ranked tiers
+pub async fn validate_rules(
+    pool: &PgPool,
+    rules: Vec<AccessRuleData>,
+) -> Result<Vec<AccessRuleData>, AccessRuleError> {
+    let mut validated: Vec<AccessRuleData> = Vec::with_capacity(rules.len());
+
+    for rule in rules {
+        if validated.iter().any(|r| r.issuer_id == rule.issuer_id) {
+            return Err(AccessRuleError::Invalid(
+                "Each issuer can be listed once".to_string(),
+            ));
+        }
+        if CardIssuer::find_by_id(pool, rule.issuer_id)
+            .await?
+            .is_none()
+        {
+            return Err(AccessRuleError::Invalid(format!(
+                "Issuer {} not found",
+                rule.issuer_id
+            )));
+        }
+
+        let min_tier = rule
+            .min_tier
+            .map(|tier| tier.trim().to_string())
+            .filter(|tier| !tier.is_empty());
+        if let Some(tier) = &min_tier {
+            let tiers = MembershipTier::list_by_issuer(pool, rule.issuer_id).await?;
+            if !tiers.iter().any(|t| &t.label == tier) {
+                return Err(AccessRuleError::Invalid(format!(
+                    "Tier \"{}\" is not a tier of issuer {}",
+                    tier, rule.issuer_id
+                )));
+            }
+        }
+
+        validated.push(AccessRuleData {
+            issuer_id: rule.issuer_id,
+            min_tier,
+        });
+    }
+
+    Ok(validated)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AccessRuleError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("{0}")]
+    Invalid(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn tiers(issuer_id: Uuid, labels: &[&str]) -> Vec<MembershipTier> {
+        labels
+            .iter()
+            .enumerate()
+            .map(|(rank, label)| MembershipTier {
+                id: Uuid::new_v4(),
+                issuer_id,
+                label: label.to_string(),
+                rank: rank as i32,
+                created_at: Utc::now(),
+            })
+            .collect()
+    }
+
+    fn rule(event_id: Uuid, issuer_id: Uuid, min_tier: Option<&str>) -> EventAccessRule {
+        EventAccessRule {
+            event_id,
+            issuer_id,
+            min_tier_label: min_tier.map(str::to_string),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_meets_min_tier() {
+        let tiers = tiers(Uuid::new_v4(), &["一般", "白銀", "黃金"]);
+
+        assert!(meets_min_tier(&tiers, "白銀", "黃金"));
+        assert!(meets_min_tier(&tiers, "白銀", "白銀"));
+        assert!(!meets_min_tier(&tiers, "白銀", "一般"));
+        // Unranked labels only match themselves
+        assert!(!meets_min_tier(&tiers, "白銀", "Member"));
+        assert!(meets_min_tier(&[], "Member", "Member"));
+    }
+
+    #[test]
+    fn test_accepted_issuers_default_to_event_issuer() {
+        let (event_id, issuer_id, partner_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let access = EventAccess {
+            event_issuer_id: issuer_id,
+            rules: Vec::new(),
+            tiers: Vec::new(),
+        };
+        assert_eq!(access.accepted_issuers(), vec![issuer_id]);
+
+        let access = EventAccess {
+            event_issuer_id: issuer_id,
+            rules: vec![
+                rule(event_id, issuer_id, None),
+                rule(event_id, partner_id, Some("黃金")),
+            ],
+            tiers: tiers(partner_id, &["一般", "黃金", "鑽石"]),
+        };
+        assert_eq!(access.accepted_issuers(), vec![issuer_id, partner_id]);
+        assert_eq!(access.min_tier(issuer_id), None);
+        assert_eq!(
+            access.accepted_tiers(partner_id),
+            Some(vec!["黃金".to_string(), "鑽石".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_tier_too_low_reason() {
+        let decision = AccessDecision::TierTooLow {
+            required: "黃金".to_string(),
+            actual: "一般".to_string(),
+        };
+        assert_eq!(decision.result_type(), Some("tier_too_low"));
+        assert!(decision.failure_reason().unwrap().contains("黃金"));
+        assert_eq!(AccessDecision::Allowed.failure_reason(), None);
+    }
+}
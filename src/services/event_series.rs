@@ -0,0 +1,171 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    event::Event,
+    event_access_rule::{AccessRuleData, EventAccessRule, SeriesAccessRule},
+    event_series::EventSeries,
+};
+use crate::services::reservations;
+
+/// Occurrences are materialized this many days ahead of the series' local today
+pub const MATERIALIZE_AHEAD_DAYS: i64 = 56;
+
+/// Local dates to materialize occurrences for as of `today`: after the last
+/// materialized date (never before today) through the look-ahead horizon
+fn materialize_window(
+    starts_on: NaiveDate,
+    materialized_until: Option<NaiveDate>,
+    today: NaiveDate,
+) -> (NaiveDate, NaiveDate) {
+    let next = materialized_until.map_or(starts_on, |date| date + Duration::days(1));
+
+    (
+        next.max(today),
+        today + Duration::days(MATERIALIZE_AHEAD_DAYS),
+    )
+}
+
+/// Creates the series' occurrences due within the look-ahead window, copying
+/// the series' access rules to each
+///
+/// Dates that already have an occurrence (including cancelled ones) are skipped,
+/// so running this again, or on several instances, is harmless.
+pub async fn materialize(
+    pool: &PgPool,
+    series: &EventSeries,
+    now: DateTime<Utc>,
+) -> Result<Vec<Event>, sqlx::Error> {
+    let today = now.with_timezone(&series.timezone()).date_naive();
+    let (from, through) = materialize_window(series.starts_on, series.materialized_until, today);
+
+    let rules: Vec<AccessRuleData> = SeriesAccessRule::list_by_series(pool, series.id)
+        .await?
+        .iter()
+        .map(SeriesAccessRule::data)
+        .collect();
+
+    let mut created = Vec::new();
+    for date in series.rule().dates_between(from, through) {
+        let schedule = match series.occurrence_schedule(date) {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                // e.g. the start time falls in a daylight saving gap that day
+                tracing::warn!(
+                    series_id = %series.id,
+                    date = %date,
+                    error = %e,
+                    "Skipped series occurrence"
+                );
+                continue;
+            }
+        };
+
+        // An occurrence never exists without the series' rules
+        let mut tx = pool.begin().await?;
+        if let Some(event) = Event::create_occurrence(&mut *tx, series, date, &schedule).await? {
+            if !rules.is_empty() {
+                EventAccessRule::write_for_event(&mut tx, event.id, &rules).await?;
+            }
+            tx.commit().await?;
+            created.push(event);
+        }
+    }
+
+    EventSeries::set_materialized_until(pool, series.id, through).await?;
+
+    if !created.is_empty() {
+        tracing::info!(
+            series_id = %series.id,
+            created = created.len(),
+            "Materialized series occurrences"
+        );
+    }
+
+    Ok(created)
+}
+
+/// Applies a series' changed details to its upcoming occurrences that were not
+/// edited on their own; returns how many were updated
+pub async fn apply_details(
+    pool: &PgPool,
+    series: &EventSeries,
+    capacity_changed: bool,
+) -> Result<usize, sqlx::Error> {
+    let events = Event::apply_series_details(pool, series).await?;
+
+    // Spots added (or the limit removed) go to each occurrence's waitlist
+    if capacity_changed {
+        for event in &events {
+            reservations::capacity_changed(pool, event).await?;
+        }
+    }
+
+    Ok(events.len())
+}
+
+/// Replaces a series' access rules and those of its upcoming occurrences whose
+/// rules were not edited on their own
+pub async fn replace_access_rules(
+    pool: &PgPool,
+    series_id: Uuid,
+    rules: &[AccessRuleData],
+) -> Result<usize, sqlx::Error> {
+    SeriesAccessRule::replace_for_series(pool, series_id, rules).await?;
+
+    let now = Utc::now();
+    let mut updated = 0;
+    for event in Event::list_by_series(pool, series_id).await? {
+        if event.is_active && event.starts_at > now && !event.series_overridden {
+            EventAccessRule::replace_for_event(pool, event.id, rules).await?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Ends a series and cancels its occurrences that have not started yet
+///
+/// Returns the number of occurrences cancelled, or None if the series had already ended.
+pub async fn end(pool: &PgPool, series_id: Uuid) -> Result<Option<u64>, sqlx::Error> {
+    if !EventSeries::end(pool, series_id).await? {
+        return Ok(None);
+    }
+
+    let cancelled = Event::cancel_upcoming_in_series(pool, series_id).await?;
+    tracing::info!(series_id = %series_id, cancelled, "Event series ended");
+
+    Ok(Some(cancelled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_materialize_window() {
+        let today = date("2025-12-10");
+
+        // A new series starting in the past is materialized from today
+        assert_eq!(
+            materialize_window(date("2025-11-01"), None, today),
+            (today, date("2026-02-04"))
+        );
+        // ...and one starting later from its first date
+        assert_eq!(
+            materialize_window(date("2025-12-20"), None, today).0,
+            date("2025-12-20")
+        );
+        // Later runs continue after the last materialized date
+        assert_eq!(
+            materialize_window(date("2025-11-01"), Some(date("2026-01-15")), today).0,
+            date("2026-01-16")
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{
+    EcdsaKeyPair, KeyPair, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED,
+    ECDSA_P256_SHA256_FIXED_SIGNING,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The only signature algorithm VPass signs and accepts (ECDSA P-256 / SHA-256)
+pub const ES256: &str = "ES256";
+
+/// Length of one P-256 coordinate
+const COORDINATE_LEN: usize = 32;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum JoseError {
+    #[error("Malformed JWT: {0}")]
+    Malformed(String),
+
+    #[error("Unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("Invalid key")]
+    InvalidKey,
+
+    #[error("Signature verification failed")]
+    BadSignature,
+
+    #[error("Signing failed")]
+    SigningFailed,
+}
+
+pub fn b64url(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn b64url_decode(encoded: &str) -> Result<Vec<u8>, JoseError> {
+    URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| JoseError::Malformed(format!("invalid base64url: {}", e)))
+}
+
+/// SHA-256 of `data`, base64url encoded (SD-JWT digests, thumbprints)
+pub fn sha256_b64url(data: &[u8]) -> String {
+    b64url(digest(&SHA256, data).as_ref())
+}
+
+/// A P-256 public key in JWK form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    pub y: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kid: Option<String>,
+}
+
+impl Jwk {
+    /// Builds a JWK from an uncompressed SEC1 point (0x04 || x || y)
+    pub fn from_public_key(point: &[u8]) -> Result<Self, JoseError> {
+        if point.len() != 1 + 2 * COORDINATE_LEN || point[0] != 0x04 {
+            return Err(JoseError::InvalidKey);
+        }
+        let (x, y) = point[1..].split_at(COORDINATE_LEN);
+
+        Ok(Self {
+            kty: "EC".to_string(),
+            crv: "P-256".to_string(),
+            x: b64url(x),
+            y: b64url(y),
+            kid: None,
+        })
+    }
+
+    fn to_public_key(&self) -> Result<Vec<u8>, JoseError> {
+        if self.kty != "EC" || self.crv != "P-256" {
+            return Err(JoseError::InvalidKey);
+        }
+        let x = b64url_decode(&self.x).map_err(|_| JoseError::InvalidKey)?;
+        let y = b64url_decode(&self.y).map_err(|_| JoseError::InvalidKey)?;
+        if x.len() != COORDINATE_LEN || y.len() != COORDINATE_LEN {
+            return Err(JoseError::InvalidKey);
+        }
+
+        let mut point = Vec::with_capacity(1 + 2 * COORDINATE_LEN);
+        point.push(0x04);
+        point.extend_from_slice(&x);
+        point.extend_from_slice(&y);
+        Ok(point)
+    }
+
+    /// RFC 7638 thumbprint, used as key ID
+    pub fn thumbprint(&self) -> String {
+        // Required members only, in lexicographic order, no whitespace
+        let canonical = format!(
+            r#"{{"crv":"{}","kty":"{}","x":"{}","y":"{}"}}"#,
+            self.crv, self.kty, self.x, self.y
+        );
+        sha256_b64url(canonical.as_bytes())
+    }
+
+    /// Checks an ES256 signature over `message`
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), JoseError> {
+        let point = self.to_public_key()?;
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, point)
+            .verify(message, signature)
+            .map_err(|_| JoseError::BadSignature)
+    }
+}
+
+/// An ES256 private key
+pub struct Es256Key {
+    pair: EcdsaKeyPair,
+    rng: SystemRandom,
+}
+
+impl Es256Key {
+    /// Generates a new key, returned as PKCS#8 for storage
+    pub fn generate_pkcs8() -> Result<Vec<u8>, JoseError> {
+        let rng = SystemRandom::new();
+        EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .map(|doc| doc.as_ref().to_vec())
+            .map_err(|_| JoseError::SigningFailed)
+    }
+
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, JoseError> {
+        let rng = SystemRandom::new();
+        let pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &rng)
+            .map_err(|_| JoseError::InvalidKey)?;
+
+        Ok(Self { pair, rng })
+    }
+
+    pub fn public_jwk(&self) -> Jwk {
+        Jwk::from_public_key(self.pair.public_key().as_ref())
+            .expect("ring returns uncompressed P-256 points")
+    }
+
+    /// Signs a JWT; `alg` is set in the header
+    pub fn sign(&self, header: Value, claims: &Value) -> Result<String, JoseError> {
+        let mut header = header;
+        header["alg"] = Value::from(ES256);
+
+        let signing_input = format!(
+            "{}.{}",
+            b64url(header.to_string().as_bytes()),
+            b64url(claims.to_string().as_bytes())
+        );
+        let signature = self
+            .pair
+            .sign(&self.rng, signing_input.as_bytes())
+            .map_err(|_| JoseError::SigningFailed)?;
+
+        Ok(format!("{}.{}", signing_input, b64url(signature.as_ref())))
+    }
+}
+
+/// A compact JWT split into its parts; the signature is not checked until [`DecodedJwt::verify`]
+#[derive(Debug, Clone)]
+pub struct DecodedJwt {
+    pub header: Value,
+    pub claims: Value,
+    signing_input: String,
+    signature: Vec<u8>,
+}
+
+impl DecodedJwt {
+    pub fn decode(jwt: &str) -> Result<Self, JoseError> {
+        let mut parts = jwt.split('.');
+        let (Some(header), Some(claims), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(JoseError::Malformed(
+                "JWT does not have 3 parts".to_string(),
+            ));
+        };
+
+        let parse = |part: &str, name: &str| -> Result<Value, JoseError> {
+            let value: Value = serde_json::from_slice(&b64url_decode(part)?)
+                .map_err(|e| JoseError::Malformed(format!("invalid {} JSON: {}", name, e)))?;
+            if !value.is_object() {
+                return Err(JoseError::Malformed(format!("{} is not an object", name)));
+            }
+            Ok(value)
+        };
+
+        Ok(Self {
+            header: parse(header, "header")?,
+            claims: parse(claims, "payload")?,
+            signing_input: format!("{}.{}", header, claims),
+            signature: b64url_decode(signature)?,
+        })
+    }
+
+    pub fn header_str(&self, name: &str) -> Option<&str> {
+        self.header.get(name).and_then(Value::as_str)
+    }
+
+    pub fn claim_str(&self, name: &str) -> Option<&str> {
+        self.claims.get(name).and_then(Value::as_str)
+    }
+
+    pub fn claim_i64(&self, name: &str) -> Option<i64> {
+        self.claims.get(name).and_then(Value::as_i64)
+    }
+
+    /// Checks the signature with `jwk`; only ES256 is accepted
+    pub fn verify(&self, jwk: &Jwk) -> Result<(), JoseError> {
+        match self.header_str("alg") {
+            Some(ES256) => jwk.verify(self.signing_input.as_bytes(), &self.signature),
+            other => Err(JoseError::UnsupportedAlgorithm(
+                other.unwrap_or("none").to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn test_key() -> Es256Key {
+        Es256Key::from_pkcs8(&Es256Key::generate_pkcs8().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = test_key();
+        let jwt = key
+            .sign(json!({"typ": "JWT"}), &json!({"sub": "member"}))
+            .unwrap();
+
+        let decoded = DecodedJwt::decode(&jwt).unwrap();
+        assert_eq!(decoded.header_str("alg"), Some(ES256));
+        assert_eq!(decoded.claim_str("sub"), Some("member"));
+        assert!(decoded.verify(&key.public_jwk()).is_ok());
+        assert_eq!(
+            decoded.verify(&test_key().public_jwk()),
+            Err(JoseError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_tampered_payload_fails() {
+        let key = test_key();
+        let jwt = key.sign(json!({}), &json!({"tier": "basic"})).unwrap();
+
+        let parts: Vec<&str> = jwt.split('.').collect();
+        let forged = format!(
+            "{}.{}.{}",
+            parts[0],
+            b64url(br#"{"tier":"gold"}"#),
+            parts[2]
+        );
+
+        let decoded = DecodedJwt::decode(&forged).unwrap();
+        assert_eq!(
+            decoded.verify(&key.public_jwk()),
+            Err(JoseError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_algorithms() {
+        let key = test_key();
+        let unsigned = format!(
+            "{}.{}.",
+            b64url(br#"{"alg":"none"}"#),
+            b64url(br#"{"sub":"member"}"#)
+        );
+
+        let decoded = DecodedJwt::decode(&unsigned).unwrap();
+        assert_eq!(
+            decoded.verify(&key.public_jwk()),
+            Err(JoseError::UnsupportedAlgorithm("none".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_jwk_round_trip() {
+        let jwk = test_key().public_jwk();
+        let point = jwk.to_public_key().unwrap();
+
+        assert_eq!(Jwk::from_public_key(&point).unwrap(), jwk);
+        assert_eq!(jwk.thumbprint().len(), 43);
+    }
+}
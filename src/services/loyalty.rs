@@ -0,0 +1,233 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::models::{
+    card::{CardStatus, MembershipCard},
+    verification_event::VerificationEvent,
+};
+
+/// A lapse between two cards up to this long still counts as continuous membership
+/// (e.g. a member who renews a few days after their card expired)
+const MAX_GAP_DAYS: i64 = 7;
+
+/// How long and how actively a member has been with a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Loyalty {
+    /// Start of the member's continuous membership
+    pub member_since: DateTime<Utc>,
+    /// Full months of continuous membership
+    pub tenure_months: u32,
+    /// Events the member was admitted to with the channel's cards
+    pub events_attended: i64,
+}
+
+impl Loyalty {
+    /// e.g. "15 個月"
+    pub fn tenure_label(&self) -> String {
+        if self.tenure_months == 0 {
+            "未滿 1 個月".to_string()
+        } else {
+            format!("{} 個月", self.tenure_months)
+        }
+    }
+}
+
+/// When a card stopped covering its holder: replaced or deleted, expired, or no
+/// longer active, e.g. revoked (whichever came first), or `now` if it still does
+fn covered_until(card: &MembershipCard, now: DateTime<Utc>) -> DateTime<Utc> {
+    let left_active = (card.status != CardStatus::Active).then_some(card.status_changed_at);
+
+    [card.deleted_at, card.expires_at, left_active]
+        .into_iter()
+        .flatten()
+        .fold(now, DateTime::min)
+}
+
+/// Start of the continuous membership made up of the first period and the ones
+/// before it; periods are (start, end), newest first
+fn continuous_since(
+    periods: &[(DateTime<Utc>, DateTime<Utc>)],
+    max_gap: Duration,
+) -> Option<DateTime<Utc>> {
+    let (mut since, _) = *periods.first()?;
+
+    for &(start, end) in &periods[1..] {
+        if end + max_gap < since {
+            break;
+        }
+        since = since.min(start);
+    }
+
+    Some(since)
+}
+
+/// Full calendar months from `from` to `to`
+fn months_between(from: NaiveDate, to: NaiveDate) -> u32 {
+    let months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    let months = if to.day() < from.day() {
+        months - 1
+    } else {
+        months
+    };
+
+    months.max(0) as u32
+}
+
+/// Computes a card holder's tenure and activity with the card's issuer
+///
+/// Tenure runs from the membership confirmation of the earliest card in the
+/// unbroken chain of cards ending with this one, so reissued cards carry the
+/// member's history over, up to today (or until the card stopped being valid).
+pub async fn for_card(pool: &PgPool, card: &MembershipCard) -> Result<Loyalty, sqlx::Error> {
+    let now = Utc::now();
+    let history = MembershipCard::list_history(pool, card.member_id, card.issuer_id).await?;
+
+    // This card first, then the ones it replaced
+    let periods: Vec<_> = std::iter::once(card)
+        .chain(
+            history
+                .iter()
+                .filter(|c| c.id != card.id && c.issued_at <= card.issued_at),
+        )
+        .map(|c| (c.membership_confirmed_at, covered_until(c, now)))
+        .collect();
+
+    let member_since = continuous_since(&periods, Duration::days(MAX_GAP_DAYS))
+        .unwrap_or(card.membership_confirmed_at);
+    let until = covered_until(card, now);
+
+    let events_attended =
+        VerificationEvent::count_events_attended(pool, card.member_id, card.issuer_id).await?;
+
+    Ok(Loyalty {
+        member_since,
+        tenure_months: months_between(member_since.date_naive(), until.date_naive()),
+        events_attended,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap()
+    }
+
+    fn card(
+        status: CardStatus,
+        confirmed_at: DateTime<Utc>,
+        status_changed_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> MembershipCard {
+        MembershipCard {
+            id: uuid::Uuid::new_v4(),
+            issuer_id: uuid::Uuid::nil(),
+            member_id: uuid::Uuid::nil(),
+            membership_level_label: "會員".to_string(),
+            membership_confirmed_at: confirmed_at,
+            verification_comment_id: "comment".to_string(),
+            verification_video_id: "video".to_string(),
+            snapshot_json: serde_json::json!({}),
+            status,
+            status_changed_at,
+            expires_at: Some(expires_at),
+            last_verified_at: None,
+            verification_failures: 0,
+            deleted_at: None,
+            issued_at: confirmed_at,
+            wallet_transaction_id: None,
+            wallet_qr_code: None,
+            wallet_deep_link: None,
+            wallet_cid: None,
+            wallet_scanned_at: None,
+        }
+    }
+
+    #[test]
+    fn test_covered_until_ends_when_card_left_active() {
+        let now = at(2025, 12, 1);
+
+        let active = card(
+            CardStatus::Active,
+            at(2025, 6, 1),
+            at(2025, 6, 1),
+            at(2026, 1, 1),
+        );
+        assert_eq!(covered_until(&active, now), now);
+
+        // Marked expired by the subscription checker, expires_at left untouched
+        let expired = card(
+            CardStatus::Expired,
+            at(2025, 1, 1),
+            at(2025, 3, 1),
+            at(2026, 1, 1),
+        );
+        assert_eq!(covered_until(&expired, now), at(2025, 3, 1));
+
+        let revoked = card(
+            CardStatus::Revoked,
+            at(2025, 1, 1),
+            at(2025, 2, 1),
+            at(2026, 1, 1),
+        );
+        assert_eq!(covered_until(&revoked, now), at(2025, 2, 1));
+    }
+
+    #[test]
+    fn test_continuous_since_stops_at_lapsed_cards() {
+        let now = at(2025, 12, 1);
+        let gap = Duration::days(MAX_GAP_DAYS);
+        let history = [
+            card(
+                CardStatus::Active,
+                at(2025, 9, 1),
+                at(2025, 9, 1),
+                at(2026, 1, 1),
+            ),
+            // Expired months before the current card, though its expires_at is later
+            card(
+                CardStatus::Expired,
+                at(2024, 1, 1),
+                at(2025, 2, 1),
+                at(2026, 1, 1),
+            ),
+        ];
+        let periods: Vec<_> = history
+            .iter()
+            .map(|c| (c.membership_confirmed_at, covered_until(c, now)))
+            .collect();
+
+        assert_eq!(continuous_since(&periods, gap), Some(at(2025, 9, 1)));
+    }
+
+    #[test]
+    fn test_continuous_since_follows_reissued_cards() {
+        let gap = Duration::days(MAX_GAP_DAYS);
+        let periods = [
+            (at(2025, 6, 1), at(2025, 12, 1)),
+            // Replaced by the card above the day it was issued
+            (at(2025, 1, 10), at(2025, 6, 1)),
+            // Expired a few days before the next card
+            (at(2024, 9, 1), at(2025, 1, 5)),
+            // Lapsed for months
+            (at(2023, 1, 1), at(2023, 6, 1)),
+        ];
+
+        assert_eq!(continuous_since(&periods, gap), Some(at(2024, 9, 1)));
+        assert_eq!(continuous_since(&periods[..1], gap), Some(at(2025, 6, 1)));
+        assert_eq!(continuous_since(&[], gap), None);
+    }
+
+    #[test]
+    fn test_months_between() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(months_between(date(2024, 9, 1), date(2025, 12, 1)), 15);
+        assert_eq!(months_between(date(2024, 9, 15), date(2025, 12, 14)), 14);
+        assert_eq!(months_between(date(2025, 12, 1), date(2025, 12, 20)), 0);
+        assert_eq!(months_between(date(2025, 12, 20), date(2025, 12, 1)), 0);
+    }
+}
@@ -15,6 +15,52 @@ pub enum MembershipCheckError {
 
     #[error("Membership has expired (403 Forbidden)")]
     MembershipExpired,
+
+    #[error("YouTube API daily quota exceeded")]
+    QuotaExceeded,
+
+    #[error("YouTube API rate limit exceeded")]
+    RateLimited,
+}
+
+/// Quota cost of a `videos.list` / `commentThreads.list` call (YouTube Data API v3)
+pub const LIST_CALL_QUOTA_COST: i32 = 1;
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorResponse {
+    error: ApiErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    #[serde(default)]
+    errors: Vec<ApiErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorDetail {
+    reason: Option<String>,
+}
+
+/// Distinguishes quota/rate-limit 403s from a plain "no access" 403
+///
+/// YouTube reports quota exhaustion and rate limiting as 403 with an error reason,
+/// which must not be mistaken for a lapsed membership.
+fn classify_forbidden(body: &str) -> Option<MembershipCheckError> {
+    let response: ApiErrorResponse = serde_json::from_str(body).ok()?;
+
+    response
+        .error
+        .errors
+        .iter()
+        .filter_map(|e| e.reason.as_deref())
+        .find_map(|reason| match reason {
+            "quotaExceeded" | "dailyLimitExceeded" => Some(MembershipCheckError::QuotaExceeded),
+            "rateLimitExceeded" | "userRateLimitExceeded" => {
+                Some(MembershipCheckError::RateLimited)
+            }
+            _ => None,
+        })
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,6 +80,7 @@ struct VideoItem {
 /// - Ok(true) - User is still a member (200 OK with video data)
 /// - Ok(false) - User is not a member (403 Forbidden or empty items)
 /// - Err(TokenExpired) - Access token needs refresh (401 Unauthorized)
+/// - Err(QuotaExceeded) / Err(RateLimited) - Quota or rate limit hit (403 with reason, or 429)
 /// - Err(ApiError) - Other YouTube API errors
 pub async fn check_video_access(
     access_token: &str,
@@ -67,9 +114,14 @@ pub async fn check_video_access(
             Ok(!video_response.items.is_empty())
         }
         StatusCode::FORBIDDEN => {
-            // User no longer has membership access
-            Ok(false)
+            let body = response.text().await.unwrap_or_default();
+            match classify_forbidden(&body) {
+                Some(e) => Err(e),
+                // User no longer has membership access
+                None => Ok(false),
+            }
         }
+        StatusCode::TOO_MANY_REQUESTS => Err(MembershipCheckError::RateLimited),
         StatusCode::NOT_FOUND => {
             // Video not found or user doesn't have access
             Ok(false)
@@ -111,7 +163,14 @@ pub async fn check_comment_access(
 
     match response.status() {
         StatusCode::OK => Ok(true),
-        StatusCode::FORBIDDEN => Ok(false),
+        StatusCode::FORBIDDEN => {
+            let body = response.text().await.unwrap_or_default();
+            match classify_forbidden(&body) {
+                Some(e) => Err(e),
+                None => Ok(false),
+            }
+        }
+        StatusCode::TOO_MANY_REQUESTS => Err(MembershipCheckError::RateLimited),
         StatusCode::UNAUTHORIZED => Err(MembershipCheckError::TokenExpired),
         other => {
             let error_text = response
@@ -140,4 +199,23 @@ mod tests {
         // Result depends on actual API response
         assert!(result.is_ok() || result.is_err());
     }
+
+    #[test]
+    fn test_classify_forbidden() {
+        let quota = r#"{"error":{"code":403,"errors":[{"reason":"quotaExceeded"}]}}"#;
+        assert!(matches!(
+            classify_forbidden(quota),
+            Some(MembershipCheckError::QuotaExceeded)
+        ));
+
+        let rate = r#"{"error":{"code":403,"errors":[{"reason":"userRateLimitExceeded"}]}}"#;
+        assert!(matches!(
+            classify_forbidden(rate),
+            Some(MembershipCheckError::RateLimited)
+        ));
+
+        let forbidden = r#"{"error":{"code":403,"errors":[{"reason":"forbidden"}]}}"#;
+        assert!(classify_forbidden(forbidden).is_none());
+        assert!(classify_forbidden("not json").is_none());
+    }
 }
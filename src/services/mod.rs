@@ -1,10 +1,32 @@
 // Services module - Business logic
 
+pub mod admission;
+pub mod attendance_credentials;
 pub mod card_issuer;
 pub mod card_verifier;
+pub mod circuit_breaker;
 pub mod comment_verifier;
+pub mod credential_cipher;
+pub mod credential_schema;
+pub mod credential_verifier;
+pub mod event_access;
+pub mod event_series;
+pub mod jose;
+pub mod loyalty;
 pub mod membership_checker;
 pub mod oauth;
+pub mod offline_scanning;
 pub mod oidvp_verifier;
+pub mod openid_wallet;
+pub mod presentation_token;
+pub mod qr_render;
+pub mod reservations;
+pub mod scanner_devices;
+pub mod sd_jwt;
+pub mod signing_keys;
+pub mod status_list;
+pub mod task_queue;
+pub mod wallet_accounts;
+pub mod wallet_provider;
 pub mod wallet_qr;
 pub mod youtube_channel;
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{
+    attendance_credential::AttendanceCredential,
+    card::MembershipCard,
+    event::Event,
+    event_reservation::EventReservation,
+    issuer::CardIssuer,
+    offline_bundle::OfflineBundle,
+    verification_event::{CreateVerificationEventData, VerificationEvent},
+};
+use crate::services::admission::{self, AdmissionDecision, ScanDirection};
+use crate::services::event_access::{AccessDecision, EventAccess};
+use crate::services::jose::{b64url, JoseError};
+use crate::services::presentation_token::{self, PresentationTokenError, PRESENTATION_TOKEN_TYP};
+use crate::services::scanner_devices::ScannerIdentity;
+use crate::services::signing_keys::{self, SigningKeyError};
+
+/// `typ` of a signed event bundle
+pub const BUNDLE_TYP: &str = "vpass-bundle+jwt";
+
+/// How long a scanner may verify offline with a bundle before downloading a new one
+pub const BUNDLE_VALIDITY_HOURS: i64 = 24;
+
+/// Most scans accepted in one upload
+pub const MAX_SCANS_PER_BATCH: usize = 500;
+
+/// How far a scanner's clock may be off from VPass's
+const CLOCK_SKEW_SECS: i64 = 300;
+
+/// Results an offline scanner reports; like online scans, tokens that do not
+/// verify are not uploaded
+const DEVICE_RESULTS: &[&str] = &[
+    "success",
+    "card_revoked",
+    "card_suspended",
+    "token_reused",
+    "tier_too_low",
+];
+
+#[derive(thiserror::Error, Debug)]
+pub enum OfflineScanError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("Signing key error: {0}")]
+    SigningKey(#[from] SigningKeyError),
+
+    #[error("Signing failed: {0}")]
+    Signing(#[from] JoseError),
+
+    #[error("Presentation token error: {0}")]
+    PresentationToken(PresentationTokenError),
+
+    #[error("At most {MAX_SCANS_PER_BATCH} scans can be uploaded at once (got {0})")]
+    TooManyScans(usize),
+}
+
+/// An event bundle signed with VPass's own key
+#[derive(Debug, Clone)]
+pub struct SignedBundle {
+    pub bundle_id: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A scan verified on a device against a bundle
+#[derive(Debug, Clone, Deserialize)]
+pub struct OfflineScan {
+    /// Assigned by the device; re-uploading a scan is a no-op
+    pub scan_id: Uuid,
+    pub token: String,
+    pub scanned_at: DateTime<Utc>,
+    /// The bundle the scan was verified with, if not the batch's
+    #[serde(default)]
+    pub bundle_id: Option<String>,
+    /// The device's verdict (a `verification_result` value)
+    pub result: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IngestSummary {
+    pub recorded: usize,
+    pub duplicates: usize,
+    /// Scans that cannot be recorded (unknown result, no bundle issued for the
+    /// event, dated before the bundle was issued or after the upload, the
+    /// operator's own card)
+    pub rejected: Vec<Uuid>,
+}
+
+fn random_bundle_id() -> String {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator available");
+    b64url(&bytes)
+}
+
+/// Signs the bundle a scanner needs to verify presentation tokens without a
+/// connection: the event, the accepted issuers and their published keys, and the
+/// cards currently revoked or suspended or below the event's minimum tier, and
+/// the cards holding a reserved spot
+pub async fn issue_bundle(
+    pool: &PgPool,
+    config: &Config,
+    event: &Event,
+) -> Result<SignedBundle, OfflineScanError> {
+    let access = EventAccess::load(pool, event).await?;
+    let issuer_ids = access.accepted_issuers();
+
+    let mut issuers = Vec::with_capacity(issuer_ids.len());
+    let mut tier_denied_cards = Vec::new();
+    for issuer_id in &issuer_ids {
+        let Some(issuer) = CardIssuer::find_by_id(pool, *issuer_id).await? else {
+            continue;
+        };
+        let jwks = signing_keys::jwks(pool, Some(issuer.id)).await?;
+        let accepted_tiers = access.accepted_tiers(issuer.id);
+        if let Some(tiers) = &accepted_tiers {
+            tier_denied_cards
+                .extend(MembershipCard::list_below_tier(pool, issuer.id, tiers).await?);
+        }
+        issuers.push(json!({
+            "id": issuer.id,
+            "channel_name": issuer.channel_name,
+            "keys": jwks["keys"],
+            "accepted_tiers": accepted_tiers,
+        }));
+    }
+    let denied_cards = MembershipCard::list_denied(pool, &issuer_ids).await?;
+    let reserved_cards = EventReservation::list_reserved_cards(pool, event.id).await?;
+
+    let bundle_id = random_bundle_id();
+    let now = Utc::now();
+    let expires_at = now + Duration::hours(BUNDLE_VALIDITY_HOURS);
+    let claims = json!({
+        "iss": signing_keys::vpass_did(config),
+        "jti": bundle_id,
+        "iat": now.timestamp(),
+        "exp": expires_at.timestamp(),
+        "event": {
+            "id": event.id,
+            "name": event.event_name,
+            "issuer_id": event.issuer_id,
+        },
+        "token_typ": PRESENTATION_TOKEN_TYP,
+        "issuers": issuers,
+        "denied_cards": denied_cards,
+        "tier_denied_cards": tier_denied_cards,
+        "reserved_cards": reserved_cards,
+    });
+
+    let (signing_key, key) = signing_keys::active_key(pool, config, None).await?;
+    let token = key.sign(
+        json!({
+            "typ": BUNDLE_TYP,
+            "kid": signing_keys::verification_method_id(config, &signing_key),
+        }),
+        &claims,
+    )?;
+
+    signing_keys::record_signed(pool, &signing_key, Some(expires_at)).await?;
+    OfflineBundle::create(pool, &bundle_id, event.id, now, expires_at).await?;
+
+    Ok(SignedBundle {
+        bundle_id,
+        token,
+        expires_at,
+    })
+}
+
+/// Result recorded for a scan whose token VPass cannot authenticate
+fn unauthenticated_result(error: &PresentationTokenError) -> &'static str {
+    match error {
+        PresentationTokenError::Jose(_) | PresentationTokenError::UnknownKey => "invalid_signature",
+        _ => "invalid_payload",
+    }
+}
+
+/// What became of one uploaded scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ingested {
+    Recorded,
+    /// Uploaded before
+    Duplicate,
+    /// The scanner's operator scanned their own card
+    Rejected,
+}
+
+/// When an uploaded scan happened, as far as VPass can tell: the device's time,
+/// within the validity of the bundle it verified with and no later than the
+/// upload; None if the device's time is off by more than its clock may be
+fn scan_time(
+    scanned_at: DateTime<Utc>,
+    bundle: &OfflineBundle,
+    uploaded_at: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let skew = Duration::seconds(CLOCK_SKEW_SECS);
+    if scanned_at < bundle.issued_at - skew || scanned_at > uploaded_at + skew {
+        return None;
+    }
+
+    Some(
+        scanned_at
+            .max(bundle.issued_at)
+            .min(bundle.expires_at)
+            .min(uploaded_at),
+    )
+}
+
+/// Records one uploaded scan
+async fn ingest_scan(
+    pool: &PgPool,
+    event: &Event,
+    access: &EventAccess,
+    scanner: &ScannerIdentity,
+    bundle_id: &str,
+    scan: OfflineScan,
+) -> Result<Ingested, OfflineScanError> {
+    if VerificationEvent::client_scan_exists(pool, scan.scan_id).await? {
+        return Ok(Ingested::Duplicate);
+    }
+
+    // The device's verdict is what happened at the door; VPass re-checks the
+    // token (allowing for the device's clock) and records a forged or reused
+    // token instead
+    let checked_at = scan.scanned_at - Duration::seconds(CLOCK_SKEW_SECS);
+    let mut card = None;
+    let mut server_check = None;
+    let result = match presentation_token::authenticate(
+        pool,
+        &scan.token,
+        &access.accepted_issuers(),
+        checked_at,
+    )
+    .await
+    {
+        Ok(claims) => {
+            card = MembershipCard::find_by_id(pool, claims.card_id).await?;
+            if card
+                .as_ref()
+                .is_some_and(|card| scanner.is_card_holder(card))
+            {
+                return Ok(Ingested::Rejected);
+            }
+            let access_decision = card.as_ref().map(|card| access.check(card));
+            match presentation_token::consume(pool, &claims, event.id).await {
+                Ok(()) => match access_decision {
+                    None => "card_not_found".to_string(),
+                    // A card below the minimum tier the device's bundle predates
+                    Some(AccessDecision::TierTooLow { .. }) if scan.result == "success" => {
+                        "tier_too_low".to_string()
+                    }
+                    Some(_) => scan.result.clone(),
+                },
+                Err(PresentationTokenError::AlreadyUsed) => "token_reused".to_string(),
+                // A card printed again since the device's bundle was downloaded
+                Err(e @ PresentationTokenError::Revoked) => {
+                    server_check = Some(e.to_string());
+                    unauthenticated_result(&e).to_string()
+                }
+                Err(e) => return Err(OfflineScanError::PresentationToken(e)),
+            }
+        }
+        Err(
+            e @ (PresentationTokenError::DatabaseError(_) | PresentationTokenError::SigningKey(_)),
+        ) => return Err(OfflineScanError::PresentationToken(e)),
+        Err(e) => {
+            server_check = Some(e.to_string());
+            unauthenticated_result(&e).to_string()
+        }
+    };
+
+    let data = CreateVerificationEventData {
+        event_id: event.id,
+        card_id: card.as_ref().map(|card| card.id),
+        verification_result: result,
+        verification_context: Some(scanner.annotate(json!({
+            "method": "offline_bundle",
+            "bundle_id": bundle_id,
+            "device_result": scan.result,
+            "server_check": server_check,
+            "uploaded_at": Utc::now(),
+        }))),
+        raw_payload: Some(scan.token),
+    };
+
+    // A device cannot know the card already entered at another gate, or that
+    // the event filled up; such scans are recorded as `already_checked_in` and
+    // `capacity_reached`
+    let recorded = match card {
+        Some(card) if data.verification_result == "success" => {
+            let (mut tx, decision, attendance) =
+                admission::check(pool, event, &card, ScanDirection::Entry, scan.scanned_at).await?;
+            let mut data = CreateVerificationEventData {
+                verification_result: decision.result_type().to_string(),
+                ..data
+            };
+            if let (AdmissionDecision::Admit, Some(context)) = (
+                &decision,
+                data.verification_context
+                    .as_mut()
+                    .and_then(|c| c.as_object_mut()),
+            ) {
+                context.insert("attendance".to_string(), attendance.as_str().into());
+            }
+            let recorded = VerificationEvent::create_offline_scan(
+                &mut *tx,
+                data,
+                scan.scan_id,
+                scan.scanned_at,
+            )
+            .await?;
+            if decision == AdmissionDecision::Admit && recorded.is_some() {
+                AttendanceCredential::record(&mut *tx, event.id, card.id, scan.scanned_at).await?;
+            }
+            tx.commit().await?;
+            recorded
+        }
+        _ => {
+            VerificationEvent::create_offline_scan(pool, data, scan.scan_id, scan.scanned_at)
+                .await?
+        }
+    };
+
+    Ok(match recorded {
+        Some(_) => Ingested::Recorded,
+        None => Ingested::Duplicate,
+    })
+}
+
+/// Records a batch of scans uploaded by an offline scanner
+///
+/// Scans already uploaded are skipped, so a scanner can retry an upload whose
+/// response it did not receive. Scans are dated within the validity of the
+/// bundle they were verified with (see `scan_time`), so a device clock cannot
+/// make an expired token pass or backdate an admission.
+pub async fn ingest_scans(
+    pool: &PgPool,
+    event: &Event,
+    scanner: &ScannerIdentity,
+    bundle_id: Option<&str>,
+    scans: Vec<OfflineScan>,
+) -> Result<IngestSummary, OfflineScanError> {
+    if scans.len() > MAX_SCANS_PER_BATCH {
+        return Err(OfflineScanError::TooManyScans(scans.len()));
+    }
+
+    let access = EventAccess::load(pool, event).await?;
+    let uploaded_at = Utc::now();
+    let mut bundles: HashMap<String, Option<OfflineBundle>> = HashMap::new();
+    let mut summary = IngestSummary::default();
+
+    for scan in scans {
+        let bundle = match scan.bundle_id.as_deref().or(bundle_id) {
+            Some(id) => {
+                if !bundles.contains_key(id) {
+                    let bundle = OfflineBundle::find_for_event(pool, id, event.id).await?;
+                    bundles.insert(id.to_string(), bundle);
+                }
+                bundles[id].as_ref()
+            }
+            None => None,
+        };
+        let scanned_at = bundle.and_then(|bundle| scan_time(scan.scanned_at, bundle, uploaded_at));
+        let (Some(bundle), Some(scanned_at)) = (bundle, scanned_at) else {
+            summary.rejected.push(scan.scan_id);
+            continue;
+        };
+        if !DEVICE_RESULTS.contains(&scan.result.as_str()) {
+            summary.rejected.push(scan.scan_id);
+            continue;
+        }
+
+        let scan_id = scan.scan_id;
+        let scan = OfflineScan { scanned_at, ..scan };
+        match ingest_scan(pool, event, &access, scanner, &bundle.id, scan).await? {
+            Ingested::Recorded => summary.recorded += 1,
+            Ingested::Duplicate => summary.duplicates += 1,
+            Ingested::Rejected => summary.rejected.push(scan_id),
+        }
+    }
+
+    tracing::info!(
+        event_id = %event.id,
+        recorded = summary.recorded,
+        duplicates = summary.duplicates,
+        rejected = summary.rejected.len(),
+        "Ingested offline scans"
+    );
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_scan_time_stays_within_the_bundle() {
+        let issued_at = Utc.with_ymd_and_hms(2026, 1, 9, 10, 0, 0).unwrap();
+        let bundle = OfflineBundle {
+            id: "bundle".to_string(),
+            event_id: Uuid::nil(),
+            issued_at,
+            expires_at: issued_at + Duration::hours(BUNDLE_VALIDITY_HOURS),
+        };
+        let uploaded_at = issued_at + Duration::hours(2);
+        let at = |minutes| issued_at + Duration::minutes(minutes);
+
+        assert_eq!(scan_time(at(30), &bundle, uploaded_at), Some(at(30)));
+        // A device clock slightly behind or ahead
+        assert_eq!(scan_time(at(-2), &bundle, uploaded_at), Some(issued_at));
+        assert_eq!(scan_time(at(122), &bundle, uploaded_at), Some(uploaded_at));
+        // Dated before the bundle existed, or after the upload
+        assert_eq!(scan_time(at(-60), &bundle, uploaded_at), None);
+        assert_eq!(scan_time(at(180), &bundle, uploaded_at), None);
+
+        // Uploaded after the bundle expired
+        let late_upload = bundle.expires_at + Duration::hours(5);
+        assert_eq!(
+            scan_time(at(26 * 60), &bundle, late_upload),
+            Some(bundle.expires_at)
+        );
+    }
+
+    #[test]
+    fn test_unauthenticated_result() {
+        assert_eq!(
+            unauthenticated_result(&PresentationTokenError::UnknownKey),
+            "invalid_signature"
+        );
+        assert_eq!(
+            unauthenticated_result(&PresentationTokenError::Jose(JoseError::BadSignature)),
+            "invalid_signature"
+        );
+        assert_eq!(
+            unauthenticated_result(&PresentationTokenError::Expired),
+            "invalid_payload"
+        );
+    }
+}
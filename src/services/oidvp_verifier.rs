@@ -21,6 +21,9 @@ pub enum OidvpError {
 
     #[error("Verification failed: {0}")]
     VerificationFailed(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
 }
 
 /// Request to generate verification QR code
@@ -0,0 +1,793 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Duration, Utc};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Map, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{
+    card::{CardStatus, MembershipCard},
+    credential_field::IssuerCredentialField,
+    credential_status_list::CredentialStatusList,
+    issuer::WalletProviderKind,
+    openid_credential_offer::OpenIdCredentialOffer,
+    openid_presentation::OpenIdPresentation,
+};
+use crate::services::{
+    credential_schema,
+    jose::{b64url, DecodedJwt, Jwk, ES256},
+    oidvp_verifier::{ClaimData, CredentialData, OidvpError, QrCodeResponse, ResultResponse},
+    qr_render::{self, QrRenderOptions},
+    sd_jwt::{self, Presentation, SdJwtError},
+    signing_keys::{self, SigningKeyError},
+    status_list,
+    wallet_provider::{CredentialOffer, IssuedCredential, PresentationRequest, WalletProvider},
+    wallet_qr::{WalletQrError, WalletQrResponse},
+};
+
+/// The one credential type the self-hosted issuer offers
+pub const CREDENTIAL_CONFIGURATION_ID: &str = "vpass_membership";
+
+/// SD-JWT VC format identifier (also the credential's `typ`)
+pub const CREDENTIAL_FORMAT: &str = "dc+sd-jwt";
+
+/// Format identifier used by wallets implementing earlier drafts
+const LEGACY_CREDENTIAL_FORMAT: &str = "vc+sd-jwt";
+
+pub const PRE_AUTHORIZED_GRANT: &str = "urn:ietf:params:oauth:grant-type:pre-authorized_code";
+
+const PROOF_TYP: &str = "openid4vci-proof+jwt";
+
+/// How long a member has to scan an offer
+const OFFER_TTL_MINUTES: i64 = 30;
+const ACCESS_TOKEN_TTL_SECS: i64 = 300;
+/// Same lifetime as a Taiwan wallet verification QR code
+const PRESENTATION_TTL_SECS: i64 = 300;
+/// How far a key proof's `iat` may be from now
+const PROOF_MAX_SKEW_SECS: i64 = 300;
+
+#[derive(thiserror::Error, Debug)]
+pub enum OpenIdError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("Signing key error: {0}")]
+    SigningKey(#[from] SigningKeyError),
+
+    #[error("Credential signing failed: {0}")]
+    Signing(#[from] SdJwtError),
+
+    #[error("Unsupported grant type")]
+    UnsupportedGrantType,
+
+    #[error("Pre-authorized code is invalid, expired or already used")]
+    InvalidGrant,
+
+    #[error("Access token is invalid or expired")]
+    InvalidToken,
+
+    #[error("Invalid proof: {0}")]
+    InvalidProof(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Credential can no longer be issued: {0}")]
+    CredentialUnavailable(String),
+
+    #[error("Presentation request not found")]
+    UnknownPresentation,
+
+    #[error("Status list not found")]
+    UnknownStatusList,
+
+    #[error("Issuer not found")]
+    UnknownIssuer,
+
+    #[error("Presentation request expired or already answered")]
+    PresentationClosed,
+
+    #[error("Presentation rejected: {0}")]
+    PresentationRejected(String),
+}
+
+impl OpenIdError {
+    /// OAuth / OpenID4VC error code sent to the wallet
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            OpenIdError::DatabaseError(_)
+            | OpenIdError::SigningKey(_)
+            | OpenIdError::Signing(_) => "server_error",
+            OpenIdError::UnsupportedGrantType => "unsupported_grant_type",
+            OpenIdError::InvalidGrant => "invalid_grant",
+            OpenIdError::InvalidToken => "invalid_token",
+            OpenIdError::InvalidProof(_) => "invalid_proof",
+            OpenIdError::CredentialUnavailable(_) => "credential_request_denied",
+            OpenIdError::InvalidRequest(_)
+            | OpenIdError::UnknownPresentation
+            | OpenIdError::UnknownStatusList
+            | OpenIdError::UnknownIssuer
+            | OpenIdError::PresentationClosed
+            | OpenIdError::PresentationRejected(_) => "invalid_request",
+        }
+    }
+}
+
+/// Random URL-safe secret (codes, tokens, nonces)
+fn random_secret() -> String {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator available");
+    b64url(&bytes)
+}
+
+/// Secrets are only stored hashed
+fn hash_secret(secret: &str) -> String {
+    hex::encode(digest(&SHA256, secret.as_bytes()))
+}
+
+/// Credential issuer identifier (and `iss` of issued credentials)
+pub fn credential_issuer(config: &Config) -> String {
+    config.base_url.trim_end_matches('/').to_string()
+}
+
+fn vct(credential_issuer: &str) -> String {
+    format!("{}/credentials/membership", credential_issuer)
+}
+
+fn credential_uri(credential_issuer: &str, credential_id: Uuid) -> String {
+    format!("{}/credentials/{}", credential_issuer, credential_id)
+}
+
+fn response_uri(credential_issuer: &str, presentation_id: Uuid) -> String {
+    format!("{}/oid4vp/responses/{}", credential_issuer, presentation_id)
+}
+
+/// Client ID of a presentation request; responses go back to the same URI
+fn verifier_client_id(response_uri: &str) -> String {
+    format!("redirect_uri:{}", response_uri)
+}
+
+fn qr_data_url(data: &str) -> Result<String, qr_render::QrRenderError> {
+    let png = qr_render::render_qr_png(data, QrRenderOptions::default())?;
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+/// Credential issuer metadata (`/.well-known/openid-credential-issuer`)
+pub fn issuer_metadata(config: &Config) -> Value {
+    let issuer = credential_issuer(config);
+
+    json!({
+        "credential_issuer": issuer,
+        "credential_endpoint": format!("{}/oid4vci/credential", issuer),
+        "display": [{ "name": "VPass", "locale": "zh-TW" }, { "name": "VPass", "locale": "en" }],
+        "credential_configurations_supported": {
+            CREDENTIAL_CONFIGURATION_ID: {
+                "format": CREDENTIAL_FORMAT,
+                "vct": vct(&issuer),
+                "scope": CREDENTIAL_CONFIGURATION_ID,
+                "cryptographic_binding_methods_supported": ["jwk"],
+                "credential_signing_alg_values_supported": [ES256],
+                "proof_types_supported": {
+                    "jwt": { "proof_signing_alg_values_supported": [ES256] }
+                },
+                "display": [
+                    { "name": "頻道會員卡", "locale": "zh-TW" },
+                    { "name": "Channel Membership Card", "locale": "en" }
+                ]
+            }
+        }
+    })
+}
+
+/// Authorization server metadata (`/.well-known/oauth-authorization-server`)
+pub fn authorization_server_metadata(config: &Config) -> Value {
+    let issuer = credential_issuer(config);
+
+    json!({
+        "issuer": issuer,
+        "token_endpoint": format!("{}/oid4vci/token", issuer),
+        "grant_types_supported": [PRE_AUTHORIZED_GRANT],
+        "pre-authorized_grant_anonymous_access_supported": true,
+    })
+}
+
+/// SD-JWT VC issuer metadata (`/.well-known/jwt-vc-issuer`), so any verifier can
+/// check VPass credential signatures
+pub async fn jwt_vc_issuer_metadata(pool: &PgPool, config: &Config) -> Result<Value, OpenIdError> {
+    Ok(json!({
+        "issuer": credential_issuer(config),
+        "jwks": signing_keys::jwks(pool, None).await?,
+    }))
+}
+
+/// Token endpoint: exchanges a pre-authorized code for an access token and c_nonce
+pub async fn exchange_code(
+    pool: &PgPool,
+    grant_type: &str,
+    pre_authorized_code: Option<&str>,
+) -> Result<Value, OpenIdError> {
+    if grant_type != PRE_AUTHORIZED_GRANT {
+        return Err(OpenIdError::UnsupportedGrantType);
+    }
+    let code = pre_authorized_code.ok_or(OpenIdError::InvalidGrant)?;
+
+    let access_token = random_secret();
+    let c_nonce = random_secret();
+    let offer = OpenIdCredentialOffer::redeem_code(
+        pool,
+        &hash_secret(code),
+        &hash_secret(&access_token),
+        Utc::now() + Duration::seconds(ACCESS_TOKEN_TTL_SECS),
+        &c_nonce,
+    )
+    .await?
+    .ok_or(OpenIdError::InvalidGrant)?;
+
+    tracing::info!(offer_id = %offer.id, card_id = %offer.card_id, "Pre-authorized code redeemed");
+
+    Ok(json!({
+        "access_token": access_token,
+        "token_type": "Bearer",
+        "expires_in": ACCESS_TOKEN_TTL_SECS,
+        "c_nonce": c_nonce,
+        "c_nonce_expires_in": ACCESS_TOKEN_TTL_SECS,
+    }))
+}
+
+/// Checks the wallet's key proof and returns the holder key to bind the credential to
+fn verify_proof(
+    proof_jwt: &str,
+    audience: &str,
+    c_nonce: Option<&str>,
+    now: i64,
+) -> Result<Jwk, OpenIdError> {
+    let proof =
+        DecodedJwt::decode(proof_jwt).map_err(|e| OpenIdError::InvalidProof(e.to_string()))?;
+
+    if proof.header_str("typ") != Some(PROOF_TYP) {
+        return Err(OpenIdError::InvalidProof(format!(
+            "typ must be {}",
+            PROOF_TYP
+        )));
+    }
+
+    let holder_key: Jwk = proof
+        .header
+        .get("jwk")
+        .cloned()
+        .and_then(|jwk| serde_json::from_value(jwk).ok())
+        .ok_or_else(|| OpenIdError::InvalidProof("proof must carry a P-256 jwk".to_string()))?;
+    proof
+        .verify(&holder_key)
+        .map_err(|e| OpenIdError::InvalidProof(e.to_string()))?;
+
+    if proof.claim_str("aud") != Some(audience) {
+        return Err(OpenIdError::InvalidProof("wrong audience".to_string()));
+    }
+    if c_nonce.is_some() && proof.claim_str("nonce") != c_nonce {
+        return Err(OpenIdError::InvalidProof(
+            "wrong or missing nonce".to_string(),
+        ));
+    }
+    let issued_at = proof
+        .claim_i64("iat")
+        .ok_or_else(|| OpenIdError::InvalidProof("missing iat".to_string()))?;
+    if (now - issued_at).abs() > PROOF_MAX_SKEW_SECS {
+        return Err(OpenIdError::InvalidProof(
+            "iat too far from now".to_string(),
+        ));
+    }
+
+    Ok(Jwk {
+        kid: None,
+        ..holder_key
+    })
+}
+
+/// Credential endpoint: issues the SD-JWT VC for the offer the access token belongs to
+///
+/// Every credential field is selectively disclosable; the credential is bound to
+/// the key the wallet proved possession of.
+pub async fn issue_credential(
+    pool: &PgPool,
+    config: &Config,
+    access_token: &str,
+    request: &Value,
+) -> Result<Value, OpenIdError> {
+    let offer = OpenIdCredentialOffer::find_by_access_token(pool, &hash_secret(access_token))
+        .await?
+        .filter(|offer| offer.issued_at.is_none())
+        .ok_or(OpenIdError::InvalidToken)?;
+
+    let requested_type = request
+        .get("credential_configuration_id")
+        .or_else(|| request.get("format"))
+        .and_then(Value::as_str);
+    if let Some(requested) = requested_type {
+        if ![
+            CREDENTIAL_CONFIGURATION_ID,
+            CREDENTIAL_FORMAT,
+            LEGACY_CREDENTIAL_FORMAT,
+        ]
+        .contains(&requested)
+        {
+            return Err(OpenIdError::InvalidRequest(format!(
+                "unsupported credential {}",
+                requested
+            )));
+        }
+    }
+
+    let proof_jwt = request
+        .pointer("/proof/jwt")
+        .or_else(|| request.pointer("/proofs/jwt/0"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| OpenIdError::InvalidProof("missing jwt proof".to_string()))?;
+
+    let issuer = credential_issuer(config);
+    let now = Utc::now();
+    let holder_key = verify_proof(
+        proof_jwt,
+        &issuer,
+        offer.c_nonce.as_deref(),
+        now.timestamp(),
+    )?;
+
+    let card = MembershipCard::find_by_id(pool, offer.card_id)
+        .await?
+        .ok_or_else(|| OpenIdError::CredentialUnavailable("card not found".to_string()))?;
+    if card.status != CardStatus::Active || card.is_expired() {
+        return Err(OpenIdError::CredentialUnavailable(
+            "card is no longer active".to_string(),
+        ));
+    }
+
+    let (signing_key, key) = signing_keys::active_key(pool, config, Some(card.issuer_id)).await?;
+    let credential_id = Uuid::new_v4();
+    let status_index = CredentialStatusList::assign_index(pool, card.issuer_id, offer.id).await?;
+
+    let mut claims = Map::new();
+    claims.insert(
+        "iss".to_string(),
+        json!(signing_keys::issuer_did(config, card.issuer_id)),
+    );
+    claims.insert("iat".to_string(), json!(now.timestamp()));
+    if let Some(expires_at) = card.expires_at {
+        claims.insert("exp".to_string(), json!(expires_at.timestamp()));
+    }
+    claims.insert("vct".to_string(), json!(vct(&issuer)));
+    claims.insert(
+        "jti".to_string(),
+        json!(credential_uri(&issuer, credential_id)),
+    );
+    claims.insert("cnf".to_string(), json!({ "jwk": holder_key }));
+    claims.insert(
+        "status".to_string(),
+        status_list::status_claim(&issuer, card.issuer_id, status_index),
+    );
+
+    let disclosable = offer
+        .claims
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let credential = sd_jwt::issue(
+        &key,
+        json!({
+            "typ": CREDENTIAL_FORMAT,
+            "kid": signing_keys::verification_method_id(config, &signing_key),
+        }),
+        claims,
+        disclosable,
+    )?;
+    // Credentials of cards without an expiry keep the key published for good
+    signing_keys::record_signed(pool, &signing_key, card.expires_at).await?;
+
+    // Only one credential per offer, even if the wallet retries concurrently
+    if !OpenIdCredentialOffer::mark_issued(pool, offer.id, credential_id).await? {
+        return Err(OpenIdError::InvalidToken);
+    }
+
+    tracing::info!(
+        offer_id = %offer.id,
+        card_id = %offer.card_id,
+        credential_id = %credential_id,
+        "SD-JWT credential issued"
+    );
+
+    // `credential` for wallets implementing earlier drafts of OpenID4VCI
+    Ok(json!({
+        "credentials": [{ "credential": credential }],
+        "credential": credential,
+    }))
+}
+
+/// Picks the SD-JWT out of a vp_token: a DCQL response object (`{"<id>": [..]}`),
+/// a JSON string, or the bare presentation
+fn presented_credential(vp_token: &str) -> String {
+    match serde_json::from_str::<Value>(vp_token) {
+        Ok(Value::Object(by_query)) => by_query
+            .values()
+            .next()
+            .and_then(|value| match value {
+                Value::Array(presentations) => presentations.first().and_then(Value::as_str),
+                other => other.as_str(),
+            })
+            .unwrap_or_default()
+            .to_string(),
+        Ok(Value::String(presentation)) => presentation,
+        _ => vp_token.to_string(),
+    }
+}
+
+/// Verifies a presented membership credential; rejections are `PresentationRejected`
+async fn verify_presentation(
+    pool: &PgPool,
+    config: &Config,
+    request: &OpenIdPresentation,
+    presented: &str,
+) -> Result<CredentialData, OpenIdError> {
+    let rejected = |reason: &str| OpenIdError::PresentationRejected(reason.to_string());
+
+    let presentation = Presentation::parse(presented).map_err(|e| rejected(&e.to_string()))?;
+    let credential = &presentation.credential;
+
+    if !matches!(
+        credential.header_str("typ"),
+        Some(CREDENTIAL_FORMAT | LEGACY_CREDENTIAL_FORMAT)
+    ) {
+        return Err(rejected("not an SD-JWT VC"));
+    }
+
+    let issuer = credential_issuer(config);
+    let kid = credential.header_str("kid").unwrap_or_default();
+    let issuer_key = signing_keys::public_key(pool, kid)
+        .await?
+        .ok_or_else(|| rejected("credential was not signed by VPass"))?;
+    credential
+        .verify(&issuer_key.jwk)
+        .map_err(|e| rejected(&e.to_string()))?;
+
+    // Signed by the issuer's own key, or by VPass's key before issuers had keys
+    let expected_iss = match issuer_key.issuer_id {
+        Some(issuer_id) => signing_keys::issuer_did(config, issuer_id),
+        None => issuer.clone(),
+    };
+    if credential.claim_str("iss") != Some(expected_iss.as_str())
+        || credential.claim_str("vct") != Some(vct(&issuer).as_str())
+    {
+        return Err(rejected("not a VPass membership credential"));
+    }
+
+    let now = Utc::now().timestamp();
+    if credential.claim_i64("exp").is_some_and(|exp| exp <= now) {
+        return Err(rejected("credential expired"));
+    }
+
+    let client_id = verifier_client_id(&response_uri(&issuer, request.id));
+    presentation
+        .verify_key_binding(&client_id, &request.nonce, now)
+        .map_err(|e| rejected(&e.to_string()))?;
+    let disclosed = presentation
+        .disclosed_claims()
+        .map_err(|e| rejected(&e.to_string()))?;
+
+    let credential_id = credential
+        .claim_str("jti")
+        .and_then(|jti| jti.strip_prefix(&format!("{}/credentials/", issuer)))
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| rejected("credential has no VPass credential ID"))?;
+    let offer = OpenIdCredentialOffer::find_by_credential_id(pool, credential_id)
+        .await?
+        .ok_or_else(|| rejected("unknown credential"))?;
+
+    if offer.revoked_at.is_some() {
+        return Err(rejected("credential revoked"));
+    }
+    if offer.issuer_id != request.issuer_id
+        || issuer_key.issuer_id.is_some_and(|id| id != offer.issuer_id)
+    {
+        return Err(rejected("credential belongs to another channel"));
+    }
+
+    let mut claims: Vec<ClaimData> = disclosed
+        .into_iter()
+        .map(|(name, value)| ClaimData {
+            cname: name.clone(),
+            ename: name,
+            value: value
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| value.to_string()),
+        })
+        .collect();
+    // The scanner looks up the card itself; it is never disclosed by the holder
+    claims.push(ClaimData {
+        ename: "cardId".to_string(),
+        cname: "卡片 ID".to_string(),
+        value: offer.card_id.to_string(),
+    });
+
+    Ok(CredentialData {
+        credential_type: vct(&issuer),
+        claims,
+    })
+}
+
+/// Response endpoint (direct_post): verifies the wallet's vp_token and stores the
+/// result for the scanner to pick up
+pub async fn receive_presentation(
+    pool: &PgPool,
+    config: &Config,
+    presentation_id: Uuid,
+    vp_token: &str,
+    state: Option<&str>,
+) -> Result<(), OpenIdError> {
+    let request = OpenIdPresentation::find_by_id(pool, presentation_id)
+        .await?
+        .ok_or(OpenIdError::UnknownPresentation)?;
+
+    if request.completed_at.is_some() || request.is_expired() {
+        return Err(OpenIdError::PresentationClosed);
+    }
+    if state.is_some_and(|state| state != request.id.to_string()) {
+        return Err(OpenIdError::InvalidRequest("state mismatch".to_string()));
+    }
+
+    let transaction_id = request.id.to_string();
+    let result = match verify_presentation(pool, config, &request, &presented_credential(vp_token))
+        .await
+    {
+        Ok(credential) => ResultResponse {
+            verify_result: true,
+            result_description: "Credential verified".to_string(),
+            transaction_id,
+            data: Some(vec![credential]),
+        },
+        Err(OpenIdError::PresentationRejected(reason)) => {
+            tracing::warn!(presentation_id = %request.id, reason = %reason, "Presentation rejected");
+            ResultResponse {
+                verify_result: false,
+                result_description: reason,
+                transaction_id,
+                data: None,
+            }
+        }
+        Err(e) => return Err(e),
+    };
+
+    let stored = serde_json::to_value(&result).expect("result serializes");
+    if !OpenIdPresentation::complete(pool, request.id, &stored).await? {
+        return Err(OpenIdError::PresentationClosed);
+    }
+
+    tracing::info!(
+        presentation_id = %request.id,
+        verify_result = result.verify_result,
+        "Presentation response received"
+    );
+
+    Ok(())
+}
+
+/// VPass's own OpenID4VCI issuer / OpenID4VP verifier
+pub struct OpenIdWallet {
+    pool: PgPool,
+    credential_issuer: String,
+}
+
+impl OpenIdWallet {
+    pub fn new(pool: &PgPool, config: &Config) -> Self {
+        Self {
+            pool: pool.clone(),
+            credential_issuer: credential_issuer(config),
+        }
+    }
+}
+
+#[async_trait]
+impl WalletProvider for OpenIdWallet {
+    fn kind(&self) -> WalletProviderKind {
+        WalletProviderKind::OpenId
+    }
+
+    async fn check_health(&self) -> Result<(), WalletQrError> {
+        // Runs in-process; nothing to reach
+        Ok(())
+    }
+
+    async fn offer_credential(
+        &self,
+        offer: CredentialOffer<'_>,
+    ) -> Result<WalletQrResponse, WalletQrError> {
+        let claims: Map<String, Value> = offer
+            .fields
+            .into_iter()
+            .map(|field| (field.ename, Value::String(field.content)))
+            .collect();
+
+        let code = random_secret();
+        let row = OpenIdCredentialOffer::create(
+            &self.pool,
+            offer.card.id,
+            offer.issuer.id,
+            &Value::Object(claims),
+            &hash_secret(&code),
+            Utc::now() + Duration::minutes(OFFER_TTL_MINUTES),
+        )
+        .await?;
+
+        let credential_offer = json!({
+            "credential_issuer": self.credential_issuer,
+            "credential_configuration_ids": [CREDENTIAL_CONFIGURATION_ID],
+            "grants": {
+                PRE_AUTHORIZED_GRANT: { "pre-authorized_code": code }
+            }
+        });
+        let deep_link = format!(
+            "openid-credential-offer://?{}",
+            url::form_urlencoded::Serializer::new(String::new())
+                .append_pair("credential_offer", &credential_offer.to_string())
+                .finish()
+        );
+        let qr_code =
+            qr_data_url(&deep_link).map_err(|e| WalletQrError::ApiError(e.to_string()))?;
+
+        tracing::info!(offer_id = %row.id, card_id = %offer.card.id, "OpenID4VCI credential offer created");
+
+        Ok(WalletQrResponse {
+            transaction_id: row.id.to_string(),
+            qr_code,
+            deep_link,
+        })
+    }
+
+    async fn poll_issuance(&self, transaction_id: &str) -> Result<IssuedCredential, WalletQrError> {
+        let offer = match Uuid::parse_str(transaction_id) {
+            Ok(id) => OpenIdCredentialOffer::find_by_id(&self.pool, id).await?,
+            Err(_) => None,
+        }
+        .ok_or_else(|| WalletQrError::ApiError(format!("Unknown offer {}", transaction_id)))?;
+
+        offer
+            .credential_id
+            .map(|id| IssuedCredential {
+                cid: id.to_string(),
+            })
+            .ok_or(WalletQrError::CredentialNotReady)
+    }
+
+    async fn revoke(&self, cid: &str) -> Result<(), WalletQrError> {
+        let credential_id = Uuid::parse_str(cid)
+            .map_err(|_| WalletQrError::ApiError(format!("Invalid credential ID {}", cid)))?;
+        OpenIdCredentialOffer::revoke(&self.pool, credential_id).await?;
+
+        tracing::info!(cid = %cid, "Credential revoked");
+        Ok(())
+    }
+
+    async fn request_presentation(
+        &self,
+        request: PresentationRequest<'_>,
+    ) -> Result<QrCodeResponse, OidvpError> {
+        let nonce = random_secret();
+        let row = OpenIdPresentation::create(
+            &self.pool,
+            request.event.id,
+            request.issuer.id,
+            &nonce,
+            Utc::now() + Duration::seconds(PRESENTATION_TTL_SECS),
+        )
+        .await?;
+
+        // Ask for every field of the channel's credential schema
+        let schema = IssuerCredentialField::list_by_issuer(&self.pool, request.issuer.id).await?;
+        let claims: Vec<Value> = credential_schema::mappings_for_issuer(&schema)
+            .into_iter()
+            .map(|mapping| json!({ "path": [mapping.ename] }))
+            .collect();
+        let dcql_query = json!({
+            "credentials": [{
+                "id": CREDENTIAL_CONFIGURATION_ID,
+                "format": CREDENTIAL_FORMAT,
+                "meta": { "vct_values": [vct(&self.credential_issuer)] },
+                "claims": claims,
+            }]
+        });
+
+        let response_uri = response_uri(&self.credential_issuer, row.id);
+        let auth_uri = format!(
+            "openid4vp://?{}",
+            url::form_urlencoded::Serializer::new(String::new())
+                .append_pair("client_id", &verifier_client_id(&response_uri))
+                .append_pair("response_type", "vp_token")
+                .append_pair("response_mode", "direct_post")
+                .append_pair("response_uri", &response_uri)
+                .append_pair("nonce", &nonce)
+                .append_pair("state", &row.id.to_string())
+                .append_pair("dcql_query", &dcql_query.to_string())
+                .finish()
+        );
+        let qrcode_image =
+            qr_data_url(&auth_uri).map_err(|e| OidvpError::ApiError(e.to_string()))?;
+
+        Ok(QrCodeResponse {
+            transaction_id: row.id.to_string(),
+            qrcode_image,
+            auth_uri,
+        })
+    }
+
+    async fn fetch_result(&self, transaction_id: &str) -> Result<ResultResponse, OidvpError> {
+        let request = match Uuid::parse_str(transaction_id) {
+            Ok(id) => OpenIdPresentation::find_by_id(&self.pool, id).await?,
+            Err(_) => None,
+        }
+        .ok_or_else(|| OidvpError::ApiError(format!("Unknown transaction {}", transaction_id)))?;
+
+        match request.result {
+            Some(result) => serde_json::from_value(result)
+                .map_err(|e| OidvpError::ApiError(format!("Stored result unreadable: {}", e))),
+            None if request.is_expired() => Err(OidvpError::Expired),
+            None => Err(OidvpError::NotReady),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::jose::Es256Key;
+
+    const NOW: i64 = 1_765_000_000;
+
+    fn holder_proof(holder: &Es256Key, audience: &str, nonce: &str, iat: i64) -> String {
+        holder
+            .sign(
+                json!({ "typ": PROOF_TYP, "jwk": holder.public_jwk() }),
+                &json!({ "aud": audience, "nonce": nonce, "iat": iat }),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let holder = Es256Key::from_pkcs8(&Es256Key::generate_pkcs8().unwrap()).unwrap();
+        let proof = holder_proof(&holder, "https://vpass.example", "n-1", NOW);
+
+        let key = verify_proof(&proof, "https://vpass.example", Some("n-1"), NOW).unwrap();
+        assert_eq!(key, holder.public_jwk());
+
+        assert!(verify_proof(&proof, "https://other.example", Some("n-1"), NOW).is_err());
+        assert!(verify_proof(&proof, "https://vpass.example", Some("n-2"), NOW).is_err());
+        assert!(verify_proof(&proof, "https://vpass.example", Some("n-1"), NOW + 3600).is_err());
+    }
+
+    #[test]
+    fn test_presented_credential() {
+        assert_eq!(presented_credential("a.b.c~d~"), "a.b.c~d~");
+        assert_eq!(presented_credential(r#""a.b.c~""#), "a.b.c~");
+        assert_eq!(
+            presented_credential(r#"{"vpass_membership": ["a.b.c~k"]}"#),
+            "a.b.c~k"
+        );
+    }
+
+    #[test]
+    fn test_hash_secret_is_stable() {
+        assert_eq!(hash_secret("code"), hash_secret("code"));
+        assert_ne!(hash_secret("code"), hash_secret("other"));
+        assert_eq!(hash_secret("code").len(), 64);
+    }
+}
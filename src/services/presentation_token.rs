@@ -0,0 +1,394 @@
+use chrono::{DateTime, Duration, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{
+    card::MembershipCard,
+    presentation_token_nonce::PresentationTokenNonce,
+    printed_card_token::{PrintedCardToken, PrintedTokenUse},
+};
+use crate::services::jose::{b64url, DecodedJwt, JoseError};
+use crate::services::signing_keys::{self, SigningKeyError};
+
+/// `typ` of a card presentation token
+pub const PRESENTATION_TOKEN_TYP: &str = "vpass-card+jwt";
+
+/// How long a presentation token can be scanned; the member's page rotates it sooner
+pub const PRESENTATION_TOKEN_TTL_SECS: i64 = 60;
+
+/// How long the token on a printed card can be scanned; printing the card again
+/// revokes it sooner
+pub const PRINTED_TOKEN_TTL_DAYS: i64 = 180;
+
+/// How long used nonces are kept after their token expired; offline scans uploaded
+/// within this window are still checked for reuse
+pub const NONCE_RETENTION_DAYS: i64 = 7;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PresentationTokenError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("Signing key error: {0}")]
+    SigningKey(#[from] SigningKeyError),
+
+    #[error("Invalid token: {0}")]
+    Jose(#[from] JoseError),
+
+    #[error("Not a VPass card token")]
+    WrongType,
+
+    #[error("Token claim `{0}` is missing or invalid")]
+    InvalidClaim(&'static str),
+
+    #[error("Token signed by an unknown key")]
+    UnknownKey,
+
+    #[error("Token expired")]
+    Expired,
+
+    #[error("Token was issued for a channel the event does not accept")]
+    WrongIssuer,
+
+    #[error("Token was already used")]
+    AlreadyUsed,
+
+    #[error("Printed card was replaced by a newer print")]
+    Revoked,
+}
+
+/// A token signed for a card, shown as a QR code
+#[derive(Debug, Clone)]
+pub struct PresentationToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Claims of a token whose signature and freshness have been checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresentationClaims {
+    pub card_id: Uuid,
+    pub issuer_id: Uuid,
+    pub nonce: String,
+    /// Printed on a card: the nonce is the print's serial, used once per event
+    pub printed: bool,
+    pub expires_at: DateTime<Utc>,
+}
+
+fn random_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator available");
+    b64url(&bytes)
+}
+
+/// Claims are kept short: the token has to fit a QR code scanned off a phone screen
+fn token_claims(
+    card_id: Uuid,
+    issuer_id: Uuid,
+    nonce: &str,
+    printed: bool,
+    now: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Value {
+    let mut claims = json!({
+        "sub": card_id,
+        "iid": issuer_id,
+        "nonce": nonce,
+        "iat": now.timestamp(),
+        "exp": expires_at.timestamp(),
+    });
+    if printed {
+        claims["prt"] = true.into();
+    }
+
+    claims
+}
+
+/// Signs a token for a card with its issuer's key
+async fn sign(
+    pool: &PgPool,
+    config: &Config,
+    card: &MembershipCard,
+    nonce: &str,
+    printed: bool,
+    ttl: Duration,
+) -> Result<PresentationToken, PresentationTokenError> {
+    let (signing_key, key) = signing_keys::active_key(pool, config, Some(card.issuer_id)).await?;
+    let now = Utc::now();
+    let expires_at = now + ttl;
+    let claims = token_claims(card.id, card.issuer_id, nonce, printed, now, expires_at);
+
+    let token = key.sign(
+        json!({
+            "typ": PRESENTATION_TOKEN_TYP,
+            "kid": signing_key.kid,
+        }),
+        &claims,
+    )?;
+    signing_keys::record_signed(pool, &signing_key, Some(expires_at)).await?;
+
+    Ok(PresentationToken { token, expires_at })
+}
+
+/// Signs a single-use presentation token for a card, shown on the member's page
+pub async fn issue(
+    pool: &PgPool,
+    config: &Config,
+    card: &MembershipCard,
+) -> Result<PresentationToken, PresentationTokenError> {
+    let nonce = random_nonce();
+    sign(
+        pool,
+        config,
+        card,
+        &nonce,
+        false,
+        Duration::seconds(PRESENTATION_TOKEN_TTL_SECS),
+    )
+    .await
+}
+
+/// Signs the token printed on a card
+///
+/// A printed card is shown again at every event, so its nonce is a serial that
+/// can be used once per event. Printing the card again revokes the previous
+/// serial, so a lost or copied print stops working.
+pub async fn issue_printed(
+    pool: &PgPool,
+    config: &Config,
+    card: &MembershipCard,
+) -> Result<PresentationToken, PresentationTokenError> {
+    let serial = random_nonce();
+    let token = sign(
+        pool,
+        config,
+        card,
+        &serial,
+        true,
+        Duration::days(PRINTED_TOKEN_TTL_DAYS),
+    )
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    PrintedCardToken::create(&mut tx, card.id, &serial).await?;
+    tx.commit().await?;
+
+    Ok(token)
+}
+
+/// Reads the claims of a decoded token, rejecting other token types and expired tokens
+fn check_claims(
+    jwt: &DecodedJwt,
+    now: DateTime<Utc>,
+) -> Result<PresentationClaims, PresentationTokenError> {
+    if jwt.header_str("typ") != Some(PRESENTATION_TOKEN_TYP) {
+        return Err(PresentationTokenError::WrongType);
+    }
+
+    let uuid_claim = |name: &'static str| {
+        jwt.claim_str(name)
+            .and_then(|value| Uuid::parse_str(value).ok())
+            .ok_or(PresentationTokenError::InvalidClaim(name))
+    };
+    let card_id = uuid_claim("sub")?;
+    let issuer_id = uuid_claim("iid")?;
+    let nonce = jwt
+        .claim_str("nonce")
+        .filter(|nonce| !nonce.is_empty() && nonce.len() <= 64)
+        .ok_or(PresentationTokenError::InvalidClaim("nonce"))?
+        .to_string();
+    let printed = match jwt.claims.get("prt") {
+        None => false,
+        Some(printed) => printed
+            .as_bool()
+            .ok_or(PresentationTokenError::InvalidClaim("prt"))?,
+    };
+    let expires_at = jwt
+        .claim_i64("exp")
+        .and_then(|exp| DateTime::from_timestamp(exp, 0))
+        .ok_or(PresentationTokenError::InvalidClaim("exp"))?;
+
+    if expires_at <= now {
+        return Err(PresentationTokenError::Expired);
+    }
+
+    Ok(PresentationClaims {
+        card_id,
+        issuer_id,
+        nonce,
+        printed,
+        expires_at,
+    })
+}
+
+/// Checks a token scanned at `scanned_at` for an event accepting the cards of
+/// `issuer_ids`
+///
+/// The signature must come from a key of the issuer named in the token, so a
+/// token cannot be forged for another channel's card.
+pub async fn authenticate(
+    pool: &PgPool,
+    token: &str,
+    issuer_ids: &[Uuid],
+    scanned_at: DateTime<Utc>,
+) -> Result<PresentationClaims, PresentationTokenError> {
+    let jwt = DecodedJwt::decode(token.trim())?;
+    let claims = check_claims(&jwt, scanned_at)?;
+
+    let kid = jwt
+        .header_str("kid")
+        .ok_or(PresentationTokenError::UnknownKey)?;
+    let key = signing_keys::public_key(pool, kid)
+        .await?
+        .filter(|key| key.issuer_id == Some(claims.issuer_id))
+        .ok_or(PresentationTokenError::UnknownKey)?;
+    jwt.verify(&key.jwk)?;
+
+    if !issuer_ids.contains(&claims.issuer_id) {
+        return Err(PresentationTokenError::WrongIssuer);
+    }
+
+    Ok(claims)
+}
+
+/// Records a token's nonce, failing if the token was already used
+///
+/// A printed card's serial is recorded against `event_id` instead, failing if it
+/// was already used at the event or the card was printed again since.
+pub async fn consume(
+    pool: &PgPool,
+    claims: &PresentationClaims,
+    event_id: Uuid,
+) -> Result<(), PresentationTokenError> {
+    if claims.printed {
+        return match PrintedCardToken::use_at_event(pool, &claims.nonce, claims.card_id, event_id)
+            .await?
+        {
+            PrintedTokenUse::Accepted => Ok(()),
+            PrintedTokenUse::AlreadyUsed => Err(PresentationTokenError::AlreadyUsed),
+            PrintedTokenUse::Revoked => Err(PresentationTokenError::Revoked),
+        };
+    }
+
+    let purge_before = Utc::now() - Duration::days(NONCE_RETENTION_DAYS);
+    if !PresentationTokenNonce::consume(
+        pool,
+        &claims.nonce,
+        claims.card_id,
+        claims.expires_at,
+        purge_before,
+    )
+    .await?
+    {
+        return Err(PresentationTokenError::AlreadyUsed);
+    }
+
+    Ok(())
+}
+
+/// Verifies a token scanned now at an event and uses it up, so a screenshot of
+/// an already scanned QR code is rejected
+pub async fn verify(
+    pool: &PgPool,
+    token: &str,
+    issuer_ids: &[Uuid],
+    event_id: Uuid,
+) -> Result<PresentationClaims, PresentationTokenError> {
+    let claims = authenticate(pool, token, issuer_ids, Utc::now()).await?;
+    consume(pool, &claims, event_id).await?;
+
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::jose::Es256Key;
+
+    fn sign(typ: &str, claims: &Value) -> DecodedJwt {
+        let key = Es256Key::from_pkcs8(&Es256Key::generate_pkcs8().unwrap()).unwrap();
+        let token = key.sign(json!({ "typ": typ }), claims).unwrap();
+        DecodedJwt::decode(&token).unwrap()
+    }
+
+    #[test]
+    fn test_check_claims() {
+        let (card_id, issuer_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+        let expires_at = now + Duration::seconds(PRESENTATION_TOKEN_TTL_SECS);
+        let jwt = sign(
+            PRESENTATION_TOKEN_TYP,
+            &token_claims(card_id, issuer_id, "n0nce", false, now, expires_at),
+        );
+
+        let claims = check_claims(&jwt, now).unwrap();
+        assert_eq!(claims.card_id, card_id);
+        assert_eq!(claims.issuer_id, issuer_id);
+        assert_eq!(claims.nonce, "n0nce");
+        assert!(!claims.printed);
+
+        assert!(matches!(
+            check_claims(&jwt, expires_at),
+            Err(PresentationTokenError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_check_claims_of_printed_token() {
+        let (card_id, issuer_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+        let expires_at = now + Duration::days(PRINTED_TOKEN_TTL_DAYS);
+        let jwt = sign(
+            PRESENTATION_TOKEN_TYP,
+            &token_claims(card_id, issuer_id, "serial", true, now, expires_at),
+        );
+
+        let claims = check_claims(&jwt, now + Duration::days(30)).unwrap();
+        assert_eq!(claims.card_id, card_id);
+        assert_eq!(claims.nonce, "serial");
+        assert!(claims.printed);
+
+        // A printed token still needs its serial
+        let without_serial = sign(
+            PRESENTATION_TOKEN_TYP,
+            &json!({ "sub": card_id, "iid": issuer_id, "prt": true, "exp": expires_at.timestamp() }),
+        );
+        assert!(matches!(
+            check_claims(&without_serial, now),
+            Err(PresentationTokenError::InvalidClaim("nonce"))
+        ));
+    }
+
+    #[test]
+    fn test_check_claims_rejects_other_tokens() {
+        let card_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let credential = sign(
+            "vc+sd-jwt",
+            &token_claims(
+                card_id,
+                Uuid::new_v4(),
+                "n0nce",
+                false,
+                now,
+                now + Duration::seconds(PRESENTATION_TOKEN_TTL_SECS),
+            ),
+        );
+        assert!(matches!(
+            check_claims(&credential, now),
+            Err(PresentationTokenError::WrongType)
+        ));
+
+        let unsigned_card = sign(PRESENTATION_TOKEN_TYP, &json!({ "card_id": card_id }));
+        assert!(matches!(
+            check_claims(&unsigned_card, now),
+            Err(PresentationTokenError::InvalidClaim("sub"))
+        ));
+    }
+}
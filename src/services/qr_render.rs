@@ -0,0 +1,394 @@
+use std::fmt::Write as _;
+use std::io::Cursor;
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, Utc};
+use image::{GrayImage, Luma};
+use qrcode::{Color, EcLevel, QrCode};
+
+/// Pixels per QR module when no size is requested
+pub const DEFAULT_MODULE_SIZE: u32 = 8;
+pub const MAX_MODULE_SIZE: u32 = 32;
+
+/// Quiet zone in modules; the QR spec asks for 4
+pub const DEFAULT_MARGIN: u32 = 4;
+pub const MAX_MARGIN: u32 = 16;
+
+/// Card face size in SVG user units (ID-1 card aspect ratio, 85.6 x 54 mm)
+const CARD_WIDTH: u32 = 856;
+const CARD_HEIGHT: u32 = 540;
+/// PNG card faces are rendered at this multiple of the SVG size (~300 dpi)
+const CARD_PNG_SCALE: f32 = 1.5;
+
+const CARD_FONT_FAMILY: &str =
+    "'Noto Sans CJK TC', 'Noto Sans TC', 'Noto Sans CJK JP', 'PingFang TC', 'Microsoft JhengHei', sans-serif";
+
+/// Installed fonts used for the generic families, in order of preference
+const SANS_SERIF_FALLBACKS: &[&str] = &[
+    "Noto Sans CJK TC",
+    "Noto Sans TC",
+    "Noto Sans CJK JP",
+    "DejaVu Sans",
+    "Liberation Sans",
+];
+const MONOSPACE_FALLBACKS: &[&str] = &[
+    "Noto Sans Mono CJK TC",
+    "DejaVu Sans Mono",
+    "Liberation Mono",
+];
+
+#[derive(thiserror::Error, Debug)]
+pub enum QrRenderError {
+    #[error("QR encoding failed: {0}")]
+    Encode(#[from] qrcode::types::QrError),
+
+    #[error("PNG encoding failed: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("Card rendering failed: {0}")]
+    Render(String),
+}
+
+/// Size options for a rendered QR code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QrRenderOptions {
+    /// Pixels per module
+    pub module_size: u32,
+    /// Quiet zone around the code, in modules
+    pub margin: u32,
+}
+
+impl Default for QrRenderOptions {
+    fn default() -> Self {
+        Self {
+            module_size: DEFAULT_MODULE_SIZE,
+            margin: DEFAULT_MARGIN,
+        }
+    }
+}
+
+impl QrRenderOptions {
+    /// Builds options from user input, keeping values within sane bounds
+    pub fn clamped(module_size: Option<u32>, margin: Option<u32>) -> Self {
+        Self {
+            module_size: module_size
+                .unwrap_or(DEFAULT_MODULE_SIZE)
+                .clamp(1, MAX_MODULE_SIZE),
+            margin: margin.unwrap_or(DEFAULT_MARGIN).min(MAX_MARGIN),
+        }
+    }
+}
+
+/// What goes on a printable card face
+#[derive(Debug, Clone)]
+pub struct CardFace<'a> {
+    pub channel_name: &'a str,
+    pub tier_label: &'a str,
+    pub member_name: &'a str,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub card_id: &'a str,
+    /// Content of the QR code printed on the card
+    pub qr_data: &'a str,
+}
+
+/// Encoded QR modules, row by row
+struct QrMatrix {
+    width: u32,
+    modules: Vec<Color>,
+}
+
+impl QrMatrix {
+    fn encode(data: &str) -> Result<Self, QrRenderError> {
+        // Medium error correction survives print smudges and phone screen glare
+        let code = QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)?;
+
+        Ok(Self {
+            width: code.width() as u32,
+            modules: code.to_colors(),
+        })
+    }
+
+    fn dark_modules(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.modules
+            .iter()
+            .enumerate()
+            .filter(|(_, color)| **color == Color::Dark)
+            .map(|(i, _)| (i as u32 % self.width, i as u32 / self.width))
+    }
+
+    /// Appends the dark modules as one SVG path, one unit per module, offset by `margin`
+    fn write_svg_path(&self, out: &mut String, margin: u32) {
+        out.push_str(r##"<path fill="#000000" shape-rendering="crispEdges" d=""##);
+        for (x, y) in self.dark_modules() {
+            let _ = write!(out, "M{} {}h1v1h-1z", x + margin, y + margin);
+        }
+        out.push_str(r#""/>"#);
+    }
+}
+
+/// Renders `data` as a standalone SVG QR code
+pub fn render_qr_svg(data: &str, options: QrRenderOptions) -> Result<String, QrRenderError> {
+    let matrix = QrMatrix::encode(data)?;
+    let units = matrix.width + 2 * options.margin;
+    let pixels = units * options.module_size;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r##"<?xml version="1.0" encoding="UTF-8"?><svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{pixels}" height="{pixels}" viewBox="0 0 {units} {units}"><rect width="{units}" height="{units}" fill="#ffffff"/>"##,
+    );
+    matrix.write_svg_path(&mut svg, options.margin);
+    svg.push_str("</svg>");
+
+    Ok(svg)
+}
+
+/// Renders `data` as a grayscale PNG QR code
+pub fn render_qr_png(data: &str, options: QrRenderOptions) -> Result<Vec<u8>, QrRenderError> {
+    let matrix = QrMatrix::encode(data)?;
+    let size = options.module_size;
+    let pixels = (matrix.width + 2 * options.margin) * size;
+
+    let mut img = GrayImage::from_pixel(pixels, pixels, Luma([255]));
+    for (x, y) in matrix.dark_modules() {
+        let left = (x + options.margin) * size;
+        let top = (y + options.margin) * size;
+        for py in top..top + size {
+            for px in left..left + size {
+                img.put_pixel(px, py, Luma([0]));
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png)?;
+
+    Ok(png)
+}
+
+/// Renders a printable card face as SVG
+///
+/// Text is left as SVG text, so the viewer's fonts are used.
+pub fn render_card_svg(face: &CardFace<'_>) -> Result<String, QrRenderError> {
+    let matrix = QrMatrix::encode(face.qr_data)?;
+
+    let qr_size = 300;
+    let qr_x = CARD_WIDTH - qr_size - 48;
+    let qr_y = 168;
+    // Two modules of quiet zone; the white panel behind the code adds the rest
+    let qr_units = matrix.width + 4;
+
+    let expiry = face
+        .expires_at
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "無期限".to_string());
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r##"<?xml version="1.0" encoding="UTF-8"?><svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="{font}">
+<rect x="1" y="1" width="{iw}" height="{ih}" rx="32" fill="#ffffff" stroke="#d7dce3" stroke-width="2"/>
+<path d="M1 33a32 32 0 0 1 32-32h{band_w}a32 32 0 0 1 32 32v103H1z" fill="#1e293b"/>
+<text x="48" y="82" font-size="40" font-weight="700" fill="#ffffff">{channel}</text>
+<text x="48" y="118" font-size="22" fill="#cbd5e1">YouTube 會員卡</text>
+<text x="48" y="208" font-size="20" fill="#64748b">會員</text>
+<text x="48" y="246" font-size="32" font-weight="700" fill="#0f172a">{member}</text>
+<text x="48" y="304" font-size="20" fill="#64748b">等級</text>
+<text x="48" y="340" font-size="28" fill="#0f172a">{tier}</text>
+<text x="48" y="398" font-size="20" fill="#64748b">有效期限</text>
+<text x="48" y="434" font-size="28" fill="#0f172a">{expiry}</text>
+<text x="48" y="500" font-size="14" fill="#94a3b8" font-family="monospace">{card_id}</text>
+<rect x="{panel_x}" y="{panel_y}" width="{panel}" height="{panel}" rx="12" fill="#ffffff" stroke="#e2e8f0" stroke-width="2"/>
+<svg x="{qr_x}" y="{qr_y}" width="{qr_size}" height="{qr_size}" viewBox="0 0 {qr_units} {qr_units}">"##,
+        w = CARD_WIDTH,
+        h = CARD_HEIGHT,
+        iw = CARD_WIDTH - 2,
+        ih = CARD_HEIGHT - 2,
+        band_w = CARD_WIDTH - 2 - 64,
+        font = CARD_FONT_FAMILY,
+        channel = escape_xml(&truncate(face.channel_name, 28)),
+        member = escape_xml(&truncate(face.member_name, 14)),
+        tier = escape_xml(&truncate(face.tier_label, 14)),
+        expiry = expiry,
+        card_id = escape_xml(face.card_id),
+        panel_x = qr_x - 12,
+        panel_y = qr_y - 12,
+        panel = qr_size + 24,
+    );
+    matrix.write_svg_path(&mut svg, 2);
+    svg.push_str("</svg>\n</svg>");
+
+    Ok(svg)
+}
+
+/// Renders a printable card face as PNG
+///
+/// Text is drawn with the fonts installed on the server; CJK names need a CJK
+/// font such as Noto Sans CJK to be present. Rasterising (and loading the fonts
+/// on first use) is CPU and disk bound, so it runs on the blocking thread pool.
+pub async fn render_card_png(face: &CardFace<'_>) -> Result<Vec<u8>, QrRenderError> {
+    let svg = render_card_svg(face)?;
+
+    tokio::task::spawn_blocking(move || rasterize_card(&svg))
+        .await
+        .map_err(|e| QrRenderError::Render(e.to_string()))?
+}
+
+fn rasterize_card(svg: &str) -> Result<Vec<u8>, QrRenderError> {
+    let options = resvg::usvg::Options {
+        fontdb: system_fonts(),
+        ..Default::default()
+    };
+    let tree = resvg::usvg::Tree::from_str(svg, &options)
+        .map_err(|e| QrRenderError::Render(e.to_string()))?;
+
+    let width = (CARD_WIDTH as f32 * CARD_PNG_SCALE) as u32;
+    let height = (CARD_HEIGHT as f32 * CARD_PNG_SCALE) as u32;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| QrRenderError::Render("Invalid card size".to_string()))?;
+
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::from_scale(CARD_PNG_SCALE, CARD_PNG_SCALE),
+        &mut pixmap.as_mut(),
+    );
+
+    pixmap
+        .encode_png()
+        .map_err(|e| QrRenderError::Render(e.to_string()))
+}
+
+/// Loads the fonts used for card faces, so the first card printed does not wait
+/// for the system font scan
+pub fn preload_fonts() {
+    system_fonts();
+}
+
+/// System fonts, loaded once on first use
+fn system_fonts() -> Arc<resvg::usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<resvg::usvg::fontdb::Database>> = OnceLock::new();
+
+    FONTS
+        .get_or_init(|| {
+            let mut db = resvg::usvg::fontdb::Database::new();
+            db.load_system_fonts();
+
+            // fontdb maps the generic families to Windows fonts; point them at installed ones
+            if let Some(family) = first_installed(&db, SANS_SERIF_FALLBACKS) {
+                db.set_sans_serif_family(family);
+            }
+            if let Some(family) = first_installed(&db, MONOSPACE_FALLBACKS) {
+                db.set_monospace_family(family);
+            }
+
+            tracing::debug!(faces = db.len(), "Loaded fonts for card rendering");
+            Arc::new(db)
+        })
+        .clone()
+}
+
+fn first_installed(db: &resvg::usvg::fontdb::Database, candidates: &[&str]) -> Option<String> {
+    candidates
+        .iter()
+        .find(|candidate| {
+            db.faces()
+                .any(|face| face.families.iter().any(|(name, _)| name == *candidate))
+        })
+        .map(|family| family.to_string())
+}
+
+/// Shortens text so it stays clear of the QR code (sized for full-width CJK glyphs)
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max_chars - 1).collect();
+    short.push('…');
+    short
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_clamped() {
+        assert_eq!(
+            QrRenderOptions::clamped(None, None),
+            QrRenderOptions::default()
+        );
+
+        let options = QrRenderOptions::clamped(Some(0), Some(100));
+        assert_eq!(options.module_size, 1);
+        assert_eq!(options.margin, MAX_MARGIN);
+    }
+
+    #[test]
+    fn test_qr_png_dimensions() {
+        let options = QrRenderOptions {
+            module_size: 4,
+            margin: 2,
+        };
+        let png = render_qr_png("https://example.com", options).unwrap();
+
+        let img = image::load_from_memory(&png).unwrap();
+        // Version 2 code: 25 modules, plus the margin on both sides
+        assert_eq!(img.width(), (25 + 4) * 4);
+        assert_eq!(img.height(), img.width());
+    }
+
+    #[test]
+    fn test_card_svg_escapes_text() {
+        let face = CardFace {
+            channel_name: "Tom & Jerry <Official>",
+            tier_label: "會員",
+            member_name: "小明",
+            expires_at: None,
+            card_id: "card",
+            qr_data: r#"{"card_id":"card"}"#,
+        };
+        let svg = render_card_svg(&face).unwrap();
+
+        assert!(svg.contains("Tom &amp; Jerry &lt;Official&gt;"));
+        assert!(svg.contains("無期限"));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("短名稱", 14), "短名稱");
+        assert_eq!(truncate("一二三四五", 3), "一二…");
+    }
+
+    #[tokio::test]
+    async fn test_card_png_renders() {
+        let face = CardFace {
+            channel_name: "Channel",
+            tier_label: "Member",
+            member_name: "Someone",
+            expires_at: Some(Utc::now()),
+            card_id: "card",
+            qr_data: "data",
+        };
+        let png = render_card_png(&face).await.unwrap();
+
+        let img = image::load_from_memory(&png).unwrap();
+        assert_eq!(img.width(), (CARD_WIDTH as f32 * CARD_PNG_SCALE) as u32);
+    }
+}
@@ -0,0 +1,160 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{card::MembershipCard, event::Event, event_reservation::EventReservation};
+use crate::services::event_access::{AccessDecision, EventAccess};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReservationError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("Event is no longer active")]
+    EventInactive,
+
+    #[error("No valid card accepted by this event")]
+    NoEligibleCard,
+}
+
+/// Whether an event with `occupancy` spots taken has room for one more
+pub fn has_room(capacity: Option<i32>, occupancy: i64) -> bool {
+    capacity.is_none_or(|capacity| occupancy < capacity as i64)
+}
+
+/// The member's valid card the event accepts, if any
+pub async fn eligible_card(
+    pool: &PgPool,
+    event: &Event,
+    member_id: Uuid,
+) -> Result<Option<MembershipCard>, sqlx::Error> {
+    let access = EventAccess::load(pool, event).await?;
+
+    for issuer_id in access.accepted_issuers() {
+        let cards = MembershipCard::find_active_unexpired_cards(pool, issuer_id, member_id).await?;
+        if let Some(card) = cards
+            .into_iter()
+            .find(|card| access.check(card) == AccessDecision::Allowed)
+        {
+            return Ok(Some(card));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Reserves a spot for a member, or puts them on the waitlist when the event is full
+///
+/// A member who already has an open RSVP gets it back unchanged.
+pub async fn reserve(
+    pool: &PgPool,
+    event: &Event,
+    member_id: Uuid,
+) -> Result<EventReservation, ReservationError> {
+    if !event.is_active {
+        return Err(ReservationError::EventInactive);
+    }
+
+    let card = eligible_card(pool, event, member_id)
+        .await?
+        .ok_or(ReservationError::NoEligibleCard)?;
+
+    let mut tx = pool.begin().await?;
+    EventReservation::lock_capacity(&mut tx, event.id).await?;
+
+    if let Some(existing) =
+        EventReservation::find_open_for_member(&mut *tx, event.id, member_id).await?
+    {
+        return Ok(existing);
+    }
+
+    let occupancy = EventReservation::occupancy(&mut *tx, event.id).await?;
+    let status = if has_room(event.capacity, occupancy) {
+        "reserved"
+    } else {
+        "waitlisted"
+    };
+    let reservation =
+        EventReservation::create(&mut *tx, event.id, member_id, card.id, status).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        event_id = %event.id,
+        reservation_id = %reservation.id,
+        status,
+        "RSVP recorded"
+    );
+
+    Ok(reservation)
+}
+
+/// Cancels a member's open RSVP, handing a freed spot to the waitlist
+/// Returns false if the member had no open RSVP.
+pub async fn cancel(pool: &PgPool, event: &Event, member_id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    EventReservation::lock_capacity(&mut tx, event.id).await?;
+
+    let Some(reservation) =
+        EventReservation::find_open_for_member(&mut *tx, event.id, member_id).await?
+    else {
+        return Ok(false);
+    };
+
+    EventReservation::cancel(&mut *tx, reservation.id).await?;
+    if reservation.is_reserved() {
+        promote_waitlist(&mut tx, event).await?;
+    }
+    tx.commit().await?;
+
+    tracing::info!(event_id = %event.id, reservation_id = %reservation.id, "RSVP cancelled");
+
+    Ok(true)
+}
+
+/// Moves waitlisted RSVPs to reserved, oldest first, while the event has room
+/// The caller holds the event's capacity lock.
+pub async fn promote_waitlist(
+    conn: &mut PgConnection,
+    event: &Event,
+) -> Result<usize, sqlx::Error> {
+    let mut promoted = 0;
+
+    while has_room(
+        event.capacity,
+        EventReservation::occupancy(&mut *conn, event.id).await?,
+    ) {
+        let Some(reservation) = EventReservation::promote_next(&mut *conn, event.id).await? else {
+            break;
+        };
+        tracing::info!(
+            event_id = %event.id,
+            reservation_id = %reservation.id,
+            "Promoted RSVP from waitlist"
+        );
+        promoted += 1;
+    }
+
+    Ok(promoted)
+}
+
+/// Promotes the waitlist after an event's capacity changed
+pub async fn capacity_changed(pool: &PgPool, event: &Event) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    EventReservation::lock_capacity(&mut tx, event.id).await?;
+    let promoted = promote_waitlist(&mut tx, event).await?;
+    tx.commit().await?;
+
+    Ok(promoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_room() {
+        assert!(has_room(None, 10_000));
+        assert!(has_room(Some(100), 99));
+        assert!(!has_room(Some(100), 100));
+        assert!(!has_room(Some(1), 2));
+    }
+}
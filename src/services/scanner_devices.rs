@@ -0,0 +1,142 @@
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::card::MembershipCard;
+use crate::models::scanner_device::{CreateScannerDeviceData, ScannerDevice};
+use crate::services::jose::b64url;
+
+/// Header a paired scanner sends its device token in
+pub const DEVICE_TOKEN_HEADER: &str = "x-scanner-device";
+
+/// A device just registered, with the token it pairs with (shown once)
+#[derive(Debug, Clone)]
+pub struct RegisteredDevice {
+    pub device: ScannerDevice,
+    pub token: String,
+}
+
+/// Who is operating a scanner: a paired device, a signed-in member, or both
+#[derive(Debug, Clone, Default)]
+pub struct ScannerIdentity {
+    pub device: Option<ScannerDevice>,
+    pub member_id: Option<Uuid>,
+}
+
+impl ScannerIdentity {
+    /// Device and operator recorded in a scan's `verification_context`
+    pub fn context(&self) -> Value {
+        json!({
+            "device": self.device.as_ref().map(|device| json!({
+                "id": device.id,
+                "name": device.name,
+            })),
+            "operator": {
+                "name": self.device.as_ref().and_then(|device| device.operator_name.clone()),
+                "member_id": self.member_id,
+            },
+        })
+    }
+
+    /// Whether the scanner is operated by the card's own holder, who must not
+    /// admit themselves
+    pub fn is_card_holder(&self, card: &MembershipCard) -> bool {
+        self.member_id == Some(card.member_id)
+    }
+
+    /// Adds device and operator to a scan's `verification_context`
+    pub fn annotate(&self, mut context: Value) -> Value {
+        if let (Some(target), Value::Object(identity)) = (context.as_object_mut(), self.context()) {
+            target.extend(identity);
+        }
+        context
+    }
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator available");
+    b64url(&bytes)
+}
+
+/// Device tokens are only stored hashed
+fn hash_token(token: &str) -> String {
+    hex::encode(digest(&SHA256, token.as_bytes()))
+}
+
+/// Registers a scanner device for an event
+pub async fn register(
+    pool: &PgPool,
+    event_id: Uuid,
+    name: String,
+    operator_name: Option<String>,
+    registered_by: Option<Uuid>,
+) -> Result<RegisteredDevice, sqlx::Error> {
+    let token = random_token();
+    let device = ScannerDevice::create(
+        pool,
+        CreateScannerDeviceData {
+            event_id,
+            name,
+            operator_name,
+            token_hash: hash_token(&token),
+            registered_by,
+        },
+    )
+    .await?;
+
+    tracing::info!(event_id = %event_id, device_id = %device.id, "Registered scanner device");
+
+    Ok(RegisteredDevice { device, token })
+}
+
+/// Finds the event's device a token belongs to; revoked devices and devices of
+/// other events are not found
+pub async fn authenticate(
+    pool: &PgPool,
+    event_id: Uuid,
+    token: &str,
+) -> Result<Option<ScannerDevice>, sqlx::Error> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Ok(None);
+    }
+
+    ScannerDevice::authenticate(pool, event_id, &hash_token(token)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_annotate_context() {
+        let device = ScannerDevice {
+            id: Uuid::new_v4(),
+            event_id: Uuid::new_v4(),
+            name: "A 入口".to_string(),
+            operator_name: Some("小明".to_string()),
+            token_hash: hash_token("token"),
+            registered_by: None,
+            created_at: Utc::now(),
+            last_seen_at: None,
+            revoked_at: None,
+        };
+        let identity = ScannerIdentity {
+            device: Some(device.clone()),
+            member_id: None,
+        };
+
+        let context = identity.annotate(json!({ "method": "vpass_card_qr" }));
+        assert_eq!(context["method"], "vpass_card_qr");
+        assert_eq!(context["device"]["id"], json!(device.id));
+        assert_eq!(context["device"]["name"], "A 入口");
+        assert_eq!(context["operator"]["name"], "小明");
+        assert!(context["operator"]["member_id"].is_null());
+    }
+}
@@ -0,0 +1,349 @@
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Map, Value};
+
+use crate::services::jose::{
+    b64url, b64url_decode, sha256_b64url, DecodedJwt, Es256Key, JoseError, Jwk,
+};
+
+/// Digest algorithm for disclosures
+pub const SD_ALG: &str = "sha-256";
+
+/// `typ` of the holder's key binding JWT
+pub const KB_JWT_TYP: &str = "kb+jwt";
+
+const SALT_LEN: usize = 16;
+
+/// How far a key binding JWT's `iat` may be from now
+const KB_MAX_SKEW_SECS: i64 = 300;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum SdJwtError {
+    #[error(transparent)]
+    Jose(#[from] JoseError),
+
+    #[error("Malformed SD-JWT: {0}")]
+    Malformed(String),
+
+    #[error("Disclosure was not issued with this credential")]
+    UnknownDisclosure,
+
+    #[error("Key binding failed: {0}")]
+    KeyBinding(String),
+}
+
+/// One selectively disclosable claim: base64url of `[salt, name, value]`
+#[derive(Debug, Clone)]
+pub struct Disclosure {
+    pub encoded: String,
+    pub name: String,
+    pub value: Value,
+}
+
+impl Disclosure {
+    fn new(rng: &SystemRandom, name: String, value: Value) -> Result<Self, SdJwtError> {
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill(&mut salt).map_err(|_| JoseError::SigningFailed)?;
+
+        let encoded = b64url(json!([b64url(&salt), name, value]).to_string().as_bytes());
+        Ok(Self {
+            encoded,
+            name,
+            value,
+        })
+    }
+
+    pub fn parse(encoded: &str) -> Result<Self, SdJwtError> {
+        let array: Vec<Value> = serde_json::from_slice(&b64url_decode(encoded)?)
+            .map_err(|e| SdJwtError::Malformed(format!("invalid disclosure: {}", e)))?;
+
+        match <[Value; 3]>::try_from(array) {
+            Ok([Value::String(_salt), Value::String(name), value]) => Ok(Self {
+                encoded: encoded.to_string(),
+                name,
+                value,
+            }),
+            _ => Err(SdJwtError::Malformed(
+                "disclosure must be [salt, name, value]".to_string(),
+            )),
+        }
+    }
+
+    /// The digest listed in the credential's `_sd` array
+    pub fn digest(&self) -> String {
+        sha256_b64url(self.encoded.as_bytes())
+    }
+}
+
+/// Issues an SD-JWT
+///
+/// `claims` are always visible; each of `disclosable` is only revealed when the
+/// holder presents its disclosure. Returns `<jwt>~<disclosure>~...~`.
+pub fn issue(
+    key: &Es256Key,
+    header: Value,
+    mut claims: Map<String, Value>,
+    disclosable: Vec<(String, Value)>,
+) -> Result<String, SdJwtError> {
+    let rng = SystemRandom::new();
+    let disclosures = disclosable
+        .into_iter()
+        .map(|(name, value)| Disclosure::new(&rng, name, value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Sorted, so the order does not reveal which digest belongs to which claim
+    let mut digests: Vec<String> = disclosures.iter().map(Disclosure::digest).collect();
+    digests.sort();
+
+    claims.insert("_sd".to_string(), json!(digests));
+    claims.insert("_sd_alg".to_string(), json!(SD_ALG));
+
+    let mut issued = key.sign(header, &Value::Object(claims))?;
+    for disclosure in &disclosures {
+        issued.push('~');
+        issued.push_str(&disclosure.encoded);
+    }
+    issued.push('~');
+
+    Ok(issued)
+}
+
+/// An SD-JWT as presented by a holder: the issuer-signed JWT, the disclosures the
+/// holder chose to reveal, and the key binding JWT
+#[derive(Debug, Clone)]
+pub struct Presentation {
+    pub credential: DecodedJwt,
+    pub disclosures: Vec<Disclosure>,
+    pub key_binding: Option<DecodedJwt>,
+    /// Everything the key binding JWT's `sd_hash` covers
+    sd_hash_input: String,
+}
+
+impl Presentation {
+    /// Splits a presentation; no signature is checked here
+    pub fn parse(presented: &str) -> Result<Self, SdJwtError> {
+        let (disclosed, key_binding) = presented
+            .rsplit_once('~')
+            .ok_or_else(|| SdJwtError::Malformed("missing ~ separator".to_string()))?;
+
+        let mut parts = disclosed.split('~');
+        let credential = DecodedJwt::decode(parts.next().unwrap_or_default())?;
+        let disclosures = parts
+            .filter(|part| !part.is_empty())
+            .map(Disclosure::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        let key_binding = match key_binding {
+            "" => None,
+            jwt => Some(DecodedJwt::decode(jwt)?),
+        };
+
+        Ok(Self {
+            credential,
+            disclosures,
+            key_binding,
+            sd_hash_input: format!("{}~", disclosed),
+        })
+    }
+
+    /// Returns the disclosed claims, after checking each disclosure was issued
+    /// with the credential (its digest is in `_sd`)
+    pub fn disclosed_claims(&self) -> Result<Map<String, Value>, SdJwtError> {
+        let claims = &self.credential.claims;
+
+        if let Some(alg) = claims.get("_sd_alg") {
+            if alg.as_str() != Some(SD_ALG) {
+                return Err(SdJwtError::Malformed(format!(
+                    "unsupported _sd_alg {}",
+                    alg
+                )));
+            }
+        }
+
+        let digests: Vec<&str> = claims
+            .get("_sd")
+            .and_then(Value::as_array)
+            .map(|digests| digests.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut disclosed = Map::new();
+        for disclosure in &self.disclosures {
+            if !digests.contains(&disclosure.digest().as_str()) {
+                return Err(SdJwtError::UnknownDisclosure);
+            }
+            if claims.get(&disclosure.name).is_some() || disclosed.contains_key(&disclosure.name) {
+                return Err(SdJwtError::Malformed(format!(
+                    "claim `{}` disclosed twice",
+                    disclosure.name
+                )));
+            }
+            disclosed.insert(disclosure.name.clone(), disclosure.value.clone());
+        }
+
+        Ok(disclosed)
+    }
+
+    /// Checks the key binding JWT: signed with the holder key in the credential's
+    /// `cnf`, addressed to `audience`, answering `nonce`, and covering exactly the
+    /// presented disclosures
+    pub fn verify_key_binding(
+        &self,
+        audience: &str,
+        nonce: &str,
+        now: i64,
+    ) -> Result<(), SdJwtError> {
+        let key_binding = self
+            .key_binding
+            .as_ref()
+            .ok_or_else(|| SdJwtError::KeyBinding("missing key binding JWT".to_string()))?;
+
+        if key_binding.header_str("typ") != Some(KB_JWT_TYP) {
+            return Err(SdJwtError::KeyBinding(format!(
+                "typ must be {}",
+                KB_JWT_TYP
+            )));
+        }
+
+        let holder_key: Jwk = self
+            .credential
+            .claims
+            .pointer("/cnf/jwk")
+            .cloned()
+            .and_then(|jwk| serde_json::from_value(jwk).ok())
+            .ok_or_else(|| SdJwtError::KeyBinding("credential has no holder key".to_string()))?;
+        key_binding.verify(&holder_key)?;
+
+        if key_binding.claim_str("aud") != Some(audience) {
+            return Err(SdJwtError::KeyBinding("wrong audience".to_string()));
+        }
+        if key_binding.claim_str("nonce") != Some(nonce) {
+            return Err(SdJwtError::KeyBinding("wrong nonce".to_string()));
+        }
+
+        let issued_at = key_binding
+            .claim_i64("iat")
+            .ok_or_else(|| SdJwtError::KeyBinding("missing iat".to_string()))?;
+        if (now - issued_at).abs() > KB_MAX_SKEW_SECS {
+            return Err(SdJwtError::KeyBinding("iat too far from now".to_string()));
+        }
+
+        if key_binding.claim_str("sd_hash") != Some(&sha256_b64url(self.sd_hash_input.as_bytes())) {
+            return Err(SdJwtError::KeyBinding(
+                "sd_hash does not match the presented disclosures".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_765_000_000;
+
+    fn new_key() -> Es256Key {
+        Es256Key::from_pkcs8(&Es256Key::generate_pkcs8().unwrap()).unwrap()
+    }
+
+    /// Issues a credential bound to `holder` with two disclosable claims
+    fn issue_to(issuer: &Es256Key, holder: &Es256Key) -> String {
+        let mut claims = Map::new();
+        claims.insert("iss".to_string(), json!("https://vpass.example"));
+        claims.insert("cnf".to_string(), json!({ "jwk": holder.public_jwk() }));
+
+        issue(
+            issuer,
+            json!({"typ": "dc+sd-jwt"}),
+            claims,
+            vec![
+                ("name".to_string(), json!("星詠")),
+                ("tier".to_string(), json!("Gold")),
+            ],
+        )
+        .unwrap()
+    }
+
+    /// What a wallet sends: the chosen disclosures plus a key binding JWT
+    fn present(holder: &Es256Key, issued: &str, keep: &[&str], nonce: &str) -> String {
+        let mut parts = issued.trim_end_matches('~').split('~');
+        let mut presented = format!("{}~", parts.next().unwrap());
+        for disclosure in parts {
+            if keep.contains(&Disclosure::parse(disclosure).unwrap().name.as_str()) {
+                presented.push_str(disclosure);
+                presented.push('~');
+            }
+        }
+
+        let key_binding = holder
+            .sign(
+                json!({"typ": KB_JWT_TYP}),
+                &json!({
+                    "aud": "verifier",
+                    "nonce": nonce,
+                    "iat": NOW,
+                    "sd_hash": sha256_b64url(presented.as_bytes()),
+                }),
+            )
+            .unwrap();
+
+        presented + &key_binding
+    }
+
+    #[test]
+    fn test_selective_disclosure() {
+        let (issuer, holder) = (new_key(), new_key());
+        let issued = issue_to(&issuer, &holder);
+        let presented = present(&holder, &issued, &["tier"], "n-1");
+
+        let presentation = Presentation::parse(&presented).unwrap();
+        assert!(presentation.credential.verify(&issuer.public_jwk()).is_ok());
+        assert!(presentation
+            .verify_key_binding("verifier", "n-1", NOW + 10)
+            .is_ok());
+
+        let claims = presentation.disclosed_claims().unwrap();
+        assert_eq!(claims.get("tier"), Some(&json!("Gold")));
+        assert!(claims.get("name").is_none());
+    }
+
+    #[test]
+    fn test_key_binding_checks() {
+        let (issuer, holder) = (new_key(), new_key());
+        let issued = issue_to(&issuer, &holder);
+
+        let presentation =
+            Presentation::parse(&present(&holder, &issued, &["name"], "n-1")).unwrap();
+        assert!(presentation
+            .verify_key_binding("verifier", "other-nonce", NOW)
+            .is_err());
+        assert!(presentation
+            .verify_key_binding("other-verifier", "n-1", NOW)
+            .is_err());
+        assert!(presentation
+            .verify_key_binding("verifier", "n-1", NOW + 3600)
+            .is_err());
+
+        // Signed by someone other than the holder the credential is bound to
+        let stolen = Presentation::parse(&present(&new_key(), &issued, &["name"], "n-1")).unwrap();
+        assert!(stolen.verify_key_binding("verifier", "n-1", NOW).is_err());
+
+        // Issued credential without key binding
+        let bare = Presentation::parse(&issued).unwrap();
+        assert!(bare.verify_key_binding("verifier", "n-1", NOW).is_err());
+    }
+
+    #[test]
+    fn test_forged_disclosure_is_rejected() {
+        let (issuer, holder) = (new_key(), new_key());
+        let issued = issue_to(&issuer, &holder);
+
+        let jwt = issued.split('~').next().unwrap();
+        let forged = b64url(json!(["salt", "tier", "Platinum"]).to_string().as_bytes());
+        let presentation = Presentation::parse(&format!("{}~{}~", jwt, forged)).unwrap();
+
+        assert_eq!(
+            presentation.disclosed_claims().unwrap_err(),
+            SdJwtError::UnknownDisclosure
+        );
+    }
+}
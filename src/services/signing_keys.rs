@@ -0,0 +1,337 @@
+use chrono::{DateTime, Duration, Utc};
+use secrecy::ExposeSecret;
+use serde_json::{json, Value};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::signing_key::SigningKey;
+use crate::services::credential_cipher::{CipherError, CredentialCipher};
+use crate::services::jose::{b64url, b64url_decode, Es256Key, JoseError, Jwk};
+
+#[derive(thiserror::Error, Debug)]
+pub enum SigningKeyError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("CREDENTIAL_ENCRYPTION_KEY is not configured")]
+    MissingEncryptionKey,
+
+    #[error("Stored signing key is unusable: {0}")]
+    Cipher(#[from] CipherError),
+
+    #[error("Signing key error: {0}")]
+    Jose(#[from] JoseError),
+}
+
+/// Context binding a private key to its row
+fn private_key_context(kid: &str) -> String {
+    format!("signing_keys.private_key:{}", kid)
+}
+
+fn cipher(config: &Config) -> Result<CredentialCipher, SigningKeyError> {
+    config
+        .credential_cipher()?
+        .ok_or(SigningKeyError::MissingEncryptionKey)
+}
+
+/// How long a retired key stays published after the last credential it signed
+/// expired, for verifiers checking late or allowing for clock skew
+pub const RETIRED_KEY_GRACE_DAYS: i64 = 7;
+
+/// Recorded expiries are rounded up to this, so a key signing short-lived tokens
+/// (e.g. presentation tokens every minute) is not written to each time
+const SIGNED_UNTIL_STEP_HOURS: i64 = 1;
+
+/// `did:web` identifier of a path under VPass's base URL
+///
+/// `https://vpass.example:8443` becomes `did:web:vpass.example%3A8443`; path
+/// segments become `:`-separated. A DID with a path resolves to `<path>/did.json`
+/// rather than `/.well-known/did.json`, so VPass serves its own document at both.
+fn did_web(base_url: &str, segments: &[&str]) -> String {
+    let base = base_url.trim_end_matches('/');
+    let host_and_path = base.split_once("://").map(|(_, rest)| rest).unwrap_or(base);
+
+    let mut parts = host_and_path.split('/');
+    let mut did = format!(
+        "did:web:{}",
+        parts.next().unwrap_or_default().replace(':', "%3A")
+    );
+    for segment in parts.chain(segments.iter().copied()) {
+        did.push(':');
+        did.push_str(segment);
+    }
+    did
+}
+
+/// VPass's own DID (`/.well-known/did.json`, or `/did.json` under a base URL path)
+pub fn vpass_did(config: &Config) -> String {
+    did_web(&config.base_url, &[])
+}
+
+/// An issuer's DID (`/issuers/{id}/did.json`); `iss` of the credentials it issues
+pub fn issuer_did(config: &Config, issuer_id: Uuid) -> String {
+    did_web(&config.base_url, &["issuers", &issuer_id.to_string()])
+}
+
+fn owner_did(config: &Config, issuer_id: Option<Uuid>) -> String {
+    match issuer_id {
+        Some(issuer_id) => issuer_did(config, issuer_id),
+        None => vpass_did(config),
+    }
+}
+
+/// Verification method ID of a key, used as the JWT `kid`
+pub fn verification_method_id(config: &Config, key: &SigningKey) -> String {
+    format!("{}#{}", owner_did(config, key.issuer_id), key.kid)
+}
+
+/// Generates a key for an issuer (None: VPass's own) and stores it encrypted
+/// Returns None if the issuer got an active key concurrently.
+async fn generate_key(
+    executor: impl PgExecutor<'_>,
+    cipher: &CredentialCipher,
+    issuer_id: Option<Uuid>,
+) -> Result<Option<(SigningKey, Es256Key)>, SigningKeyError> {
+    let pkcs8 = Es256Key::generate_pkcs8()?;
+    let key = Es256Key::from_pkcs8(&pkcs8)?;
+
+    let mut jwk = key.public_jwk();
+    let kid = jwk.thumbprint();
+    jwk.kid = Some(kid.clone());
+
+    let private_key_encrypted = cipher.encrypt(&private_key_context(&kid), &b64url(&pkcs8))?;
+    let public_jwk = serde_json::to_value(&jwk).expect("JWK serializes");
+    let Some(row) = SigningKey::create(
+        executor,
+        issuer_id,
+        &kid,
+        &private_key_encrypted,
+        &public_jwk,
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    tracing::info!(kid = %kid, issuer_id = ?issuer_id, "Generated credential signing key");
+
+    Ok(Some((row, key)))
+}
+
+fn decrypt_key(cipher: &CredentialCipher, row: &SigningKey) -> Result<Es256Key, SigningKeyError> {
+    let pkcs8 = cipher.decrypt(&private_key_context(&row.kid), &row.private_key_encrypted)?;
+    Ok(Es256Key::from_pkcs8(&b64url_decode(
+        pkcs8.expose_secret(),
+    )?)?)
+}
+
+/// Returns the key an issuer (None: VPass itself) signs with, generating one on first use
+pub async fn active_key(
+    pool: &PgPool,
+    config: &Config,
+    issuer_id: Option<Uuid>,
+) -> Result<(SigningKey, Es256Key), SigningKeyError> {
+    let cipher = cipher(config)?;
+
+    if let Some(row) = SigningKey::find_active(pool, issuer_id).await? {
+        let key = decrypt_key(&cipher, &row)?;
+        return Ok((row, key));
+    }
+
+    if let Some(generated) = generate_key(pool, &cipher, issuer_id).await? {
+        return Ok(generated);
+    }
+
+    // Another request generated the issuer's first key at the same time
+    let row = SigningKey::find_active(pool, issuer_id)
+        .await?
+        .ok_or(SigningKeyError::DatabaseError(sqlx::Error::RowNotFound))?;
+    let key = decrypt_key(&cipher, &row)?;
+    Ok((row, key))
+}
+
+/// Whether the key is already kept published until `expires_at` (None: for good)
+fn keeps_published(key: &SigningKey, expires_at: Option<DateTime<Utc>>) -> bool {
+    match expires_at {
+        _ if key.signed_unexpiring => true,
+        Some(expires_at) => key.signed_until.is_some_and(|until| until >= expires_at),
+        None => false,
+    }
+}
+
+/// Records that `key` signed a credential or token valid until `expires_at`
+/// (None: never expires), so it stays published while that can be verified
+///
+/// Call before handing out what was signed.
+pub async fn record_signed(
+    pool: &PgPool,
+    key: &SigningKey,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(), SigningKeyError> {
+    if keeps_published(key, expires_at) {
+        return Ok(());
+    }
+
+    let expires_at = expires_at.map(|at| at + Duration::hours(SIGNED_UNTIL_STEP_HOURS));
+    SigningKey::record_signed(pool, key.id, expires_at).await?;
+
+    Ok(())
+}
+
+/// Retires an issuer's active key and generates a new one
+///
+/// The retired key stays published until what it signed expired (see
+/// [`record_signed`]), plus [`RETIRED_KEY_GRACE_DAYS`].
+pub async fn rotate_key(
+    pool: &PgPool,
+    config: &Config,
+    issuer_id: Uuid,
+) -> Result<SigningKey, SigningKeyError> {
+    let cipher = cipher(config)?;
+    let mut tx = pool.begin().await?;
+
+    SigningKey::retire_active(&mut *tx, Some(issuer_id)).await?;
+    let (row, _) = generate_key(&mut *tx, &cipher, Some(issuer_id))
+        .await?
+        .ok_or(SigningKeyError::DatabaseError(sqlx::Error::RowNotFound))?;
+
+    tx.commit().await?;
+
+    tracing::info!(issuer_id = %issuer_id, kid = %row.kid, "Rotated issuer signing key");
+
+    Ok(row)
+}
+
+/// A public key and who it signs for
+pub struct PublicKey {
+    pub jwk: Jwk,
+    /// None for VPass's own key
+    pub issuer_id: Option<Uuid>,
+}
+
+/// Returns the public key with key ID `kid` (a thumbprint or a verification method
+/// ID ending in one), retired keys included
+pub async fn public_key(pool: &PgPool, kid: &str) -> Result<Option<PublicKey>, SigningKeyError> {
+    let thumbprint = kid.rsplit_once('#').map(|(_, t)| t).unwrap_or(kid);
+    let Some(row) = SigningKey::find_by_kid(pool, thumbprint).await? else {
+        return Ok(None);
+    };
+
+    Ok(serde_json::from_value(row.public_jwk)
+        .ok()
+        .map(|jwk| PublicKey {
+            jwk,
+            issuer_id: row.issuer_id,
+        }))
+}
+
+/// Keys of an issuer (None: VPass's own) that may have signed a credential still
+/// in circulation: the active key and retired ones whose credentials have not
+/// all expired
+pub async fn published_keys(
+    pool: &PgPool,
+    issuer_id: Option<Uuid>,
+) -> Result<Vec<SigningKey>, SigningKeyError> {
+    let signed_since = Utc::now() - Duration::days(RETIRED_KEY_GRACE_DAYS);
+    Ok(SigningKey::list_by_issuer(pool, issuer_id, signed_since).await?)
+}
+
+/// JWK Set of an issuer's (None: VPass's own) published keys
+pub async fn jwks(pool: &PgPool, issuer_id: Option<Uuid>) -> Result<Value, SigningKeyError> {
+    let keys: Vec<Value> = published_keys(pool, issuer_id)
+        .await?
+        .into_iter()
+        .map(|row| row.public_jwk)
+        .collect();
+
+    Ok(json!({ "keys": keys }))
+}
+
+/// `did:web` document of an issuer (None: VPass itself) listing its published keys
+pub async fn did_document(
+    pool: &PgPool,
+    config: &Config,
+    issuer_id: Option<Uuid>,
+) -> Result<Value, SigningKeyError> {
+    let did = owner_did(config, issuer_id);
+    let keys = published_keys(pool, issuer_id).await?;
+
+    let methods: Vec<Value> = keys
+        .iter()
+        .map(|key| {
+            json!({
+                "id": verification_method_id(config, key),
+                "type": "JsonWebKey2020",
+                "controller": did,
+                "publicKeyJwk": key.public_jwk,
+            })
+        })
+        .collect();
+    let method_ids: Vec<String> = keys
+        .iter()
+        .map(|key| verification_method_id(config, key))
+        .collect();
+
+    Ok(json!({
+        "@context": [
+            "https://www.w3.org/ns/did/v1",
+            "https://w3id.org/security/suites/jws-2020/v1"
+        ],
+        "id": did,
+        "verificationMethod": methods,
+        "assertionMethod": method_ids,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(signed_until: Option<DateTime<Utc>>, signed_unexpiring: bool) -> SigningKey {
+        SigningKey {
+            id: Uuid::new_v4(),
+            kid: "kid".to_string(),
+            algorithm: "ES256".to_string(),
+            private_key_encrypted: String::new(),
+            public_jwk: json!({}),
+            created_at: Utc::now(),
+            retired_at: None,
+            issuer_id: None,
+            signed_until,
+            signed_unexpiring,
+        }
+    }
+
+    #[test]
+    fn test_keeps_published() {
+        let now = Utc::now();
+        let signed = key(Some(now + Duration::days(30)), false);
+
+        assert!(keeps_published(&signed, Some(now + Duration::days(1))));
+        assert!(!keeps_published(&signed, Some(now + Duration::days(31))));
+        // A credential that never expires
+        assert!(!keeps_published(&signed, None));
+        assert!(!keeps_published(&key(None, false), Some(now)));
+        assert!(keeps_published(&key(None, true), None));
+    }
+
+    #[test]
+    fn test_did_web_identifiers() {
+        assert_eq!(
+            did_web("https://vpass.example/", &[]),
+            "did:web:vpass.example"
+        );
+        assert_eq!(
+            did_web(
+                "https://vpass.example",
+                &["issuers", "550e8400-e29b-41d4-a716-446655440000"]
+            ),
+            "did:web:vpass.example:issuers:550e8400-e29b-41d4-a716-446655440000"
+        );
+        assert_eq!(
+            did_web("http://localhost:3000/vpass", &[]),
+            "did:web:localhost%3A3000:vpass"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::Write;
+
+use chrono::{Duration, Utc};
+use flate2::{write::ZlibEncoder, Compression};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::credential_status_list::CredentialStatusList;
+use crate::services::jose::{b64url, sha256_b64url};
+use crate::services::openid_wallet::{credential_issuer, OpenIdError};
+use crate::services::signing_keys;
+
+/// `typ` of a status list token (IETF Token Status List)
+pub const STATUS_LIST_TYP: &str = "statuslist+jwt";
+
+/// Media type the status list is served with
+pub const STATUS_LIST_MEDIA_TYPE: &str = "application/statuslist+jwt";
+
+/// Bits per credential: 0 valid, 1 invalid (revoked, expired, deleted), 2 suspended
+const STATUS_BITS: u8 = 2;
+
+/// How long verifiers may cache the list before fetching it again
+pub const STATUS_LIST_TTL_SECS: i64 = 300;
+
+/// How long a status list token is valid
+const STATUS_LIST_TOKEN_HOURS: i64 = 24;
+
+/// Size of the list served for an issuer without cards yet
+const EMPTY_LIST_BYTES: usize = 1024;
+
+/// URL the status list of an issuer is served at (the token's `sub`)
+pub fn status_list_uri(credential_issuer: &str, issuer_id: Uuid) -> String {
+    format!("{}/status-lists/{}", credential_issuer, issuer_id)
+}
+
+/// `status` claim of a credential with index `index` in its issuer's list
+pub fn status_claim(credential_issuer: &str, issuer_id: Uuid, index: i32) -> Value {
+    json!({
+        "status_list": {
+            "idx": index,
+            "uri": status_list_uri(credential_issuer, issuer_id),
+        }
+    })
+}
+
+/// Entity tag of the list's current contents
+pub fn etag(list: &CredentialStatusList) -> String {
+    format!("\"{}\"", sha256_b64url(&list.statuses))
+}
+
+/// `status_list` claim: the statuses, zlib compressed and base64url encoded
+fn encode_statuses(statuses: &[u8]) -> Value {
+    let statuses = match statuses {
+        [] => &[0u8; EMPTY_LIST_BYTES][..],
+        statuses => statuses,
+    };
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(statuses)
+        .expect("writing to a Vec does not fail");
+    let compressed = encoder.finish().expect("writing to a Vec does not fail");
+
+    json!({
+        "bits": STATUS_BITS,
+        "lst": b64url(&compressed),
+    })
+}
+
+/// Signs the status list token of an issuer's list
+pub async fn sign_status_list(
+    pool: &PgPool,
+    config: &Config,
+    list: &CredentialStatusList,
+) -> Result<String, OpenIdError> {
+    let (signing_key, key) = signing_keys::active_key(pool, config, Some(list.issuer_id)).await?;
+    let now = Utc::now();
+    let expires_at = now + Duration::hours(STATUS_LIST_TOKEN_HOURS);
+
+    let claims = json!({
+        "sub": status_list_uri(&credential_issuer(config), list.issuer_id),
+        "iss": signing_keys::issuer_did(config, list.issuer_id),
+        "iat": now.timestamp(),
+        "exp": expires_at.timestamp(),
+        "ttl": STATUS_LIST_TTL_SECS,
+        "status_list": encode_statuses(&list.statuses),
+    });
+
+    let token = key
+        .sign(
+            json!({
+                "typ": STATUS_LIST_TYP,
+                "kid": signing_keys::verification_method_id(config, &signing_key),
+            }),
+            &claims,
+        )
+        .map_err(|e| OpenIdError::Signing(e.into()))?;
+    signing_keys::record_signed(pool, &signing_key, Some(expires_at)).await?;
+
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::jose::b64url_decode;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    /// What a verifier does: decompress `lst` and read the card's 2 bits
+    fn status_at(status_list: &Value, index: usize) -> u8 {
+        let compressed = b64url_decode(status_list["lst"].as_str().unwrap()).unwrap();
+        let mut statuses = Vec::new();
+        ZlibDecoder::new(&compressed[..])
+            .read_to_end(&mut statuses)
+            .unwrap();
+
+        (statuses[index / 4] >> ((index % 4) * 2)) & 0b11
+    }
+
+    #[test]
+    fn test_encode_statuses() {
+        // Card 0 valid, 1 revoked, 2 suspended, 5 revoked (bit layout written by the trigger)
+        let mut statuses = vec![0u8; 1024];
+        statuses[0] = 0b0010_0100;
+        statuses[1] = 0b0000_0100;
+
+        let status_list = encode_statuses(&statuses);
+        assert_eq!(status_list["bits"], 2);
+        assert_eq!(status_at(&status_list, 0), 0);
+        assert_eq!(status_at(&status_list, 1), 1);
+        assert_eq!(status_at(&status_list, 2), 2);
+        assert_eq!(status_at(&status_list, 5), 1);
+        assert_eq!(status_at(&status_list, 4095), 0);
+    }
+
+    #[test]
+    fn test_empty_list_is_padded() {
+        assert_eq!(
+            status_at(&encode_statuses(&[]), EMPTY_LIST_BYTES * 4 - 1),
+            0
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::Duration;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{
+    background_task::{BackgroundTask, TaskPayload},
+    card::MembershipCard,
+    issuer::CardIssuer,
+};
+use crate::services::{card_issuer, wallet_accounts, wallet_provider, wallet_qr};
+
+/// How long a worker may hold a task before another worker picks it up again
+pub const TASK_LEASE_MINUTES: i64 = 5;
+
+/// Delay before the first retry; doubled on each further attempt
+const BASE_RETRY_DELAY_SECS: i64 = 30;
+const MAX_RETRY_DELAY_SECS: i64 = 60 * 60;
+
+#[derive(thiserror::Error, Debug)]
+pub enum TaskError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
+    #[error("Invalid task payload: {0}")]
+    InvalidPayload(#[from] serde_json::Error),
+
+    #[error("Issuer API not configured")]
+    IssuerApiNotConfigured,
+
+    #[error("Wallet account error: {0}")]
+    WalletAccount(#[from] wallet_accounts::WalletAccountError),
+
+    #[error("{0}")]
+    Issuance(#[from] card_issuer::CardIssuanceError),
+
+    #[error("Wallet API error: {0}")]
+    WalletQr(#[from] wallet_qr::WalletQrError),
+}
+
+impl TaskError {
+    /// Whether the wallet API is down; such failures do not count as attempts
+    pub fn is_wallet_outage(&self) -> bool {
+        match self {
+            TaskError::WalletQr(e)
+            | TaskError::Issuance(card_issuer::CardIssuanceError::WalletQrGeneration(e)) => {
+                e.is_outage()
+            }
+            _ => false,
+        }
+    }
+
+    /// How long the wallet API's circuit breaker still refuses calls
+    fn circuit_retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            TaskError::WalletQr(wallet_qr::WalletQrError::CircuitOpen(remaining))
+            | TaskError::Issuance(card_issuer::CardIssuanceError::WalletQrGeneration(
+                wallet_qr::WalletQrError::CircuitOpen(remaining),
+            )) => Some(*remaining),
+            _ => None,
+        }
+    }
+
+    /// Whether retrying can help; permanent failures are dead-lettered right away
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self,
+            TaskError::InvalidPayload(_)
+                | TaskError::Issuance(
+                    card_issuer::CardIssuanceError::CardNotFound
+                        | card_issuer::CardIssuanceError::MissingVcUid
+                        | card_issuer::CardIssuanceError::InvalidCredentialField(_)
+                )
+        )
+    }
+}
+
+/// Returns the backoff before the next attempt, given the attempts made so far
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    let secs = BASE_RETRY_DELAY_SECS.saturating_mul(1_i64 << exponent);
+    Duration::seconds(secs.min(MAX_RETRY_DELAY_SECS))
+}
+
+/// Executes a task's side effect
+/// Wallet calls use the wallet provider (and account) of the card's issuer.
+pub async fn execute(
+    pool: &PgPool,
+    config: &Config,
+    payload: &TaskPayload,
+) -> Result<(), TaskError> {
+    match payload {
+        TaskPayload::GenerateWalletQr { card_id } => {
+            card_issuer::attach_wallet_qr(pool, config, *card_id).await?;
+        }
+        TaskPayload::RevokeWalletCredential { card_id, cid } => {
+            // Deleted cards keep their row, so the issuer can still be found
+            let card = MembershipCard::find_by_id(pool, *card_id)
+                .await?
+                .ok_or(card_issuer::CardIssuanceError::CardNotFound)?;
+            let issuer = CardIssuer::find_by_id(pool, card.issuer_id)
+                .await?
+                .ok_or(card_issuer::CardIssuanceError::IssuerNotFound)?;
+            // Revoked through the provider that issued it, even if the issuer switched since
+            let kind = wallet_provider::kind_of_credential(pool, cid).await?;
+            let wallet = wallet_provider::provider_of_kind(pool, config, &issuer, kind)?
+                .ok_or(TaskError::IssuerApiNotConfigured)?;
+
+            wallet.revoke(cid).await?;
+            tracing::info!(card_id = %card_id, cid = %cid, "Wallet credential revoked");
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs a claimed task and records the outcome
+/// Returns true if the task succeeded.
+pub async fn run(
+    pool: &PgPool,
+    config: &Config,
+    task: &BackgroundTask,
+) -> Result<bool, sqlx::Error> {
+    let result = match task.task_payload() {
+        Ok(payload) => execute(pool, config, &payload).await,
+        Err(e) => Err(TaskError::InvalidPayload(e)),
+    };
+
+    match result {
+        Ok(()) => {
+            BackgroundTask::complete(pool, task.id).await?;
+            tracing::info!(task_id = %task.id, kind = %task.kind, "Task succeeded");
+            Ok(true)
+        }
+        Err(e) if e.is_wallet_outage() => {
+            // Wait at least until the circuit breaker lets calls through again
+            let breaker_wait = e
+                .circuit_retry_after()
+                .and_then(|d| Duration::from_std(d).ok())
+                .unwrap_or_else(Duration::zero);
+            let delay = retry_delay(task.attempts).max(breaker_wait);
+
+            BackgroundTask::defer(pool, task.id, &e.to_string(), delay).await?;
+            tracing::warn!(
+                task_id = %task.id,
+                kind = %task.kind,
+                retry_in_secs = delay.num_seconds(),
+                error = %e,
+                "Wallet API unavailable, task deferred"
+            );
+
+            Ok(false)
+        }
+        Err(e) => {
+            let retry_in = e.is_retryable().then(|| retry_delay(task.attempts));
+            let dead = BackgroundTask::fail(pool, task.id, &e.to_string(), retry_in).await?;
+
+            if dead {
+                tracing::error!(
+                    task_id = %task.id,
+                    kind = %task.kind,
+                    attempts = task.attempts,
+                    error = %e,
+                    "Task dead-lettered"
+                );
+            } else {
+                tracing::warn!(
+                    task_id = %task.id,
+                    kind = %task.kind,
+                    attempts = task.attempts,
+                    error = %e,
+                    "Task failed, will retry"
+                );
+            }
+
+            Ok(false)
+        }
+    }
+}
+
+/// Claims and runs a task immediately, e.g. right after enqueueing it in a request
+/// Returns false if the task failed or a worker already claimed it.
+pub async fn run_now(pool: &PgPool, config: &Config, task_id: Uuid) -> Result<bool, sqlx::Error> {
+    let claimed = BackgroundTask::claim(
+        pool,
+        task_id,
+        Uuid::new_v4(),
+        Duration::minutes(TASK_LEASE_MINUTES),
+    )
+    .await?;
+
+    match claimed {
+        Some(task) => run(pool, config, &task).await,
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::seconds(30));
+        assert_eq!(retry_delay(2), Duration::seconds(60));
+        assert_eq!(retry_delay(4), Duration::seconds(240));
+        assert_eq!(retry_delay(20), Duration::seconds(MAX_RETRY_DELAY_SECS));
+    }
+}
@@ -0,0 +1,374 @@
+use std::net::IpAddr;
+
+use secrecy::{ExposeSecret, Secret};
+use url::{Host, Url};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{event::Event, issuer::CardIssuer};
+use crate::services::credential_cipher::{CipherError, CredentialCipher};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WalletAccountError {
+    #[error("CREDENTIAL_ENCRYPTION_KEY is not configured")]
+    MissingEncryptionKey,
+
+    #[error("Stored wallet API token is unusable: {0}")]
+    Cipher(#[from] CipherError),
+}
+
+/// Whose wallet account an API call is made with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountScope {
+    Event,
+    Issuer,
+    Global,
+}
+
+/// Base URL and access token of a Taiwan Digital Wallet API account
+pub struct WalletApiAccount {
+    pub api_url: String,
+    access_token: Secret<String>,
+    pub scope: AccountScope,
+    /// Issuer identifier (`iss`) of the credentials the account issues, if known
+    pub credential_issuer: Option<String>,
+}
+
+impl WalletApiAccount {
+    pub fn access_token(&self) -> &str {
+        self.access_token.expose_secret()
+    }
+
+    /// Returns (api_base_url, access_token), the shape the wallet API clients take
+    pub fn as_config(&self) -> (&str, &str) {
+        (&self.api_url, self.access_token())
+    }
+}
+
+/// Checks that a wallet API base URL entered by an admin is an absolute https URL
+///
+/// VPass sends the stored access token to this URL, so hosts on VPass's own
+/// network (loopback, private and link-local addresses) are refused.
+pub fn validate_api_url(api_url: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid wallet API URL: {}", api_url);
+    let url = Url::parse(api_url).map_err(|_| invalid())?;
+    if url.scheme() != "https" {
+        return Err(format!("Wallet API URL must use https: {}", api_url));
+    }
+
+    let internal = match url.host().ok_or_else(invalid)? {
+        Host::Domain(domain) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Host::Ipv4(ip) => is_internal_ip(IpAddr::V4(ip)),
+        Host::Ipv6(ip) => is_internal_ip(IpAddr::V6(ip)),
+    };
+    if internal {
+        return Err(format!(
+            "Wallet API URL must not point to a private network: {}",
+            api_url
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // Carrier-grade NAT (100.64.0.0/10)
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_internal_ip(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local (fc00::/7) and link-local (fe80::/10)
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// Context binding an issuer's encrypted issuer API token to its row
+pub fn issuer_token_context(issuer_id: Uuid) -> String {
+    format!("card_issuers.wallet_issuer_token:{}", issuer_id)
+}
+
+/// Context binding an issuer's encrypted verifier API token to its row
+pub fn verifier_token_context(issuer_id: Uuid) -> String {
+    format!("card_issuers.wallet_verifier_token:{}", issuer_id)
+}
+
+/// Context binding an event's encrypted verifier API token to its row
+pub fn event_verifier_token_context(event_id: Uuid) -> String {
+    format!("events.verifier_token:{}", event_id)
+}
+
+/// Fails unless tokens can be encrypted, so a form is rejected before anything is saved
+pub fn ensure_can_store_tokens(config: &Config) -> Result<(), WalletAccountError> {
+    cipher(config).map(|_| ())
+}
+
+fn cipher(config: &Config) -> Result<CredentialCipher, WalletAccountError> {
+    config
+        .credential_cipher()?
+        .ok_or(WalletAccountError::MissingEncryptionKey)
+}
+
+/// Encrypts an access token for storage under `context`
+pub fn encrypt_token(
+    config: &Config,
+    context: &str,
+    token: &str,
+) -> Result<String, WalletAccountError> {
+    Ok(cipher(config)?.encrypt(context, token)?)
+}
+
+/// An account configured on a row: its URL (optional) and encrypted token
+struct StoredAccount<'a> {
+    api_url: Option<&'a str>,
+    token_encrypted: Option<&'a str>,
+    context: String,
+    scope: AccountScope,
+    credential_issuer: Option<&'a str>,
+}
+
+/// Picks the first stored account that has a token, otherwise the global account
+///
+/// A stored account without its own URL uses the global URL (same wallet service,
+/// different organization). The global token is never sent to a stored URL.
+fn resolve(
+    config: &Config,
+    stored: &[StoredAccount<'_>],
+    global_url: Option<&str>,
+    global_token: Option<&Secret<String>>,
+) -> Result<Option<WalletApiAccount>, WalletAccountError> {
+    if let Some(account) = stored.iter().find(|a| a.token_encrypted.is_some()) {
+        let Some(api_url) = account.api_url.or(global_url) else {
+            return Ok(None);
+        };
+        let token = cipher(config)?.decrypt(
+            &account.context,
+            account.token_encrypted.unwrap_or_default(),
+        )?;
+
+        return Ok(Some(WalletApiAccount {
+            api_url: api_url.to_string(),
+            access_token: token,
+            scope: account.scope,
+            credential_issuer: account.credential_issuer.map(str::to_string),
+        }));
+    }
+
+    Ok(global_url
+        .zip(global_token)
+        .map(|(api_url, token)| WalletApiAccount {
+            api_url: api_url.to_string(),
+            access_token: token.clone(),
+            scope: AccountScope::Global,
+            credential_issuer: config.wallet_credential_issuer.clone(),
+        }))
+}
+
+/// Returns the issuer API account cards of `issuer` are issued with, if any is configured
+pub fn issuer_api(
+    config: &Config,
+    issuer: &CardIssuer,
+) -> Result<Option<WalletApiAccount>, WalletAccountError> {
+    resolve(
+        config,
+        &[StoredAccount {
+            api_url: issuer.wallet_issuer_api_url.as_deref(),
+            token_encrypted: issuer.wallet_issuer_token_encrypted.as_deref(),
+            context: issuer_token_context(issuer.id),
+            scope: AccountScope::Issuer,
+            credential_issuer: issuer.wallet_credential_issuer.as_deref(),
+        }],
+        config.issuer_api_url.as_deref(),
+        config.issuer_access_token.as_ref(),
+    )
+}
+
+/// Returns the verifier API account for an event: the event's own, else its issuer's,
+/// else the global one
+pub fn verifier_api(
+    config: &Config,
+    issuer: &CardIssuer,
+    event: &Event,
+) -> Result<Option<WalletApiAccount>, WalletAccountError> {
+    resolve(
+        config,
+        &[
+            StoredAccount {
+                api_url: event.verifier_api_url.as_deref(),
+                token_encrypted: event.verifier_token_encrypted.as_deref(),
+                context: event_verifier_token_context(event.id),
+                scope: AccountScope::Event,
+                credential_issuer: None,
+            },
+            StoredAccount {
+                api_url: issuer.wallet_verifier_api_url.as_deref(),
+                token_encrypted: issuer.wallet_verifier_token_encrypted.as_deref(),
+                context: verifier_token_context(issuer.id),
+                scope: AccountScope::Issuer,
+                credential_issuer: None,
+            },
+        ],
+        config.verifier_api_url.as_deref(),
+        config.verifier_access_token.as_ref(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+    fn test_config() -> Config {
+        Config {
+            database_url: String::new(),
+            base_url: "http://localhost:3000".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            youtube_client_id: String::new(),
+            youtube_client_secret: Secret::new(String::new()),
+            youtube_api_key: None,
+            youtube_daily_quota: 10_000,
+            subscription_check_concurrency: 1,
+            issuer_api_url: Some("https://global.example".to_string()),
+            issuer_access_token: Some(Secret::new("global-token".to_string())),
+            verifier_api_url: None,
+            verifier_access_token: None,
+            wallet_credential_issuer: None,
+            wallet_issuer_jwks_url: None,
+            wallet_issuer_jwks: None,
+            session_secret: Secret::new(String::new()),
+            credential_encryption_key: Some(Secret::new(KEY.to_string())),
+        }
+    }
+
+    fn stored<'a>(api_url: Option<&'a str>, token_encrypted: Option<&'a str>) -> StoredAccount<'a> {
+        StoredAccount {
+            api_url,
+            token_encrypted,
+            context: "test".to_string(),
+            scope: AccountScope::Issuer,
+            credential_issuer: Some("did:web:own.example"),
+        }
+    }
+
+    fn resolve_issuer(
+        config: &Config,
+        account: StoredAccount<'_>,
+    ) -> Result<Option<WalletApiAccount>, WalletAccountError> {
+        resolve(
+            config,
+            &[account],
+            config.issuer_api_url.as_deref(),
+            config.issuer_access_token.as_ref(),
+        )
+    }
+
+    #[test]
+    fn test_falls_back_to_global_account() {
+        let mut config = test_config();
+        config.wallet_credential_issuer = Some("did:web:global.example".to_string());
+        let account = resolve_issuer(&config, stored(None, None))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(account.scope, AccountScope::Global);
+        assert_eq!(
+            account.credential_issuer.as_deref(),
+            Some("did:web:global.example")
+        );
+        assert_eq!(
+            account.as_config(),
+            ("https://global.example", "global-token")
+        );
+    }
+
+    #[test]
+    fn test_own_token_is_used_with_own_or_global_url() {
+        let config = test_config();
+        let token = encrypt_token(&config, "test", "own-token").unwrap();
+
+        let account = resolve_issuer(&config, stored(Some("https://own.example"), Some(&token)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.scope, AccountScope::Issuer);
+        assert_eq!(account.as_config(), ("https://own.example", "own-token"));
+        assert_eq!(
+            account.credential_issuer.as_deref(),
+            Some("did:web:own.example")
+        );
+
+        let account = resolve_issuer(&config, stored(None, Some(&token)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.as_config(), ("https://global.example", "own-token"));
+    }
+
+    #[test]
+    fn test_own_url_without_token_uses_global_account() {
+        let config = test_config();
+        let account = resolve_issuer(&config, stored(Some("https://own.example"), None))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(account.scope, AccountScope::Global);
+        assert_eq!(account.api_url, "https://global.example");
+    }
+
+    #[test]
+    fn test_stored_token_requires_key() {
+        let mut config = test_config();
+        let token = encrypt_token(&config, "test", "own-token").unwrap();
+        config.credential_encryption_key = None;
+
+        assert!(matches!(
+            resolve_issuer(&config, stored(None, Some(&token))),
+            Err(WalletAccountError::MissingEncryptionKey)
+        ));
+    }
+
+    #[test]
+    fn test_validate_api_url() {
+        assert!(validate_api_url("https://issuer-sandbox.wallet.gov.tw").is_ok());
+        assert!(validate_api_url("https://203.0.113.7/api").is_ok());
+        assert!(validate_api_url("ftp://example.com").is_err());
+        assert!(validate_api_url("not a url").is_err());
+        assert!(validate_api_url("http://issuer-sandbox.wallet.gov.tw").is_err());
+    }
+
+    #[test]
+    fn test_validate_api_url_rejects_internal_hosts() {
+        for url in [
+            "https://localhost:8080",
+            "https://api.localhost",
+            "https://127.0.0.1",
+            "https://10.0.0.5",
+            "https://192.168.1.1",
+            "https://169.254.169.254/latest/meta-data",
+            "https://100.64.0.1",
+            "https://0.0.0.0",
+            "https://[::1]",
+            "https://[fd00::1]",
+            "https://[fe80::1]",
+            "https://[::ffff:127.0.0.1]",
+        ] {
+            assert!(validate_api_url(url).is_err(), "{} accepted", url);
+        }
+    }
+}
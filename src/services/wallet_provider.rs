@@ -0,0 +1,262 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::{
+    card::MembershipCard,
+    event::Event,
+    issuer::{CardIssuer, WalletProviderKind},
+    openid_credential_offer::OpenIdCredentialOffer,
+};
+use crate::services::{
+    credential_verifier::WalletCredentialVerifier,
+    oidvp_verifier::{self, OidvpError, QrCodeResponse, ResultResponse},
+    openid_wallet::OpenIdWallet,
+    wallet_accounts::{self, WalletAccountError, WalletApiAccount},
+    wallet_qr::{self, WalletQrError, WalletQrField, WalletQrResponse},
+};
+
+/// A card to put into the member's wallet
+pub struct CredentialOffer<'a> {
+    pub card: &'a MembershipCard,
+    pub issuer: &'a CardIssuer,
+    pub fields: Vec<WalletQrField>,
+}
+
+/// A credential the member accepted into their wallet
+pub struct IssuedCredential {
+    /// Credential ID, used to revoke it
+    pub cid: String,
+}
+
+/// A presentation request shown at an event's scanner
+pub struct PresentationRequest<'a> {
+    pub event: &'a Event,
+    pub issuer: &'a CardIssuer,
+}
+
+/// A wallet ecosystem VPass issues credentials into and verifies them from
+///
+/// Providers are obtained per role: [`issuer_provider`] for the issuance calls and
+/// [`verifier_provider`] for the presentation calls, since e.g. the Taiwan wallet
+/// uses separate issuer and verifier accounts.
+#[async_trait]
+pub trait WalletProvider: Send + Sync {
+    fn kind(&self) -> WalletProviderKind;
+
+    /// Checks that the wallet backend is reachable
+    async fn check_health(&self) -> Result<(), WalletQrError>;
+
+    /// Creates a credential offer; the returned transaction ID is stored on the card
+    async fn offer_credential(
+        &self,
+        offer: CredentialOffer<'_>,
+    ) -> Result<WalletQrResponse, WalletQrError>;
+
+    /// Returns the issued credential once the member accepted the offer
+    /// (`CredentialNotReady` until then)
+    async fn poll_issuance(&self, transaction_id: &str) -> Result<IssuedCredential, WalletQrError>;
+
+    /// Revokes an issued credential; revoking twice is not an error
+    async fn revoke(&self, cid: &str) -> Result<(), WalletQrError>;
+
+    /// Creates a presentation request for an event
+    async fn request_presentation(
+        &self,
+        request: PresentationRequest<'_>,
+    ) -> Result<QrCodeResponse, OidvpError>;
+
+    /// Returns the verification result (`NotReady` until the wallet responded)
+    async fn fetch_result(&self, transaction_id: &str) -> Result<ResultResponse, OidvpError>;
+}
+
+/// Taiwan Digital Wallet, through one issuer or verifier API account
+pub struct TaiwanWallet {
+    account: WalletApiAccount,
+    /// The credential type issued, to check returned credentials against
+    vc_uid: Option<String>,
+    credential_verifier: Option<WalletCredentialVerifier>,
+}
+
+impl TaiwanWallet {
+    fn new(config: &Config, vc_uid: Option<&str>, account: WalletApiAccount) -> Self {
+        Self {
+            credential_verifier: WalletCredentialVerifier::for_account(config, &account),
+            account,
+            vc_uid: vc_uid.map(str::to_string),
+        }
+    }
+}
+
+#[async_trait]
+impl WalletProvider for TaiwanWallet {
+    fn kind(&self) -> WalletProviderKind {
+        WalletProviderKind::TaiwanWallet
+    }
+
+    async fn check_health(&self) -> Result<(), WalletQrError> {
+        wallet_qr::check_wallet_health(&self.account.api_url, self.account.access_token()).await
+    }
+
+    async fn offer_credential(
+        &self,
+        offer: CredentialOffer<'_>,
+    ) -> Result<WalletQrResponse, WalletQrError> {
+        let vc_uid = self.vc_uid.as_deref().ok_or(WalletQrError::MissingVcUid)?;
+        // Offering a credential whose ID could not be recorded would strand the member
+        if self.credential_verifier.is_none() {
+            return Err(WalletQrError::MissingCredentialIssuer);
+        }
+
+        wallet_qr::generate_wallet_qr(
+            &self.account.api_url,
+            self.account.access_token(),
+            vc_uid,
+            offer.fields,
+        )
+        .await
+    }
+
+    async fn poll_issuance(&self, transaction_id: &str) -> Result<IssuedCredential, WalletQrError> {
+        let response = wallet_qr::poll_credential_status(
+            &self.account.api_url,
+            Some(self.account.access_token()),
+            transaction_id,
+        )
+        .await?;
+
+        // The CID is only trusted once the credential's signature, issuer and type
+        // check out, so nothing is recorded for an account without a known issuer
+        let verifier = self
+            .credential_verifier
+            .as_ref()
+            .ok_or(WalletQrError::MissingCredentialIssuer)?;
+        let vc_uid = self.vc_uid.as_deref().ok_or(WalletQrError::MissingVcUid)?;
+
+        Ok(IssuedCredential {
+            cid: verifier.verify(&response.credential, vc_uid).await?,
+        })
+    }
+
+    async fn revoke(&self, cid: &str) -> Result<(), WalletQrError> {
+        wallet_qr::revoke_credential(&self.account.api_url, self.account.access_token(), cid).await
+    }
+
+    async fn request_presentation(
+        &self,
+        request: PresentationRequest<'_>,
+    ) -> Result<QrCodeResponse, OidvpError> {
+        oidvp_verifier::request_verification_qr(
+            &self.account.api_url,
+            self.account.access_token(),
+            &request.event.verifier_ref,
+        )
+        .await
+    }
+
+    async fn fetch_result(&self, transaction_id: &str) -> Result<ResultResponse, OidvpError> {
+        oidvp_verifier::poll_verification_result(
+            &self.account.api_url,
+            self.account.access_token(),
+            transaction_id,
+        )
+        .await
+    }
+}
+
+/// Returns the issuance provider of the given kind for an issuer
+/// None if it is the Taiwan wallet and no issuer API account is configured.
+pub fn provider_of_kind(
+    pool: &PgPool,
+    config: &Config,
+    issuer: &CardIssuer,
+    kind: WalletProviderKind,
+) -> Result<Option<Box<dyn WalletProvider>>, WalletAccountError> {
+    Ok(match kind {
+        WalletProviderKind::TaiwanWallet => {
+            wallet_accounts::issuer_api(config, issuer)?.map(|account| {
+                Box::new(TaiwanWallet::new(config, issuer.vc_uid.as_deref(), account))
+                    as Box<dyn WalletProvider>
+            })
+        }
+        WalletProviderKind::OpenId => Some(Box::new(OpenIdWallet::new(pool, config))),
+    })
+}
+
+/// Returns the provider new credentials of `issuer` are offered through
+pub fn issuer_provider(
+    pool: &PgPool,
+    config: &Config,
+    issuer: &CardIssuer,
+) -> Result<Option<Box<dyn WalletProvider>>, WalletAccountError> {
+    provider_of_kind(pool, config, issuer, issuer.wallet_provider_kind())
+}
+
+/// Returns the provider attendance credentials of `issuer`'s events are offered through
+/// None if the issuer has no attendance credential template or no issuer API account.
+/// Attendance credentials are issued into the Taiwan wallet only.
+pub fn attendance_provider(
+    config: &Config,
+    issuer: &CardIssuer,
+) -> Result<Option<Box<dyn WalletProvider>>, WalletAccountError> {
+    let Some(vc_uid) = issuer.attendance_vc_uid.as_deref() else {
+        return Ok(None);
+    };
+
+    Ok(wallet_accounts::issuer_api(config, issuer)?.map(|account| {
+        Box::new(TaiwanWallet::new(config, Some(vc_uid), account)) as Box<dyn WalletProvider>
+    }))
+}
+
+/// Returns the provider presentations are requested from at an event
+/// None if it is the Taiwan wallet and no verifier API account is configured.
+pub fn verifier_provider(
+    pool: &PgPool,
+    config: &Config,
+    issuer: &CardIssuer,
+    event: &Event,
+) -> Result<Option<Box<dyn WalletProvider>>, WalletAccountError> {
+    Ok(match issuer.wallet_provider_kind() {
+        WalletProviderKind::TaiwanWallet => wallet_accounts::verifier_api(config, issuer, event)?
+            .map(|account| {
+                Box::new(TaiwanWallet::new(config, issuer.vc_uid.as_deref(), account))
+                    as Box<dyn WalletProvider>
+            }),
+        WalletProviderKind::OpenId => Some(Box::new(OpenIdWallet::new(pool, config))),
+    })
+}
+
+/// Which provider a wallet transaction ID was created by
+///
+/// Cards keep working with the provider that issued them after the issuer
+/// switches provider.
+pub async fn kind_of_transaction(
+    pool: &PgPool,
+    transaction_id: &str,
+) -> Result<WalletProviderKind, sqlx::Error> {
+    if let Ok(id) = Uuid::parse_str(transaction_id) {
+        if OpenIdCredentialOffer::find_by_id(pool, id).await?.is_some() {
+            return Ok(WalletProviderKind::OpenId);
+        }
+    }
+
+    Ok(WalletProviderKind::TaiwanWallet)
+}
+
+/// Which provider a credential ID was issued by
+pub async fn kind_of_credential(
+    pool: &PgPool,
+    cid: &str,
+) -> Result<WalletProviderKind, sqlx::Error> {
+    if let Ok(id) = Uuid::parse_str(cid) {
+        if OpenIdCredentialOffer::find_by_credential_id(pool, id)
+            .await?
+            .is_some()
+        {
+            return Ok(WalletProviderKind::OpenId);
+        }
+    }
+
+    Ok(WalletProviderKind::TaiwanWallet)
+}
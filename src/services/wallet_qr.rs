@@ -1,7 +1,9 @@
-use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+use crate::services::circuit_breaker::{CircuitBreaker, WALLET_ISSUER_APIS};
+use crate::services::credential_verifier::CredentialVerifyError;
+
 #[derive(thiserror::Error, Debug)]
 pub enum WalletQrError {
     #[error("HTTP request failed: {0}")]
@@ -13,11 +15,63 @@ pub enum WalletQrError {
     #[error("Missing VC UID")]
     MissingVcUid,
 
-    #[error("Invalid JWT format: {0}")]
-    InvalidJwt(String),
+    /// Credentials returned by the account could not be verified
+    #[error("Wallet credential issuer not configured for this account")]
+    MissingCredentialIssuer,
+
+    #[error("Credential rejected: {0}")]
+    UntrustedCredential(#[from] CredentialVerifyError),
 
     #[error("Credential not ready yet")]
     CredentialNotReady,
+
+    #[error("Wallet API unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    /// Refused for the given time still
+    #[error("Wallet API temporarily disabled after repeated failures")]
+    CircuitOpen(std::time::Duration),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+impl WalletQrError {
+    /// Whether the error means the wallet API itself is down (as opposed to a bad request)
+    pub fn is_outage(&self) -> bool {
+        matches!(
+            self,
+            WalletQrError::HttpError(_)
+                | WalletQrError::ServiceUnavailable(_)
+                | WalletQrError::CircuitOpen(_)
+        )
+    }
+}
+
+/// The circuit breaker of an issuer API, keyed by its base URL
+fn breaker(api_base_url: &str) -> std::sync::Arc<CircuitBreaker> {
+    WALLET_ISSUER_APIS.get(api_base_url.trim_end_matches('/'))
+}
+
+/// Refuses the call while the issuer API's circuit breaker is open
+fn ensure_circuit_closed(api_base_url: &str) -> Result<(), WalletQrError> {
+    match breaker(api_base_url).retry_after() {
+        Some(remaining) => Err(WalletQrError::CircuitOpen(remaining)),
+        None => Ok(()),
+    }
+}
+
+/// Feeds the outcome of an issuer API call into the API's circuit breaker
+fn track_outcome<T>(
+    api_base_url: &str,
+    result: Result<T, WalletQrError>,
+) -> Result<T, WalletQrError> {
+    match &result {
+        Ok(_) => breaker(api_base_url).record_success(),
+        Err(e) if e.is_outage() => breaker(api_base_url).record_failure(),
+        Err(_) => {}
+    }
+    result
 }
 
 #[derive(Debug, Serialize)]
@@ -48,6 +102,14 @@ pub async fn check_wallet_health(
     api_base_url: &str,
     access_token: &str,
 ) -> Result<(), WalletQrError> {
+    ensure_circuit_closed(api_base_url)?;
+    track_outcome(
+        api_base_url,
+        send_health_check(api_base_url, access_token).await,
+    )
+}
+
+async fn send_health_check(api_base_url: &str, access_token: &str) -> Result<(), WalletQrError> {
     let client = Client::new();
     let base = api_base_url.trim_end_matches('/');
 
@@ -63,8 +125,8 @@ pub async fn check_wallet_health(
         .await?;
 
     if response.status().is_server_error() {
-        return Err(WalletQrError::ApiError(format!(
-            "Wallet API unavailable: HTTP {}",
+        return Err(WalletQrError::ServiceUnavailable(format!(
+            "HTTP {}",
             response.status()
         )));
     }
@@ -82,6 +144,19 @@ pub async fn generate_wallet_qr(
     access_token: &str,
     vc_uid: &str,
     fields: Vec<WalletQrField>,
+) -> Result<WalletQrResponse, WalletQrError> {
+    ensure_circuit_closed(api_base_url)?;
+    track_outcome(
+        api_base_url,
+        send_generate_wallet_qr(api_base_url, access_token, vc_uid, fields).await,
+    )
+}
+
+async fn send_generate_wallet_qr(
+    api_base_url: &str,
+    access_token: &str,
+    vc_uid: &str,
+    fields: Vec<WalletQrField>,
 ) -> Result<WalletQrResponse, WalletQrError> {
     let client = Client::new();
 
@@ -118,10 +193,11 @@ pub async fn generate_wallet_qr(
             error = %error_text,
             "Wallet API request failed"
         );
-        return Err(WalletQrError::ApiError(format!(
-            "Status {}: {}",
-            status, error_text
-        )));
+        let message = format!("Status {}: {}", status, error_text);
+        if status.is_server_error() {
+            return Err(WalletQrError::ServiceUnavailable(message));
+        }
+        return Err(WalletQrError::ApiError(message));
     }
 
     let wallet_response: WalletQrResponse = response
@@ -137,6 +213,62 @@ pub async fn generate_wallet_qr(
     Ok(wallet_response)
 }
 
+/// Revokes an issued credential in the Taiwan Digital Wallet
+///
+/// Once revoked, the credential fails wallet verification. Revoking an already
+/// revoked credential is treated as success.
+#[tracing::instrument(skip(api_base_url, access_token))]
+pub async fn revoke_credential(
+    api_base_url: &str,
+    access_token: &str,
+    cid: &str,
+) -> Result<(), WalletQrError> {
+    ensure_circuit_closed(api_base_url)?;
+    track_outcome(
+        api_base_url,
+        send_revoke_credential(api_base_url, access_token, cid).await,
+    )
+}
+
+async fn send_revoke_credential(
+    api_base_url: &str,
+    access_token: &str,
+    cid: &str,
+) -> Result<(), WalletQrError> {
+    let client = Client::new();
+
+    let base = api_base_url.trim_end_matches('/');
+    let url = format!("{}/api/credential/{}/revocation", base, cid);
+
+    let response = client
+        .put(&url)
+        .header("Access-Token", access_token)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() && status != reqwest::StatusCode::CONFLICT {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        tracing::error!(
+            status = %status,
+            error = %error_text,
+            "Credential revocation failed"
+        );
+        let message = format!("Status {}: {}", status, error_text);
+        if status.is_server_error() {
+            return Err(WalletQrError::ServiceUnavailable(message));
+        }
+        return Err(WalletQrError::ApiError(message));
+    }
+
+    tracing::info!(cid = %cid, "Credential revoked");
+
+    Ok(())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CredentialResponse {
     pub credential: String,
@@ -149,13 +281,6 @@ struct WalletApiErrorBody {
     message: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-struct JwtClaims {
-    jti: String,
-    #[serde(flatten)]
-    _other: serde_json::Value,
-}
-
 /// Polls the Taiwan Digital Wallet API to check if the credential is ready
 ///
 /// Returns the credential JWT if ready, or CredentialNotReady error if not yet scanned
@@ -230,58 +355,6 @@ pub async fn poll_credential_status(
     Ok(credential_response)
 }
 
-/// Extracts the CID from the credential JWT token
-///
-/// The CID is extracted from the `jti` field in the JWT payload.
-/// Example jti: "https://issuer-vc.wallet.gov.tw/api/credential/a16187e9-755e-48ca-a9c0-622f76fe1360"
-/// The CID would be: "a16187e9-755e-48ca-a9c0-622f76fe1360"
-#[tracing::instrument(skip(jwt_token))]
-pub fn extract_cid_from_jwt(jwt_token: &str) -> Result<String, WalletQrError> {
-    // JWT tokens can be decoded without verification for extracting claims
-    // We use insecure decoding here because we only need to extract the jti field
-    // and don't need to verify the signature
-
-    // Decode the JWT token without verification to extract the jti field
-    // JWT format: header.payload.signature
-    let parts: Vec<&str> = jwt_token.split('.').collect();
-    if parts.len() != 3 {
-        return Err(WalletQrError::InvalidJwt(
-            "JWT token does not have 3 parts".to_string(),
-        ));
-    }
-
-    // Decode the payload (second part) from base64url
-    let payload_bytes = URL_SAFE_NO_PAD
-        .decode(parts[1])
-        .map_err(|e| WalletQrError::InvalidJwt(format!("Failed to decode base64: {}", e)))?;
-
-    // Parse JSON payload
-    let claims: JwtClaims = serde_json::from_slice(&payload_bytes)
-        .map_err(|e| WalletQrError::InvalidJwt(format!("Failed to parse JSON: {}", e)))?;
-
-    let jti = claims.jti;
-
-    tracing::debug!(jti = %jti, "Extracted jti from JWT");
-
-    // Extract CID from jti URL
-    // Format: "https://issuer-vc.wallet.gov.tw/api/credential/{CID}"
-    let cid = jti
-        .split('/')
-        .next_back()
-        .ok_or_else(|| WalletQrError::InvalidJwt("jti does not contain a valid URL".to_string()))?
-        .to_string();
-
-    if cid.is_empty() {
-        return Err(WalletQrError::InvalidJwt(
-            "CID extracted from jti is empty".to_string(),
-        ));
-    }
-
-    tracing::info!(cid = %cid, "Successfully extracted CID from credential");
-
-    Ok(cid)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,66 @@
+(() => {
+  const root = document.querySelector('[data-card-present]');
+  if (!root) {
+    return;
+  }
+
+  const tokenUrl = root.getAttribute('data-token-url');
+  const image = root.querySelector('[data-present-qr]');
+  const loading = root.querySelector('[data-present-loading]');
+  const countdown = root.querySelector('[data-present-countdown]');
+  const errorMessage = root.querySelector('[data-present-error]');
+
+  // Rotate well before the token expires, so a scan never lands on a stale code
+  const refreshMargin = 15;
+  let expiresAt = 0;
+  let refreshTimer = null;
+
+  async function refresh() {
+    clearTimeout(refreshTimer);
+
+    try {
+      const response = await fetch(tokenUrl, {
+        headers: { Accept: 'application/json' },
+        credentials: 'same-origin',
+        cache: 'no-store',
+      });
+      if (!response.ok) {
+        throw new Error(`HTTP ${response.status}`);
+      }
+
+      const data = await response.json();
+      image.src = `data:image/png;base64,${data.qrcode_image}`;
+      image.hidden = false;
+      loading.hidden = true;
+      errorMessage.hidden = true;
+      expiresAt = Date.now() + data.expires_in_seconds * 1000;
+
+      const next = Math.max(data.expires_in_seconds - refreshMargin, 5);
+      refreshTimer = setTimeout(refresh, next * 1000);
+    } catch (error) {
+      console.warn('Presentation token refresh failed', error);
+      errorMessage.hidden = false;
+      refreshTimer = setTimeout(refresh, 5000);
+    }
+  }
+
+  function updateCountdown() {
+    const remaining = Math.max(0, Math.ceil((expiresAt - Date.now()) / 1000));
+    if (remaining === 0) {
+      image.hidden = true;
+      countdown.textContent = '';
+      return;
+    }
+    countdown.textContent = `有效時間 ${remaining} 秒`;
+  }
+
+  // Timers are throttled in background tabs; fetch a fresh code when the page is shown again
+  document.addEventListener('visibilitychange', () => {
+    if (document.visibilityState === 'visible') {
+      refresh();
+    }
+  });
+
+  refresh();
+  setInterval(updateCountdown, 1000);
+})();
@@ -0,0 +1,264 @@
+(() => {
+  const root = document.querySelector('[data-offline-scanner]');
+  if (!root) {
+    return;
+  }
+
+  const eventId = root.getAttribute('data-event-id');
+  const statusText = root.querySelector('[data-offline-status]');
+  const downloadButton = root.querySelector('[data-offline-download]');
+
+  const bundleKey = `vpass-bundle-${eventId}`;
+  const queueKey = `vpass-offline-scans-${eventId}`;
+  const nonceKey = `vpass-used-nonces-${eventId}`;
+
+  // Matches the server's MAX_SCANS_PER_BATCH
+  const maxBatch = 500;
+  const uploadInterval = 60000;
+
+  const reasons = {
+    wrong_type: '不是 VPass 會員卡 QR Code',
+    wrong_issuer: '本活動不接受此頻道的會員卡',
+    bad_signature: '簽章無效',
+    expired: 'QR Code 已過期，請會員重新整理頁面',
+    token_reused: 'QR Code 已使用過，請會員重新整理頁面',
+    card_revoked: '會員卡已撤銷',
+    card_suspended: '會員卡已停權',
+    tier_too_low: '會員等級不足，本活動不開放此等級入場',
+  };
+
+  function load(key, fallback) {
+    try {
+      return JSON.parse(localStorage.getItem(key)) ?? fallback;
+    } catch (error) {
+      return fallback;
+    }
+  }
+
+  // The scanner page adds a paired device's token
+  function deviceHeaders(headers) {
+    return window.scannerHeaders ? window.scannerHeaders(headers) : headers;
+  }
+
+  function save(key, value) {
+    localStorage.setItem(key, JSON.stringify(value));
+  }
+
+  function b64urlDecode(part) {
+    const base64 = part.replace(/-/g, '+').replace(/_/g, '/');
+    const binary = atob(base64 + '='.repeat((4 - (base64.length % 4)) % 4));
+    return Uint8Array.from(binary, (c) => c.charCodeAt(0));
+  }
+
+  function decodeJwt(token) {
+    const parts = token.split('.');
+    if (parts.length !== 3) {
+      throw new Error('JWT does not have 3 parts');
+    }
+    const decoder = new TextDecoder();
+    return {
+      header: JSON.parse(decoder.decode(b64urlDecode(parts[0]))),
+      claims: JSON.parse(decoder.decode(b64urlDecode(parts[1]))),
+      signingInput: `${parts[0]}.${parts[1]}`,
+      signature: b64urlDecode(parts[2]),
+    };
+  }
+
+  function currentBundle() {
+    const bundle = load(bundleKey, null);
+    if (!bundle || bundle.claims.exp * 1000 <= Date.now()) {
+      return null;
+    }
+    return bundle;
+  }
+
+  function renderStatus() {
+    const bundle = currentBundle();
+    const queued = load(queueKey, []).length;
+    const parts = [];
+    if (bundle) {
+      const until = new Date(bundle.claims.exp * 1000);
+      parts.push(`離線資料有效至 ${until.toLocaleString()}`);
+    } else {
+      parts.push('尚未下載離線資料');
+    }
+    if (queued > 0) {
+      parts.push(`待上傳 ${queued} 筆`);
+    }
+    statusText.textContent = parts.join(' · ');
+  }
+
+  async function download() {
+    downloadButton.disabled = true;
+    try {
+      const response = await fetch(`/verify/${eventId}/bundle`, {
+        headers: deviceHeaders({ Accept: 'application/json' }),
+        credentials: 'same-origin',
+        cache: 'no-store',
+      });
+      if (!response.ok) {
+        throw new Error(`HTTP ${response.status}`);
+      }
+
+      const data = await response.json();
+      save(bundleKey, {
+        bundle_id: data.bundle_id,
+        token: data.bundle,
+        claims: decodeJwt(data.bundle).claims,
+      });
+      renderStatus();
+    } catch (error) {
+      console.warn('Bundle download failed', error);
+      statusText.textContent = '無法下載離線資料（需以主辦方帳號登入並連線）';
+    } finally {
+      downloadButton.disabled = false;
+    }
+  }
+
+  async function verifySignature(jwk, jwt) {
+    const key = await crypto.subtle.importKey(
+      'jwk',
+      { kty: jwk.kty, crv: jwk.crv, x: jwk.x, y: jwk.y },
+      { name: 'ECDSA', namedCurve: 'P-256' },
+      false,
+      ['verify'],
+    );
+    return crypto.subtle.verify(
+      { name: 'ECDSA', hash: 'SHA-256' },
+      key,
+      jwt.signature,
+      new TextEncoder().encode(jwt.signingInput),
+    );
+  }
+
+  // Same checks as the server: type, issuer key, signature, expiry, single use,
+  // card status, minimum tier
+  async function check(token, bundle) {
+    let jwt;
+    try {
+      jwt = decodeJwt(token);
+    } catch (error) {
+      return { result: 'invalid_payload', reason: reasons.wrong_type };
+    }
+    if (jwt.header.typ !== bundle.claims.token_typ) {
+      return { result: 'invalid_payload', reason: reasons.wrong_type };
+    }
+
+    const issuer = bundle.claims.issuers.find((i) => i.id === jwt.claims.iid);
+    if (!issuer) {
+      return { result: 'invalid_payload', reason: reasons.wrong_issuer };
+    }
+    const jwk = issuer.keys.find((k) => k.kid === jwt.header.kid);
+    if (!jwk || jwt.header.alg !== 'ES256' || !(await verifySignature(jwk, jwt))) {
+      return { result: 'invalid_payload', reason: reasons.bad_signature };
+    }
+    if (jwt.claims.exp * 1000 <= Date.now()) {
+      return { result: 'invalid_payload', reason: reasons.expired };
+    }
+
+    const now = Date.now();
+    const usedNonces = Object.fromEntries(
+      Object.entries(load(nonceKey, {})).filter(([, exp]) => exp * 1000 > now),
+    );
+    const reused = jwt.claims.nonce in usedNonces;
+    usedNonces[jwt.claims.nonce] = jwt.claims.exp;
+    save(nonceKey, usedNonces);
+
+    const cardId = jwt.claims.sub;
+    if (reused) {
+      return { result: 'token_reused', reason: reasons.token_reused, cardId };
+    }
+
+    const denied = bundle.claims.denied_cards.find((c) => c.id === cardId);
+    if (denied) {
+      const result = `card_${denied.status}`;
+      return { result, reason: reasons[result], cardId };
+    }
+
+    if ((bundle.claims.tier_denied_cards ?? []).includes(cardId)) {
+      return { result: 'tier_too_low', reason: reasons.tier_too_low, cardId };
+    }
+
+    return { result: 'success', cardId };
+  }
+
+  // Verifies a token against the downloaded bundle; null when there is no usable bundle
+  async function verify(token) {
+    const bundle = currentBundle();
+    if (!bundle) {
+      return null;
+    }
+
+    const trimmed = token.trim();
+    const outcome = await check(trimmed, bundle);
+
+    // Like online scans, only tokens that verified are recorded
+    if (outcome.cardId) {
+      const queue = load(queueKey, []);
+      queue.push({
+        scan_id: crypto.randomUUID(),
+        token: trimmed,
+        scanned_at: new Date().toISOString(),
+        bundle_id: bundle.bundle_id,
+        result: outcome.result,
+      });
+      save(queueKey, queue);
+      renderStatus();
+    }
+
+    const verified = outcome.result === 'success';
+    // Capacity for walk-ins is only enforced once the scans are uploaded
+    const reserved = (bundle.claims.reserved_cards ?? []).includes(outcome.cardId);
+    return {
+      status: 'completed',
+      verify_result: verified,
+      result_description: verified ? '會員卡有效（離線驗證）' : outcome.reason,
+      card_status: outcome.result,
+      member_info: verified ? { cardId: outcome.cardId, mode: '離線驗證' } : null,
+      attendance: verified ? (reserved ? 'reserved' : 'walk_in') : undefined,
+    };
+  }
+
+  let uploading = false;
+
+  async function upload() {
+    const queue = load(queueKey, []);
+    if (uploading || queue.length === 0 || !navigator.onLine) {
+      return;
+    }
+
+    uploading = true;
+    const batch = queue.slice(0, maxBatch);
+    try {
+      const bundle = load(bundleKey, null);
+      const response = await fetch(`/verify/${eventId}/offline-scans`, {
+        method: 'POST',
+        headers: deviceHeaders({ 'Content-Type': 'application/json', Accept: 'application/json' }),
+        credentials: 'same-origin',
+        body: JSON.stringify({ bundle_id: bundle?.bundle_id ?? null, scans: batch }),
+      });
+      if (!response.ok) {
+        throw new Error(`HTTP ${response.status}`);
+      }
+      await response.json();
+
+      // Scans queued while uploading stay in the queue
+      const uploaded = new Set(batch.map((scan) => scan.scan_id));
+      save(queueKey, load(queueKey, []).filter((scan) => !uploaded.has(scan.scan_id)));
+    } catch (error) {
+      console.warn('Offline scan upload failed', error);
+    } finally {
+      uploading = false;
+      renderStatus();
+    }
+  }
+
+  downloadButton.addEventListener('click', download);
+  window.addEventListener('online', upload);
+  setInterval(upload, uploadInterval);
+
+  window.VPassOffline = { verify, upload };
+
+  renderStatus();
+  upload();
+})();
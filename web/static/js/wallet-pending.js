@@ -0,0 +1,43 @@
+(() => {
+  const pendingRoot = document.querySelector('[data-wallet-pending]');
+  if (!pendingRoot) {
+    return;
+  }
+
+  const statusUrl = pendingRoot.getAttribute('data-status-url');
+  if (!statusUrl) {
+    return;
+  }
+
+  // The background worker retries with backoff, so a slow poll is enough
+  const pollInterval = 15000;
+  const maxPolls = 240;
+  let pollCount = 0;
+
+  async function checkStatus() {
+    pollCount += 1;
+
+    try {
+      const response = await fetch(statusUrl, {
+        headers: { Accept: 'application/json' },
+        credentials: 'same-origin',
+      });
+
+      if (response.ok) {
+        const data = await response.json();
+        if (data.ready) {
+          window.location.reload();
+          return;
+        }
+      }
+    } catch (error) {
+      console.warn('Wallet status check failed', error);
+    }
+
+    if (pollCount < maxPolls) {
+      setTimeout(checkStatus, pollInterval);
+    }
+  }
+
+  setTimeout(checkStatus, pollInterval);
+})();